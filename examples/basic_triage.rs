@@ -75,7 +75,7 @@ fn print_detailed_results(system_info: &SystemInfo) {
     println!("Hostname: {}", system_info.hostname);
     println!("OS Type: {:?}", system_info.os_type);
     
-    if let Some(ip) = &system_info.ip_address {
+    for ip in &system_info.ip_addresses {
         println!("IP Address: {}", ip);
     }
     
@@ -1,14 +1,19 @@
 // src/artifacts/collector.rs
 use crate::image::TskImage;
+use crate::parsers::EventSummary;
+use crate::sigma::SigmaRule;
 use crate::utils::progress::ProgressTracker;
 use anyhow::Result;
 use rayon::prelude::*;
 use std::sync::Arc;
+use super::types::*;
+use super::{LinuxArtifactCollector, WindowsArtifactCollector};
 
 pub struct ForensicCollector {
     progress: Arc<ProgressTracker>,
     max_file_size: Option<u64>,
     calculate_hashes: bool,
+    sigma_rules: Vec<SigmaRule>,
 }
 
 impl ForensicCollector {
@@ -17,12 +22,20 @@ impl ForensicCollector {
             progress: Arc::new(ProgressTracker::new()),
             max_file_size: None,
             calculate_hashes: true,
+            sigma_rules: Vec::new(),
         }
     }
 
+    /// Evaluate these Sigma rules against the parsed event logs
+    pub fn with_sigma_rules(mut self, rules: Vec<SigmaRule>) -> Self {
+        self.sigma_rules = rules;
+        self
+    }
+
     pub fn analyze(&self, image: &TskImage) -> Result<SystemInfo> {
         // Try Windows analysis first
         if let Ok(info) = WindowsArtifactCollector::new(image, Arc::clone(&self.progress))
+            .with_sigma_rules(&self.sigma_rules)
             .collect() {
             return Ok(info);
         }
@@ -35,13 +48,34 @@ impl ForensicCollector {
 
         Ok(SystemInfo {
             hostname: String::from("unknown"),
-            ip_address: None,
+            ip_addresses: Vec::new(),
+            network_interfaces: Vec::new(),
             domain: None,
             os_type: OsType::Unknown,
             os_version: None,
+            os_details: None,
             install_date: None,
             timezone: None,
+            timezone_bias: None,
+            last_shutdown: None,
             users: Vec::new(),
+            accounts: Vec::new(),
+            user_activity: UserActivity::default(),
+            shortcuts: Vec::new(),
+            jump_lists: Vec::new(),
+            recycle_bin: Vec::new(),
+            browser_history: Vec::new(),
+            execution: ExecutionEvidence::default(),
+            persistence: Vec::new(),
+            scheduled_tasks: Vec::new(),
+            wmi_persistence: None,
+            usb_devices: Vec::new(),
+            network_history: NetworkHistory::default(),
+            srum: None,
+            software: Vec::new(),
+            event_logs: Vec::new(),
+            events: EventSummary::default(),
+            sigma_hits: Vec::new(),
             artifacts: Vec::new(),
         })
     }
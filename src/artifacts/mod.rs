@@ -5,6 +5,6 @@ mod linux;
 mod types;
 
 pub use collector::ForensicCollector;
-pub use windows::WindowsArtifactCollector;
+pub use windows::{extract_registry_info, RegistryInfo, WindowsArtifactCollector};
 pub use linux::LinuxArtifactCollector;
 pub use types::*;
\ No newline at end of file
@@ -2,17 +2,61 @@
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 use std::path::PathBuf;
+use crate::parsers::{
+    Browser, BrowserActivity, EventSummary, JumpList, PrefetchFile, RecycledFile, ScheduledTaskDefinition, ShellLink, Srum,
+    WlanProfile, WmiPersistence,
+};
+use crate::sigma::SigmaHit;
+use crate::registry::{
+    Amcache, InstalledProgram, MappedDrive, MruEntry, NetworkProfile, PersistenceEntry, RdpConnection, SamAccount,
+    ShellBagEntry, ShimCache, TaskCacheEntry, UsbDevice, UserAssistEntry,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SystemInfo {
     pub hostname: String,
-    pub ip_address: Option<String>,
+    /// Addresses from every interface, static and DHCP-assigned
+    pub ip_addresses: Vec<String>,
+    pub network_interfaces: Vec<NetworkInterface>,
     pub domain: Option<String>,
     pub os_type: OsType,
     pub os_version: Option<String>,
+    pub os_details: Option<OsDetails>,
     pub install_date: Option<DateTime<Utc>>,
     pub timezone: Option<String>,
+    /// Offset from UTC in minutes, as stored by Windows (UTC = local + bias)
+    pub timezone_bias: Option<i32>,
+    pub last_shutdown: Option<DateTime<Utc>>,
     pub users: Vec<UserInfo>,
+    /// Local accounts from the SAM hive
+    pub accounts: Vec<SamAccount>,
+    pub user_activity: UserActivity,
+    /// Shortcut files from Recent, Desktop, Start Menu and elsewhere
+    pub shortcuts: Vec<Shortcut>,
+    /// Automatic and custom jump lists from each profile
+    pub jump_lists: Vec<JumpListFile>,
+    /// Deleted files from `$Recycle.Bin` and the XP `RECYCLER`
+    pub recycle_bin: Vec<RecycleBinEntry>,
+    /// Visits, downloads, searches, form entries and cookies per browser profile
+    pub browser_history: Vec<BrowserProfile>,
+    pub execution: ExecutionEvidence,
+    /// Autostart locations and BAM/DAM execution records
+    pub persistence: Vec<PersistenceEntry>,
+    /// Task files joined with the scheduler's registry cache
+    pub scheduled_tasks: Vec<ScheduledTask>,
+    /// Permanent event subscriptions from the WMI repository
+    pub wmi_persistence: Option<WmiPersistence>,
+    pub usb_devices: Vec<UsbDevice>,
+    pub network_history: NetworkHistory,
+    /// Per-application network and resource usage from SRUDB.dat
+    pub srum: Option<Srum>,
+    /// Installed programs, machine-wide and per user
+    pub software: Vec<InstalledProgram>,
+    pub event_logs: Vec<EventLogInfo>,
+    /// Curated tables of logon, account, service, process and other events
+    pub events: EventSummary,
+    /// Event log records matched by the supplied Sigma rules
+    pub sigma_hits: Vec<SigmaHit>,
     pub artifacts: Vec<ArtifactInfo>,
 }
 
@@ -23,6 +67,35 @@ pub enum OsType {
     Unknown,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OsDetails {
+    pub product_name: Option<String>,
+    pub edition: Option<String>,
+    /// Feature release, e.g. `22H2` (`ReleaseId` on older builds)
+    pub display_version: Option<String>,
+    pub build: Option<String>,
+    /// Update build revision
+    pub ubr: Option<u32>,
+    pub registered_owner: Option<String>,
+    pub registered_organization: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NetworkInterface {
+    /// Interface GUID from `Tcpip\Parameters\Interfaces`
+    pub guid: String,
+    pub dhcp_enabled: bool,
+    pub ip_addresses: Vec<String>,
+    pub subnet_masks: Vec<String>,
+    pub default_gateways: Vec<String>,
+    pub dhcp_server: Option<String>,
+    pub dns_servers: Vec<String>,
+    pub domain: Option<String>,
+    pub lease_obtained: Option<DateTime<Utc>>,
+    pub lease_expires: Option<DateTime<Utc>>,
+    pub last_write: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserInfo {
     pub username: String,
@@ -32,6 +105,131 @@ pub struct UserInfo {
     pub account_created: Option<DateTime<Utc>>,
 }
 
+/// Per-user program execution and file and folder access records from
+/// NTUSER.DAT and UsrClass.dat
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UserActivity {
+    pub userassist: Vec<UserAssistEntry>,
+    pub mru: Vec<MruEntry>,
+    pub shellbags: Vec<ShellBagEntry>,
+}
+
+/// A `.lnk` file found in the image
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Shortcut {
+    pub path: PathBuf,
+    /// Owner of the profile the shortcut is in
+    pub user: Option<String>,
+    pub location: ShortcutLocation,
+    pub link: ShellLink,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShortcutLocation {
+    /// Created by Explorer or Office when a file is opened
+    Recent,
+    Desktop,
+    StartMenu,
+    Other,
+}
+
+/// A jump list file from a user's Recent folder
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JumpListFile {
+    pub path: PathBuf,
+    pub user: String,
+    pub jump_list: JumpList,
+}
+
+/// A Chromium or Firefox profile directory and its activity, most recent first
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrowserProfile {
+    pub browser: Browser,
+    pub user: String,
+    pub path: PathBuf,
+    pub activity: Vec<BrowserActivity>,
+}
+
+/// A file in a user's Recycle Bin
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecycleBinEntry {
+    pub sid: String,
+    pub user: Option<String>,
+    /// The `$I` or INFO2 file the metadata came from
+    pub info_path: PathBuf,
+    /// The recycled file or folder, if still present
+    pub recycled_path: Option<PathBuf>,
+    pub file: RecycledFile,
+    /// The recycled item is a file rather than a folder, so it can be
+    /// copied out of the image next to the results
+    #[serde(skip)]
+    pub recoverable: bool,
+}
+
+/// System-wide evidence of programs present on or run by the system
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExecutionEvidence {
+    pub shimcache: Option<ShimCache>,
+    pub amcache: Option<Amcache>,
+    /// Executable paths from RecentFileCache.bcf (Windows 7), most recent first
+    pub recent_file_cache: Vec<String>,
+    /// Parsed Windows\Prefetch files, most recently run first
+    pub prefetch: Vec<PrefetchFile>,
+}
+
+/// A scheduled task from its file under `System32\Tasks`, its
+/// `TaskCache` registry entry, or both
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    /// Task path, e.g. `\Microsoft\Windows\Defrag\ScheduledDefrag`
+    pub path: String,
+    pub file: Option<PathBuf>,
+    pub definition: Option<ScheduledTaskDefinition>,
+    pub cache: Option<TaskCacheEntry>,
+    pub indicators: Vec<TaskIndicator>,
+}
+
+/// Signs that a task was hidden or tampered with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskIndicator {
+    /// `Hidden` is set in the definition
+    HiddenSetting,
+    /// The `SD` value was deleted from the `Tree` key
+    SecurityDescriptorRemoved,
+    /// `Index` is zero
+    IndexZero,
+    /// Registered in the cache but the task file is gone
+    MissingDefinition,
+    /// A task file the scheduler has no cache entry for
+    MissingFromRegistry,
+}
+
+/// Networks the system joined and remote hosts and shares its users
+/// connected to
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NetworkHistory {
+    pub profiles: Vec<NetworkProfile>,
+    pub wlan_profiles: Vec<WlanProfile>,
+    pub rdp_connections: Vec<RdpConnection>,
+    pub mapped_drives: Vec<MappedDrive>,
+}
+
+/// Record counts and time span of an event log
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventLogInfo {
+    pub name: String,
+    pub path: PathBuf,
+    pub records: usize,
+    /// Records carved from damaged chunks or chunk slack
+    pub recovered_records: usize,
+    pub first_event: Option<DateTime<Utc>>,
+    pub last_event: Option<DateTime<Utc>>,
+    /// The log was not closed cleanly
+    pub dirty: bool,
+    /// Chunks failing checksum validation
+    pub corrupt_chunks: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ArtifactInfo {
     pub path: PathBuf,
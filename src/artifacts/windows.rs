@@ -1,12 +1,79 @@
 // src/artifacts/windows.rs
-use crate::registry::RegistryParser;
-use anyhow::Result;
+use crate::image::{TskImage, FileType};
+use crate::parsers::{
+    info2_recycled_name, parse_automatic_destinations, parse_chromium_cookies, parse_chromium_history,
+    parse_chromium_web_data, parse_custom_destinations, parse_evtx, parse_firefox_form_history, parse_firefox_places,
+    parse_info2, parse_lnk, parse_prefetch, parse_recentfilecache, parse_recycle_bin_info, parse_setupapi_log,
+    parse_scheduled_task, parse_srum, parse_wlan_profile, parse_wmi_repository, Browser, BrowserActivity,
+    EventSummarizer, EventSummary, EvtxLog, PrefetchFile, RecycledFile, WlanProfile, WmiPersistence,
+};
+use crate::registry::{
+    analyze_persistence, collect_installed_software, parse_amcache, parse_mapped_drives, parse_mru,
+    parse_network_list, parse_rdp_connections, parse_sam, parse_shellbags, parse_shimcache, parse_task_cache,
+    parse_userassist, reconstruct_usb_history, RegistryHive, RegistryKey, RegistryParser, SamAccount, UsbDevice,
+};
+use crate::sigma::{evaluate_sigma, SigmaHit, SigmaRule};
+use crate::utils::progress::ProgressTracker;
+use crate::utils::time::unix_to_datetime;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use super::types::*;
 
+const SYSTEM: &str = "HKLM\\SYSTEM";
+const SOFTWARE: &str = "HKLM\\SOFTWARE";
+const PROFILE_LIST: &str = "Microsoft\\Windows NT\\CurrentVersion\\ProfileList";
+const CURRENT_VERSION: &str = "Microsoft\\Windows NT\\CurrentVersion";
+const AMCACHE: &str = "/Windows/AppCompat/Programs/Amcache.hve";
+const RECENT_FILE_CACHE: &str = "/Windows/AppCompat/Programs/RecentFileCache.bcf";
+const SETUPAPI_LOG: &str = "/Windows/INF/setupapi.dev.log";
+const WLAN_INTERFACES: &str = "/ProgramData/Microsoft/Wlansvc/Profiles/Interfaces";
+const EVENT_LOGS: &str = "/Windows/System32/winevt/Logs";
+const PREFETCH: &str = "/Windows/Prefetch";
+const SRUM: &str = "/Windows/System32/sru/SRUDB.dat";
+const TASKS: &str = "/Windows/System32/Tasks";
+const WMI_REPOSITORY: &str = "/Windows/System32/wbem/Repository";
+const USERS: &str = "/Users";
+const RECYCLE_BIN: &str = "/$Recycle.Bin";
+/// Windows 2000/XP Recycle Bin, indexed by a single INFO2 file per user
+const RECYCLER: &str = "/RECYCLER";
+const JUMP_LISTS: &str = "AppData/Roaming/Microsoft/Windows/Recent";
+/// Chromium user data directories, relative to the profile
+const CHROMIUM_PROFILES: &[(Browser, &str)] = &[
+    (Browser::Chrome, "AppData/Local/Google/Chrome/User Data"),
+    (Browser::Edge, "AppData/Local/Microsoft/Edge/User Data"),
+    (Browser::Brave, "AppData/Local/BraveSoftware/Brave-Browser/User Data"),
+    (Browser::Opera, "AppData/Roaming/Opera Software/Opera Stable"),
+    (Browser::Opera, "AppData/Roaming/Opera Software/Opera GX Stable"),
+];
+const FIREFOX_PROFILES: &str = "AppData/Roaming/Mozilla/Firefox/Profiles";
+/// Deepest directory level searched when walking the whole image
+const MAX_WALK_DEPTH: usize = 32;
+
+/// Parses a browser's SQLite database and its write-ahead log
+type BrowserParser = fn(&[u8], Option<&[u8]>) -> Result<Vec<BrowserActivity>>;
+
+/// System details gathered from the SYSTEM and SOFTWARE hives
+#[derive(Debug, Default)]
+pub struct RegistryInfo {
+    pub hostname: String,
+    pub domain: Option<String>,
+    pub interfaces: Vec<NetworkInterface>,
+    pub os_version: Option<String>,
+    pub os_details: Option<OsDetails>,
+    pub install_date: Option<DateTime<Utc>>,
+    pub timezone: Option<String>,
+    /// Minutes to add to local time to get UTC
+    pub timezone_bias: Option<i32>,
+    pub last_shutdown: Option<DateTime<Utc>>,
+}
+
 pub struct WindowsArtifactCollector<'a> {
     image: &'a TskImage,
     progress: Arc<ProgressTracker>,
     registry: RegistryParser,
+    sigma_rules: &'a [SigmaRule],
 }
 
 impl<'a> WindowsArtifactCollector<'a> {
@@ -15,54 +82,818 @@ impl<'a> WindowsArtifactCollector<'a> {
             image,
             progress,
             registry: RegistryParser::new(),
+            sigma_rules: &[],
         }
     }
 
-    pub fn collect(&self) -> Result<SystemInfo> {
+    pub fn with_sigma_rules(mut self, rules: &'a [SigmaRule]) -> Self {
+        self.sigma_rules = rules;
+        self
+    }
+
+    pub fn collect(&mut self) -> Result<SystemInfo> {
         let progress = self.progress.create_task("Windows Analysis");
-        
+
         // Find Windows directory
         progress.set_status("Locating Windows installation");
         let system_root = self.find_windows_directory()?;
 
         // Extract registry hives
         progress.set_status("Analyzing registry");
-        let registry_info = self.extract_registry_info(&system_root)?;
+        self.registry.load_from_image(self.image)?;
+        let registry_info = extract_registry_info(&self.registry)?;
 
         // Collect users
         progress.set_status("Processing user profiles");
-        let users = self.collect_user_profiles(&system_root)?;
+        let accounts = self.collect_accounts();
+        let users = self.collect_user_profiles(&accounts)?;
+        let user_activity = self.collect_user_activity();
+
+        progress.set_status("Searching for shortcut files");
+        let shortcuts = self.collect_shortcuts();
+        let jump_lists = self.collect_jump_lists();
+
+        progress.set_status("Parsing Recycle Bin");
+        let recycle_bin = self.collect_recycle_bin(&users);
+
+        progress.set_status("Reading browser history");
+        let browser_history = self.collect_browser_history();
+
+        progress.set_status("Collecting execution evidence");
+        let execution = self.collect_execution_evidence();
+
+        progress.set_status("Checking persistence locations");
+        let persistence = analyze_persistence(&self.registry);
+        let scheduled_tasks = self.collect_scheduled_tasks();
+
+        progress.set_status("Reading WMI repository");
+        let wmi_persistence = self.collect_wmi_persistence();
+
+        progress.set_status("Reconstructing USB device history");
+        let usb_devices = self.collect_usb_devices(registry_info.timezone_bias);
+
+        progress.set_status("Collecting network history");
+        let network_history = self.collect_network_history(registry_info.timezone_bias);
+
+        progress.set_status("Reading SRUM database");
+        let srum = self.image.read_file(Path::new(SRUM))
+            .and_then(|data| parse_srum(&data))
+            .map_err(|e| log::debug!("Skipping SRUDB.dat: {}", e))
+            .ok();
+
+        progress.set_status("Building software inventory");
+        let software = collect_installed_software(&self.registry);
+
+        progress.set_status(if self.sigma_rules.is_empty() {
+            "Parsing event logs"
+        } else {
+            "Parsing event logs and evaluating Sigma rules"
+        });
+        let (event_logs, events, sigma_hits) = self.process_event_logs();
 
         // Collect artifacts
         progress.set_status("Collecting artifacts");
         let artifacts = self.collect_artifacts(&system_root)?;
 
+        let mut ip_addresses: Vec<String> = Vec::new();
+        for address in registry_info.interfaces.iter().flat_map(|i| &i.ip_addresses) {
+            if !ip_addresses.contains(address) {
+                ip_addresses.push(address.clone());
+            }
+        }
+
         Ok(SystemInfo {
             hostname: registry_info.hostname,
-            ip_address: registry_info.ip_address,
+            ip_addresses,
+            network_interfaces: registry_info.interfaces,
             domain: registry_info.domain,
             os_type: OsType::Windows,
             os_version: registry_info.os_version,
+            os_details: registry_info.os_details,
             install_date: registry_info.install_date,
             timezone: registry_info.timezone,
+            timezone_bias: registry_info.timezone_bias,
+            last_shutdown: registry_info.last_shutdown,
             users,
+            accounts,
+            user_activity,
+            shortcuts,
+            jump_lists,
+            recycle_bin,
+            browser_history,
+            execution,
+            persistence,
+            scheduled_tasks,
+            wmi_persistence,
+            usb_devices,
+            network_history,
+            srum,
+            software,
+            event_logs,
+            events,
+            sigma_hits,
             artifacts,
         })
     }
 
     fn find_windows_directory(&self) -> Result<PathBuf> {
-        // Implementation
+        for candidate in ["/Windows", "/WINNT"] {
+            let path = PathBuf::from(candidate);
+            if self.image.list_directory(&path).is_ok() {
+                return Ok(path);
+            }
+        }
+        bail!("No Windows directory found")
     }
 
-    fn extract_registry_info(&self, system_root: &Path) -> Result<RegistryInfo> {
-        // Implementation
+    /// Local accounts from the SAM hive, if one was found
+    fn collect_accounts(&self) -> Vec<SamAccount> {
+        let Some(sam) = self.registry.hive("HKLM\\SAM") else {
+            return Vec::new();
+        };
+
+        parse_sam(sam).unwrap_or_else(|e| {
+            log::warn!("Failed to parse SAM hive: {}", e);
+            Vec::new()
+        })
     }
 
-    fn collect_user_profiles(&self, system_root: &Path) -> Result<Vec<UserInfo>> {
-        // Implementation
+    /// Build user entries from the ProfileList, enriched with SAM account
+    /// details. Local accounts that never logged on have no profile and are
+    /// listed with an empty profile path.
+    fn collect_user_profiles(&self, accounts: &[SamAccount]) -> Result<Vec<UserInfo>> {
+        let mut users = Vec::new();
+
+        let profiles = self.registry
+            .enumerate_subkeys(SOFTWARE, PROFILE_LIST)
+            .unwrap_or_default();
+
+        for profile in profiles {
+            let Some(path) = profile.string_value("ProfileImagePath") else {
+                continue;
+            };
+
+            let sid = profile.name().to_string();
+            let account = accounts.iter().find(|a| a.sid.as_deref() == Some(sid.as_str()));
+            let username = account
+                .map(|a| a.username.clone())
+                .unwrap_or_else(|| path.rsplit('\\').next().unwrap_or_default().to_string());
+
+            users.push(UserInfo {
+                username,
+                sid: Some(sid),
+                profile_path: PathBuf::from(path),
+                last_login: account.and_then(|a| a.last_login),
+                account_created: account.and_then(|a| a.created),
+            });
+        }
+
+        for account in accounts {
+            if users.iter().any(|u| u.sid.is_some() && u.sid == account.sid) {
+                continue;
+            }
+
+            users.push(UserInfo {
+                username: account.username.clone(),
+                sid: account.sid.clone(),
+                profile_path: PathBuf::new(),
+                last_login: account.last_login,
+                account_created: account.created,
+            });
+        }
+
+        // Without SOFTWARE or SAM, fall back to the profile directories on disk
+        if users.is_empty() {
+            for entry in self.image.list_directory(Path::new("/Users"))? {
+                if matches!(entry.file_type, FileType::Directory) && !entry.name.starts_with('.') {
+                    users.push(UserInfo {
+                        profile_path: Path::new("/Users").join(&entry.name),
+                        username: entry.name,
+                        sid: None,
+                        last_login: None,
+                        account_created: None,
+                    });
+                }
+            }
+        }
+
+        Ok(users)
+    }
+
+    /// UserAssist, MRU and ShellBag records from every user's hives
+    fn collect_user_activity(&self) -> UserActivity {
+        let mut activity = UserActivity::default();
+
+        for (user, hive) in self.registry.user_hives() {
+            activity.userassist.extend(parse_userassist(hive, user));
+            activity.mru.extend(parse_mru(hive, user));
+            // XP keeps ShellBags in NTUSER.DAT
+            activity.shellbags.extend(parse_shellbags(hive, user));
+        }
+        for (user, hive) in self.registry.user_class_hives() {
+            activity.shellbags.extend(parse_shellbags(hive, user));
+        }
+
+        activity
+    }
+
+    /// Every `.lnk` file in the image, classified by the folder it is in
+    fn collect_shortcuts(&self) -> Vec<Shortcut> {
+        let mut paths = Vec::new();
+        self.find_files(Path::new("/"), ".lnk", 0, &mut paths);
+
+        let mut shortcuts = Vec::new();
+        for path in paths {
+            match self.image.read_file(&path).and_then(|data| parse_lnk(&data)) {
+                Ok(link) => shortcuts.push(Shortcut {
+                    user: profile_owner(&path),
+                    location: shortcut_location(&path),
+                    path,
+                    link,
+                }),
+                Err(e) => log::debug!("Skipping {}: {}", path.display(), e),
+            }
+        }
+
+        shortcuts
+    }
+
+    /// Automatic and custom destinations from every profile under /Users
+    fn collect_jump_lists(&self) -> Vec<JumpListFile> {
+        let mut jump_lists = Vec::new();
+
+        for profile in self.image.list_directory(Path::new(USERS)).unwrap_or_default() {
+            if !matches!(profile.file_type, FileType::Directory) || profile.name.starts_with('.') {
+                continue;
+            }
+            let recent = Path::new(USERS).join(&profile.name).join(JUMP_LISTS);
+
+            for (folder, extension) in [
+                ("AutomaticDestinations", ".automaticdestinations-ms"),
+                ("CustomDestinations", ".customdestinations-ms"),
+            ] {
+                let dir = recent.join(folder);
+                for entry in self.image.list_directory(&dir).unwrap_or_default() {
+                    let name = entry.name.to_lowercase();
+                    let Some(app_id) = name.strip_suffix(extension) else {
+                        continue;
+                    };
+                    let path = dir.join(&entry.name);
+                    let parsed = self.image.read_file(&path).and_then(|data| match folder {
+                        "AutomaticDestinations" => parse_automatic_destinations(&data, app_id),
+                        _ => parse_custom_destinations(&data, app_id),
+                    });
+                    match parsed {
+                        Ok(jump_list) => jump_lists.push(JumpListFile {
+                            path,
+                            user: profile.name.clone(),
+                            jump_list,
+                        }),
+                        Err(e) => log::debug!("Skipping {}: {}", path.display(), e),
+                    }
+                }
+            }
+        }
+
+        jump_lists
+    }
+
+    /// `$I` files and INFO2 records from each SID's Recycle Bin folder,
+    /// paired with the recycled file where it still exists
+    fn collect_recycle_bin(&self, users: &[UserInfo]) -> Vec<RecycleBinEntry> {
+        let mut entries = Vec::new();
+
+        for root in [RECYCLE_BIN, RECYCLER] {
+            let root = Path::new(root);
+            for sid in self.image.list_directory(root).unwrap_or_default() {
+                if !matches!(sid.file_type, FileType::Directory) || !sid.name.starts_with("S-") {
+                    continue;
+                }
+                let dir = root.join(&sid.name);
+                let listing = self.image.list_directory(&dir).unwrap_or_default();
+
+                // Metadata file, parsed record and the name of its recycled file
+                let mut records: Vec<(PathBuf, RecycledFile, Option<String>)> = Vec::new();
+                for entry in &listing {
+                    let path = dir.join(&entry.name);
+                    if entry.name.eq_ignore_ascii_case("INFO2") {
+                        match self.image.read_file(&path).and_then(|data| parse_info2(&data)) {
+                            Ok(files) => records.extend(files.into_iter().map(|file| {
+                                let name = info2_recycled_name(&file);
+                                (path.clone(), file, name)
+                            })),
+                            Err(e) => log::debug!("Skipping {}: {}", path.display(), e),
+                        }
+                    } else if let Some(suffix) = entry.name.strip_prefix("$I")
+                        && matches!(entry.file_type, FileType::Regular)
+                    {
+                        match self.image.read_file(&path).and_then(|data| parse_recycle_bin_info(&data)) {
+                            Ok(file) => records.push((path, file, Some(format!("$R{}", suffix)))),
+                            Err(e) => log::debug!("Skipping {}: {}", path.display(), e),
+                        }
+                    }
+                }
+
+                for (info_path, file, name) in records {
+                    let recycled = name.and_then(|name| listing.iter().find(|e| e.name.eq_ignore_ascii_case(&name)));
+                    let recycled_path = recycled.map(|e| dir.join(&e.name));
+
+                    entries.push(RecycleBinEntry {
+                        sid: sid.name.clone(),
+                        user: users.iter()
+                            .find(|u| u.sid.as_deref() == Some(sid.name.as_str()))
+                            .map(|u| u.username.clone()),
+                        info_path,
+                        recycled_path,
+                        file,
+                        recoverable: recycled.is_some_and(|e| matches!(e.file_type, FileType::Regular)),
+                    });
+                }
+            }
+        }
+
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.file.deleted));
+        entries
+    }
+
+    /// Chromium and Firefox profiles under each user, with their SQLite
+    /// databases read along with any `-wal` file
+    fn collect_browser_history(&self) -> Vec<BrowserProfile> {
+        let mut profiles = Vec::new();
+
+        for user in self.image.list_directory(Path::new(USERS)).unwrap_or_default() {
+            if !matches!(user.file_type, FileType::Directory) || user.name.starts_with('.') {
+                continue;
+            }
+            let home = Path::new(USERS).join(&user.name);
+
+            // Chromium keeps profiles ("Default", "Profile 1") under its user
+            // data directory; Opera uses the directory itself
+            for (browser, base) in CHROMIUM_PROFILES {
+                let base = home.join(base);
+                let mut dirs = vec![base.clone()];
+                for entry in self.image.list_directory(&base).unwrap_or_default() {
+                    if matches!(entry.file_type, FileType::Directory) && !entry.name.starts_with('.') {
+                        dirs.push(base.join(&entry.name));
+                    }
+                }
+                for dir in dirs {
+                    let listing = self.image.list_directory(&dir).unwrap_or_default();
+                    if !listing.iter().any(|e| e.name == "History" && matches!(e.file_type, FileType::Regular)) {
+                        continue;
+                    }
+                    let mut activity = self.read_browser_database(&dir.join("History"), parse_chromium_history);
+                    // Cookies moved into the Network directory in Chrome 96
+                    for cookies in ["Network/Cookies", "Cookies"] {
+                        activity.extend(self.read_browser_database(&dir.join(cookies), parse_chromium_cookies));
+                    }
+                    activity.extend(self.read_browser_database(&dir.join("Web Data"), parse_chromium_web_data));
+                    profiles.push(browser_profile(*browser, &user.name, dir, activity));
+                }
+            }
+
+            let firefox = home.join(FIREFOX_PROFILES);
+            for entry in self.image.list_directory(&firefox).unwrap_or_default() {
+                if !matches!(entry.file_type, FileType::Directory) || entry.name.starts_with('.') {
+                    continue;
+                }
+                let dir = firefox.join(&entry.name);
+                let mut activity = self.read_browser_database(&dir.join("places.sqlite"), parse_firefox_places);
+                activity.extend(self.read_browser_database(&dir.join("formhistory.sqlite"), parse_firefox_form_history));
+                if !activity.is_empty() {
+                    profiles.push(browser_profile(Browser::Firefox, &user.name, dir, activity));
+                }
+            }
+        }
+
+        profiles
+    }
+
+    fn read_browser_database(&self, path: &Path, parse: BrowserParser) -> Vec<BrowserActivity> {
+        let Ok(data) = self.image.read_file(path) else {
+            return Vec::new();
+        };
+        let mut wal_path = path.as_os_str().to_owned();
+        wal_path.push("-wal");
+        let wal = self.image.read_file(Path::new(&wal_path)).ok();
+
+        parse(&data, wal.as_deref()).unwrap_or_else(|e| {
+            log::debug!("Skipping {}: {}", path.display(), e);
+            Vec::new()
+        })
+    }
+
+    /// Recursively collect regular files with the given (lowercase) extension
+    fn find_files(&self, dir: &Path, extension: &str, depth: usize, out: &mut Vec<PathBuf>) {
+        if depth > MAX_WALK_DEPTH {
+            return;
+        }
+        for entry in self.image.list_directory(dir).unwrap_or_default() {
+            if entry.name == "." || entry.name == ".." {
+                continue;
+            }
+            let path = dir.join(&entry.name);
+            match entry.file_type {
+                FileType::Directory => self.find_files(&path, extension, depth + 1, out),
+                FileType::Regular if entry.name.to_lowercase().ends_with(extension) => out.push(path),
+                _ => {}
+            }
+        }
+    }
+
+    /// Program execution evidence from the system hives, Amcache.hve and
+    /// RecentFileCache.bcf
+    fn collect_execution_evidence(&self) -> ExecutionEvidence {
+        let shimcache = self.registry.hive(SYSTEM).and_then(|system| {
+            parse_shimcache(system)
+                .map_err(|e| log::warn!("Failed to parse ShimCache: {}", e))
+                .ok()
+        });
+
+        // Amcache.hve is not mounted with the other hives, so load it here
+        let amcache = self.image.read_file(Path::new(AMCACHE))
+            .and_then(RegistryHive::from_bytes)
+            .map(|hive| parse_amcache(&hive))
+            .map_err(|e| log::debug!("Skipping Amcache.hve: {}", e))
+            .ok();
+
+        let recent_file_cache = self.image.read_file(Path::new(RECENT_FILE_CACHE))
+            .and_then(|data| parse_recentfilecache(&data))
+            .unwrap_or_else(|e| {
+                log::debug!("Skipping RecentFileCache.bcf: {}", e);
+                Vec::new()
+            });
+
+        ExecutionEvidence { shimcache, amcache, recent_file_cache, prefetch: self.collect_prefetch() }
+    }
+
+    fn collect_prefetch(&self) -> Vec<PrefetchFile> {
+        let root = Path::new(PREFETCH);
+        let mut files = Vec::new();
+
+        for entry in self.image.list_directory(root).unwrap_or_default() {
+            if !matches!(entry.file_type, FileType::Regular) || !entry.name.to_lowercase().ends_with(".pf") {
+                continue;
+            }
+            let path = root.join(&entry.name);
+            match self.image.read_file(&path).and_then(|data| parse_prefetch(&data)) {
+                Ok(file) => files.push(file),
+                Err(e) => log::debug!("Skipping {}: {}", path.display(), e),
+            }
+        }
+
+        files.sort_by(|a, b| b.last_run_times.first().cmp(&a.last_run_times.first()));
+        files
+    }
+
+    /// Task files joined with the TaskCache entries in the SOFTWARE hive by
+    /// task path, flagging tasks hidden by setting or registry tampering
+    fn collect_scheduled_tasks(&self) -> Vec<ScheduledTask> {
+        let mut cache = self.registry.hive(SOFTWARE).map(parse_task_cache).unwrap_or_default();
+        let root = Path::new(TASKS);
+        let mut paths = Vec::new();
+        self.find_files(root, "", 0, &mut paths);
+
+        let mut tasks = Vec::new();
+        for file in paths {
+            let Ok(relative) = file.strip_prefix(root) else {
+                continue;
+            };
+            let path: String = relative.components()
+                .map(|c| format!("\\{}", c.as_os_str().to_string_lossy()))
+                .collect();
+            let definition = self.image.read_file(&file)
+                .and_then(|data| parse_scheduled_task(&data))
+                .map_err(|e| log::debug!("Skipping {}: {}", file.display(), e))
+                .ok();
+            let entry = cache.iter()
+                .position(|entry| entry.path.eq_ignore_ascii_case(&path))
+                .map(|i| cache.swap_remove(i));
+
+            let mut indicators = Vec::new();
+            if definition.as_ref().is_some_and(|d| d.hidden) {
+                indicators.push(TaskIndicator::HiddenSetting);
+            }
+            // Without a SOFTWARE hive every task would look unregistered
+            if entry.is_none() && self.registry.hive(SOFTWARE).is_some() {
+                indicators.push(TaskIndicator::MissingFromRegistry);
+            }
+            tasks.push(ScheduledTask { path, file: Some(file), definition, cache: entry, indicators });
+        }
+        tasks.extend(cache.into_iter().map(|entry| ScheduledTask {
+            path: entry.path.clone(),
+            file: None,
+            definition: None,
+            cache: Some(entry),
+            indicators: vec![TaskIndicator::MissingDefinition],
+        }));
+
+        for task in &mut tasks {
+            if let Some(entry) = &task.cache {
+                if entry.sd_removed {
+                    task.indicators.push(TaskIndicator::SecurityDescriptorRemoved);
+                }
+                if entry.index == Some(0) {
+                    task.indicators.push(TaskIndicator::IndexZero);
+                }
+            }
+        }
+
+        tasks.sort_by_key(|task| task.path.to_lowercase());
+        tasks
+    }
+
+    /// Event subscriptions from the CIM repository, which keeps up to three
+    /// mapping files and uses the newest
+    fn collect_wmi_persistence(&self) -> Option<WmiPersistence> {
+        let root = Path::new(WMI_REPOSITORY);
+        let objects = self.image.read_file(&root.join("OBJECTS.DATA")).ok()?;
+        let index = self.image.read_file(&root.join("INDEX.BTR")).unwrap_or_default();
+        let mappings: Vec<Vec<u8>> = ["MAPPING1.MAP", "MAPPING2.MAP", "MAPPING3.MAP"].iter()
+            .filter_map(|name| self.image.read_file(&root.join(name)).ok())
+            .collect();
+        let mappings: Vec<&[u8]> = mappings.iter().map(Vec::as_slice).collect();
+
+        parse_wmi_repository(&objects, &index, &mappings)
+            .map_err(|e| log::debug!("Skipping WMI repository: {}", e))
+            .ok()
+    }
+
+    /// Correlate USBSTOR with the first-install times in setupapi.dev.log,
+    /// which are logged in local time
+    fn collect_usb_devices(&self, timezone_bias: Option<i32>) -> Vec<UsbDevice> {
+        let setupapi = self.image.read_file(Path::new(SETUPAPI_LOG))
+            .map(|data| parse_setupapi_log(&String::from_utf8_lossy(&data), timezone_bias.unwrap_or(0)))
+            .unwrap_or_default();
+
+        reconstruct_usb_history(&self.registry, &setupapi)
+    }
+
+    /// Known networks, WLAN profiles, and each user's RDP and mapped drive history
+    fn collect_network_history(&self, timezone_bias: Option<i32>) -> NetworkHistory {
+        let mut history = NetworkHistory {
+            profiles: self.registry.hive(SOFTWARE)
+                .map(|software| parse_network_list(software, timezone_bias.unwrap_or(0)))
+                .unwrap_or_default(),
+            wlan_profiles: self.collect_wlan_profiles(),
+            ..Default::default()
+        };
+
+        for (user, hive) in self.registry.user_hives() {
+            history.rdp_connections.extend(parse_rdp_connections(hive, user));
+            history.mapped_drives.extend(parse_mapped_drives(hive, user));
+        }
+
+        history
+    }
+
+    fn collect_wlan_profiles(&self) -> Vec<WlanProfile> {
+        let mut profiles = Vec::new();
+        let root = Path::new(WLAN_INTERFACES);
+
+        for interface in self.image.list_directory(root).unwrap_or_default() {
+            if !matches!(interface.file_type, FileType::Directory) || interface.name.starts_with('.') {
+                continue;
+            }
+            let dir = root.join(&interface.name);
+
+            for entry in self.image.list_directory(&dir).unwrap_or_default() {
+                if !entry.name.to_lowercase().ends_with(".xml") {
+                    continue;
+                }
+                let path = dir.join(&entry.name);
+                match self.image.read_file(&path).and_then(|data| parse_wlan_profile(&String::from_utf8_lossy(&data))) {
+                    Ok(mut profile) => {
+                        profile.interface_guid = Some(interface.name.clone());
+                        profiles.push(profile);
+                    }
+                    Err(e) => log::debug!("Skipping {}: {}", path.display(), e),
+                }
+            }
+        }
+
+        profiles
+    }
+
+    /// Summarize and evaluate each log as it is parsed, so that only one
+    /// log's records are held in memory at a time
+    fn process_event_logs(&self) -> (Vec<EventLogInfo>, EventSummary, Vec<SigmaHit>) {
+        let root = Path::new(EVENT_LOGS);
+        let mut logs = Vec::new();
+        let mut summarizer = EventSummarizer::default();
+        let mut sigma_hits = Vec::new();
+
+        for entry in self.image.list_directory(root).unwrap_or_default() {
+            if !matches!(entry.file_type, FileType::Regular) || !entry.name.to_lowercase().ends_with(".evtx") {
+                continue;
+            }
+            let path = root.join(&entry.name);
+            let log = match self.image.read_file(&path).and_then(|data| parse_evtx(&data)) {
+                Ok(log) => log,
+                Err(e) => {
+                    log::debug!("Skipping {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+
+            for record in &log.records {
+                summarizer.add(record);
+            }
+            sigma_hits.extend(evaluate_sigma(self.sigma_rules, &log.records));
+            logs.push(event_log_info(&path, &log));
+        }
+
+        (logs, summarizer.finish(), sigma_hits)
     }
 
     fn collect_artifacts(&self, system_root: &Path) -> Result<Vec<ArtifactInfo>> {
         // Implementation
     }
-}
\ No newline at end of file
+}
+
+/// User name from a path under `/Users` or `/Documents and Settings`
+fn profile_owner(path: &Path) -> Option<String> {
+    let mut components = path.components().skip(1);
+    let root = components.next()?.as_os_str().to_string_lossy().to_lowercase();
+    if root != "users" && root != "documents and settings" {
+        return None;
+    }
+    components.next().map(|user| user.as_os_str().to_string_lossy().into_owned())
+}
+
+fn shortcut_location(path: &Path) -> ShortcutLocation {
+    let path = path.to_string_lossy().to_lowercase();
+    if path.contains("/recent/") {
+        ShortcutLocation::Recent
+    } else if path.contains("/desktop/") {
+        ShortcutLocation::Desktop
+    } else if path.contains("/start menu/") {
+        ShortcutLocation::StartMenu
+    } else {
+        ShortcutLocation::Other
+    }
+}
+
+fn browser_profile(browser: Browser, user: &str, path: PathBuf, mut activity: Vec<BrowserActivity>) -> BrowserProfile {
+    activity.sort_by_key(|a| std::cmp::Reverse(a.timestamp));
+    BrowserProfile { browser, user: user.to_string(), path, activity }
+}
+
+fn event_log_info(path: &Path, log: &EvtxLog) -> EventLogInfo {
+    let timestamps = || log.records.iter().filter_map(|r| r.timestamp);
+
+    EventLogInfo {
+        name: path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default(),
+        path: path.to_path_buf(),
+        records: log.records.len(),
+        recovered_records: log.records.iter().filter(|r| r.recovered).count(),
+        first_event: timestamps().min(),
+        last_event: timestamps().max(),
+        dirty: log.header.dirty,
+        corrupt_chunks: log.chunks.iter()
+            .filter(|c| !c.header_checksum_valid || !c.data_checksum_valid)
+            .count(),
+    }
+}
+
+/// Read host, network, OS and time zone details from the SYSTEM and
+/// SOFTWARE hives loaded as `HKLM\SYSTEM` and `HKLM\SOFTWARE`
+pub fn extract_registry_info(registry: &RegistryParser) -> Result<RegistryInfo> {
+    let Some(system) = registry.hive(SYSTEM) else {
+        bail!("SYSTEM hive not found");
+    };
+    let control_set = system.current_control_set()?;
+    let tcpip = format!("{}\\Services\\Tcpip\\Parameters", control_set);
+    let control = format!("{}\\Control", control_set);
+
+    let mut info = RegistryInfo {
+        hostname: string_value(registry, SYSTEM, &format!("{}\\ComputerName\\ComputerName", control), "ComputerName")
+            .or_else(|| string_value(registry, SYSTEM, &tcpip, "Hostname"))
+            .unwrap_or_else(|| String::from("unknown")),
+        domain: string_value(registry, SYSTEM, &tcpip, "Domain")
+            .or_else(|| string_value(registry, SYSTEM, &tcpip, "NV Domain")),
+        interfaces: collect_interfaces(registry, &tcpip),
+        ..Default::default()
+    };
+
+    let time_zone = format!("{}\\TimeZoneInformation", control);
+    info.timezone = string_value(registry, SYSTEM, &time_zone, "TimeZoneKeyName")
+        .or_else(|| string_value(registry, SYSTEM, &time_zone, "StandardName"));
+    // Bias is a signed DWORD of minutes
+    info.timezone_bias = registry.get_value(SYSTEM, &time_zone, "Bias").ok()
+        .and_then(|v| v.as_integer())
+        .map(|bias| bias as u32 as i32);
+
+    info.last_shutdown = registry
+        .get_value(SYSTEM, &format!("{}\\Windows", control), "ShutdownTime").ok()
+        .and_then(|v| v.as_filetime());
+
+    if let Ok(current) = registry.get_key(SOFTWARE, CURRENT_VERSION) {
+        let details = OsDetails {
+            product_name: current.string_value("ProductName"),
+            edition: current.string_value("EditionID"),
+            display_version: current.string_value("DisplayVersion").or_else(|| current.string_value("ReleaseId")),
+            build: current.string_value("CurrentBuildNumber").or_else(|| current.string_value("CurrentBuild")),
+            ubr: current.integer_value("UBR").map(|v| v as u32),
+            registered_owner: current.string_value("RegisteredOwner"),
+            registered_organization: current.string_value("RegisteredOrganization"),
+        };
+
+        // InstallTime (FILETIME) is more precise than InstallDate (Unix seconds)
+        info.install_date = current.get_value("InstallTime").ok()
+            .and_then(|v| v.as_filetime())
+            .or_else(|| current.integer_value("InstallDate").and_then(|secs| unix_to_datetime(secs as i64)));
+
+        info.os_version = format_os_version(&details);
+        info.os_details = Some(details);
+    }
+
+    Ok(info)
+}
+
+/// Static and DHCP configuration of every TCP/IP interface
+fn collect_interfaces(registry: &RegistryParser, tcpip: &str) -> Vec<NetworkInterface> {
+    let interfaces = registry
+        .enumerate_subkeys(SYSTEM, &format!("{}\\Interfaces", tcpip))
+        .unwrap_or_default();
+
+    interfaces.iter().map(parse_interface).collect()
+}
+
+fn string_value(registry: &RegistryParser, hive: &str, path: &str, name: &str) -> Option<String> {
+    registry.get_key(hive, path).ok()?.string_value(name)
+}
+
+fn parse_interface(key: &RegistryKey) -> NetworkInterface {
+    let string = |name: &str| key.string_value(name).filter(|s| s != "0.0.0.0");
+    // Address lists are REG_MULTI_SZ, except name servers, which are one
+    // comma- or space-separated string
+    let list = |name: &str| -> Vec<String> {
+        match key.get_value(name) {
+            Ok(value) => match value.as_multi_string() {
+                Some(items) => items.to_vec(),
+                None => value.as_string_lossy()
+                    .map(|s| s.split([',', ' ']).map(str::to_string).collect())
+                    .unwrap_or_default(),
+            },
+            Err(_) => Vec::new(),
+        }
+        .into_iter()
+        .filter(|s| !s.is_empty() && s != "0.0.0.0")
+        .collect()
+    };
+    let unix_time = |name: &str| key.integer_value(name).and_then(|secs| unix_to_datetime(secs as i64));
+
+    let dhcp_enabled = key.integer_value("EnableDHCP").is_some_and(|v| v != 0);
+
+    let mut ip_addresses = list("IPAddress");
+    let mut subnet_masks = list("SubnetMask");
+    let mut default_gateways = list("DefaultGateway");
+    if dhcp_enabled {
+        ip_addresses.extend(string("DhcpIPAddress"));
+        subnet_masks.extend(string("DhcpSubnetMask"));
+        default_gateways.extend(list("DhcpDefaultGateway"));
+    }
+
+    let mut dns_servers = list("NameServer");
+    if dns_servers.is_empty() {
+        dns_servers = list("DhcpNameServer");
+    }
+
+    NetworkInterface {
+        guid: key.name().to_string(),
+        dhcp_enabled,
+        ip_addresses,
+        subnet_masks,
+        default_gateways,
+        dhcp_server: string("DhcpServer"),
+        dns_servers,
+        domain: string("Domain").or_else(|| string("DhcpDomain")),
+        lease_obtained: unix_time("LeaseObtainedTime"),
+        lease_expires: unix_time("LeaseTerminatesTime"),
+        last_write: key.last_write_time(),
+    }
+}
+
+/// e.g. `Windows 10 Pro 22H2 (build 19045.3803)`
+fn format_os_version(details: &OsDetails) -> Option<String> {
+    let mut version = details.product_name.clone()?;
+
+    // Windows 11 still reports "Windows 10" as its ProductName
+    let build_number = details.build.as_deref().and_then(|b| b.parse::<u32>().ok());
+    if build_number.is_some_and(|b| b >= 22000) && version.starts_with("Windows 10") {
+        version = version.replacen("Windows 10", "Windows 11", 1);
+    }
+
+    if let Some(display_version) = &details.display_version {
+        version.push_str(&format!(" {}", display_version));
+    }
+    if let Some(build) = &details.build {
+        match details.ubr {
+            Some(ubr) => version.push_str(&format!(" (build {}.{})", build, ubr)),
+            None => version.push_str(&format!(" (build {})", build)),
+        }
+    }
+
+    Some(version)
+}
@@ -0,0 +1,258 @@
+// src/ese/mod.rs
+//! Native reader for Extensible Storage Engine (JET Blue) databases such
+//! as SRUDB.dat. Tables are found through the MSysObjects catalog and read
+//! by walking their B-trees; long values and compressed tagged columns are
+//! resolved, but indexes are not used.
+mod page;
+mod record;
+
+use std::collections::{BTreeMap, HashSet};
+use anyhow::{Result, bail, Context};
+use page::{Entry, Page};
+use record::{decode_record, RecordContext};
+use record::{COLTYP_LONG, COLTYP_SHORT, COLTYP_TEXT, COLTYP_UNSIGNED_BYTE, COLTYP_UNSIGNED_SHORT};
+
+pub use record::{EseRecord, EseValue};
+
+const SIGNATURE: u32 = 0x89AB_CDEF;
+const PAGE_SIZE_OFFSET: usize = 236;
+const DEFAULT_PAGE_SIZE: usize = 4096;
+/// Pages at or above this size use the extended page format
+const LARGE_PAGE_SIZE: usize = 16384;
+
+/// The catalog's root page
+const CATALOG_PAGE: u32 = 4;
+const CATALOG_TABLE: u16 = 1;
+const CATALOG_COLUMN: u16 = 2;
+const CATALOG_LONG_VALUE: u16 = 4;
+
+/// B-tree depth and long value sizes beyond which data is assumed corrupt
+const MAX_TREE_DEPTH: usize = 16;
+const MAX_LONG_VALUE: usize = 64 * 1024 * 1024;
+
+/// A table column definition
+#[derive(Debug, Clone)]
+pub struct EseColumn {
+    pub id: u32,
+    pub name: String,
+    /// JET column type (`JET_coltyp`)
+    pub column_type: u32,
+    pub size: u32,
+    pub codepage: u32,
+}
+
+/// A table and the root pages of its data and long value trees
+#[derive(Debug, Clone)]
+pub struct EseTable {
+    pub name: String,
+    pub fdp_page: u32,
+    pub lv_page: Option<u32>,
+    pub columns: Vec<EseColumn>,
+}
+
+pub struct EseDatabase<'a> {
+    data: &'a [u8],
+    page_size: usize,
+    tables: Vec<EseTable>,
+}
+
+impl<'a> EseDatabase<'a> {
+    /// Parse the file header and catalog
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        if data.len() < PAGE_SIZE_OFFSET + 4 {
+            bail!("ESE database too small");
+        }
+        if u32::from_le_bytes(data[4..8].try_into().unwrap()) != SIGNATURE {
+            bail!("Invalid ESE database signature");
+        }
+        let page_size = match u32::from_le_bytes(data[PAGE_SIZE_OFFSET..PAGE_SIZE_OFFSET + 4].try_into().unwrap()) as usize {
+            0 => DEFAULT_PAGE_SIZE,
+            size if size.is_power_of_two() && (2048..=65536).contains(&size) => size,
+            size => bail!("Unsupported ESE page size {}", size),
+        };
+
+        let mut database = Self { data, page_size, tables: Vec::new() };
+        database.tables = database.read_catalog().context("Failed to read ESE catalog")?;
+        Ok(database)
+    }
+
+    pub fn tables(&self) -> &[EseTable] {
+        &self.tables
+    }
+
+    /// Find a table by name, ignoring case
+    pub fn table(&self, name: &str) -> Option<&EseTable> {
+        self.tables.iter().find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Every record in a table; records that fail to decode are skipped
+    pub fn records(&self, name: &str) -> Result<Vec<EseRecord>> {
+        let Some(table) = self.table(name) else {
+            bail!("ESE table '{}' not found", name);
+        };
+        let long_values = match table.lv_page {
+            Some(page) => self.long_values(page)?,
+            None => BTreeMap::new(),
+        };
+        let context = RecordContext {
+            columns: &table.columns,
+            long_values: &long_values,
+            large_pages: self.large_pages(),
+        };
+
+        let mut records = Vec::new();
+        for entry in self.leaf_entries(table.fdp_page)? {
+            match decode_record(entry.data, &context) {
+                Ok(record) => records.push(record),
+                Err(e) => log::debug!("Skipping record in {}: {}", table.name, e),
+            }
+        }
+        Ok(records)
+    }
+
+    fn large_pages(&self) -> bool {
+        self.page_size >= LARGE_PAGE_SIZE
+    }
+
+    /// Database page N follows the header and its shadow copy
+    fn page(&self, number: u32) -> Result<Page<'a>> {
+        let offset = (number as usize + 1) * self.page_size;
+        let Some(data) = self.data.get(offset..offset + self.page_size) else {
+            bail!("ESE page {} is beyond the end of the file", number);
+        };
+        Page::parse(data, self.large_pages())
+    }
+
+    /// Leaf entries of the B-tree rooted at `root`, in key order
+    fn leaf_entries(&self, root: u32) -> Result<Vec<Entry<'a>>> {
+        let mut entries = Vec::new();
+        let mut visited = HashSet::new();
+        self.walk(root, 0, &mut visited, &mut entries)?;
+        Ok(entries)
+    }
+
+    fn walk(&self, number: u32, depth: usize, visited: &mut HashSet<u32>, entries: &mut Vec<Entry<'a>>) -> Result<()> {
+        if depth > MAX_TREE_DEPTH || !visited.insert(number) {
+            bail!("ESE B-tree loop at page {}", number);
+        }
+        let page = self.page(number)?;
+        if page.is_space_tree() {
+            return Ok(());
+        }
+        if page.is_leaf() {
+            entries.extend(page.entries());
+            return Ok(());
+        }
+        // Branch entries point at their child page
+        for entry in page.entries() {
+            let Some(child) = entry.data.get(..4) else {
+                continue;
+            };
+            let child = u32::from_le_bytes(child.try_into().unwrap());
+            if let Err(e) = self.walk(child, depth + 1, visited, entries) {
+                log::debug!("Skipping ESE page {}: {}", child, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reassemble long values: a header keyed by the 4-byte ID, then
+    /// segments keyed by ID and offset, both big-endian
+    fn long_values(&self, root: u32) -> Result<BTreeMap<u32, Vec<u8>>> {
+        let mut values: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+        for entry in self.leaf_entries(root)? {
+            if entry.key.len() != 8 {
+                continue;
+            }
+            let id = u32::from_be_bytes(entry.key[..4].try_into().unwrap());
+            let offset = u32::from_be_bytes(entry.key[4..8].try_into().unwrap()) as usize;
+            let end = offset + entry.data.len();
+            if end > MAX_LONG_VALUE {
+                continue;
+            }
+            let value = values.entry(id).or_default();
+            if value.len() < end {
+                value.resize(end, 0);
+            }
+            value[offset..end].copy_from_slice(entry.data);
+        }
+        Ok(values)
+    }
+
+    /// MSysObjects lists each table followed by its columns, indexes and
+    /// long value tree, all sharing the table's object ID
+    fn read_catalog(&self) -> Result<Vec<EseTable>> {
+        let columns = catalog_columns();
+        let no_long_values = BTreeMap::new();
+        let context = RecordContext {
+            columns: &columns,
+            long_values: &no_long_values,
+            large_pages: self.large_pages(),
+        };
+
+        let mut tables: BTreeMap<i64, EseTable> = BTreeMap::new();
+        for entry in self.leaf_entries(CATALOG_PAGE)? {
+            let Ok(record) = decode_record(entry.data, &context) else {
+                continue;
+            };
+            let field = |name: &str| record.get(name).and_then(EseValue::as_i64).unwrap_or_default();
+            let name = record.get("Name").and_then(EseValue::as_str).unwrap_or_default().to_string();
+            let table_id = field("ObjidTable");
+
+            match field("Type") as u16 {
+                CATALOG_TABLE => {
+                    tables.insert(table_id, EseTable {
+                        name,
+                        fdp_page: field("ColtypOrPgnoFDP") as u32,
+                        lv_page: None,
+                        columns: Vec::new(),
+                    });
+                }
+                CATALOG_COLUMN => {
+                    if let Some(table) = tables.get_mut(&table_id) {
+                        table.columns.push(EseColumn {
+                            id: field("Id") as u32,
+                            name,
+                            column_type: field("ColtypOrPgnoFDP") as u32,
+                            size: field("SpaceUsage") as u32,
+                            codepage: field("PagesOrLocale") as u32,
+                        });
+                    }
+                }
+                CATALOG_LONG_VALUE => {
+                    if let Some(table) = tables.get_mut(&table_id) {
+                        table.lv_page = Some(field("ColtypOrPgnoFDP") as u32);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(tables.into_values().collect())
+    }
+}
+
+/// The catalog's own columns, which it cannot describe itself
+fn catalog_columns() -> Vec<EseColumn> {
+    let column = |id, name: &str, column_type| EseColumn {
+        id,
+        name: name.to_string(),
+        column_type,
+        size: 0,
+        codepage: 0,
+    };
+    vec![
+        column(1, "ObjidTable", COLTYP_LONG),
+        column(2, "Type", COLTYP_SHORT),
+        column(3, "Id", COLTYP_LONG),
+        column(4, "ColtypOrPgnoFDP", COLTYP_LONG),
+        column(5, "SpaceUsage", COLTYP_LONG),
+        column(6, "Flags", COLTYP_LONG),
+        column(7, "PagesOrLocale", COLTYP_LONG),
+        column(8, "RootFlag", COLTYP_UNSIGNED_BYTE),
+        column(9, "RecordOffset", COLTYP_SHORT),
+        column(10, "LCMapFlags", COLTYP_LONG),
+        column(11, "KeyMost", COLTYP_UNSIGNED_SHORT),
+        column(128, "Name", COLTYP_TEXT),
+    ]
+}
@@ -0,0 +1,107 @@
+// src/ese/page.rs
+//! Database pages and the tagged entries they hold
+use anyhow::{Result, bail};
+
+const PAGE_FLAG_LEAF: u32 = 0x02;
+const PAGE_FLAG_SPACE_TREE: u32 = 0x20;
+
+const TAG_DEFUNCT: u16 = 0x02;
+/// The entry shares a prefix with the page's common key
+const TAG_COMMON_KEY: u16 = 0x04;
+
+/// Header sizes before and after the extended (16K/32K) page format
+const HEADER_SIZE: usize = 40;
+const EXTENDED_HEADER_SIZE: usize = 80;
+
+/// A B-tree entry: its full key and the data after it
+pub(super) struct Entry<'a> {
+    pub key: Vec<u8>,
+    pub data: &'a [u8],
+}
+
+pub(super) struct Page<'a> {
+    flags: u32,
+    /// Tag values, each with its flags
+    tags: Vec<(&'a [u8], u16)>,
+    /// Flags live in the top bits of each value's first word rather than
+    /// in the tag
+    large: bool,
+}
+
+impl<'a> Page<'a> {
+    pub fn parse(data: &'a [u8], large: bool) -> Result<Self> {
+        let header_size = if large { EXTENDED_HEADER_SIZE } else { HEADER_SIZE };
+        if data.len() < header_size {
+            bail!("Truncated page");
+        }
+        let flags = u32::from_le_bytes(data[36..40].try_into().unwrap());
+        let tag_count = u16::from_le_bytes([data[34], data[35]]) as usize;
+        let mask = if large { 0x7FFF } else { 0x1FFF };
+
+        let mut tags = Vec::with_capacity(tag_count);
+        for i in 0..tag_count {
+            let Some(tag) = data.len().checked_sub(4 * (i + 1)).and_then(|at| data.get(at..at + 4)) else {
+                bail!("Page tag array overruns the page");
+            };
+            let size = (u16::from_le_bytes([tag[0], tag[1]]) & mask) as usize;
+            let raw_offset = u16::from_le_bytes([tag[2], tag[3]]);
+            let offset = header_size + (raw_offset & mask) as usize;
+            let Some(value) = data.get(offset..offset + size) else {
+                bail!("Page tag {} points outside the page", i);
+            };
+            let tag_flags = if large {
+                value.get(1).map_or(0, |b| (*b >> 5) as u16)
+            } else {
+                raw_offset >> 13
+            };
+            tags.push((value, tag_flags));
+        }
+
+        Ok(Self { flags, tags, large })
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.flags & PAGE_FLAG_LEAF != 0
+    }
+
+    pub fn is_space_tree(&self) -> bool {
+        self.flags & PAGE_FLAG_SPACE_TREE != 0
+    }
+
+    /// Live entries, with keys expanded from the page's common key (tag 0)
+    pub fn entries(&self) -> Vec<Entry<'a>> {
+        let common_key = self.tags.first().map(|(value, _)| *value).unwrap_or_default();
+        let mut entries = Vec::new();
+
+        for &(value, flags) in self.tags.iter().skip(1) {
+            if flags & TAG_DEFUNCT != 0 {
+                continue;
+            }
+            // The first word of a large page value has the flags masked in
+            let word = |at: usize| {
+                let word = value.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize);
+                if at == 0 && self.large { word.map(|w| w & 0x1FFF) } else { word }
+            };
+
+            let mut pos = 0;
+            let mut key = Vec::new();
+            if flags & TAG_COMMON_KEY != 0 {
+                let Some(common) = word(0) else {
+                    continue;
+                };
+                key.extend_from_slice(&common_key[..common.min(common_key.len())]);
+                pos = 2;
+            }
+            let Some(local) = word(pos) else {
+                continue;
+            };
+            let Some(local_key) = value.get(pos + 2..pos + 2 + local) else {
+                continue;
+            };
+            key.extend_from_slice(local_key);
+            entries.push(Entry { key, data: &value[pos + 2 + local..] });
+        }
+
+        entries
+    }
+}
@@ -0,0 +1,309 @@
+// src/ese/record.rs
+//! Decoding of data records: fixed, variable and tagged columns
+use std::collections::BTreeMap;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Serialize, Deserialize};
+use crate::utils::bytes::utf16z;
+use crate::utils::guid::format_guid;
+use crate::utils::lzxpress::decompress_lzxpress;
+use super::EseColumn;
+
+/// JET column types; binary columns decode as raw bytes
+pub(super) const COLTYP_BIT: u32 = 1;
+pub(super) const COLTYP_UNSIGNED_BYTE: u32 = 2;
+pub(super) const COLTYP_SHORT: u32 = 3;
+pub(super) const COLTYP_LONG: u32 = 4;
+pub(super) const COLTYP_CURRENCY: u32 = 5;
+pub(super) const COLTYP_IEEE_SINGLE: u32 = 6;
+pub(super) const COLTYP_IEEE_DOUBLE: u32 = 7;
+pub(super) const COLTYP_DATE_TIME: u32 = 8;
+pub(super) const COLTYP_TEXT: u32 = 10;
+pub(super) const COLTYP_LONG_TEXT: u32 = 12;
+pub(super) const COLTYP_UNSIGNED_LONG: u32 = 14;
+pub(super) const COLTYP_LONG_LONG: u32 = 15;
+pub(super) const COLTYP_GUID: u32 = 16;
+pub(super) const COLTYP_UNSIGNED_SHORT: u32 = 17;
+
+const CODEPAGE_UNICODE: u32 = 1200;
+
+/// Tagged column data flags
+const TAGGED_COMPRESSED: u8 = 0x02;
+const TAGGED_LONG_VALUE: u8 = 0x04;
+const TAGGED_MULTI_VALUE: u8 = 0x08;
+
+/// Compression schemes, in the top five bits of compressed data
+const COMPRESSION_7BIT_ASCII: u8 = 1;
+const COMPRESSION_7BIT_UNICODE: u8 = 2;
+const COMPRESSION_LZXPRESS: u8 = 3;
+
+/// A decoded column value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EseValue {
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    DateTime(DateTime<Utc>),
+    Text(String),
+    Binary(Vec<u8>),
+    Guid(String),
+    Multiple(Vec<EseValue>),
+}
+
+/// Column values by name; null columns are absent
+pub type EseRecord = BTreeMap<String, EseValue>;
+
+impl EseValue {
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            EseValue::Integer(value) => Some(*value),
+            EseValue::Boolean(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            EseValue::Text(text) | EseValue::Guid(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            EseValue::Binary(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn as_datetime(&self) -> Option<DateTime<Utc>> {
+        match self {
+            EseValue::DateTime(time) => Some(*time),
+            _ => None,
+        }
+    }
+}
+
+/// Where the columns of a record come from: the table's definitions and
+/// its long value store
+pub(super) struct RecordContext<'a> {
+    pub columns: &'a [EseColumn],
+    pub long_values: &'a BTreeMap<u32, Vec<u8>>,
+    /// Tagged offsets use 15 bits and always carry a flags byte
+    pub large_pages: bool,
+}
+
+pub(super) fn decode_record(data: &[u8], context: &RecordContext) -> Result<EseRecord> {
+    if data.len() < 4 {
+        bail!("Truncated record");
+    }
+    let last_fixed = data[0] as u32;
+    let last_variable = data[1] as u32;
+    let variable_offset = u16::from_le_bytes([data[2], data[3]]) as usize;
+    let mut record = EseRecord::new();
+
+    // Fixed columns in ID order, then a bitmap of which are null
+    let mut fixed: Vec<&EseColumn> = context.columns.iter().filter(|c| c.id <= last_fixed && c.id < 128).collect();
+    fixed.sort_by_key(|c| c.id);
+    let bitmap_offset = 4 + fixed.iter().map(|c| fixed_size(c)).sum::<usize>();
+    let bitmap = |id: u32| {
+        let bit = (id - 1) as usize;
+        data.get(bitmap_offset + bit / 8).is_some_and(|b| b & (1 << (bit % 8)) != 0)
+    };
+    let mut pos = 4;
+    for column in &fixed {
+        let size = fixed_size(column);
+        let Some(value) = data.get(pos..pos + size) else {
+            break;
+        };
+        if !bitmap(column.id)
+            && let Some(value) = decode_value(column, value)
+        {
+            record.insert(column.name.clone(), value);
+        }
+        pos += size;
+    }
+
+    // Variable columns: an array of cumulative end offsets, the top bit
+    // marking null, then the data
+    let variable_count = last_variable.saturating_sub(127) as usize;
+    let variable_data = variable_offset + variable_count * 2;
+    let mut start = 0;
+    for i in 0..variable_count {
+        let Some(end) = data.get(variable_offset + i * 2..variable_offset + i * 2 + 2) else {
+            bail!("Truncated variable column offsets");
+        };
+        let end = u16::from_le_bytes([end[0], end[1]]);
+        let null = end & 0x8000 != 0;
+        let end = (end & 0x7FFF) as usize;
+        let id = 128 + i as u32;
+        if !null
+            && let Some(column) = context.columns.iter().find(|c| c.id == id)
+            && let Some(value) = data.get(variable_data + start..variable_data + end)
+            && let Some(value) = decode_value(column, value)
+        {
+            record.insert(column.name.clone(), value);
+        }
+        start = end;
+    }
+
+    // Tagged columns: (ID, offset) pairs, the first offset marking the end
+    // of the array
+    let tagged = data.get(variable_data + start..).unwrap_or_default();
+    if tagged.len() >= 4 {
+        let mask = if context.large_pages { 0x7FFF } else { 0x3FFF };
+        let offset_at = |i: usize| tagged.get(i * 4 + 2..i * 4 + 4).map(|b| u16::from_le_bytes([b[0], b[1]]));
+        let count = (offset_at(0).unwrap_or_default() & mask) as usize / 4;
+
+        for i in 0..count {
+            let (Some(id), Some(raw)) = (tagged.get(i * 4..i * 4 + 2), offset_at(i)) else {
+                break;
+            };
+            let id = u16::from_le_bytes([id[0], id[1]]) as u32;
+            let begin = (raw & mask) as usize;
+            let end = if i + 1 < count { offset_at(i + 1).map_or(tagged.len(), |o| (o & mask) as usize) } else { tagged.len() };
+            let Some(mut value) = tagged.get(begin..end) else {
+                continue;
+            };
+            let Some(column) = context.columns.iter().find(|c| c.id == id) else {
+                continue;
+            };
+
+            let mut flags = 0;
+            if (context.large_pages || raw & 0x4000 != 0) && !value.is_empty() {
+                flags = value[0];
+                value = &value[1..];
+            }
+            match decode_tagged(column, value, flags, context) {
+                Ok(Some(value)) => {
+                    record.insert(column.name.clone(), value);
+                }
+                Ok(None) => {}
+                Err(e) => log::debug!("Column {}: {}", column.name, e),
+            }
+        }
+    }
+
+    Ok(record)
+}
+
+fn decode_tagged(column: &EseColumn, value: &[u8], flags: u8, context: &RecordContext) -> Result<Option<EseValue>> {
+    if flags & TAGGED_LONG_VALUE != 0 {
+        let Some(id) = value.get(..4).map(|b| u32::from_le_bytes(b.try_into().unwrap())) else {
+            bail!("Truncated long value reference");
+        };
+        let Some(data) = context.long_values.get(&id) else {
+            bail!("Missing long value {:#x}", id);
+        };
+        return Ok(decode_value(column, data));
+    }
+    if flags & TAGGED_MULTI_VALUE != 0 {
+        // Offsets to each value, the first also giving the array's size
+        let offset = |i: usize| value.get(i * 2..i * 2 + 2).map(|b| (u16::from_le_bytes([b[0], b[1]]) & 0x7FFF) as usize);
+        let count = offset(0).unwrap_or_default() / 2;
+        let values = (0..count)
+            .filter_map(|i| {
+                let end = if i + 1 < count { offset(i + 1)? } else { value.len() };
+                decode_value(column, value.get(offset(i)?..end)?)
+            })
+            .collect();
+        return Ok(Some(EseValue::Multiple(values)));
+    }
+    if flags & TAGGED_COMPRESSED != 0 {
+        return Ok(decode_value(column, &decompress(value)?));
+    }
+    Ok(decode_value(column, value))
+}
+
+/// Decompress a compressed column value
+pub(super) fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    let Some(&first) = data.first() else {
+        return Ok(Vec::new());
+    };
+    match first >> 3 {
+        COMPRESSION_7BIT_ASCII => Ok(decompress_7bit(data, false)),
+        COMPRESSION_7BIT_UNICODE => Ok(decompress_7bit(data, true)),
+        COMPRESSION_LZXPRESS => {
+            // The uncompressed size follows the type byte
+            let size = data.get(1..3).map_or(0, |b| u16::from_le_bytes([b[0], b[1]]) as usize);
+            decompress_lzxpress(data.get(3..).unwrap_or_default(), size)
+        }
+        other => bail!("Unsupported column compression {}", other),
+    }
+}
+
+/// 7-bit packing: characters stored LSB first, the low three bits of the
+/// first byte giving the number of bits used in the last byte, minus one.
+/// Unicode data expands each character to a UTF-16 code unit.
+fn decompress_7bit(data: &[u8], unicode: bool) -> Vec<u8> {
+    let packed = &data[1..];
+    let used_in_last = (data[0] & 0x07) as usize + 1;
+    let total_bits = (packed.len() * 8).saturating_sub(8 - used_in_last);
+    let mut output = Vec::with_capacity(total_bits / 7 * if unicode { 2 } else { 1 });
+
+    let (mut bits, mut count) = (0u32, 0);
+    let mut remaining = total_bits;
+    for &byte in packed {
+        bits |= (byte as u32) << count;
+        count += 8;
+        while count >= 7 && remaining >= 7 {
+            output.push((bits & 0x7F) as u8);
+            if unicode {
+                output.push(0);
+            }
+            bits >>= 7;
+            count -= 7;
+            remaining -= 7;
+        }
+    }
+
+    output
+}
+
+fn fixed_size(column: &EseColumn) -> usize {
+    match column.column_type {
+        COLTYP_BIT | COLTYP_UNSIGNED_BYTE => 1,
+        COLTYP_SHORT | COLTYP_UNSIGNED_SHORT => 2,
+        COLTYP_LONG | COLTYP_UNSIGNED_LONG | COLTYP_IEEE_SINGLE => 4,
+        COLTYP_CURRENCY | COLTYP_IEEE_DOUBLE | COLTYP_DATE_TIME | COLTYP_LONG_LONG => 8,
+        COLTYP_GUID => 16,
+        _ => column.size as usize,
+    }
+}
+
+fn decode_value(column: &EseColumn, data: &[u8]) -> Option<EseValue> {
+    let bytes = |n: usize| data.get(..n);
+    Some(match column.column_type {
+        COLTYP_BIT => EseValue::Boolean(*data.first()? != 0),
+        COLTYP_UNSIGNED_BYTE => EseValue::Integer(*data.first()? as i64),
+        COLTYP_SHORT => EseValue::Integer(i16::from_le_bytes(bytes(2)?.try_into().ok()?) as i64),
+        COLTYP_UNSIGNED_SHORT => EseValue::Integer(u16::from_le_bytes(bytes(2)?.try_into().ok()?) as i64),
+        COLTYP_LONG => EseValue::Integer(i32::from_le_bytes(bytes(4)?.try_into().ok()?) as i64),
+        COLTYP_UNSIGNED_LONG => EseValue::Integer(u32::from_le_bytes(bytes(4)?.try_into().ok()?) as i64),
+        COLTYP_CURRENCY | COLTYP_LONG_LONG => EseValue::Integer(i64::from_le_bytes(bytes(8)?.try_into().ok()?)),
+        COLTYP_IEEE_SINGLE => EseValue::Float(f32::from_le_bytes(bytes(4)?.try_into().ok()?) as f64),
+        COLTYP_IEEE_DOUBLE => EseValue::Float(f64::from_le_bytes(bytes(8)?.try_into().ok()?)),
+        COLTYP_DATE_TIME => {
+            let days = f64::from_le_bytes(bytes(8)?.try_into().ok()?);
+            ole_date(days).map_or(EseValue::Float(days), EseValue::DateTime)
+        }
+        COLTYP_TEXT | COLTYP_LONG_TEXT if column.codepage == CODEPAGE_UNICODE => {
+            EseValue::Text(utf16z(data))
+        }
+        COLTYP_TEXT | COLTYP_LONG_TEXT => {
+            EseValue::Text(data.iter().take_while(|&&b| b != 0).map(|&b| b as char).collect())
+        }
+        COLTYP_GUID => EseValue::Guid(format_guid(data)?),
+        _ => EseValue::Binary(data.to_vec()),
+    })
+}
+
+/// OLE automation date: days since 1899-12-30, the fraction being the time
+fn ole_date(days: f64) -> Option<DateTime<Utc>> {
+    if !days.is_finite() || days.abs() > 3_000_000.0 {
+        return None;
+    }
+    let epoch = Utc.with_ymd_and_hms(1899, 12, 30, 0, 0, 0).single()?;
+    epoch.checked_add_signed(Duration::milliseconds((days * 86_400_000.0).round() as i64))
+}
@@ -2,7 +2,7 @@
 mod tsk;
 mod ewf;
 
-pub use tsk::TskImage;
+pub use tsk::{TskImage, DirEntry, FileReader, FileType};
 pub use ewf::EwfImage;
 
 #[derive(Debug, Clone, Copy)]
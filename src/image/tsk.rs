@@ -1,6 +1,8 @@
 // src/image/tsk.rs
 use std::path::{Path, PathBuf};
 use std::ffi::{CString, CStr};
+use std::io::Read;
+use std::marker::PhantomData;
 use std::ptr;
 use anyhow::{Result, bail, Context};
 use parking_lot::RwLock;
@@ -109,6 +111,32 @@ impl TskImage {
         }
     }
 
+    /// Open a file to be read in pieces, for files that should not be held
+    /// in memory whole
+    pub fn open_file(&self, path: &Path) -> Result<FileReader<'_>> {
+        unsafe {
+            let path_str = CString::new(path.to_string_lossy().as_bytes())?;
+            let file = tsk_fs_file_open(self.fs_info, ptr::null_mut(), path_str.as_ptr());
+
+            if file.is_null() {
+                bail!("Failed to open file: {}", path.display());
+            }
+
+            let meta = (*file).meta;
+            if meta.is_null() {
+                tsk_fs_file_close(file);
+                bail!("File has no metadata");
+            }
+
+            Ok(FileReader {
+                file,
+                offset: 0,
+                size: (*meta).size as u64,
+                image: PhantomData,
+            })
+        }
+    }
+
     pub fn list_directory(&self, path: &Path) -> Result<Vec<DirEntry>> {
         unsafe {
             let path_str = CString::new(path.to_string_lossy().as_bytes())?;
@@ -168,6 +196,47 @@ impl Drop for TskImage {
     }
 }
 
+/// A file opened with [`TskImage::open_file`], read from the image as needed
+pub struct FileReader<'a> {
+    file: *mut ::tsk_fs_file,
+    offset: u64,
+    size: u64,
+    image: PhantomData<&'a TskImage>,
+}
+
+impl Read for FileReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = (self.size - self.offset).min(buf.len() as u64);
+        if length == 0 {
+            return Ok(0);
+        }
+
+        let read = unsafe {
+            tsk_fs_file_read(
+                self.file,
+                self.offset as i64,
+                buf.as_mut_ptr() as *mut i8,
+                length,
+                ::TSK_FS_FILE_READ_FLAG_ENUM::TSK_FS_FILE_READ_FLAG_NONE as i32,
+            )
+        };
+        if read < 0 {
+            return Err(std::io::Error::other("Failed to read file"));
+        }
+
+        self.offset += read as u64;
+        Ok(read as usize)
+    }
+}
+
+impl Drop for FileReader<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            tsk_fs_file_close(self.file);
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct DirEntry {
     pub name: String,
@@ -5,15 +5,36 @@ pub mod image;
 pub mod artifacts;
 pub mod registry;
 pub mod filesystem;
+pub mod shellitem;
+pub mod parsers;
+pub mod ese;
+pub mod sigma;
 pub mod utils;
 
 pub use image::TskImage;
 pub use artifacts::collector::ForensicCollector;
 pub use artifacts::types::{ArtifactInfo, SystemInfo};
+pub use registry::RegistryParser;
+pub use sigma::SigmaRule;
 
 /// Main entry point for forensic analysis
 pub fn analyze_image(path: &std::path::Path) -> Result<SystemInfo> {
     let image = TskImage::new(path)?;
     let collector = ForensicCollector::new();
     collector.analyze(&image)
-}
\ No newline at end of file
+}
+
+/// Analyze an image and evaluate Sigma rules against its event logs
+pub fn analyze_image_with_sigma(path: &std::path::Path, rules: Vec<SigmaRule>) -> Result<SystemInfo> {
+    let image = TskImage::new(path)?;
+    let collector = ForensicCollector::new().with_sigma_rules(rules);
+    collector.analyze(&image)
+}
+
+/// Open an image and load its registry hives for ad hoc queries
+pub fn load_registry(path: &std::path::Path) -> Result<RegistryParser> {
+    let image = TskImage::new(path)?;
+    let mut registry = RegistryParser::new();
+    registry.load_from_image(&image)?;
+    Ok(registry)
+}
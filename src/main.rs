@@ -1,29 +1,133 @@
 // src/main.rs
-use anyhow::Result;
-use clap::Parser;
-use forensic_triage::{analyze_image, SystemInfo};
-use std::path::PathBuf;
+use anyhow::{Result, Context};
+use clap::{Parser, Subcommand, ValueEnum};
+use forensic_triage::{analyze_image_with_sigma, load_registry, TskImage};
+use forensic_triage::parsers::parse_evtx;
+use forensic_triage::sigma::{evaluate_sigma, load_sigma_rules};
+use forensic_triage::registry::{matches_to_reg, matches_to_table, RegistryHive, RegistryQuery};
+use forensic_triage::utils::time::parse_datetime;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
-#[clap(author, version, about)]
+#[clap(author, version, about, args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// Path to forensic image
-    #[clap(parse(from_os_str))]
-    image: PathBuf,
+    #[clap(required = true)]
+    image: Option<PathBuf>,
 
     /// Output directory for artifacts
-    #[clap(short, long, parse(from_os_str), default_value = "output")]
+    #[clap(short, long, default_value = "output")]
     output: PathBuf,
 
     /// Number of threads to use
     #[clap(short, long)]
     threads: Option<usize>,
 
+    /// Directory of Sigma rules to evaluate against the event logs
+    #[clap(long)]
+    sigma: Option<PathBuf>,
+
     /// Enable verbose logging
-    #[clap(short, long)]
+    #[clap(short, long, global = true)]
     verbose: bool,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Query registry hives in an image, e.g. 'HKU\*\Software\Microsoft\Windows\CurrentVersion\Run'
+    Reg {
+        /// Path to forensic image
+        image: PathBuf,
+
+        /// Key path; '*' and '?' glob within a key name, '**' spans any depth
+        query: String,
+
+        /// Regex the value name must match
+        #[clap(long)]
+        value: Option<String>,
+
+        /// Regex the value data must match
+        #[clap(long)]
+        data: Option<String>,
+
+        /// Only keys last written at or after this time (RFC 3339 or YYYY-MM-DD)
+        #[clap(long)]
+        after: Option<String>,
+
+        /// Only keys last written at or before this time (RFC 3339 or YYYY-MM-DD)
+        #[clap(long)]
+        before: Option<String>,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// Compare two versions of a hive, e.g. from a VSS snapshot and the live volume
+    RegDiff {
+        /// Old hive file, or image when --hive is given
+        old: PathBuf,
+
+        /// New hive file, or image when --hive is given
+        new: PathBuf,
+
+        /// Compare this mounted hive (e.g. 'HKLM\SOFTWARE') between two images
+        #[clap(long)]
+        hive: Option<String>,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value = "json")]
+        format: DiffFormat,
+    },
+
+    /// Render the records of an .evtx file, including those carved from damaged chunks
+    Evtx {
+        /// Path to event log file
+        file: PathBuf,
+
+        /// Output format; JSON is one record per line
+        #[clap(short, long, value_enum, default_value = "xml")]
+        format: EventFormat,
+
+        /// Print the hits of these Sigma rules, as JSON lines, instead of the records
+        #[clap(long)]
+        sigma: Option<PathBuf>,
+    },
+
+    /// Decrypt SAM hashes, LSA secrets and cached domain logons from an image
+    #[cfg(feature = "credentials")]
+    Creds {
+        /// Path to forensic image
+        image: PathBuf,
+
+        /// Include plaintext LSA secret values, not just their names and hashes
+        #[clap(long)]
+        reveal_secrets: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
+    Reg,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DiffFormat {
+    Json,
+    Reg,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum EventFormat {
+    Xml,
+    Json,
+}
+
 fn main() -> Result<()> {
     let args = Args::parse();
 
@@ -41,20 +145,139 @@ fn main() -> Result<()> {
             .build_global()?;
     }
 
+    match args.command {
+        Some(Command::Reg { image, query, value, data, after, before, format }) => {
+            let mut query = RegistryQuery::new(&query)?;
+            if let Some(value) = value {
+                query = query.value_name(&value)?;
+            }
+            if let Some(data) = data {
+                query = query.data(&data)?;
+            }
+            let after = after.as_deref().map(parse_datetime).transpose()?;
+            let before = before.as_deref().map(parse_datetime).transpose()?;
+            query = query.modified_between(after, before);
+
+            let registry = load_registry(&image)?;
+            let matches = registry.query(&query)?;
+
+            match format {
+                OutputFormat::Table => print!("{}", matches_to_table(&matches)),
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&matches)?),
+                OutputFormat::Reg => print!("{}", matches_to_reg(&matches)),
+            }
+            Ok(())
+        }
+        Some(Command::RegDiff { old, new, hive, format }) => {
+            let (old_hive, new_hive, mount) = match hive {
+                Some(name) => {
+                    let old_registry = load_registry(&old)?;
+                    let new_registry = load_registry(&new)?;
+                    let old_hive = old_registry.hive(&name)
+                        .context(format!("Hive '{}' not found in {}", name, old.display()))?
+                        .clone();
+                    let new_hive = new_registry.hive(&name)
+                        .context(format!("Hive '{}' not found in {}", name, new.display()))?
+                        .clone();
+                    (old_hive, new_hive, name)
+                }
+                None => {
+                    // Without a mount name, the patch is rooted at the hive's file name
+                    let mount = new.file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    (RegistryHive::from_file(&old)?, RegistryHive::from_file(&new)?, mount)
+                }
+            };
+
+            let diff = old_hive.diff(&new_hive)?;
+            match format {
+                DiffFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+                DiffFormat::Reg => print!("{}", diff.to_reg(&mount)),
+            }
+            Ok(())
+        }
+        Some(Command::Evtx { file, format, sigma }) => {
+            let data = std::fs::read(&file).context(format!("Failed to read {}", file.display()))?;
+            let log = parse_evtx(&data)?;
+            if let Some(dir) = sigma {
+                let rules = load_sigma_rules(&dir)?;
+                for hit in evaluate_sigma(&rules, &log.records) {
+                    println!("{}", serde_json::to_string(&hit)?);
+                }
+                return Ok(());
+            }
+            for record in &log.records {
+                match format {
+                    EventFormat::Xml => print!("{}", record.to_xml()),
+                    EventFormat::Json => println!("{}", record.to_json()),
+                }
+            }
+            Ok(())
+        }
+        #[cfg(feature = "credentials")]
+        Some(Command::Creds { image, reveal_secrets }) => {
+            let registry = load_registry(&image)?;
+            let credentials = forensic_triage::registry::extract_credentials(&registry, reveal_secrets)?;
+            println!("{}", serde_json::to_string_pretty(&credentials)?);
+            Ok(())
+        }
+        None => {
+            // clap guarantees an image when no subcommand is given
+            let image = args.image.expect("image is required");
+            triage(&image, &args.output, args.sigma.as_deref())
+        }
+    }
+}
+
+fn triage(image: &Path, output: &Path, sigma: Option<&Path>) -> Result<()> {
     // Create output directory
-    std::fs::create_dir_all(&args.output)?;
+    std::fs::create_dir_all(output)?;
 
     // Analyze image
-    log::info!("Analyzing image: {}", args.image.display());
-    let system_info = analyze_image(&args.image)?;
+    log::info!("Analyzing image: {}", image.display());
+    let rules = match sigma {
+        Some(dir) => {
+            let rules = load_sigma_rules(dir)?;
+            log::info!("Loaded {} Sigma rules from {}", rules.len(), dir.display());
+            rules
+        }
+        None => Vec::new(),
+    };
+    let system_info = analyze_image_with_sigma(image, rules)?;
 
     // Write results
-    let output_file = args.output.join("triage_results.json");
+    let output_file = output.join("triage_results.json");
     std::fs::write(
         &output_file,
         serde_json::to_string_pretty(&system_info)?,
     )?;
 
+    // Recovered Recycle Bin files go next to the results, by SID. They are
+    // streamed from the image, as they can be arbitrarily large.
+    let recoverable: Vec<_> = system_info.recycle_bin.iter().filter(|entry| entry.recoverable).collect();
+    if !recoverable.is_empty() {
+        let image = TskImage::new(image)?;
+        for entry in recoverable {
+            let Some(path) = &entry.recycled_path else {
+                continue;
+            };
+            let Some(name) = path.file_name() else {
+                continue;
+            };
+            let dir = output.join("recycle_bin").join(&entry.sid);
+            std::fs::create_dir_all(&dir)?;
+            let mut reader = match image.open_file(path) {
+                Ok(reader) => reader,
+                Err(e) => {
+                    log::debug!("Failed to recover {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            std::io::copy(&mut reader, &mut std::fs::File::create(dir.join(name))?)?;
+        }
+    }
+
     log::info!("Analysis complete. Results written to: {}", output_file.display());
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,237 @@
+// src/parsers/browser.rs
+//! Chromium (Chrome, Edge, Brave, Opera) and Firefox history databases,
+//! normalized into a single activity record
+use std::collections::HashMap;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::utils::sqlite::{SqliteDatabase, SqliteRow, SqliteValue};
+use crate::utils::time::{prtime_to_datetime, unix_to_datetime, webkit_to_datetime};
+
+/// Form history field holding Firefox search bar entries
+const FIREFOX_SEARCH_FIELD: &str = "searchbar-history";
+const FIREFOX_DOWNLOAD_DESTINATION: &str = "downloads/destinationFileURI";
+const FIREFOX_DOWNLOAD_METADATA: &str = "downloads/metaData";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Browser {
+    Chrome,
+    Edge,
+    Brave,
+    Opera,
+    Firefox,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BrowserActivityKind {
+    Visit,
+    Download,
+    Search,
+    /// Autofill or form history entry
+    FormEntry,
+    Cookie,
+}
+
+/// A visit, download, search, form entry or cookie
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserActivity {
+    pub kind: BrowserActivityKind,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Page visited or searched from, download source, or cookie host and path
+    pub url: Option<String>,
+    pub title: Option<String>,
+    /// Form field or cookie name
+    pub name: Option<String>,
+    /// Search terms, form value or download target path
+    pub value: Option<String>,
+    /// Visits to the URL, or uses of the form value
+    pub count: Option<i64>,
+    /// Download size in bytes
+    pub size: Option<i64>,
+}
+
+impl BrowserActivity {
+    fn new(kind: BrowserActivityKind, timestamp: Option<DateTime<Utc>>) -> Self {
+        Self {
+            kind,
+            timestamp,
+            url: None,
+            title: None,
+            name: None,
+            value: None,
+            count: None,
+            size: None,
+        }
+    }
+}
+
+/// Visits, downloads and search terms from a Chromium `History` database
+pub fn parse_chromium_history(data: &[u8], wal: Option<&[u8]>) -> Result<Vec<BrowserActivity>> {
+    let database = SqliteDatabase::parse(data, wal)?;
+    let urls: HashMap<i64, SqliteRow> = rows(&database, "urls").into_iter()
+        .filter_map(|row| Some((int(&row, "id")?, row)))
+        .collect();
+    let mut activity = Vec::new();
+
+    for visit in rows(&database, "visits") {
+        let mut record = BrowserActivity::new(BrowserActivityKind::Visit, int(&visit, "visit_time").and_then(webkit_to_datetime));
+        if let Some(url) = int(&visit, "url").and_then(|id| urls.get(&id)) {
+            record.url = text(url, "url");
+            record.title = text(url, "title");
+            record.count = int(url, "visit_count");
+        }
+        activity.push(record);
+    }
+
+    // The last URL of each download's redirect chain is where it came from
+    let mut chains: HashMap<i64, (i64, String)> = HashMap::new();
+    for link in rows(&database, "downloads_url_chains") {
+        let (Some(id), Some(index), Some(url)) = (int(&link, "id"), int(&link, "chain_index"), text(&link, "url")) else {
+            continue;
+        };
+        if chains.get(&id).is_none_or(|(last, _)| index > *last) {
+            chains.insert(id, (index, url));
+        }
+    }
+    for download in rows(&database, "downloads") {
+        let mut record = BrowserActivity::new(BrowserActivityKind::Download, int(&download, "start_time").and_then(webkit_to_datetime));
+        record.url = int(&download, "id")
+            .and_then(|id| chains.get(&id))
+            .map(|(_, url)| url.clone())
+            .or_else(|| text(&download, "tab_url"));
+        record.value = text(&download, "target_path").or_else(|| text(&download, "current_path"));
+        record.size = int(&download, "total_bytes").filter(|&size| size > 0).or_else(|| int(&download, "received_bytes"));
+        activity.push(record);
+    }
+
+    for search in rows(&database, "keyword_search_terms") {
+        let url = int(&search, "url_id").and_then(|id| urls.get(&id));
+        let timestamp = url.and_then(|url| int(url, "last_visit_time")).and_then(webkit_to_datetime);
+        let mut record = BrowserActivity::new(BrowserActivityKind::Search, timestamp);
+        record.url = url.and_then(|url| text(url, "url"));
+        record.value = text(&search, "term");
+        activity.push(record);
+    }
+
+    Ok(activity)
+}
+
+/// Cookie metadata from a Chromium `Cookies` database; values are
+/// encrypted and left out
+pub fn parse_chromium_cookies(data: &[u8], wal: Option<&[u8]>) -> Result<Vec<BrowserActivity>> {
+    let database = SqliteDatabase::parse(data, wal)?;
+    Ok(rows(&database, "cookies").iter()
+        .map(|cookie| {
+            let mut record = BrowserActivity::new(BrowserActivityKind::Cookie, int(cookie, "creation_utc").and_then(webkit_to_datetime));
+            record.url = text(cookie, "host_key").map(|host| host + &text(cookie, "path").unwrap_or_default());
+            record.name = text(cookie, "name");
+            record
+        })
+        .collect())
+}
+
+/// Autofill entries from a Chromium `Web Data` database
+pub fn parse_chromium_web_data(data: &[u8], wal: Option<&[u8]>) -> Result<Vec<BrowserActivity>> {
+    let database = SqliteDatabase::parse(data, wal)?;
+    Ok(rows(&database, "autofill").iter()
+        .map(|entry| {
+            let timestamp = int(entry, "date_last_used").or_else(|| int(entry, "date_created")).and_then(unix_to_datetime);
+            let mut record = BrowserActivity::new(BrowserActivityKind::FormEntry, timestamp);
+            record.name = text(entry, "name");
+            record.value = text(entry, "value");
+            record.count = int(entry, "count");
+            record
+        })
+        .collect())
+}
+
+/// Visits and downloads from a Firefox `places.sqlite` database
+pub fn parse_firefox_places(data: &[u8], wal: Option<&[u8]>) -> Result<Vec<BrowserActivity>> {
+    let database = SqliteDatabase::parse(data, wal)?;
+    let places: HashMap<i64, SqliteRow> = rows(&database, "moz_places").into_iter()
+        .filter_map(|row| Some((int(&row, "id")?, row)))
+        .collect();
+    let mut activity = Vec::new();
+
+    for visit in rows(&database, "moz_historyvisits") {
+        let mut record = BrowserActivity::new(BrowserActivityKind::Visit, int(&visit, "visit_date").and_then(prtime_to_datetime));
+        if let Some(place) = int(&visit, "place_id").and_then(|id| places.get(&id)) {
+            record.url = text(place, "url");
+            record.title = text(place, "title");
+            record.count = int(place, "visit_count");
+        }
+        activity.push(record);
+    }
+
+    // Downloads are annotations on the source URL's place
+    let attributes: HashMap<i64, String> = rows(&database, "moz_anno_attributes").into_iter()
+        .filter_map(|row| Some((int(&row, "id")?, text(&row, "name")?)))
+        .collect();
+    let mut downloads: HashMap<i64, BrowserActivity> = HashMap::new();
+    for annotation in rows(&database, "moz_annos") {
+        let Some(place_id) = int(&annotation, "place_id") else {
+            continue;
+        };
+        let attribute = int(&annotation, "anno_attribute_id").and_then(|id| attributes.get(&id));
+        let download = || {
+            let mut record = BrowserActivity::new(BrowserActivityKind::Download, None);
+            record.url = places.get(&place_id).and_then(|place| text(place, "url"));
+            record
+        };
+        match attribute.map(String::as_str) {
+            Some(FIREFOX_DOWNLOAD_DESTINATION) => {
+                let record = downloads.entry(place_id).or_insert_with(download);
+                record.timestamp = int(&annotation, "dateAdded").and_then(prtime_to_datetime);
+                record.value = text(&annotation, "content");
+            }
+            Some(FIREFOX_DOWNLOAD_METADATA) => {
+                let metadata: Option<serde_json::Value> = text(&annotation, "content").and_then(|c| serde_json::from_str(&c).ok());
+                downloads.entry(place_id).or_insert_with(download).size =
+                    metadata.and_then(|m| m.get("fileSize")?.as_i64());
+            }
+            _ => {}
+        }
+    }
+    activity.extend(downloads.into_values());
+
+    Ok(activity)
+}
+
+/// Form and search bar history from a Firefox `formhistory.sqlite` database
+pub fn parse_firefox_form_history(data: &[u8], wal: Option<&[u8]>) -> Result<Vec<BrowserActivity>> {
+    let database = SqliteDatabase::parse(data, wal)?;
+    Ok(rows(&database, "moz_formhistory").iter()
+        .map(|entry| {
+            let name = text(entry, "fieldname");
+            let kind = if name.as_deref() == Some(FIREFOX_SEARCH_FIELD) {
+                BrowserActivityKind::Search
+            } else {
+                BrowserActivityKind::FormEntry
+            };
+            let mut record = BrowserActivity::new(kind, int(entry, "lastUsed").and_then(prtime_to_datetime));
+            record.name = name;
+            record.value = text(entry, "value");
+            record.count = int(entry, "timesUsed");
+            record
+        })
+        .collect())
+}
+
+/// Rows of a table, or none if it is missing or unreadable
+fn rows(database: &SqliteDatabase, table: &str) -> Vec<SqliteRow> {
+    if database.table(table).is_none() {
+        return Vec::new();
+    }
+    database.rows(table).unwrap_or_else(|e| {
+        log::debug!("Skipping table {}: {}", table, e);
+        Vec::new()
+    })
+}
+
+fn int(row: &SqliteRow, column: &str) -> Option<i64> {
+    row.get(column).and_then(SqliteValue::as_i64)
+}
+
+fn text(row: &SqliteRow, column: &str) -> Option<String> {
+    row.get(column).and_then(SqliteValue::as_str).filter(|s| !s.is_empty()).map(str::to_string)
+}
@@ -0,0 +1,491 @@
+// src/parsers/evtx/binxml.rs
+//! Binary XML decoding. Records are usually a single template instance: the
+//! template (element structure with numbered substitution slots) is stored
+//! once per chunk and each record supplies only the substitution values.
+use std::collections::HashMap;
+use std::rc::Rc;
+use anyhow::{Result, bail, Context};
+use crate::utils::bytes::{hex, utf16};
+use crate::utils::guid::format_guid;
+use crate::utils::sid::parse_sid;
+use crate::utils::time::{filetime_to_datetime, systemtime_to_datetime};
+use super::xml::{XmlElement, XmlNode};
+
+const TOKEN_EOF: u8 = 0x00;
+const TOKEN_OPEN_START: u8 = 0x01;
+const TOKEN_CLOSE_START: u8 = 0x02;
+const TOKEN_CLOSE_EMPTY: u8 = 0x03;
+const TOKEN_END_ELEMENT: u8 = 0x04;
+const TOKEN_VALUE: u8 = 0x05;
+const TOKEN_ATTRIBUTE: u8 = 0x06;
+const TOKEN_CDATA: u8 = 0x07;
+const TOKEN_CHAR_REF: u8 = 0x08;
+const TOKEN_ENTITY_REF: u8 = 0x09;
+const TOKEN_PI_TARGET: u8 = 0x0A;
+const TOKEN_PI_DATA: u8 = 0x0B;
+const TOKEN_TEMPLATE_INSTANCE: u8 = 0x0C;
+const TOKEN_NORMAL_SUBSTITUTION: u8 = 0x0D;
+const TOKEN_OPTIONAL_SUBSTITUTION: u8 = 0x0E;
+const TOKEN_FRAGMENT_HEADER: u8 = 0x0F;
+/// Set on element and attribute tokens that are followed by more attributes
+const FLAG_MORE: u8 = 0x40;
+
+const TYPE_NULL: u8 = 0x00;
+const TYPE_STRING: u8 = 0x01;
+const TYPE_ANSI_STRING: u8 = 0x02;
+const TYPE_INT8: u8 = 0x03;
+const TYPE_UINT8: u8 = 0x04;
+const TYPE_INT16: u8 = 0x05;
+const TYPE_UINT16: u8 = 0x06;
+const TYPE_INT32: u8 = 0x07;
+const TYPE_UINT32: u8 = 0x08;
+const TYPE_INT64: u8 = 0x09;
+const TYPE_UINT64: u8 = 0x0A;
+const TYPE_REAL32: u8 = 0x0B;
+const TYPE_REAL64: u8 = 0x0C;
+const TYPE_BOOL: u8 = 0x0D;
+const TYPE_BINARY: u8 = 0x0E;
+const TYPE_GUID: u8 = 0x0F;
+const TYPE_SIZE_T: u8 = 0x10;
+const TYPE_FILETIME: u8 = 0x11;
+const TYPE_SYSTEMTIME: u8 = 0x12;
+const TYPE_SID: u8 = 0x13;
+const TYPE_HEX_INT32: u8 = 0x14;
+const TYPE_HEX_INT64: u8 = 0x15;
+const TYPE_BINXML: u8 = 0x21;
+const TYPE_ARRAY: u8 = 0x80;
+
+/// Template definitions: next-template offset, GUID, data size, then data
+const TEMPLATE_HEADER: usize = 24;
+/// Names: next-name offset, hash, character count, then the characters
+const NAME_HEADER: usize = 8;
+/// Guards against corrupt counts and self-referencing templates
+const MAX_SUBSTITUTIONS: usize = 4096;
+/// Combined nesting of elements, templates and embedded BinXML
+const MAX_DEPTH: usize = 64;
+
+/// A node of a template before its substitutions are filled in
+#[derive(Debug)]
+enum TemplateNode {
+    Element {
+        name: String,
+        attributes: Vec<(String, Vec<TemplateNode>)>,
+        children: Vec<TemplateNode>,
+    },
+    Text(String),
+    Substitution { index: usize, optional: bool },
+    /// An already rendered nested template instance
+    Rendered(Vec<XmlNode>),
+}
+
+/// A decoded substitution value
+#[derive(Debug)]
+enum Value {
+    Null,
+    Text(String),
+    Xml(Vec<XmlNode>),
+}
+
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+    end: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8], pos: usize, end: usize) -> Self {
+        Self { data, pos, end: end.min(data.len()) }
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        if self.pos + length > self.end {
+            bail!("BinXML truncated at offset {:#x}", self.pos);
+        }
+        let bytes = &self.data[self.pos..self.pos + length];
+        self.pos += length;
+        Ok(bytes)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        (self.pos < self.end).then(|| self.data[self.pos])
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    /// A string prefixed with its length in UTF-16 code units
+    fn utf16(&mut self) -> Result<String> {
+        let count = self.u16()? as usize;
+        Ok(utf16(self.take(count * 2)?))
+    }
+}
+
+/// Decodes the BinXML of the records of one chunk, caching its templates
+pub(super) struct BinXmlParser<'a> {
+    chunk: &'a [u8],
+    templates: HashMap<usize, Rc<Vec<TemplateNode>>>,
+    depth: usize,
+}
+
+impl<'a> BinXmlParser<'a> {
+    pub fn new(chunk: &'a [u8]) -> Self {
+        Self { chunk, templates: HashMap::new(), depth: 0 }
+    }
+
+    /// Render the BinXML fragment at `start..end` of the chunk
+    pub fn parse(&mut self, start: usize, end: usize) -> Result<Vec<XmlNode>> {
+        let mut cursor = Cursor::new(self.chunk, start, end);
+        let nodes = self.parse_nodes(&mut cursor)?;
+        Ok(instantiate(&nodes, &[]))
+    }
+
+    /// Parse tokens up to the end of the enclosing element or fragment
+    fn parse_nodes(&mut self, c: &mut Cursor) -> Result<Vec<TemplateNode>> {
+        let mut nodes = Vec::new();
+
+        while let Some(token) = c.peek() {
+            c.pos += 1;
+            match token & !FLAG_MORE {
+                TOKEN_EOF | TOKEN_END_ELEMENT => break,
+                TOKEN_FRAGMENT_HEADER => {
+                    c.take(3)?;
+                }
+                TOKEN_OPEN_START => nodes.push(self.parse_element(c, token & FLAG_MORE != 0)?),
+                TOKEN_VALUE => nodes.push(TemplateNode::Text(read_value_text(c)?)),
+                TOKEN_CDATA => nodes.push(TemplateNode::Text(c.utf16()?)),
+                TOKEN_CHAR_REF => nodes.push(TemplateNode::Text(char_ref(c.u16()?))),
+                TOKEN_ENTITY_REF => nodes.push(TemplateNode::Text(entity_ref(&self.read_name(c)?))),
+                TOKEN_PI_TARGET => {
+                    self.read_name(c)?;
+                }
+                TOKEN_PI_DATA => {
+                    c.utf16()?;
+                }
+                TOKEN_TEMPLATE_INSTANCE => {
+                    nodes.push(TemplateNode::Rendered(self.parse_template_instance(c)?));
+                }
+                TOKEN_NORMAL_SUBSTITUTION | TOKEN_OPTIONAL_SUBSTITUTION => {
+                    nodes.push(read_substitution(c, token)?);
+                }
+                other => bail!("Unexpected BinXML token {:#04x} at offset {:#x}", other, c.pos - 1),
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    fn parse_element(&mut self, c: &mut Cursor, has_attributes: bool) -> Result<TemplateNode> {
+        let _dependency_id = c.u16()?;
+        let _data_size = c.u32()?;
+        let name = self.read_name(c)?;
+
+        let mut attributes = Vec::new();
+        if has_attributes {
+            let _list_size = c.u32()?;
+            while c.peek().is_some_and(|t| t & !FLAG_MORE == TOKEN_ATTRIBUTE) {
+                c.pos += 1;
+                let name = self.read_name(c)?;
+                attributes.push((name, self.parse_attribute_value(c)?));
+            }
+        }
+
+        let children = match c.u8()? {
+            TOKEN_CLOSE_EMPTY => Vec::new(),
+            TOKEN_CLOSE_START => self.nested(|parser| parser.parse_nodes(c))?,
+            token => bail!("Unexpected BinXML token {:#04x} in element {}", token, name),
+        };
+        Ok(TemplateNode::Element { name, attributes, children })
+    }
+
+    fn parse_attribute_value(&mut self, c: &mut Cursor) -> Result<Vec<TemplateNode>> {
+        let mut parts = Vec::new();
+
+        while let Some(token) = c.peek() {
+            match token & !FLAG_MORE {
+                TOKEN_VALUE => {
+                    c.pos += 1;
+                    parts.push(TemplateNode::Text(read_value_text(c)?));
+                }
+                TOKEN_CHAR_REF => {
+                    c.pos += 1;
+                    parts.push(TemplateNode::Text(char_ref(c.u16()?)));
+                }
+                TOKEN_ENTITY_REF => {
+                    c.pos += 1;
+                    parts.push(TemplateNode::Text(entity_ref(&self.read_name(c)?)));
+                }
+                TOKEN_NORMAL_SUBSTITUTION | TOKEN_OPTIONAL_SUBSTITUTION => {
+                    c.pos += 1;
+                    parts.push(read_substitution(c, token)?);
+                }
+                _ => break,
+            }
+        }
+
+        Ok(parts)
+    }
+
+    /// Run `parse` one level deeper, failing past `MAX_DEPTH` rather than
+    /// recursing without bound on crafted input
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth >= MAX_DEPTH {
+            bail!("BinXML nested too deeply");
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Names are stored once per chunk; the first use defines them inline
+    fn read_name(&self, c: &mut Cursor) -> Result<String> {
+        let offset = c.u32()? as usize;
+        let count = self.chunk.get(offset + 6..offset + 8)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+            .context("Name offset outside chunk")?;
+        let characters = self.chunk.get(offset + NAME_HEADER..offset + NAME_HEADER + count * 2)
+            .context("Name outside chunk")?;
+
+        if offset == c.pos {
+            c.pos += NAME_HEADER + (count + 1) * 2;
+        }
+        Ok(utf16(characters))
+    }
+
+    fn parse_template_instance(&mut self, c: &mut Cursor) -> Result<Vec<XmlNode>> {
+        let _version = c.u8()?;
+        let _template_id = c.u32()?;
+        let offset = c.u32()? as usize;
+
+        let template = self.template(offset)?;
+        if offset == c.pos {
+            c.pos += TEMPLATE_HEADER + template_size(self.chunk, offset)?;
+        }
+
+        let count = c.u32()? as usize;
+        if count > MAX_SUBSTITUTIONS {
+            bail!("Implausible substitution count {}", count);
+        }
+        let mut descriptors = Vec::with_capacity(count);
+        for _ in 0..count {
+            let size = c.u16()? as usize;
+            let value_type = c.u8()?;
+            c.u8()?;
+            descriptors.push((size, value_type));
+        }
+
+        let mut values = Vec::with_capacity(count);
+        for (size, value_type) in descriptors {
+            let start = c.pos;
+            let data = c.take(size)?;
+            values.push(self.decode_value(data, value_type, start)?);
+        }
+
+        Ok(instantiate(&template, &values))
+    }
+
+    fn template(&mut self, offset: usize) -> Result<Rc<Vec<TemplateNode>>> {
+        if let Some(template) = self.templates.get(&offset) {
+            return Ok(template.clone());
+        }
+
+        let start = offset + TEMPLATE_HEADER;
+        let mut cursor = Cursor::new(self.chunk, start, start + template_size(self.chunk, offset)?);
+        let template = Rc::new(self.nested(|parser| parser.parse_nodes(&mut cursor))?);
+        self.templates.insert(offset, template.clone());
+        Ok(template)
+    }
+
+    /// Render a substitution value as text, or as nodes for embedded BinXML
+    fn decode_value(&mut self, data: &[u8], value_type: u8, offset: usize) -> Result<Value> {
+        if data.is_empty() || value_type == TYPE_NULL {
+            return Ok(Value::Null);
+        }
+
+        if value_type == TYPE_BINXML {
+            let mut cursor = Cursor::new(self.chunk, offset, offset + data.len());
+            let nodes = self.nested(|parser| parser.parse_nodes(&mut cursor))?;
+            return Ok(Value::Xml(instantiate(&nodes, &[])));
+        }
+
+        if value_type & TYPE_ARRAY != 0 {
+            let element_type = value_type & !TYPE_ARRAY;
+            let items: Vec<String> = match element_type {
+                TYPE_STRING => utf16(data).split('\0').filter(|s| !s.is_empty()).map(str::to_string).collect(),
+                TYPE_ANSI_STRING => ansi(data).split('\0').filter(|s| !s.is_empty()).map(str::to_string).collect(),
+                _ => match fixed_size(element_type) {
+                    Some(size) => data.chunks_exact(size).map(|item| format_scalar(item, element_type)).collect(),
+                    None => return Ok(Value::Text(hex(data).to_uppercase())),
+                },
+            };
+            return Ok(Value::Text(items.join(",")));
+        }
+
+        Ok(Value::Text(format_scalar(data, value_type)))
+    }
+}
+
+fn template_size(chunk: &[u8], offset: usize) -> Result<usize> {
+    let size = chunk.get(offset + 20..offset + 24).context("Template offset outside chunk")?;
+    Ok(u32::from_le_bytes(size.try_into()?) as usize)
+}
+
+fn read_substitution(c: &mut Cursor, token: u8) -> Result<TemplateNode> {
+    let index = c.u16()? as usize;
+    let _value_type = c.u8()?;
+    Ok(TemplateNode::Substitution { index, optional: token == TOKEN_OPTIONAL_SUBSTITUTION })
+}
+
+/// Literal values in templates are always strings
+fn read_value_text(c: &mut Cursor) -> Result<String> {
+    match c.u8()? {
+        TYPE_STRING => c.utf16(),
+        value_type => bail!("Unsupported BinXML literal value type {:#04x}", value_type),
+    }
+}
+
+fn fixed_size(value_type: u8) -> Option<usize> {
+    match value_type {
+        TYPE_INT8 | TYPE_UINT8 => Some(1),
+        TYPE_INT16 | TYPE_UINT16 => Some(2),
+        TYPE_INT32 | TYPE_UINT32 | TYPE_REAL32 | TYPE_BOOL | TYPE_HEX_INT32 => Some(4),
+        TYPE_INT64 | TYPE_UINT64 | TYPE_REAL64 | TYPE_FILETIME | TYPE_HEX_INT64 | TYPE_SIZE_T => Some(8),
+        TYPE_GUID | TYPE_SYSTEMTIME => Some(16),
+        _ => None,
+    }
+}
+
+/// Format a value the way Event Viewer renders it in XML
+fn format_scalar(data: &[u8], value_type: u8) -> String {
+    let int = |n: usize| -> Option<u64> {
+        let mut bytes = [0u8; 8];
+        bytes[..n].copy_from_slice(data.get(..n)?);
+        Some(u64::from_le_bytes(bytes))
+    };
+
+    let text = match value_type {
+        TYPE_STRING => Some(utf16(data).trim_end_matches('\0').to_string()),
+        TYPE_ANSI_STRING => Some(ansi(data).trim_end_matches('\0').to_string()),
+        TYPE_INT8 => int(1).map(|v| (v as i8).to_string()),
+        TYPE_UINT8 => int(1).map(|v| v.to_string()),
+        TYPE_INT16 => int(2).map(|v| (v as i16).to_string()),
+        TYPE_UINT16 => int(2).map(|v| v.to_string()),
+        TYPE_INT32 => int(4).map(|v| (v as i32).to_string()),
+        TYPE_UINT32 => int(4).map(|v| v.to_string()),
+        TYPE_INT64 => int(8).map(|v| (v as i64).to_string()),
+        TYPE_UINT64 => int(8).map(|v| v.to_string()),
+        TYPE_REAL32 => int(4).map(|v| f32::from_bits(v as u32).to_string()),
+        TYPE_REAL64 => int(8).map(|v| f64::from_bits(v).to_string()),
+        TYPE_BOOL => int(4).map(|v| (v != 0).to_string()),
+        // Event Viewer shows binary data as uppercase hex
+        TYPE_BINARY => Some(hex(data).to_uppercase()),
+        TYPE_GUID => format_guid(data),
+        TYPE_SIZE_T if data.len() == 4 => int(4).map(|v| format!("0x{:08x}", v)),
+        TYPE_SIZE_T => int(8).map(|v| format!("0x{:016x}", v)),
+        // FILETIMEs keep their full 100ns precision
+        TYPE_FILETIME => int(8).and_then(filetime_to_datetime)
+            .map(|t| format!("{}.{:07}Z", t.format("%Y-%m-%dT%H:%M:%S"), t.timestamp_subsec_nanos() / 100)),
+        TYPE_SYSTEMTIME => systemtime_to_datetime(data)
+            .map(|t| t.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
+        TYPE_SID => parse_sid(data).map(|(sid, _)| sid),
+        TYPE_HEX_INT32 => int(4).map(|v| format!("0x{:x}", v)),
+        TYPE_HEX_INT64 => int(8).map(|v| format!("0x{:x}", v)),
+        _ => None,
+    };
+
+    text.unwrap_or_else(|| hex(data).to_uppercase())
+}
+
+/// Fill a template's substitution slots with the instance's values
+fn instantiate(nodes: &[TemplateNode], values: &[Value]) -> Vec<XmlNode> {
+    let mut out = Vec::new();
+
+    for node in nodes {
+        match node {
+            TemplateNode::Element { name, attributes, children } => {
+                let attributes = attributes.iter()
+                    .filter_map(|(name, parts)| {
+                        let value = attribute_value(parts, values)?;
+                        Some((name.clone(), value))
+                    })
+                    .collect();
+                out.push(XmlNode::Element(XmlElement {
+                    name: name.clone(),
+                    attributes,
+                    children: instantiate(children, values),
+                }));
+            }
+            TemplateNode::Text(text) => push_text(&mut out, text),
+            TemplateNode::Substitution { index, .. } => match values.get(*index) {
+                Some(Value::Text(text)) => push_text(&mut out, text),
+                Some(Value::Xml(nodes)) => out.extend(nodes.iter().cloned()),
+                Some(Value::Null) | None => {}
+            },
+            TemplateNode::Rendered(nodes) => out.extend(nodes.iter().cloned()),
+        }
+    }
+
+    out
+}
+
+/// An attribute made up only of empty optional substitutions is omitted
+fn attribute_value(parts: &[TemplateNode], values: &[Value]) -> Option<String> {
+    let mut text = String::new();
+    let mut present = false;
+
+    for part in parts {
+        match part {
+            TemplateNode::Text(literal) => {
+                text.push_str(literal);
+                present = true;
+            }
+            TemplateNode::Substitution { index, optional } => match values.get(*index) {
+                Some(Value::Text(value)) => {
+                    text.push_str(value);
+                    present = true;
+                }
+                Some(Value::Xml(_)) => present = true,
+                Some(Value::Null) | None => present |= !optional,
+            },
+            _ => {}
+        }
+    }
+
+    present.then_some(text)
+}
+
+fn push_text(out: &mut Vec<XmlNode>, text: &str) {
+    if let Some(XmlNode::Text(previous)) = out.last_mut() {
+        previous.push_str(text);
+    } else {
+        out.push(XmlNode::Text(text.to_string()));
+    }
+}
+
+fn char_ref(code: u16) -> String {
+    char::from_u32(code as u32).map(String::from).unwrap_or_default()
+}
+
+fn entity_ref(name: &str) -> String {
+    match name {
+        "amp" => "&".to_string(),
+        "lt" => "<".to_string(),
+        "gt" => ">".to_string(),
+        "quot" => "\"".to_string(),
+        "apos" => "'".to_string(),
+        _ => format!("&{};", name),
+    }
+}
+
+fn ansi(data: &[u8]) -> String {
+    data.iter().map(|&b| b as char).collect()
+}
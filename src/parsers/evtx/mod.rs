@@ -0,0 +1,286 @@
+// src/parsers/evtx/mod.rs
+//! Native parser for Windows XML event logs (`.evtx`). The file is a 4 KiB
+//! header followed by 64 KiB chunks, each with its own string and template
+//! tables. Records in dirty or corrupt chunks, and in the slack space after
+//! the last record of a chunk, are carved and marked as recovered.
+mod binxml;
+mod summary;
+mod xml;
+
+use std::collections::BTreeMap;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::utils::bytes::{u16_at, u32_at, u64_at};
+use crate::utils::time::filetime_to_datetime;
+use binxml::BinXmlParser;
+
+pub use summary::{
+    summarize_events, AccountAction, AccountChange, EventSummarizer, EventSummary, LogClear, LogonAction,
+    LogonEvent, PowerShellCommand, ProcessCreation, RdpAction, RdpSessionEvent, ScriptBlock, ServiceInstall,
+    TaskAction, TaskChange,
+};
+pub use xml::{XmlElement, XmlNode};
+
+const FILE_SIGNATURE: &[u8] = b"ElfFile\0";
+const CHUNK_SIGNATURE: &[u8] = b"ElfChnk\0";
+const RECORD_SIGNATURE: &[u8] = b"**\0\0";
+
+const FILE_HEADER_SIZE: usize = 4096;
+const CHUNK_SIZE: usize = 65536;
+const CHUNK_HEADER_SIZE: usize = 512;
+/// Signature, size, record ID and timestamp precede the BinXML
+const RECORD_HEADER_SIZE: usize = 24;
+
+const FLAG_DIRTY: u32 = 0x1;
+const FLAG_FULL: u32 = 0x2;
+
+/// The file header
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvtxHeader {
+    pub first_chunk: u64,
+    pub last_chunk: u64,
+    pub next_record_id: u64,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub chunk_count: u16,
+    /// Set while the log is open for writing; the header may be stale
+    pub dirty: bool,
+    pub full: bool,
+    pub checksum_valid: bool,
+}
+
+/// Validation results for one chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvtxChunk {
+    pub index: usize,
+    pub first_record_id: u64,
+    pub last_record_id: u64,
+    pub header_checksum_valid: bool,
+    pub data_checksum_valid: bool,
+    pub records: usize,
+    pub recovered_records: usize,
+}
+
+/// A parsed event log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvtxLog {
+    pub header: EvtxHeader,
+    pub chunks: Vec<EvtxChunk>,
+    /// Records ordered by record ID
+    pub records: Vec<EvtxRecord>,
+}
+
+/// An event record, with the commonly used System fields pulled out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvtxRecord {
+    pub record_id: u64,
+    /// `System/TimeCreated@SystemTime`, or when the record was written
+    pub timestamp: Option<DateTime<Utc>>,
+    pub provider: Option<String>,
+    pub channel: Option<String>,
+    pub event_id: Option<u32>,
+    pub level: Option<u8>,
+    pub computer: Option<String>,
+    pub user_sid: Option<String>,
+    /// `EventData/Data` by name, or `UserData` fields. Unnamed data items
+    /// are keyed `param1`, `param2`, ... after their message inserts.
+    pub event_data: BTreeMap<String, String>,
+    /// Carved from a dirty or corrupt chunk, or from chunk slack
+    pub recovered: bool,
+    #[serde(skip)]
+    pub root: XmlElement,
+}
+
+impl EvtxRecord {
+    /// Render the record as event XML
+    pub fn to_xml(&self) -> String {
+        self.root.to_xml()
+    }
+
+    /// Render the record as JSON, keyed by element name
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ self.root.name.clone(): self.root.to_json() })
+    }
+
+    fn from_xml(root: XmlElement, record_id: u64, written: Option<DateTime<Utc>>, recovered: bool) -> Self {
+        let system = root.child("System");
+        let text = |name: &str| system
+            .and_then(|s| s.child(name))
+            .map(XmlElement::text)
+            .filter(|t| !t.is_empty());
+        let attribute = |name: &str, attribute: &str| system
+            .and_then(|s| s.child(name))
+            .and_then(|e| e.attribute(attribute))
+            .map(str::to_string);
+
+        let timestamp = attribute("TimeCreated", "SystemTime")
+            .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .or(written);
+
+        Self {
+            record_id,
+            timestamp,
+            provider: attribute("Provider", "Name"),
+            channel: text("Channel"),
+            event_id: text("EventID").and_then(|id| id.parse().ok()),
+            level: text("Level").and_then(|level| level.parse().ok()),
+            computer: text("Computer"),
+            user_sid: attribute("Security", "UserID"),
+            event_data: event_data(&root),
+            recovered,
+            root,
+        }
+    }
+}
+
+fn event_data(root: &XmlElement) -> BTreeMap<String, String> {
+    let mut data = BTreeMap::new();
+
+    if let Some(event_data) = root.child("EventData") {
+        for (i, item) in event_data.elements().enumerate() {
+            let key = match item.attribute("Name") {
+                Some(name) => name.to_string(),
+                None if item.name == "Data" => format!("param{}", i + 1),
+                None => item.name.clone(),
+            };
+            data.insert(key, item.text());
+        }
+    }
+
+    // UserData holds a single provider-defined element
+    if let Some(user_data) = root.child("UserData").and_then(|u| u.elements().next()) {
+        for item in user_data.elements() {
+            data.insert(item.name.clone(), item.text());
+        }
+    }
+
+    data
+}
+
+/// Parse an event log, recovering what it can from damaged chunks
+pub fn parse_evtx(data: &[u8]) -> Result<EvtxLog> {
+    if data.len() < FILE_HEADER_SIZE || !data.starts_with(FILE_SIGNATURE) {
+        bail!("Not an EVTX file");
+    }
+
+    let flags = u32_at(data, 120).unwrap_or_default();
+    let header = EvtxHeader {
+        first_chunk: u64_at(data, 8).unwrap_or_default(),
+        last_chunk: u64_at(data, 16).unwrap_or_default(),
+        next_record_id: u64_at(data, 24).unwrap_or_default(),
+        minor_version: u16_at(data, 36).unwrap_or_default(),
+        major_version: u16_at(data, 38).unwrap_or_default(),
+        chunk_count: u16_at(data, 42).unwrap_or_default(),
+        dirty: flags & FLAG_DIRTY != 0,
+        full: flags & FLAG_FULL != 0,
+        checksum_valid: u32_at(data, 124) == Some(crc32fast::hash(&data[..120])),
+    };
+
+    // The chunk count is unreliable in dirty logs, so walk every chunk
+    let mut chunks = Vec::new();
+    let mut records = Vec::new();
+    for (index, chunk) in data[FILE_HEADER_SIZE..].chunks_exact(CHUNK_SIZE).enumerate() {
+        if !chunk.starts_with(CHUNK_SIGNATURE) {
+            continue;
+        }
+        let (info, chunk_records) = parse_chunk(chunk, index);
+        chunks.push(info);
+        records.extend(chunk_records);
+    }
+
+    // Prefer the intact copy when a record was also carved
+    records.sort_by_key(|r| (r.record_id, r.recovered));
+    records.dedup_by_key(|r| r.record_id);
+
+    Ok(EvtxLog { header, chunks, records })
+}
+
+fn parse_chunk(chunk: &[u8], index: usize) -> (EvtxChunk, Vec<EvtxRecord>) {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&chunk[..120]);
+    hasher.update(&chunk[128..CHUNK_HEADER_SIZE]);
+    let header_checksum_valid = u32_at(chunk, 124) == Some(hasher.finalize());
+
+    let free_space = (u32_at(chunk, 48).unwrap_or_default() as usize).clamp(CHUNK_HEADER_SIZE, CHUNK_SIZE);
+    let data_checksum_valid = u32_at(chunk, 52) == Some(crc32fast::hash(&chunk[CHUNK_HEADER_SIZE..free_space]));
+
+    let mut parser = BinXmlParser::new(chunk);
+    let mut records = Vec::new();
+
+    let carve_from = if header_checksum_valid && data_checksum_valid {
+        let mut pos = CHUNK_HEADER_SIZE;
+        while pos + RECORD_HEADER_SIZE <= free_space && chunk[pos..].starts_with(RECORD_SIGNATURE) {
+            match parse_record(&mut parser, chunk, pos, false) {
+                Ok((record, size)) => {
+                    records.push(record);
+                    pos += size;
+                }
+                Err(e) => {
+                    log::debug!("Chunk {} record at {:#x}: {}", index, pos, e);
+                    break;
+                }
+            }
+        }
+        // Continue past a damaged record and into the slack space
+        pos
+    } else {
+        log::debug!("Chunk {} fails checksum validation; carving records", index);
+        CHUNK_HEADER_SIZE
+    };
+    let intact = records.len();
+    records.extend(carve_records(&mut parser, chunk, carve_from));
+
+    let info = EvtxChunk {
+        index,
+        first_record_id: u64_at(chunk, 24).unwrap_or_default(),
+        last_record_id: u64_at(chunk, 32).unwrap_or_default(),
+        header_checksum_valid,
+        data_checksum_valid,
+        records: records.len(),
+        recovered_records: records.len() - intact,
+    };
+    (info, records)
+}
+
+/// Scan for record signatures whose trailing size copy matches
+fn carve_records(parser: &mut BinXmlParser, chunk: &[u8], start: usize) -> Vec<EvtxRecord> {
+    let mut records = Vec::new();
+    let mut pos = start;
+
+    while pos + RECORD_HEADER_SIZE <= chunk.len() {
+        if !chunk[pos..].starts_with(RECORD_SIGNATURE) {
+            pos += 1;
+            continue;
+        }
+        match parse_record(parser, chunk, pos, true) {
+            Ok((record, size)) => {
+                records.push(record);
+                pos += size;
+            }
+            Err(_) => pos += 1,
+        }
+    }
+
+    records
+}
+
+fn parse_record(parser: &mut BinXmlParser, chunk: &[u8], pos: usize, recovered: bool) -> Result<(EvtxRecord, usize)> {
+    let size = u32_at(chunk, pos + 4).unwrap_or_default() as usize;
+    if size < RECORD_HEADER_SIZE + 4 || pos + size > chunk.len() || u32_at(chunk, pos + size - 4) != Some(size as u32) {
+        bail!("Invalid record size {}", size);
+    }
+
+    let record_id = u64_at(chunk, pos + 8).unwrap_or_default();
+    let written = filetime_to_datetime(u64_at(chunk, pos + 16).unwrap_or_default());
+    let nodes = parser.parse(pos + RECORD_HEADER_SIZE, pos + size - 4)?;
+    let Some(root) = nodes.into_iter().find_map(|node| match node {
+        XmlNode::Element(element) => Some(element),
+        XmlNode::Text(_) => None,
+    }) else {
+        bail!("Record {} has no root element", record_id);
+    };
+
+    Ok((EvtxRecord::from_xml(root, record_id, written, recovered), size))
+}
@@ -0,0 +1,529 @@
+// src/parsers/evtx/summary.rs
+//! Tables of the events checked in nearly every investigation: logons,
+//! account and group changes, service installs, log clearing, process
+//! creation, PowerShell, RDP sessions and scheduled task changes.
+use std::collections::BTreeMap;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use super::EvtxRecord;
+
+const SECURITY_AUDITING: &str = "Microsoft-Windows-Security-Auditing";
+const SERVICE_CONTROL_MANAGER: &str = "Service Control Manager";
+const EVENTLOG: &str = "Microsoft-Windows-Eventlog";
+const POWERSHELL: &str = "Microsoft-Windows-PowerShell";
+const LOCAL_SESSION_MANAGER: &str = "Microsoft-Windows-TerminalServices-LocalSessionManager";
+const REMOTE_CONNECTION_MANAGER: &str = "Microsoft-Windows-TerminalServices-RemoteConnectionManager";
+const TASK_SCHEDULER: &str = "Microsoft-Windows-TaskScheduler";
+
+/// All curated tables, each ordered by time
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct EventSummary {
+    pub logons: Vec<LogonEvent>,
+    pub account_changes: Vec<AccountChange>,
+    pub service_installs: Vec<ServiceInstall>,
+    pub log_clears: Vec<LogClear>,
+    pub process_creations: Vec<ProcessCreation>,
+    /// 4104 script blocks, reassembled from their fragments
+    pub script_blocks: Vec<ScriptBlock>,
+    /// 4103 module logging (pipeline execution details)
+    pub powershell_commands: Vec<PowerShellCommand>,
+    pub rdp_sessions: Vec<RdpSessionEvent>,
+    pub task_changes: Vec<TaskChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogonAction {
+    /// 4624
+    Logon,
+    /// 4625
+    FailedLogon,
+    /// 4634
+    Logoff,
+    /// 4648: a process supplied credentials explicitly (runas, PsExec, ...)
+    ExplicitCredentials,
+    /// 4672: administrator-equivalent privileges assigned to a new logon
+    SpecialPrivileges,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogonEvent {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub record_id: u64,
+    pub event_id: u32,
+    pub action: LogonAction,
+    /// `DOMAIN\user` of the account logged on, or whose credentials were used
+    pub user: Option<String>,
+    pub user_sid: Option<String>,
+    /// Account that requested the logon (4648)
+    pub subject_user: Option<String>,
+    pub logon_type: Option<u32>,
+    /// e.g. `Interactive`, `Network`, `RemoteInteractive`
+    pub logon_type_name: Option<String>,
+    pub logon_id: Option<String>,
+    pub source_ip: Option<String>,
+    pub source_port: Option<String>,
+    pub workstation: Option<String>,
+    /// Target server name (4648)
+    pub target_server: Option<String>,
+    pub process_name: Option<String>,
+    pub authentication_package: Option<String>,
+    /// NTSTATUS reason for failed logons, e.g. `0xc000006a` (bad password)
+    pub status: Option<String>,
+    pub sub_status: Option<String>,
+    /// Assigned privileges (4672)
+    pub privileges: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountAction {
+    /// 4720
+    UserCreated,
+    /// 4722
+    UserEnabled,
+    /// 4732: member added to a security-enabled local group
+    LocalGroupMemberAdded,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountChange {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub record_id: u64,
+    pub event_id: u32,
+    pub action: AccountAction,
+    /// The account created or enabled
+    pub account: Option<String>,
+    pub account_sid: Option<String>,
+    /// Group and member, for group changes
+    pub group: Option<String>,
+    pub member: Option<String>,
+    pub member_sid: Option<String>,
+    /// Account that made the change
+    pub changed_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceInstall {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub record_id: u64,
+    /// 7045 (System) or 4697 (Security)
+    pub event_id: u32,
+    pub service_name: Option<String>,
+    pub image_path: Option<String>,
+    pub service_type: Option<String>,
+    pub start_type: Option<String>,
+    /// Account the service runs as
+    pub account: Option<String>,
+    /// Account that installed it (4697 only)
+    pub installed_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogClear {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub record_id: u64,
+    /// 1102 (Security cleared) or 104 (any other log cleared)
+    pub event_id: u32,
+    pub log_name: Option<String>,
+    pub user: Option<String>,
+    pub backup_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessCreation {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub record_id: u64,
+    pub process_name: Option<String>,
+    pub process_id: Option<u32>,
+    /// Only present when command line auditing is enabled
+    pub command_line: Option<String>,
+    /// Recorded from Windows 10 on
+    pub parent_process_name: Option<String>,
+    pub parent_process_id: Option<u32>,
+    pub user: Option<String>,
+    pub logon_id: Option<String>,
+    /// `%%1936` (full token, UAC off or built-in admin), `%%1937`
+    /// (elevated) or `%%1938` (limited)
+    pub token_elevation_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptBlock {
+    /// Time of the first fragment
+    pub timestamp: Option<DateTime<Utc>>,
+    pub script_block_id: String,
+    pub path: Option<String>,
+    pub text: String,
+    pub fragments: u32,
+    pub expected_fragments: u32,
+    /// Every fragment was found; otherwise `text` has gaps
+    pub complete: bool,
+    pub user_sid: Option<String>,
+    /// Logged at warning level: PowerShell flagged the content as suspicious
+    pub suspicious: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerShellCommand {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub record_id: u64,
+    pub host_application: Option<String>,
+    pub command_name: Option<String>,
+    pub script_name: Option<String>,
+    pub user: Option<String>,
+    /// Pipeline output, e.g. `CommandInvocation(Invoke-WebRequest): ...`
+    pub payload: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RdpAction {
+    /// 1149: network authentication succeeded (RemoteConnectionManager)
+    Authenticated,
+    /// 21
+    Logon,
+    /// 24
+    Disconnected,
+    /// 25
+    Reconnected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RdpSessionEvent {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub record_id: u64,
+    pub event_id: u32,
+    pub action: RdpAction,
+    pub user: Option<String>,
+    pub session_id: Option<String>,
+    pub source_ip: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskAction {
+    /// 106
+    Registered,
+    /// 140
+    Updated,
+    /// 141
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskChange {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub record_id: u64,
+    pub event_id: u32,
+    pub action: TaskAction,
+    pub task_name: Option<String>,
+    pub user: Option<String>,
+}
+
+/// Build the curated tables from the records of any number of logs
+pub fn summarize_events<'a>(records: impl IntoIterator<Item = &'a EvtxRecord>) -> EventSummary {
+    let mut summarizer = EventSummarizer::default();
+    for record in records {
+        summarizer.add(record);
+    }
+    summarizer.finish()
+}
+
+/// Builds the curated tables one record at a time, so that logs can be
+/// dropped once they have been read. Only script block fragments are kept
+/// until the end, as their parts may be spread over several logs.
+#[derive(Debug, Default)]
+pub struct EventSummarizer {
+    summary: EventSummary,
+    fragments: BTreeMap<String, Vec<EvtxRecord>>,
+}
+
+impl EventSummarizer {
+    pub fn add(&mut self, record: &EvtxRecord) {
+        let (Some(provider), Some(event_id)) = (record.provider.as_deref(), record.event_id) else {
+            return;
+        };
+        let summary = &mut self.summary;
+
+        match (provider, event_id) {
+            (SECURITY_AUDITING, 4624 | 4625 | 4634 | 4648 | 4672) => {
+                summary.logons.push(logon_event(record, event_id));
+            }
+            (SECURITY_AUDITING, 4720 | 4722 | 4732) => {
+                summary.account_changes.push(account_change(record, event_id));
+            }
+            (SERVICE_CONTROL_MANAGER, 7045) | (SECURITY_AUDITING, 4697) => {
+                summary.service_installs.push(service_install(record, event_id));
+            }
+            // 1102 comes from the Eventlog provider, logged into Security
+            (EVENTLOG, 1102 | 104) => summary.log_clears.push(log_clear(record, event_id)),
+            (SECURITY_AUDITING, 4688) => summary.process_creations.push(process_creation(record)),
+            (POWERSHELL, 4104) => {
+                if let Some(id) = field(record, "ScriptBlockId") {
+                    self.fragments.entry(id).or_default().push(record.clone());
+                }
+            }
+            (POWERSHELL, 4103) => summary.powershell_commands.push(powershell_command(record)),
+            (LOCAL_SESSION_MANAGER, 21 | 24 | 25) | (REMOTE_CONNECTION_MANAGER, 1149) => {
+                summary.rdp_sessions.push(rdp_event(record, event_id));
+            }
+            (TASK_SCHEDULER, 106 | 140 | 141) => summary.task_changes.push(task_change(record, event_id)),
+            _ => {}
+        }
+    }
+
+    /// Reassemble the script blocks and order every table by time
+    pub fn finish(self) -> EventSummary {
+        let mut summary = self.summary;
+        summary.script_blocks = self.fragments.into_iter()
+            .map(|(id, parts)| reassemble_script_block(id, parts.iter().collect()))
+            .collect();
+
+        summary.logons.sort_by_key(|e| e.timestamp);
+        summary.account_changes.sort_by_key(|e| e.timestamp);
+        summary.service_installs.sort_by_key(|e| e.timestamp);
+        summary.log_clears.sort_by_key(|e| e.timestamp);
+        summary.process_creations.sort_by_key(|e| e.timestamp);
+        summary.script_blocks.sort_by_key(|e| e.timestamp);
+        summary.powershell_commands.sort_by_key(|e| e.timestamp);
+        summary.rdp_sessions.sort_by_key(|e| e.timestamp);
+        summary.task_changes.sort_by_key(|e| e.timestamp);
+        summary
+    }
+}
+
+fn logon_event(record: &EvtxRecord, event_id: u32) -> LogonEvent {
+    let logon_type = field(record, "LogonType").and_then(|t| t.parse().ok());
+    let (action, user, user_sid, logon_id) = match event_id {
+        4624 => (LogonAction::Logon, account(record, "Target"), field(record, "TargetUserSid"), field(record, "TargetLogonId")),
+        4625 => (LogonAction::FailedLogon, account(record, "Target"), field(record, "TargetUserSid"), None),
+        4634 => (LogonAction::Logoff, account(record, "Target"), field(record, "TargetUserSid"), field(record, "TargetLogonId")),
+        4648 => (LogonAction::ExplicitCredentials, account(record, "Target"), None, field(record, "SubjectLogonId")),
+        _ => (LogonAction::SpecialPrivileges, account(record, "Subject"), field(record, "SubjectUserSid"), field(record, "SubjectLogonId")),
+    };
+
+    LogonEvent {
+        timestamp: record.timestamp,
+        record_id: record.record_id,
+        event_id,
+        action,
+        user,
+        user_sid,
+        subject_user: (event_id == 4648).then(|| account(record, "Subject")).flatten(),
+        logon_type,
+        logon_type_name: logon_type.and_then(logon_type_name).map(str::to_string),
+        logon_id,
+        source_ip: field(record, "IpAddress"),
+        source_port: field(record, "IpPort").filter(|p| p != "0"),
+        workstation: field(record, "WorkstationName"),
+        target_server: field(record, "TargetServerName"),
+        process_name: field(record, "ProcessName"),
+        authentication_package: field(record, "AuthenticationPackageName"),
+        status: field(record, "Status"),
+        sub_status: field(record, "SubStatus").filter(|s| s != "0x0"),
+        privileges: field(record, "PrivilegeList")
+            .map(|list| list.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default(),
+    }
+}
+
+fn logon_type_name(logon_type: u32) -> Option<&'static str> {
+    Some(match logon_type {
+        0 => "System",
+        2 => "Interactive",
+        3 => "Network",
+        4 => "Batch",
+        5 => "Service",
+        7 => "Unlock",
+        8 => "NetworkCleartext",
+        9 => "NewCredentials",
+        10 => "RemoteInteractive",
+        11 => "CachedInteractive",
+        12 => "CachedRemoteInteractive",
+        13 => "CachedUnlock",
+        _ => return None,
+    })
+}
+
+fn account_change(record: &EvtxRecord, event_id: u32) -> AccountChange {
+    let action = match event_id {
+        4720 => AccountAction::UserCreated,
+        4722 => AccountAction::UserEnabled,
+        _ => AccountAction::LocalGroupMemberAdded,
+    };
+    // For group changes the Target fields name the group
+    let target = account(record, "Target");
+    let is_group = action == AccountAction::LocalGroupMemberAdded;
+
+    AccountChange {
+        timestamp: record.timestamp,
+        record_id: record.record_id,
+        event_id,
+        action,
+        account: if is_group { None } else { target.clone() },
+        account_sid: if is_group { None } else { field(record, "TargetSid") },
+        group: if is_group { target } else { None },
+        member: field(record, "MemberName"),
+        member_sid: field(record, "MemberSid"),
+        changed_by: account(record, "Subject"),
+    }
+}
+
+fn service_install(record: &EvtxRecord, event_id: u32) -> ServiceInstall {
+    // 7045 and 4697 name the same things differently
+    let either = |system: &str, security: &str| field(record, system).or_else(|| field(record, security));
+
+    ServiceInstall {
+        timestamp: record.timestamp,
+        record_id: record.record_id,
+        event_id,
+        service_name: field(record, "ServiceName"),
+        image_path: either("ImagePath", "ServiceFileName"),
+        service_type: field(record, "ServiceType"),
+        start_type: either("StartType", "ServiceStartType"),
+        account: either("AccountName", "ServiceAccount"),
+        installed_by: account(record, "Subject"),
+    }
+}
+
+fn log_clear(record: &EvtxRecord, event_id: u32) -> LogClear {
+    LogClear {
+        timestamp: record.timestamp,
+        record_id: record.record_id,
+        event_id,
+        log_name: field(record, "Channel").or_else(|| (event_id == 1102).then(|| "Security".to_string())),
+        user: account(record, "Subject"),
+        backup_path: field(record, "BackupPath"),
+    }
+}
+
+fn process_creation(record: &EvtxRecord) -> ProcessCreation {
+    let pid = |name: &str| field(record, name).and_then(|id| parse_hex(&id));
+
+    ProcessCreation {
+        timestamp: record.timestamp,
+        record_id: record.record_id,
+        process_name: field(record, "NewProcessName"),
+        process_id: pid("NewProcessId"),
+        command_line: field(record, "CommandLine"),
+        parent_process_name: field(record, "ParentProcessName"),
+        parent_process_id: pid("ProcessId"),
+        user: account(record, "Subject"),
+        logon_id: field(record, "SubjectLogonId"),
+        token_elevation_type: field(record, "TokenElevationType"),
+    }
+}
+
+/// Long scripts are split over several 4104 events sharing a ScriptBlockId
+fn reassemble_script_block(id: String, mut parts: Vec<&EvtxRecord>) -> ScriptBlock {
+    let number = |record: &EvtxRecord| field(record, "MessageNumber").and_then(|n| n.parse::<u32>().ok()).unwrap_or(1);
+    parts.sort_by_key(|record| number(record));
+    parts.dedup_by_key(|record| number(record));
+
+    let expected_fragments = parts.iter()
+        .filter_map(|record| field(record, "MessageTotal")?.parse().ok())
+        .max()
+        .unwrap_or(1);
+    let fragments = parts.len() as u32;
+    let complete = parts.iter().map(|record| number(record)).eq(1..=expected_fragments);
+
+    ScriptBlock {
+        timestamp: parts.iter().filter_map(|record| record.timestamp).min(),
+        script_block_id: id,
+        path: parts.iter().find_map(|record| field(record, "Path")),
+        text: parts.iter()
+            .filter_map(|record| record.event_data.get("ScriptBlockText"))
+            .map(String::as_str)
+            .collect(),
+        fragments,
+        expected_fragments,
+        complete,
+        user_sid: parts.iter().find_map(|record| record.user_sid.clone()),
+        suspicious: parts.iter().any(|record| record.level == Some(3)),
+    }
+}
+
+fn powershell_command(record: &EvtxRecord) -> PowerShellCommand {
+    // ContextInfo is a block of "Name = value" lines
+    let context = record.event_data.get("ContextInfo").map(String::as_str).unwrap_or_default();
+    let context_value = |name: &str| context.lines()
+        .filter_map(|line| line.split_once(" = "))
+        .find(|(key, _)| key.trim() == name)
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    PowerShellCommand {
+        timestamp: record.timestamp,
+        record_id: record.record_id,
+        host_application: context_value("Host Application"),
+        command_name: context_value("Command Name"),
+        script_name: context_value("Script Name"),
+        user: context_value("User"),
+        payload: field(record, "Payload"),
+    }
+}
+
+fn rdp_event(record: &EvtxRecord, event_id: u32) -> RdpSessionEvent {
+    let (action, user, source_ip) = match event_id {
+        // Param1-3 are user, domain and source address
+        1149 => {
+            let user = match (field(record, "Param2"), field(record, "Param1")) {
+                (Some(domain), Some(user)) => Some(format!("{}\\{}", domain, user)),
+                (None, user) => user,
+                (domain, None) => domain,
+            };
+            (RdpAction::Authenticated, user, field(record, "Param3"))
+        }
+        21 => (RdpAction::Logon, field(record, "User"), field(record, "Address")),
+        24 => (RdpAction::Disconnected, field(record, "User"), field(record, "Address")),
+        _ => (RdpAction::Reconnected, field(record, "User"), field(record, "Address")),
+    };
+
+    RdpSessionEvent {
+        timestamp: record.timestamp,
+        record_id: record.record_id,
+        event_id,
+        action,
+        user,
+        session_id: field(record, "SessionID"),
+        source_ip,
+    }
+}
+
+fn task_change(record: &EvtxRecord, event_id: u32) -> TaskChange {
+    let action = match event_id {
+        106 => TaskAction::Registered,
+        140 => TaskAction::Updated,
+        _ => TaskAction::Deleted,
+    };
+
+    TaskChange {
+        timestamp: record.timestamp,
+        record_id: record.record_id,
+        event_id,
+        action,
+        task_name: field(record, "TaskName"),
+        user: field(record, "UserContext").or_else(|| field(record, "UserName")),
+    }
+}
+
+/// An event data field, treating the `-` placeholder as absent
+fn field(record: &EvtxRecord, name: &str) -> Option<String> {
+    record.event_data.get(name)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty() && *value != "-")
+        .map(str::to_string)
+}
+
+/// `DOMAIN\user` from the `<prefix>UserName` and `<prefix>DomainName` fields
+fn account(record: &EvtxRecord, prefix: &str) -> Option<String> {
+    let user = field(record, &format!("{}UserName", prefix))?;
+    match field(record, &format!("{}DomainName", prefix)) {
+        Some(domain) => Some(format!("{}\\{}", domain, user)),
+        None => Some(user),
+    }
+}
+
+fn parse_hex(value: &str) -> Option<u32> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
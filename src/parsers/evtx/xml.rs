@@ -0,0 +1,127 @@
+// src/parsers/evtx/xml.rs
+use serde_json::{Map, Value};
+
+/// A node of a rendered event
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmlNode {
+    Element(XmlElement),
+    Text(String),
+}
+
+/// An element of a rendered event, with templates and substitutions resolved
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XmlElement {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlNode>,
+}
+
+impl XmlElement {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// First child element with the given name
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.elements().find(|e| e.name == name)
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &XmlElement> {
+        self.children.iter().filter_map(|node| match node {
+            XmlNode::Element(element) => Some(element),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    /// Concatenated text of the direct children
+    pub fn text(&self) -> String {
+        self.children.iter()
+            .filter_map(|node| match node {
+                XmlNode::Text(text) => Some(text.as_str()),
+                XmlNode::Element(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut out = String::new();
+        self.write_xml(&mut out, 0);
+        out
+    }
+
+    fn write_xml(&self, out: &mut String, depth: usize) {
+        let indent = "  ".repeat(depth);
+        out.push_str(&format!("{}<{}", indent, self.name));
+        for (name, value) in &self.attributes {
+            out.push_str(&format!(" {}=\"{}\"", name, escape(value)));
+        }
+
+        if self.children.is_empty() {
+            out.push_str("/>\n");
+        } else if self.elements().next().is_none() {
+            out.push_str(&format!(">{}</{}>\n", escape(&self.text()), self.name));
+        } else {
+            out.push_str(">\n");
+            for node in &self.children {
+                match node {
+                    XmlNode::Element(element) => element.write_xml(out, depth + 1),
+                    XmlNode::Text(text) if !text.trim().is_empty() => {
+                        out.push_str(&format!("{}  {}\n", indent, escape(text)));
+                    }
+                    XmlNode::Text(_) => {}
+                }
+            }
+            out.push_str(&format!("{}</{}>\n", indent, self.name));
+        }
+    }
+
+    /// Render as JSON: attributes under `#attributes`, mixed text under
+    /// `#text`, repeated children as arrays, and `<Data Name="x">` elements
+    /// keyed by their name
+    pub fn to_json(&self) -> Value {
+        let text = self.text();
+        if self.attributes.is_empty() && self.elements().next().is_none() {
+            return if text.is_empty() { Value::Null } else { Value::String(text) };
+        }
+
+        let mut map = Map::new();
+        if !self.attributes.is_empty() {
+            let attributes = self.attributes.iter()
+                .map(|(name, value)| (name.clone(), Value::String(value.clone())))
+                .collect();
+            map.insert("#attributes".to_string(), Value::Object(attributes));
+        }
+
+        for child in self.elements() {
+            let (key, value) = match child.attribute("Name").filter(|_| child.name == "Data") {
+                Some(name) => (name.to_string(), Value::String(child.text())),
+                None => (child.name.clone(), child.to_json()),
+            };
+
+            match map.get_mut(&key) {
+                Some(Value::Array(items)) => items.push(value),
+                Some(existing) => {
+                    let first = existing.take();
+                    *existing = Value::Array(vec![first, value]);
+                }
+                None => {
+                    map.insert(key, value);
+                }
+            }
+        }
+
+        if !text.trim().is_empty() {
+            map.insert("#text".to_string(), Value::String(text));
+        }
+        Value::Object(map)
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,395 @@
+// src/parsers/lnk.rs
+//! Shell Link (`.lnk`) parsing ([MS-SHLLINK]), for shortcuts on disk and
+//! those embedded in jump lists
+use std::collections::BTreeMap;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::shellitem::{id_list_path, parse_id_list, read_utf16, ShellItem};
+use crate::utils::bytes::{u16_at, u32_at, u64_at};
+use crate::utils::guid::{format_guid, known_folder_name};
+use crate::utils::time::filetime_to_datetime;
+
+const HEADER_SIZE: usize = 0x4C;
+/// `{00021401-0000-0000-C000-000000000046}`
+const LINK_CLSID: [u8; 16] = [
+    0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+const HAS_TARGET_ID_LIST: u32 = 0x01;
+const HAS_LINK_INFO: u32 = 0x02;
+const HAS_NAME: u32 = 0x04;
+const HAS_RELATIVE_PATH: u32 = 0x08;
+const HAS_WORKING_DIR: u32 = 0x10;
+const HAS_ARGUMENTS: u32 = 0x20;
+const HAS_ICON_LOCATION: u32 = 0x40;
+const IS_UNICODE: u32 = 0x80;
+
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x01;
+const COMMON_NETWORK_RELATIVE_LINK: u32 = 0x02;
+const VALID_DEVICE: u32 = 0x01;
+
+const ENVIRONMENT_BLOCK: u32 = 0xA000_0001;
+const TRACKER_BLOCK: u32 = 0xA000_0003;
+const PROPERTY_STORE_BLOCK: u32 = 0xA000_0009;
+const KNOWN_FOLDER_BLOCK: u32 = 0xA000_000B;
+
+/// `1SPS`, the serialized property storage version
+const PROPERTY_STORAGE_VERSION: u32 = 0x5350_5331;
+/// Format ID of property sets keyed by name rather than integer ID
+const NAMED_PROPERTIES: &str = "{D5CDD505-2E9C-101B-9397-08002B2CF9AE}";
+/// 100ns intervals from the UUID epoch (1582-10-15) to the FILETIME epoch
+const UUID_EPOCH_OFFSET: u64 = 5_748_192_000_000_000;
+
+/// Canonical names of properties commonly found in shortcuts
+const PROPERTY_NAMES: &[(&str, u32, &str)] = &[
+    ("{B725F130-47EF-101A-A5F1-02608C9EEBAC}", 4, "System.ItemTypeText"),
+    ("{B725F130-47EF-101A-A5F1-02608C9EEBAC}", 10, "System.ItemNameDisplay"),
+    ("{B725F130-47EF-101A-A5F1-02608C9EEBAC}", 12, "System.Size"),
+    ("{B725F130-47EF-101A-A5F1-02608C9EEBAC}", 13, "System.FileAttributes"),
+    ("{B725F130-47EF-101A-A5F1-02608C9EEBAC}", 14, "System.DateModified"),
+    ("{B725F130-47EF-101A-A5F1-02608C9EEBAC}", 15, "System.DateCreated"),
+    ("{B725F130-47EF-101A-A5F1-02608C9EEBAC}", 16, "System.DateAccessed"),
+    ("{28636AA6-953D-11D2-B5D6-00C04FD918D0}", 30, "System.ParsingPath"),
+    ("{9F4C2855-9F79-4B39-A8D0-E1D42DE1D5F3}", 5, "System.AppUserModel.ID"),
+];
+
+/// A parsed shell link
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShellLink {
+    pub flags: u32,
+    /// Attributes of the target when the link was last resolved
+    pub file_attributes: u32,
+    /// Target timestamps, not the shortcut's own
+    pub target_created: Option<DateTime<Utc>>,
+    pub target_accessed: Option<DateTime<Utc>>,
+    pub target_modified: Option<DateTime<Utc>>,
+    pub target_size: u32,
+    pub icon_index: i32,
+    pub show_command: u32,
+    pub hotkey: u16,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub target_id_list: Vec<ShellItem>,
+    pub link_info: Option<LinkInfo>,
+    /// Description
+    pub name: Option<String>,
+    pub relative_path: Option<String>,
+    pub working_dir: Option<String>,
+    pub arguments: Option<String>,
+    pub icon_location: Option<String>,
+    /// Target path with environment variables unexpanded
+    pub environment_target: Option<String>,
+    pub tracker: Option<TrackerData>,
+    /// Known folder the target is in, by GUID and name
+    pub known_folder: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, String>,
+}
+
+/// Where the target was when the link was created
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinkInfo {
+    pub drive_type: Option<DriveType>,
+    pub drive_serial_number: Option<String>,
+    pub volume_label: Option<String>,
+    pub local_base_path: Option<String>,
+    /// Share such as `\\server\share`
+    pub net_name: Option<String>,
+    /// Mapped drive letter of the share
+    pub device_name: Option<String>,
+    pub network_provider_type: Option<u32>,
+    pub common_path_suffix: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DriveType {
+    Unknown,
+    NoRootDir,
+    Removable,
+    Fixed,
+    Remote,
+    CdRom,
+    RamDisk,
+}
+
+/// Distributed Link Tracking data identifying the machine that created
+/// the link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackerData {
+    /// NetBIOS name
+    pub machine_id: String,
+    pub droid_volume: Option<String>,
+    pub droid_file: Option<String>,
+    pub birth_droid_volume: Option<String>,
+    pub birth_droid_file: Option<String>,
+    /// Node ID of the version 1 UUID in the file droid, normally the MAC
+    /// address of the creating machine
+    pub mac_address: Option<String>,
+    /// Timestamp of the file droid
+    pub droid_timestamp: Option<DateTime<Utc>>,
+}
+
+impl ShellLink {
+    /// Best available target path: LinkInfo, then the environment block,
+    /// then the ID list
+    pub fn target_path(&self) -> Option<String> {
+        if let Some(info) = &self.link_info {
+            let suffix = info.common_path_suffix.as_deref().unwrap_or_default();
+            if let Some(base) = &info.local_base_path {
+                return Some(format!("{}{}", base, suffix));
+            }
+            if let Some(share) = &info.net_name {
+                return Some(if suffix.is_empty() { share.clone() } else { format!("{}\\{}", share, suffix) });
+            }
+        }
+        if let Some(target) = &self.environment_target {
+            return Some(target.clone());
+        }
+        (!self.target_id_list.is_empty()).then(|| id_list_path(&self.target_id_list))
+    }
+}
+
+/// Parse a shell link file
+pub fn parse_lnk(data: &[u8]) -> Result<ShellLink> {
+    if data.len() < HEADER_SIZE || u32_at(data, 0) != Some(HEADER_SIZE as u32) || data[4..20] != LINK_CLSID {
+        bail!("Not a shell link");
+    }
+
+    let flags = u32_at(data, 20).unwrap_or_default();
+    let filetime = |offset: usize| u64_at(data, offset).and_then(filetime_to_datetime);
+    let mut link = ShellLink {
+        flags,
+        file_attributes: u32_at(data, 24).unwrap_or_default(),
+        target_created: filetime(28),
+        target_accessed: filetime(36),
+        target_modified: filetime(44),
+        target_size: u32_at(data, 52).unwrap_or_default(),
+        icon_index: u32_at(data, 56).unwrap_or_default() as i32,
+        show_command: u32_at(data, 60).unwrap_or_default(),
+        hotkey: u16_at(data, 64).unwrap_or_default(),
+        ..Default::default()
+    };
+
+    let mut pos = HEADER_SIZE;
+    if flags & HAS_TARGET_ID_LIST != 0 {
+        let size = u16_at(data, pos).unwrap_or_default() as usize;
+        let Some(list) = data.get(pos + 2..pos + 2 + size) else {
+            bail!("Truncated LinkTargetIDList");
+        };
+        link.target_id_list = parse_id_list(list);
+        pos += 2 + size;
+    }
+
+    if flags & HAS_LINK_INFO != 0 {
+        let size = u32_at(data, pos).unwrap_or_default() as usize;
+        let Some(info) = data.get(pos..pos + size) else {
+            bail!("Truncated LinkInfo");
+        };
+        link.link_info = parse_link_info(info);
+        pos += size;
+    }
+
+    let unicode = flags & IS_UNICODE != 0;
+    for (flag, field) in [
+        (HAS_NAME, &mut link.name),
+        (HAS_RELATIVE_PATH, &mut link.relative_path),
+        (HAS_WORKING_DIR, &mut link.working_dir),
+        (HAS_ARGUMENTS, &mut link.arguments),
+        (HAS_ICON_LOCATION, &mut link.icon_location),
+    ] {
+        if flags & flag == 0 {
+            continue;
+        }
+        let count = u16_at(data, pos).unwrap_or_default() as usize;
+        let size = if unicode { count * 2 } else { count };
+        let Some(text) = data.get(pos + 2..pos + 2 + size) else {
+            bail!("Truncated StringData");
+        };
+        *field = Some(if unicode { read_utf16(text).0 } else { read_ansi(text) });
+        pos += 2 + size;
+    }
+
+    // Extra data blocks run until one smaller than its own header
+    while let Some(size) = u32_at(data, pos).map(|s| s as usize) {
+        if size < 8 {
+            break;
+        }
+        let Some(block) = data.get(pos..pos + size) else {
+            log::debug!("Truncated extra data block at offset {:#x}", pos);
+            break;
+        };
+        parse_extra_block(block, &mut link);
+        pos += size;
+    }
+
+    Ok(link)
+}
+
+fn parse_link_info(info: &[u8]) -> Option<LinkInfo> {
+    let header_size = u32_at(info, 4)? as usize;
+    let flags = u32_at(info, 8)?;
+    let offset = |at: usize| u32_at(info, at).map(|o| o as usize).filter(|&o| o != 0);
+    // Unicode offsets follow the ANSI ones in larger headers
+    let unicode_offset = |at: usize| if header_size >= 0x24 { offset(at) } else { None };
+    let string = |ansi: Option<usize>, unicode: Option<usize>| match (unicode, ansi) {
+        (Some(o), _) => info.get(o..).map(|s| read_utf16(s).0),
+        (None, Some(o)) => info.get(o..).map(read_ansi),
+        _ => None,
+    };
+
+    let mut link_info = LinkInfo {
+        common_path_suffix: string(offset(24), unicode_offset(32)).filter(|s| !s.is_empty()),
+        ..Default::default()
+    };
+
+    if flags & VOLUME_ID_AND_LOCAL_BASE_PATH != 0 {
+        link_info.local_base_path = string(offset(16), unicode_offset(28));
+        if let Some(volume) = offset(12).and_then(|o| info.get(o..)) {
+            link_info.drive_type = u32_at(volume, 4).map(|t| match t {
+                1 => DriveType::NoRootDir,
+                2 => DriveType::Removable,
+                3 => DriveType::Fixed,
+                4 => DriveType::Remote,
+                5 => DriveType::CdRom,
+                6 => DriveType::RamDisk,
+                _ => DriveType::Unknown,
+            });
+            link_info.drive_serial_number = u32_at(volume, 8).map(|s| format!("{:04X}-{:04X}", s >> 16, s & 0xFFFF));
+            link_info.volume_label = u32_at(volume, 12)
+                .and_then(|label| match label {
+                    0x14 => u32_at(volume, 16).and_then(|o| volume.get(o as usize..)).map(|s| read_utf16(s).0),
+                    label => volume.get(label as usize..).map(read_ansi),
+                })
+                .filter(|label| !label.is_empty());
+        }
+    }
+
+    if flags & COMMON_NETWORK_RELATIVE_LINK != 0
+        && let Some(network) = offset(20).and_then(|o| info.get(o..))
+    {
+        let network_flags = u32_at(network, 4).unwrap_or_default();
+        let net_name_offset = u32_at(network, 8).unwrap_or_default() as usize;
+        let text = |ansi: usize, unicode_at: usize| {
+            // Unicode names are present when the ANSI name is past the base structure
+            match u32_at(network, unicode_at).filter(|_| net_name_offset > 0x14) {
+                Some(o) => network.get(o as usize..).map(|s| read_utf16(s).0),
+                None => network.get(ansi..).map(read_ansi),
+            }
+        };
+        link_info.net_name = text(net_name_offset, 20);
+        if network_flags & VALID_DEVICE != 0 {
+            link_info.device_name = u32_at(network, 12).and_then(|o| text(o as usize, 24));
+        }
+        link_info.network_provider_type = u32_at(network, 16);
+    }
+
+    Some(link_info)
+}
+
+fn parse_extra_block(block: &[u8], link: &mut ShellLink) {
+    match u32_at(block, 4).unwrap_or_default() {
+        ENVIRONMENT_BLOCK => {
+            let unicode = block.get(268..788).map(|s| read_utf16(s).0).filter(|s| !s.is_empty());
+            link.environment_target = unicode.or_else(|| block.get(8..268).map(read_ansi));
+        }
+        TRACKER_BLOCK => {
+            let guid = |offset: usize| block.get(offset..offset + 16).and_then(format_guid);
+            let droid_file = block.get(48..64);
+            link.tracker = Some(TrackerData {
+                machine_id: block.get(16..32).map(read_ansi).unwrap_or_default(),
+                droid_volume: guid(32),
+                droid_file: guid(48),
+                birth_droid_volume: guid(64),
+                birth_droid_file: guid(80),
+                mac_address: droid_file.map(|d| {
+                    d[10..16].iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":")
+                }),
+                droid_timestamp: droid_file.and_then(uuid_timestamp),
+            });
+        }
+        KNOWN_FOLDER_BLOCK => {
+            link.known_folder = block.get(8..24).and_then(format_guid).map(|guid| match known_folder_name(&guid) {
+                Some(name) => format!("{} ({})", name, guid),
+                None => guid,
+            });
+        }
+        PROPERTY_STORE_BLOCK => parse_property_store(&block[8..], &mut link.properties),
+        _ => {}
+    }
+}
+
+/// Timestamp of a version 1 (time-based) UUID
+fn uuid_timestamp(uuid: &[u8]) -> Option<DateTime<Utc>> {
+    let high = u16_at(uuid, 6)?;
+    if high >> 12 != 1 {
+        return None;
+    }
+    let time = ((high as u64 & 0x0FFF) << 48) | ((u16_at(uuid, 4)? as u64) << 32) | u32_at(uuid, 0)? as u64;
+    filetime_to_datetime(time.checked_sub(UUID_EPOCH_OFFSET)?)
+}
+
+/// Serialized property storage: property sets, each a format ID and
+/// properties keyed by integer ID or, for one format ID, by name
+fn parse_property_store(data: &[u8], properties: &mut BTreeMap<String, String>) {
+    let mut pos = 0;
+
+    while let Some(size) = u32_at(data, pos).map(|s| s as usize) {
+        if size < 24 || u32_at(data, pos + 4) != Some(PROPERTY_STORAGE_VERSION) {
+            break;
+        }
+        let Some(storage) = data.get(pos..pos + size) else {
+            break;
+        };
+        let format_id = format_guid(&storage[8..24]).unwrap_or_default();
+        let named = format_id == NAMED_PROPERTIES;
+
+        let mut offset = 24;
+        while let Some(value_size) = u32_at(storage, offset).map(|s| s as usize) {
+            let Some(entry) = storage.get(offset..offset + value_size).filter(|_| value_size >= 9) else {
+                break;
+            };
+            let (key, value) = if named {
+                let name_size = u32_at(entry, 4).unwrap_or_default() as usize;
+                let name = entry.get(9..9 + name_size).map(|n| read_utf16(n).0).unwrap_or_default();
+                (name, entry.get(9 + name_size..))
+            } else {
+                let id = u32_at(entry, 4).unwrap_or_default();
+                let key = PROPERTY_NAMES.iter()
+                    .find(|(format, pid, _)| *format == format_id && *pid == id)
+                    .map_or_else(|| format!("{}/{}", format_id, id), |(_, _, name)| name.to_string());
+                (key, entry.get(9..))
+            };
+            if let Some(value) = value.and_then(typed_value) {
+                properties.insert(key, value);
+            }
+            offset += value_size;
+        }
+
+        pos += size;
+    }
+}
+
+/// Render a TypedPropertyValue; vectors and other complex types are skipped
+fn typed_value(data: &[u8]) -> Option<String> {
+    let value = data.get(4..)?;
+    Some(match u16_at(data, 0)? {
+        0x02 => (u16_at(value, 0)? as i16).to_string(),
+        0x03 | 0x16 => (u32_at(value, 0)? as i32).to_string(),
+        0x0B => (u16_at(value, 0)? != 0).to_string(),
+        0x11 => value.first()?.to_string(),
+        0x12 => u16_at(value, 0)?.to_string(),
+        0x13 | 0x17 => u32_at(value, 0)?.to_string(),
+        0x14 => (u64_at(value, 0)? as i64).to_string(),
+        0x15 => u64_at(value, 0)?.to_string(),
+        0x1E => read_ansi(value.get(4..4 + u32_at(value, 0)? as usize)?),
+        0x1F => read_utf16(value.get(4..4 + u32_at(value, 0)? as usize * 2)?).0,
+        0x40 => filetime_to_datetime(u64_at(value, 0)?)?.to_rfc3339(),
+        0x48 => format_guid(value)?,
+        _ => return None,
+    })
+}
+
+/// ANSI string up to the first NUL, decoded as Latin-1
+fn read_ansi(data: &[u8]) -> String {
+    data.iter().take_while(|&&b| b != 0).map(|&b| b as char).collect()
+}
@@ -0,0 +1,40 @@
+// src/parsers/mod.rs
+//! Parsers for Windows artifact files found outside the registry
+mod browser;
+mod evtx;
+mod jumplist;
+mod lnk;
+mod prefetch;
+mod recentfilecache;
+mod recyclebin;
+mod setupapi;
+mod srum;
+mod task;
+mod wlan;
+mod wmi;
+
+pub use browser::{
+    parse_chromium_cookies, parse_chromium_history, parse_chromium_web_data, parse_firefox_form_history,
+    parse_firefox_places, Browser, BrowserActivity, BrowserActivityKind,
+};
+pub use evtx::{
+    parse_evtx, summarize_events, AccountAction, AccountChange, EventSummarizer, EventSummary, EvtxChunk,
+    EvtxHeader, EvtxLog, EvtxRecord, LogClear, LogonAction, LogonEvent, PowerShellCommand, ProcessCreation,
+    RdpAction, RdpSessionEvent, ScriptBlock, ServiceInstall, TaskAction, TaskChange, XmlElement, XmlNode,
+};
+pub use jumplist::{
+    app_id_name, parse_automatic_destinations, parse_custom_destinations, parse_destlist, JumpList, JumpListEntry,
+    JumpListKind,
+};
+pub use lnk::{parse_lnk, DriveType, LinkInfo, ShellLink, TrackerData};
+pub use prefetch::{parse_prefetch, PrefetchFile, PrefetchVolume};
+pub use recentfilecache::parse_recentfilecache;
+pub use recyclebin::{info2_recycled_name, parse_info2, parse_recycle_bin_info, RecycledFile};
+pub use setupapi::{parse_setupapi_log, SetupApiInstall};
+pub use srum::{parse_srum, Srum, SrumAppUsage, SrumConnectivity, SrumEnergy, SrumNetworkUsage};
+pub use task::{
+    parse_scheduled_task, parse_task_actions, ScheduledTaskAction, ScheduledTaskActionKind, ScheduledTaskDefinition,
+    ScheduledTaskPrincipal, ScheduledTaskTrigger,
+};
+pub use wlan::{parse_wlan_profile, WlanProfile};
+pub use wmi::{parse_wmi_repository, WmiBinding, WmiEventConsumer, WmiEventFilter, WmiPersistence};
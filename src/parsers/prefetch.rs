@@ -0,0 +1,151 @@
+// src/parsers/prefetch.rs
+use anyhow::{Result, bail, Context};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::utils::bytes::{u32_at, u64_at, utf16z};
+use crate::utils::lzxpress::decompress_lzxpress_huffman;
+use crate::utils::time::filetime_to_datetime;
+
+const SIGNATURE: &[u8] = b"SCCA";
+/// `MAM` followed by the compression type; the high bit adds a CRC
+const MAM_SIGNATURE: &[u8] = b"MAM";
+const MAM_LZXPRESS_HUFFMAN: u8 = 0x04;
+const MAM_HAS_CRC: u8 = 0x80;
+
+const HEADER_SIZE: usize = 84;
+/// Executable name field: 30 UTF-16 characters
+const NAME_OFFSET: usize = 16;
+const NAME_LENGTH: usize = 60;
+const PATH_HASH_OFFSET: usize = 76;
+
+/// Windows 10 files with this file metrics offset have a shorter file
+/// information section, moving the run count up by 8 bytes
+const V30_SHORT_METRICS_OFFSET: u32 = 0x128;
+
+/// A parsed `Windows\Prefetch\*.pf` file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrefetchFile {
+    /// 17 (XP/2003), 23 (Vista/7), 26 (8.1) or 30 (10/11)
+    pub version: u32,
+    pub executable: String,
+    /// Hash of the executable's path (and command line for hosts such as
+    /// svchost), in the file name as `NAME-XXXXXXXX.pf`
+    pub path_hash: String,
+    pub run_count: u32,
+    /// Most recent first; up to eight from version 26
+    pub last_run_times: Vec<DateTime<Utc>>,
+    pub volumes: Vec<PrefetchVolume>,
+    /// Files loaded during the first seconds of execution
+    pub files: Vec<String>,
+    pub directories: Vec<String>,
+    /// Stored MAM-compressed (Windows 10 and later)
+    pub compressed: bool,
+}
+
+/// A volume the executable referenced files on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrefetchVolume {
+    /// e.g. `\VOLUME{01d0f9e5b2c7a3e0-1a2b3c4d}` or `\DEVICE\HARDDISKVOLUME2`
+    pub device_path: String,
+    pub serial_number: String,
+    pub created: Option<DateTime<Utc>>,
+}
+
+/// Parse a prefetch file, decompressing it first if needed
+pub fn parse_prefetch(data: &[u8]) -> Result<PrefetchFile> {
+    let compressed = data.starts_with(MAM_SIGNATURE);
+    let data = if compressed {
+        let kind = data.get(3).copied().unwrap_or_default();
+        if kind & 0x0F != MAM_LZXPRESS_HUFFMAN {
+            bail!("Unsupported prefetch compression {:#04x}", kind);
+        }
+        let size = u32_at(data, 4).context("Truncated MAM header")? as usize;
+        let start = if kind & MAM_HAS_CRC != 0 { 12 } else { 8 };
+        decompress_lzxpress_huffman(data.get(start..).unwrap_or_default(), size)
+            .context("Failed to decompress prefetch file")?
+    } else {
+        data.to_vec()
+    };
+    let data = data.as_slice();
+
+    if data.len() < HEADER_SIZE || &data[4..8] != SIGNATURE {
+        bail!("Not a prefetch file");
+    }
+    let version = u32_at(data, 0).unwrap_or_default();
+    let field = |offset: usize| u32_at(data, HEADER_SIZE + offset).context("Truncated prefetch header");
+
+    let metrics_offset = field(0)? as usize;
+    let metrics_count = field(4)? as usize;
+    let strings_offset = field(16)? as usize;
+    let strings_size = field(20)? as usize;
+    let volumes_offset = field(24)? as usize;
+    let volume_count = field(28)? as usize;
+
+    let (last_run_offset, last_run_slots, run_count_offset, metric_size, volume_size) = match version {
+        17 => (36, 1, 60, 20, 40),
+        23 => (44, 1, 68, 32, 104),
+        26 => (44, 8, 124, 32, 104),
+        30 if field(0)? == V30_SHORT_METRICS_OFFSET => (44, 8, 116, 32, 96),
+        30 => (44, 8, 124, 32, 96),
+        _ => bail!("Unsupported prefetch version {}", version),
+    };
+
+    let last_run_times = (0..last_run_slots)
+        .filter_map(|i| u64_at(data, HEADER_SIZE + last_run_offset + i * 8))
+        .filter_map(filetime_to_datetime)
+        .collect();
+
+    let strings = data.get(strings_offset..strings_offset + strings_size).unwrap_or_default();
+    // The count is untrusted, so stop at the first entry past the end
+    let files = (0..metrics_count)
+        .map_while(|i| {
+            let entry = metrics_offset + i * metric_size;
+            // Version 17 entries lack the average duration
+            let name_field = if version == 17 { 8 } else { 12 };
+            Some((u32_at(data, entry + name_field)? as usize, u32_at(data, entry + name_field + 4)? as usize))
+        })
+        .filter_map(|(offset, length)| strings.get(offset..offset + length * 2).map(utf16z))
+        .collect();
+
+    let mut volumes = Vec::new();
+    let mut directories = Vec::new();
+    for i in 0..volume_count {
+        let entry = volumes_offset + i * volume_size;
+        let Some(header) = data.get(entry..entry + 36) else {
+            break;
+        };
+        let volume = |offset: usize| u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap()) as usize;
+
+        let path_start = volumes_offset + volume(0);
+        volumes.push(PrefetchVolume {
+            device_path: data.get(path_start..path_start + volume(4) * 2).map(utf16z).unwrap_or_default(),
+            serial_number: format!("{:08X}", volume(16)),
+            created: u64_at(header, 8).and_then(filetime_to_datetime),
+        });
+
+        // Directory strings: a character count, the characters, then a NUL
+        let mut pos = volumes_offset + volume(28);
+        for _ in 0..volume(32) {
+            let Some(length) = data.get(pos..pos + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize) else {
+                break;
+            };
+            let Some(text) = data.get(pos + 2..pos + 2 + length * 2) else {
+                break;
+            };
+            directories.push(utf16z(text));
+            pos += 2 + (length + 1) * 2;
+        }
+    }
+
+    Ok(PrefetchFile {
+        version,
+        executable: utf16z(&data[NAME_OFFSET..NAME_OFFSET + NAME_LENGTH]),
+        path_hash: format!("{:08X}", u32_at(data, PATH_HASH_OFFSET).unwrap_or_default()),
+        run_count: u32_at(data, HEADER_SIZE + run_count_offset).unwrap_or_default(),
+        last_run_times,
+        volumes,
+        files,
+        directories,
+        compressed,
+    })
+}
@@ -0,0 +1,35 @@
+// src/parsers/recentfilecache.rs
+use anyhow::{Result, bail};
+use crate::utils::bytes::utf16;
+
+/// `FE FF EE FF` at the start of the file
+const SIGNATURE: u32 = 0xFFEE_FFFE;
+const HEADER_SIZE: usize = 20;
+
+/// Parse `Windows\AppCompat\Programs\RecentFileCache.bcf` (Windows 7 and
+/// Server 2008 R2) into the executable paths it lists, most recent first.
+/// Entries are a DWORD character count followed by a NUL-terminated
+/// UTF-16 path; the file records no timestamps.
+pub fn parse_recentfilecache(data: &[u8]) -> Result<Vec<String>> {
+    if data.len() < HEADER_SIZE || u32::from_le_bytes(data[0..4].try_into().unwrap()) != SIGNATURE {
+        bail!("Not a RecentFileCache.bcf file");
+    }
+
+    let mut paths = Vec::new();
+    let mut pos = HEADER_SIZE;
+
+    while let Some(length) = data.get(pos..pos + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize) {
+        let start = pos + 4;
+        let Some(text) = length.checked_mul(2).and_then(|bytes| data.get(start..start + bytes)) else {
+            log::debug!("Truncated RecentFileCache entry at offset {:#x}", pos);
+            break;
+        };
+
+        paths.push(utf16(text));
+
+        // Skip the path and its terminator
+        pos = start + length * 2 + 2;
+    }
+
+    Ok(paths)
+}
@@ -0,0 +1,92 @@
+// src/parsers/recyclebin.rs
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::utils::bytes::{u32_at, u64_at, utf16z};
+use crate::utils::time::filetime_to_datetime;
+
+/// Version 1 `$I` files (Vista to 8.1) hold a fixed 260-character path
+const V1_PATH_OFFSET: usize = 24;
+const V1_PATH_SIZE: usize = 520;
+/// Version 2 (Windows 10 and later) prefixes the path with its length
+const V2_PATH_OFFSET: usize = 28;
+
+const INFO2_HEADER_SIZE: usize = 20;
+const INFO2_RECORD_SIZE: usize = 800;
+
+/// Metadata of a file moved to the Recycle Bin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecycledFile {
+    pub original_path: String,
+    pub size: u64,
+    pub deleted: Option<DateTime<Utc>>,
+    /// `$I` format version; `None` for INFO2 records
+    pub version: Option<u64>,
+    /// INFO2 record number, which names the recycled file (`Dc<index>.ext`)
+    pub index: Option<u32>,
+    /// INFO2 records of files since restored or purged
+    pub removed: bool,
+}
+
+/// Parse a `$Recycle.Bin\<SID>\$I*` file
+pub fn parse_recycle_bin_info(data: &[u8]) -> Result<RecycledFile> {
+    let version = u64_at(data, 0).unwrap_or_default();
+    let path = match version {
+        1 => data.get(V1_PATH_OFFSET..V1_PATH_OFFSET + V1_PATH_SIZE),
+        2 => u32_at(data, 24).and_then(|chars| data.get(V2_PATH_OFFSET..V2_PATH_OFFSET + chars as usize * 2)),
+        _ => bail!("Unsupported $I version {}", version),
+    };
+    let Some(path) = path else {
+        bail!("Truncated $I file");
+    };
+
+    Ok(RecycledFile {
+        original_path: utf16z(path),
+        size: u64_at(data, 8).unwrap_or_default(),
+        deleted: u64_at(data, 16).and_then(filetime_to_datetime),
+        version: Some(version),
+        index: None,
+        removed: false,
+    })
+}
+
+/// Parse a Windows 2000/XP `RECYCLER\<SID>\INFO2` file
+pub fn parse_info2(data: &[u8]) -> Result<Vec<RecycledFile>> {
+    let record_size = u32_at(data, 12).unwrap_or_default() as usize;
+    if data.len() < INFO2_HEADER_SIZE || record_size != INFO2_RECORD_SIZE {
+        bail!("Not an INFO2 file");
+    }
+
+    let files = data[INFO2_HEADER_SIZE..]
+        .chunks_exact(INFO2_RECORD_SIZE)
+        .map(|record| {
+            // Restoring or purging a file clears the first ANSI character;
+            // the Unicode path is left intact
+            let unicode = utf16z(&record[280..800]);
+            let original_path = if unicode.is_empty() {
+                record[..260].iter().take_while(|&&b| b != 0).map(|&b| b as char).collect()
+            } else {
+                unicode
+            };
+            RecycledFile {
+                original_path,
+                size: u32_at(record, 276).unwrap_or_default() as u64,
+                deleted: u64_at(record, 268).and_then(filetime_to_datetime),
+                version: None,
+                index: u32_at(record, 260),
+                removed: record[0] == 0,
+            }
+        })
+        .collect();
+
+    Ok(files)
+}
+
+/// Name INFO2 gives a recycled file: `D`, the drive letter, the record
+/// index and the original extension
+pub fn info2_recycled_name(file: &RecycledFile) -> Option<String> {
+    let drive = file.original_path.chars().next().filter(char::is_ascii_alphabetic)?;
+    let name = file.original_path.rsplit('\\').next().unwrap_or_default();
+    let extension = name.rfind('.').map(|dot| &name[dot..]).unwrap_or_default();
+    Some(format!("D{}{}{}", drive.to_ascii_lowercase(), file.index?, extension))
+}
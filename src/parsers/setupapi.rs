@@ -0,0 +1,43 @@
+// src/parsers/setupapi.rs
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+/// A device installation section from `setupapi.dev.log`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupApiInstall {
+    /// Device instance ID, e.g. `USBSTOR\Disk&Ven_SanDisk&Prod_Cruzer&Rev_1.00\4C5300012311&0`
+    pub device_id: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Parse the device installation sections of `Windows\INF\setupapi.dev.log`.
+/// Section times are local; `bias_minutes` converts them to UTC
+/// (UTC = local + bias), as stored in `TimeZoneInformation\Bias`.
+pub fn parse_setupapi_log(text: &str, bias_minutes: i32) -> Vec<SetupApiInstall> {
+    let mut installs = Vec::new();
+    let mut pending: Option<String> = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+
+        // >>>  [Device Install (Hardware initiated) - USBSTOR\Disk&Ven_...\...&0]
+        if let Some(header) = line.strip_prefix(">>>  [Device Install") {
+            pending = header.rsplit_once(" - ")
+                .map(|(_, id)| id.trim_end_matches(']').to_string());
+            continue;
+        }
+
+        // >>>  Section start 2023/01/02 10:11:12.345
+        if let Some(start) = line.strip_prefix(">>>  Section start ")
+            && let Some(device_id) = pending.take()
+            && let Ok(local) = NaiveDateTime::parse_from_str(start.trim(), "%Y/%m/%d %H:%M:%S%.f")
+        {
+            installs.push(SetupApiInstall {
+                device_id,
+                timestamp: local.and_utc() + Duration::minutes(bias_minutes as i64),
+            });
+        }
+    }
+
+    installs
+}
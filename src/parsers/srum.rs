@@ -0,0 +1,221 @@
+// src/parsers/srum.rs
+//! System Resource Usage Monitor tables from `System32\sru\SRUDB.dat`
+use std::collections::HashMap;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::ese::{EseDatabase, EseRecord, EseValue};
+use crate::utils::bytes::utf16z;
+use crate::utils::sid::parse_sid;
+use crate::utils::time::filetime_to_datetime;
+
+const ID_MAP_TABLE: &str = "SruDbIdMapTable";
+const NETWORK_USAGE_TABLE: &str = "{973F5D5C-1D90-4944-BE8E-24B94231A174}";
+const APP_USAGE_TABLE: &str = "{D10CA2FE-6FCF-4F6D-848E-B2E99266FA89}";
+const NETWORK_CONNECTIVITY_TABLE: &str = "{DD6636C4-8929-4683-974E-22C046A43763}";
+/// Energy usage, and its long-term variant with the same columns
+const ENERGY_TABLES: [&str; 2] = ["{FEE4E14F-02A9-4550-B5CE-5FA2DA202E37}", "{FEE4E14F-02A9-4550-B5CE-5FA2DA202E37}LT"];
+
+/// ID map entries of this type hold a SID; the others hold UTF-16 names
+const ID_TYPE_SID: i64 = 3;
+
+/// The decoded SRUM tables
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Srum {
+    pub network_usage: Vec<SrumNetworkUsage>,
+    pub app_usage: Vec<SrumAppUsage>,
+    pub network_connectivity: Vec<SrumConnectivity>,
+    pub energy: Vec<SrumEnergy>,
+}
+
+/// Bytes sent and received by an application over an interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SrumNetworkUsage {
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Executable path, service or package name
+    pub app: Option<String>,
+    pub user_sid: Option<String>,
+    pub interface_luid: Option<i64>,
+    pub profile_id: Option<i64>,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// CPU time and I/O of an application over an interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SrumAppUsage {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub app: Option<String>,
+    pub user_sid: Option<String>,
+    /// Cycle counts while in the foreground and background
+    pub foreground_cycle_time: u64,
+    pub background_cycle_time: u64,
+    /// Milliseconds the application was visible
+    pub face_time: u64,
+    pub foreground_bytes_read: u64,
+    pub foreground_bytes_written: u64,
+    pub background_bytes_read: u64,
+    pub background_bytes_written: u64,
+}
+
+/// How long an interface was connected to a network profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SrumConnectivity {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub app: Option<String>,
+    pub user_sid: Option<String>,
+    pub interface_luid: Option<i64>,
+    pub profile_id: Option<i64>,
+    /// Seconds
+    pub connected_time: u64,
+    pub connect_start_time: Option<DateTime<Utc>>,
+}
+
+/// Battery state snapshots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SrumEnergy {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub app: Option<String>,
+    pub user_sid: Option<String>,
+    pub event_timestamp: Option<DateTime<Utc>>,
+    pub state_transition: Option<i64>,
+    pub charge_level: Option<i64>,
+    pub cycle_count: Option<i64>,
+    pub designed_capacity: Option<i64>,
+    pub full_charged_capacity: Option<i64>,
+}
+
+/// Parse SRUDB.dat; tables missing from the database are left empty
+pub fn parse_srum(data: &[u8]) -> Result<Srum> {
+    let database = EseDatabase::parse(data)?;
+    let ids = id_map(&database);
+    let table = |name: &str| -> Vec<Row> {
+        if database.table(name).is_none() {
+            return Vec::new();
+        }
+        database.records(name)
+            .unwrap_or_else(|e| {
+                log::debug!("Skipping SRUM table {}: {}", name, e);
+                Vec::new()
+            })
+            .into_iter()
+            .map(|record| Row::new(record, &ids))
+            .collect()
+    };
+
+    let network_usage = table(NETWORK_USAGE_TABLE).into_iter()
+        .map(|row| SrumNetworkUsage {
+            interface_luid: row.int("InterfaceLuid"),
+            profile_id: row.int("L2ProfileId"),
+            bytes_sent: row.count("BytesSent"),
+            bytes_received: row.count("BytesRecvd"),
+            timestamp: row.timestamp,
+            app: row.app,
+            user_sid: row.user_sid,
+        })
+        .collect();
+
+    let app_usage = table(APP_USAGE_TABLE).into_iter()
+        .map(|row| SrumAppUsage {
+            foreground_cycle_time: row.count("ForegroundCycleTime"),
+            background_cycle_time: row.count("BackgroundCycleTime"),
+            face_time: row.count("FaceTime"),
+            foreground_bytes_read: row.count("ForegroundBytesRead"),
+            foreground_bytes_written: row.count("ForegroundBytesWritten"),
+            background_bytes_read: row.count("BackgroundBytesRead"),
+            background_bytes_written: row.count("BackgroundBytesWritten"),
+            timestamp: row.timestamp,
+            app: row.app,
+            user_sid: row.user_sid,
+        })
+        .collect();
+
+    let network_connectivity = table(NETWORK_CONNECTIVITY_TABLE).into_iter()
+        .map(|row| SrumConnectivity {
+            interface_luid: row.int("InterfaceLuid"),
+            profile_id: row.int("L2ProfileId"),
+            connected_time: row.count("ConnectedTime"),
+            connect_start_time: row.filetime("ConnectStartTime"),
+            timestamp: row.timestamp,
+            app: row.app,
+            user_sid: row.user_sid,
+        })
+        .collect();
+
+    let energy = ENERGY_TABLES.iter()
+        .flat_map(|name| table(name))
+        .map(|row| SrumEnergy {
+            event_timestamp: row.filetime("EventTimestamp"),
+            state_transition: row.int("StateTransition"),
+            charge_level: row.int("ChargeLevel"),
+            cycle_count: row.int("CycleCount"),
+            designed_capacity: row.int("DesignedCapacity"),
+            full_charged_capacity: row.int("FullChargedCapacity"),
+            timestamp: row.timestamp,
+            app: row.app,
+            user_sid: row.user_sid,
+        })
+        .collect();
+
+    Ok(Srum { network_usage, app_usage, network_connectivity, energy })
+}
+
+/// A record with the columns every SRUM table shares resolved
+struct Row {
+    record: EseRecord,
+    timestamp: Option<DateTime<Utc>>,
+    app: Option<String>,
+    user_sid: Option<String>,
+}
+
+impl Row {
+    fn new(record: EseRecord, ids: &HashMap<i64, String>) -> Self {
+        let id = |name: &str| record.get(name).and_then(EseValue::as_i64).and_then(|id| ids.get(&id)).cloned();
+        Self {
+            timestamp: record.get("TimeStamp").and_then(EseValue::as_datetime),
+            app: id("AppId"),
+            user_sid: id("UserId"),
+            record,
+        }
+    }
+
+    fn int(&self, name: &str) -> Option<i64> {
+        self.record.get(name).and_then(EseValue::as_i64)
+    }
+
+    fn count(&self, name: &str) -> u64 {
+        self.int(name).unwrap_or_default() as u64
+    }
+
+    fn filetime(&self, name: &str) -> Option<DateTime<Utc>> {
+        filetime_to_datetime(self.int(name)? as u64)
+    }
+}
+
+/// SruDbIdMapTable: the names and SIDs behind each table's AppId and UserId
+fn id_map(database: &EseDatabase) -> HashMap<i64, String> {
+    let records = database.records(ID_MAP_TABLE).unwrap_or_else(|e| {
+        log::debug!("Skipping {}: {}", ID_MAP_TABLE, e);
+        Vec::new()
+    });
+
+    let mut ids = HashMap::new();
+    for record in records {
+        let field = |name: &str| record.get(name).and_then(EseValue::as_i64);
+        let (Some(id_type), Some(index)) = (field("IdType"), field("IdIndex")) else {
+            continue;
+        };
+        let Some(blob) = record.get("IdBlob").and_then(EseValue::as_bytes) else {
+            continue;
+        };
+        let value = if id_type == ID_TYPE_SID {
+            parse_sid(blob).map(|(sid, _)| sid)
+        } else {
+            Some(utf16z(blob))
+        };
+        if let Some(value) = value {
+            ids.insert(index, value);
+        }
+    }
+    ids
+}
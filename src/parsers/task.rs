@@ -0,0 +1,245 @@
+// src/parsers/task.rs
+//! Task Scheduler 2.0 task definitions from `Windows\System32\Tasks`, and
+//! the binary action lists cached for them in the registry
+use std::collections::BTreeMap;
+use anyhow::{Result, bail};
+use serde::{Serialize, Deserialize};
+use crate::utils::bytes::utf16z;
+use crate::utils::xml::{children, element, element_text, unescape};
+
+/// Action list magic numbers in TaskCache `Actions` values
+const ACTION_EXEC: u16 = 0x6666;
+const ACTION_COM_HANDLER: u16 = 0x7777;
+const ACTION_SEND_EMAIL: u16 = 0x8888;
+const ACTION_SHOW_MESSAGE: u16 = 0x9999;
+/// Windows 8 and later; earlier versions have no context string
+const ACTIONS_VERSION: u16 = 3;
+
+/// A task's XML definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTaskDefinition {
+    /// Task path, e.g. `\Microsoft\Windows\Defrag\ScheduledDefrag`
+    pub uri: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    /// Registration date as written, usually local time without a zone
+    pub date: Option<String>,
+    pub source: Option<String>,
+    pub enabled: bool,
+    /// Hidden from the Task Scheduler UI by default
+    pub hidden: bool,
+    pub principal: Option<ScheduledTaskPrincipal>,
+    pub triggers: Vec<ScheduledTaskTrigger>,
+    pub actions: Vec<ScheduledTaskAction>,
+}
+
+/// The account a task runs as
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTaskPrincipal {
+    pub user_id: Option<String>,
+    pub group_id: Option<String>,
+    /// e.g. `InteractiveToken`, `S4U`, `Password`
+    pub logon_type: Option<String>,
+    /// `LeastPrivilege` or `HighestAvailable`
+    pub run_level: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTaskTrigger {
+    /// Element name, e.g. `LogonTrigger`, `CalendarTrigger`, `EventTrigger`
+    pub kind: String,
+    pub enabled: bool,
+    pub start_boundary: Option<String>,
+    /// Other settings by element name, nested ones as `Repetition/Interval`
+    pub details: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduledTaskActionKind {
+    Exec,
+    ComHandler,
+    SendEmail,
+    ShowMessage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTaskAction {
+    pub kind: ScheduledTaskActionKind,
+    pub command: Option<String>,
+    pub arguments: Option<String>,
+    pub working_directory: Option<String>,
+    pub class_id: Option<String>,
+    pub data: Option<String>,
+}
+
+impl ScheduledTaskAction {
+    fn new(kind: ScheduledTaskActionKind) -> Self {
+        Self { kind, command: None, arguments: None, working_directory: None, class_id: None, data: None }
+    }
+}
+
+/// Parse a task file, which is usually UTF-16 with a byte order mark
+pub fn parse_scheduled_task(data: &[u8]) -> Result<ScheduledTaskDefinition> {
+    let xml = decode_text(data);
+    let Some(task) = element(&xml, "Task") else {
+        bail!("Not a scheduled task definition");
+    };
+
+    let registration = element(task, "RegistrationInfo").unwrap_or_default();
+    let settings = element(task, "Settings").unwrap_or_default();
+    let principal = element(task, "Principal").map(|principal| ScheduledTaskPrincipal {
+        user_id: element_text(principal, "UserId"),
+        group_id: element_text(principal, "GroupId"),
+        logon_type: element_text(principal, "LogonType"),
+        run_level: element_text(principal, "RunLevel"),
+    });
+
+    let triggers = children(element(task, "Triggers").unwrap_or_default()).into_iter()
+        .map(|(kind, body)| {
+            let mut details = BTreeMap::new();
+            for (name, value) in children(body) {
+                if name == "Enabled" || name == "StartBoundary" {
+                    continue;
+                }
+                let nested = children(value);
+                if nested.is_empty() {
+                    details.insert(name.to_string(), unescape(value.trim()));
+                }
+                for (inner, inner_value) in nested {
+                    details.insert(format!("{}/{}", name, inner), unescape(inner_value.trim()));
+                }
+            }
+            ScheduledTaskTrigger {
+                kind: kind.to_string(),
+                enabled: child_text(body, "Enabled").is_none_or(|v| v == "true"),
+                start_boundary: child_text(body, "StartBoundary"),
+                details,
+            }
+        })
+        .collect();
+
+    let actions = children(element(task, "Actions").unwrap_or_default()).into_iter()
+        .filter_map(|(kind, body)| {
+            let mut action = ScheduledTaskAction::new(match kind {
+                "Exec" => ScheduledTaskActionKind::Exec,
+                "ComHandler" => ScheduledTaskActionKind::ComHandler,
+                "SendEmail" => ScheduledTaskActionKind::SendEmail,
+                "ShowMessage" => ScheduledTaskActionKind::ShowMessage,
+                _ => return None,
+            });
+            action.command = element_text(body, "Command");
+            action.arguments = element_text(body, "Arguments");
+            action.working_directory = element_text(body, "WorkingDirectory");
+            action.class_id = element_text(body, "ClassId");
+            action.data = element_text(body, "Data");
+            Some(action)
+        })
+        .collect();
+
+    Ok(ScheduledTaskDefinition {
+        uri: element_text(registration, "URI"),
+        author: element_text(registration, "Author"),
+        description: element_text(registration, "Description"),
+        date: element_text(registration, "Date"),
+        source: element_text(registration, "Source"),
+        enabled: child_text(settings, "Enabled").is_none_or(|v| v == "true"),
+        hidden: child_text(settings, "Hidden").is_some_and(|v| v == "true"),
+        principal,
+        triggers,
+        actions,
+    })
+}
+
+/// Decode the `Actions` value of a TaskCache `Tasks\{GUID}` key. Only the
+/// Windows 8+ format is understood; earlier versions yield no actions.
+pub fn parse_task_actions(data: &[u8]) -> Vec<ScheduledTaskAction> {
+    let mut reader = Reader { data, pos: 0 };
+    if reader.u16() != Some(ACTIONS_VERSION) {
+        return Vec::new();
+    }
+    // The context names the principal the actions run as
+    if reader.string().is_none() {
+        return Vec::new();
+    }
+
+    let mut actions = Vec::new();
+    while let Some(magic) = reader.u16() {
+        let _id = reader.string();
+        let action = match magic {
+            ACTION_EXEC => {
+                let mut action = ScheduledTaskAction::new(ScheduledTaskActionKind::Exec);
+                action.command = reader.string();
+                action.arguments = reader.string();
+                action.working_directory = reader.string();
+                // Flags
+                reader.u16();
+                action
+            }
+            ACTION_COM_HANDLER => {
+                let mut action = ScheduledTaskAction::new(ScheduledTaskActionKind::ComHandler);
+                action.class_id = reader.bytes(16).and_then(crate::utils::guid::format_guid);
+                action.data = reader.string();
+                action
+            }
+            // Deprecated actions; their layout is not decoded, so stop here
+            ACTION_SEND_EMAIL => {
+                actions.push(ScheduledTaskAction::new(ScheduledTaskActionKind::SendEmail));
+                break;
+            }
+            ACTION_SHOW_MESSAGE => {
+                actions.push(ScheduledTaskAction::new(ScheduledTaskActionKind::ShowMessage));
+                break;
+            }
+            _ => break,
+        };
+        actions.push(action);
+    }
+    actions
+}
+
+/// Text of a direct child element, so that e.g. the task's `Enabled` setting
+/// is not confused with one nested in `IdleSettings`
+fn child_text(xml: &str, name: &str) -> Option<String> {
+    children(xml).into_iter()
+        .find(|(child, _)| *child == name)
+        .map(|(_, text)| unescape(text.trim()))
+}
+
+/// Task files are UTF-16LE with a BOM, though UTF-8 ones turn up too
+fn decode_text(data: &[u8]) -> String {
+    let utf16 = |data: &[u8], convert: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = data.chunks_exact(2).map(|c| convert([c[0], c[1]])).collect();
+        String::from_utf16_lossy(&units)
+    };
+    match data {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        [_, 0, ..] => utf16(data, u16::from_le_bytes),
+        _ => String::from_utf8_lossy(data).into_owned(),
+    }
+}
+
+/// Little-endian fields and length-prefixed UTF-16 strings
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos + count)?;
+        self.pos += count;
+        Some(bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    /// A byte count followed by UTF-16LE text; empty strings are `None`
+    fn string(&mut self) -> Option<String> {
+        let size = u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()) as usize;
+        Some(utf16z(self.bytes(size)?)).filter(|s| !s.is_empty())
+    }
+}
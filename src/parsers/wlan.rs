@@ -0,0 +1,57 @@
+// src/parsers/wlan.rs
+use anyhow::{Result, bail};
+use serde::{Serialize, Deserialize};
+use crate::utils::xml::{element, element_text};
+
+/// A wireless profile from `ProgramData\Microsoft\Wlansvc\Profiles\Interfaces\{interface}\{profile}.xml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WlanProfile {
+    /// Interface GUID, from the directory the profile was found in
+    pub interface_guid: Option<String>,
+    pub name: String,
+    pub ssid: Option<String>,
+    /// SSID bytes in hex, for names that are not valid text
+    pub ssid_hex: Option<String>,
+    /// True for hidden networks
+    pub non_broadcast: bool,
+    /// `ESS` (infrastructure) or `IBSS` (ad hoc)
+    pub connection_type: Option<String>,
+    /// `auto` or `manual`
+    pub connection_mode: Option<String>,
+    /// e.g. `WPA2PSK`, `WPA3SAE`, `open`
+    pub authentication: Option<String>,
+    /// e.g. `AES`, `none`
+    pub encryption: Option<String>,
+    /// Whether the profile stores a key. The key itself is never reported.
+    pub has_key: bool,
+    /// Whether the stored key is DPAPI-protected rather than plaintext
+    pub key_protected: Option<bool>,
+}
+
+/// Parse a WLAN profile XML document
+pub fn parse_wlan_profile(xml: &str) -> Result<WlanProfile> {
+    let xml = xml.trim_start_matches('\u{feff}');
+    if !xml.contains("<WLANProfile") {
+        bail!("Not a WLAN profile");
+    }
+    let Some(name) = element_text(xml, "name") else {
+        bail!("WLAN profile has no name");
+    };
+
+    let ssid = element(xml, "SSID");
+    let key = element(xml, "sharedKey");
+
+    Ok(WlanProfile {
+        interface_guid: None,
+        name,
+        ssid: ssid.and_then(|s| element_text(s, "name")),
+        ssid_hex: ssid.and_then(|s| element_text(s, "hex")),
+        non_broadcast: element_text(xml, "nonBroadcast").is_some_and(|v| v == "true"),
+        connection_type: element_text(xml, "connectionType"),
+        connection_mode: element_text(xml, "connectionMode"),
+        authentication: element_text(xml, "authentication"),
+        encryption: element_text(xml, "encryption"),
+        has_key: key.is_some_and(|k| element(k, "keyMaterial").is_some()),
+        key_protected: key.and_then(|k| element_text(k, "protected")).map(|v| v == "true"),
+    })
+}
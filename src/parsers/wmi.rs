@@ -0,0 +1,388 @@
+// src/parsers/wmi.rs
+//! WMI permanent event subscriptions from the CIM repository
+//! (`Windows\System32\wbem\Repository`), the usual fileless persistence
+//! mechanism
+use std::collections::HashMap;
+use anyhow::{Result, bail};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use crate::utils::bytes::{u16_at, u32_at, utf16_units};
+
+const PAGE_SIZE: usize = 0x2000;
+const MAPPING_SIGNATURE: u32 = 0xABCD;
+const MAPPING_FOOTER: u32 = 0xDCBA;
+/// Vista and later mapping entries: page number, CRC, free space, used
+/// space, first and second ID
+const MAPPING_ENTRY_SIZE: usize = 24;
+const UNMAPPED_PAGE: u32 = 0x3FFF_FFFF;
+const INDEX_PAGE_SIGNATURE: u32 = 0xACCC;
+const INDEX_HEADER_SIZE: usize = 16;
+/// Record table entries in an OBJECTS.DATA page: ID, offset, size, CRC
+const TOC_ENTRY_SIZE: usize = 16;
+/// Instances start with their class name hash as 64 UTF-16 hex digits
+const CLASS_HASH_SIZE: usize = 128;
+/// Hash followed by two FILETIMEs
+const INSTANCE_HEADER_SIZE: usize = CLASS_HASH_SIZE + 16;
+
+const EVENT_FILTER: &str = "__EventFilter";
+const FILTER_TO_CONSUMER_BINDING: &str = "__FilterToConsumerBinding";
+/// Standard consumers of the `__EventConsumer` class
+const EVENT_CONSUMERS: &[&str] = &[
+    "ActiveScriptEventConsumer",
+    "CommandLineEventConsumer",
+    "LogFileEventConsumer",
+    "NTEventLogEventConsumer",
+    "SMTPEventConsumer",
+];
+const SCRIPT_ENGINES: &[&str] = &["VBScript", "JScript"];
+
+/// Event filters, consumers and the bindings between them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WmiPersistence {
+    pub filters: Vec<WmiEventFilter>,
+    pub consumers: Vec<WmiEventConsumer>,
+    pub bindings: Vec<WmiBinding>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WmiEventFilter {
+    pub name: Option<String>,
+    /// WQL event query, e.g. `SELECT * FROM __InstanceModificationEvent ...`
+    pub query: Option<String>,
+    pub query_language: Option<String>,
+    pub event_namespace: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WmiEventConsumer {
+    /// Consumer class, e.g. `CommandLineEventConsumer`
+    pub class: String,
+    pub name: Option<String>,
+    /// Command line, script text or other payload: the longest string
+    pub command: Option<String>,
+    pub script_engine: Option<String>,
+    /// Every string property of the instance
+    pub strings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WmiBinding {
+    pub filter: Option<String>,
+    pub consumer_class: Option<String>,
+    pub consumer: Option<String>,
+}
+
+/// Extract event subscriptions from OBJECTS.DATA, INDEX.BTR and the
+/// MAPPING*.MAP files. Instances are found by class through the index
+/// (or by scanning every record if it is unreadable), and their string
+/// properties are classified by content rather than by decoding each
+/// class's schema.
+pub fn parse_wmi_repository(objects: &[u8], index: &[u8], mappings: &[&[u8]]) -> Result<WmiPersistence> {
+    // The mapping file with the highest sequence number is current
+    let Some((object_map, index_map)) = mappings.iter()
+        .filter_map(|data| parse_mapping_file(data))
+        .max_by_key(|(version, _, _)| *version)
+        .map(|(_, objects, index)| (objects, index))
+    else {
+        bail!("No valid WMI repository mapping file");
+    };
+    let repository = Repository { objects, object_map };
+
+    let hashes: HashMap<String, &str> = [EVENT_FILTER, FILTER_TO_CONSUMER_BINDING].iter()
+        .chain(EVENT_CONSUMERS)
+        .map(|class| (class_hash(class), *class))
+        .collect();
+
+    let mut instances: Vec<(&str, Vec<String>)> = Vec::new();
+    for (hash, page, id, size) in index_keys(index, &index_map) {
+        let Some(&class) = hashes.get(&hash) else {
+            continue;
+        };
+        if let Some(record) = repository.record(page, id, Some(size)) {
+            instances.push((class, heap_strings(record.get(INSTANCE_HEADER_SIZE..).unwrap_or_default())));
+        }
+    }
+    if instances.is_empty() {
+        log::debug!("No subscriptions found through INDEX.BTR, scanning OBJECTS.DATA");
+        for record in repository.records() {
+            let Some(class) = hashes.iter()
+                .find(|(hash, _)| record.get(..CLASS_HASH_SIZE).is_some_and(|prefix| utf16_eq(prefix, hash)))
+                .map(|(_, class)| *class)
+            else {
+                continue;
+            };
+            instances.push((class, heap_strings(&record[INSTANCE_HEADER_SIZE.min(record.len())..])));
+        }
+    }
+
+    let mut bindings = Vec::new();
+    for (_, strings) in instances.iter().filter(|(class, _)| *class == FILTER_TO_CONSUMER_BINDING) {
+        let mut binding = WmiBinding { filter: None, consumer_class: None, consumer: None };
+        for (class, name) in strings.iter().filter_map(|s| parse_reference(s)) {
+            if class.eq_ignore_ascii_case(EVENT_FILTER) {
+                binding.filter = Some(name);
+            } else {
+                binding.consumer_class = Some(class);
+                binding.consumer = Some(name);
+            }
+        }
+        bindings.push(binding);
+    }
+
+    // A name referenced by a binding identifies the name property; failing
+    // that it is whatever single string is left once the others are claimed
+    let filter_names: Vec<&String> = bindings.iter().filter_map(|b| b.filter.as_ref()).collect();
+    let consumer_names: Vec<&String> = bindings.iter().filter_map(|b| b.consumer.as_ref()).collect();
+    let mut filters = Vec::new();
+    let mut consumers = Vec::new();
+    for (class, strings) in &instances {
+        match *class {
+            EVENT_FILTER => {
+                let query = strings.iter().find(|s| s.to_uppercase().starts_with("SELECT ")).cloned();
+                let query_language = strings.iter().find(|s| s.eq_ignore_ascii_case("WQL")).cloned();
+                let event_namespace = strings.iter()
+                    .find(|s| s.to_lowercase().starts_with("root\\") || s.eq_ignore_ascii_case("root"))
+                    .cloned();
+                let name = strings.iter().find(|s| filter_names.contains(s)).cloned()
+                    .or_else(|| single_remaining(strings, &[&query, &query_language, &event_namespace]));
+                filters.push(WmiEventFilter { name, query, query_language, event_namespace });
+            }
+            FILTER_TO_CONSUMER_BINDING => {}
+            consumer => {
+                let bound_name = strings.iter().find(|s| consumer_names.contains(s)).cloned();
+                let script_engine = strings.iter()
+                    .find(|s| SCRIPT_ENGINES.iter().any(|engine| s.eq_ignore_ascii_case(engine)))
+                    .cloned();
+                let command = strings.iter()
+                    .filter(|s| Some(*s) != bound_name.as_ref() && Some(*s) != script_engine.as_ref())
+                    .max_by_key(|s| s.len())
+                    .cloned();
+                let name = bound_name.or_else(|| single_remaining(strings, &[&command, &script_engine]));
+                consumers.push(WmiEventConsumer {
+                    class: consumer.to_string(),
+                    name,
+                    command,
+                    script_engine,
+                    strings: strings.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(WmiPersistence { filters, consumers, bindings })
+}
+
+/// Uppercase hex SHA-256 of the UTF-16LE uppercased class name, as used in
+/// index keys since Vista
+fn class_hash(class: &str) -> String {
+    let name: Vec<u8> = class.to_uppercase().encode_utf16().flat_map(u16::to_le_bytes).collect();
+    Sha256::digest(name).iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+fn utf16_eq(data: &[u8], text: &str) -> bool {
+    data.len() == text.len() * 2 && text.encode_utf16().flat_map(u16::to_le_bytes).eq(data.iter().copied())
+}
+
+/// Logical to physical page maps for OBJECTS.DATA and INDEX.BTR, with the
+/// file's sequence number
+fn parse_mapping_file(data: &[u8]) -> Option<(u32, Vec<u32>, Vec<u32>)> {
+    let (version, objects, end) = parse_mapping(data, 0)?;
+    let (_, index, _) = parse_mapping(data, end)?;
+    Some((version, objects, index))
+}
+
+fn parse_mapping(data: &[u8], offset: usize) -> Option<(u32, Vec<u32>, usize)> {
+    if u32_at(data, offset)? != MAPPING_SIGNATURE {
+        return None;
+    }
+    let version = u32_at(data, offset + 4)?;
+    let count = u32_at(data, offset + 20)? as usize;
+    let entries = offset + 24;
+    let pages = (0..count)
+        .map(|i| u32_at(data, entries + i * MAPPING_ENTRY_SIZE).map(|page| page & UNMAPPED_PAGE))
+        .collect::<Option<Vec<u32>>>()?;
+
+    let free = entries + count * MAPPING_ENTRY_SIZE;
+    let footer = free + 4 + u32_at(data, free)? as usize * 4;
+    if u32_at(data, footer)? != MAPPING_FOOTER {
+        return None;
+    }
+    Some((version, pages, footer + 4))
+}
+
+/// Instance keys in the index as (class hash, logical page, record ID,
+/// size). Keys look like `NS_<hash>\CI_<hash>\IL_<hash>.<page>.<id>.<size>`.
+fn index_keys(index: &[u8], map: &[u32]) -> Vec<(String, u32, u32, u32)> {
+    let mut keys = Vec::new();
+
+    for &physical in map.iter().filter(|&&page| page != UNMAPPED_PAGE) {
+        let Some(page) = index.get(physical as usize * PAGE_SIZE..(physical as usize + 1) * PAGE_SIZE) else {
+            continue;
+        };
+        if u32_at(page, 0) != Some(INDEX_PAGE_SIGNATURE) {
+            continue;
+        }
+        for key in index_page_keys(page).unwrap_or_default() {
+            let parts: Vec<&str> = key.split(['\\', '/']).collect();
+            let Some(class) = parts.iter().find_map(|part| part.strip_prefix("CI_")) else {
+                continue;
+            };
+            let Some(location) = parts.last().and_then(|part| part.strip_prefix("IL_")) else {
+                continue;
+            };
+            let numbers: Vec<u32> = location.split('.').skip(1).filter_map(|n| n.parse().ok()).collect();
+            if let [page, id, size] = numbers[..] {
+                keys.push((class.to_uppercase(), page, id, size));
+            }
+        }
+    }
+
+    keys
+}
+
+/// Keys of one B-tree page. Each key is a list of indexes into the page's
+/// string table.
+fn index_page_keys(page: &[u8]) -> Option<Vec<String>> {
+    let count = u32_at(page, INDEX_HEADER_SIZE)? as usize;
+    // Unknown dwords, then child page numbers
+    let offsets = INDEX_HEADER_SIZE + 4 + count * 4 + (count + 1) * 4;
+    let key_info_size = u16_at(page, offsets + count * 2)? as usize;
+    let key_info = offsets + count * 2 + 2;
+    let string_count = u16_at(page, key_info + key_info_size * 2)? as usize;
+    let string_offsets = key_info + key_info_size * 2 + 2;
+    let strings = string_offsets + (string_count + 1) * 2;
+
+    let string = |index: usize| -> Option<String> {
+        if index >= string_count {
+            return None;
+        }
+        let start = strings + u16_at(page, string_offsets + index * 2)? as usize;
+        let text = page.get(start..)?;
+        let end = text.iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&text[..end]).into_owned())
+    };
+
+    (0..count)
+        .map(|i| {
+            let at = key_info + u16_at(page, offsets + i * 2)? as usize * 2;
+            let parts = u16_at(page, at)? as usize;
+            (0..parts)
+                .map(|part| string(u16_at(page, at + 2 + part * 2)? as usize))
+                .collect::<Option<Vec<String>>>()
+                .map(|parts| parts.join("\\"))
+        })
+        .collect()
+}
+
+struct Repository<'a> {
+    objects: &'a [u8],
+    object_map: Vec<u32>,
+}
+
+impl<'a> Repository<'a> {
+    fn page(&self, logical: u32) -> Option<&'a [u8]> {
+        let physical = *self.object_map.get(logical as usize)? as usize;
+        if physical == UNMAPPED_PAGE as usize {
+            return None;
+        }
+        self.objects.get(physical * PAGE_SIZE..(physical + 1) * PAGE_SIZE)
+    }
+
+    /// Record table of a page, which ends at an all-zero entry
+    fn toc(page: &[u8]) -> Vec<(u32, usize, usize)> {
+        let mut entries = Vec::new();
+        for entry in page.chunks_exact(TOC_ENTRY_SIZE) {
+            let (Some(id), Some(offset), Some(size)) = (u32_at(entry, 0), u32_at(entry, 4), u32_at(entry, 8)) else {
+                break;
+            };
+            if id == 0 && offset == 0 && size == 0 {
+                break;
+            }
+            entries.push((id, offset as usize, size as usize));
+        }
+        entries
+    }
+
+    /// A record's data; records larger than the rest of their page continue
+    /// on the following logical pages
+    fn record(&self, logical: u32, id: u32, size: Option<u32>) -> Option<Vec<u8>> {
+        let page = self.page(logical)?;
+        let toc = Self::toc(page);
+        let &(_, offset, recorded_size) = toc.iter().find(|(record, _, _)| *record == id)?;
+        let size = size.map_or(recorded_size, |size| size as usize);
+        if offset < (toc.len() + 1) * TOC_ENTRY_SIZE || offset >= PAGE_SIZE {
+            return None;
+        }
+
+        let mut data = page[offset..PAGE_SIZE.min(offset + size)].to_vec();
+        let mut next = logical + 1;
+        while data.len() < size {
+            let page = self.page(next)?;
+            data.extend_from_slice(&page[..PAGE_SIZE.min(size - data.len())]);
+            next += 1;
+        }
+        Some(data)
+    }
+
+    /// Every record whose table entry looks valid, for when the index is lost
+    fn records(&self) -> Vec<Vec<u8>> {
+        (0..self.object_map.len() as u32)
+            .flat_map(|logical| {
+                let toc = self.page(logical).map(Self::toc).unwrap_or_default();
+                toc.into_iter().filter_map(move |(id, _, _)| self.record(logical, id, None))
+            })
+            .collect()
+    }
+}
+
+/// Strings in an instance's data heap: a flag byte (0 for ASCII, 1 for
+/// UTF-16LE) followed by NUL-terminated text
+fn heap_strings(data: &[u8]) -> Vec<String> {
+    let mut strings: Vec<String> = Vec::new();
+    let mut pos = 0;
+
+    while pos + 1 < data.len() {
+        let text = &data[pos + 1..];
+        let found = match data[pos] {
+            0 => text.iter().position(|&b| b == 0)
+                .filter(|&end| end >= 2 && text[..end].iter().all(|&b| (0x20..0x7F).contains(&b) || b"\t\r\n".contains(&b)))
+                .map(|end| (String::from_utf8_lossy(&text[..end]).into_owned(), end + 2)),
+            1 => {
+                let units: Vec<u16> = utf16_units(text).take_while(|&c| c != 0).collect();
+                Some(units)
+                    .filter(|units| units.len() >= 2 && units.len() * 2 + 2 <= text.len())
+                    .filter(|units| units.iter().all(|&c| c >= 0x20 || [0x09, 0x0A, 0x0D].contains(&c)))
+                    .map(|units| (String::from_utf16_lossy(&units), units.len() * 2 + 3))
+            }
+            _ => None,
+        };
+        // Skip past the flag, text and terminator
+        match found {
+            Some((string, size)) => {
+                if !strings.contains(&string) {
+                    strings.push(string);
+                }
+                pos += size;
+            }
+            None => pos += 1,
+        }
+    }
+
+    strings
+}
+
+/// Class and key value of an object path such as
+/// `CommandLineEventConsumer.Name="Updater"`
+fn parse_reference(path: &str) -> Option<(String, String)> {
+    let (class, value) = path.split_once(".Name=")?;
+    let class = class.rsplit([':', '\\']).next()?;
+    let value = value.strip_prefix('"')?.strip_suffix('"')?;
+    Some((class.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
+
+/// The only string not already claimed by another property
+fn single_remaining(strings: &[String], claimed: &[&Option<String>]) -> Option<String> {
+    let mut remaining = strings.iter().filter(|s| !claimed.iter().any(|c| c.as_ref() == Some(*s)));
+    let name = remaining.next()?;
+    remaining.next().is_none().then(|| name.clone())
+}
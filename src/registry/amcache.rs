@@ -0,0 +1,237 @@
+// src/registry/amcache.rs
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Serialize, Deserialize};
+use super::{RegistryHive, RegistryKey};
+use crate::utils::time::unix_to_datetime;
+
+/// Windows 8 and early Windows 10 layout: `Root\File\<volume>\<file reference>`
+const LEGACY_FILE_PATH: &str = "Root\\File";
+const APPLICATION_FILE_PATH: &str = "Root\\InventoryApplicationFile";
+const APPLICATION_PATH: &str = "Root\\InventoryApplication";
+const DRIVER_BINARY_PATH: &str = "Root\\InventoryDriverBinary";
+const DEVICE_PNP_PATH: &str = "Root\\InventoryDevicePnp";
+
+/// Value names of the legacy layout, which uses hex numbers
+const LEGACY_PRODUCT_NAME: &str = "0";
+const LEGACY_COMPANY_NAME: &str = "1";
+const LEGACY_FILE_VERSION: &str = "5";
+const LEGACY_FILE_SIZE: &str = "6";
+const LEGACY_DESCRIPTION: &str = "c";
+const LEGACY_LINK_DATE: &str = "f";
+const LEGACY_LAST_MODIFIED: &str = "11";
+const LEGACY_CREATED: &str = "12";
+const LEGACY_FULL_PATH: &str = "15";
+const LEGACY_PROGRAM_ID: &str = "100";
+const LEGACY_SHA1: &str = "101";
+
+/// Everything recorded in an Amcache.hve
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Amcache {
+    pub files: Vec<AmcacheFile>,
+    pub applications: Vec<AmcacheApplication>,
+    pub drivers: Vec<AmcacheDriver>,
+    pub devices: Vec<AmcacheDevice>,
+}
+
+/// An executable the inventory recorded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmcacheFile {
+    pub path: Option<String>,
+    pub sha1: Option<String>,
+    pub publisher: Option<String>,
+    pub product_name: Option<String>,
+    pub description: Option<String>,
+    pub version: Option<String>,
+    pub size: Option<u64>,
+    /// PE header compile time
+    pub link_date: Option<DateTime<Utc>>,
+    pub program_id: Option<String>,
+    /// Last write time of the file's key
+    pub first_seen: Option<DateTime<Utc>>,
+    /// File system timestamps (legacy layout only)
+    pub last_modified: Option<DateTime<Utc>>,
+    pub created: Option<DateTime<Utc>>,
+    /// MFT entry and sequence from the legacy key name
+    pub mft_entry: Option<u64>,
+    pub mft_sequence: Option<u16>,
+}
+
+/// An installed program from `InventoryApplication`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmcacheApplication {
+    pub program_id: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub publisher: Option<String>,
+    pub install_date: Option<DateTime<Utc>>,
+    pub source: Option<String>,
+    pub root_dir_path: Option<String>,
+    pub uninstall_string: Option<String>,
+    pub first_seen: Option<DateTime<Utc>>,
+}
+
+/// A driver from `InventoryDriverBinary`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmcacheDriver {
+    pub path: String,
+    pub name: Option<String>,
+    pub sha1: Option<String>,
+    pub company: Option<String>,
+    pub version: Option<String>,
+    pub product: Option<String>,
+    pub service: Option<String>,
+    pub signed: Option<bool>,
+    pub link_date: Option<DateTime<Utc>>,
+    pub last_write: Option<DateTime<Utc>>,
+    pub first_seen: Option<DateTime<Utc>>,
+}
+
+/// A Plug and Play device from `InventoryDevicePnp`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmcacheDevice {
+    pub id: String,
+    pub description: Option<String>,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub class: Option<String>,
+    pub driver_name: Option<String>,
+    pub driver_version: Option<String>,
+    pub service: Option<String>,
+    pub parent_id: Option<String>,
+    pub first_seen: Option<DateTime<Utc>>,
+}
+
+/// Parse an Amcache.hve, reading whichever of the legacy and inventory
+/// layouts are present
+pub fn parse_amcache(hive: &RegistryHive) -> Amcache {
+    let mut amcache = Amcache::default();
+
+    if let Ok(volumes) = hive.get_key(LEGACY_FILE_PATH).and_then(|key| key.enumerate_subkeys()) {
+        for volume in volumes {
+            for file in volume.enumerate_subkeys().unwrap_or_default() {
+                amcache.files.push(parse_legacy_file(&file));
+            }
+        }
+    }
+
+    amcache.files.extend(subkeys(hive, APPLICATION_FILE_PATH).iter().map(parse_application_file));
+    amcache.applications = subkeys(hive, APPLICATION_PATH).iter().map(parse_application).collect();
+    amcache.drivers = subkeys(hive, DRIVER_BINARY_PATH).iter().map(parse_driver).collect();
+    amcache.devices = subkeys(hive, DEVICE_PNP_PATH).iter().map(parse_device).collect();
+
+    amcache
+}
+
+fn parse_legacy_file(key: &RegistryKey) -> AmcacheFile {
+    // Key names are the file reference in hex: sequence in the top 16 bits
+    let reference = u64::from_str_radix(key.name(), 16).ok();
+
+    AmcacheFile {
+        path: key.string_value(LEGACY_FULL_PATH),
+        sha1: key.string_value(LEGACY_SHA1).map(|s| strip_sha1_padding(&s)),
+        publisher: key.string_value(LEGACY_COMPANY_NAME),
+        product_name: key.string_value(LEGACY_PRODUCT_NAME),
+        description: key.string_value(LEGACY_DESCRIPTION),
+        version: key.string_value(LEGACY_FILE_VERSION),
+        size: key.integer_value(LEGACY_FILE_SIZE),
+        link_date: key.integer_value(LEGACY_LINK_DATE).and_then(|secs| unix_to_datetime(secs as i64)),
+        program_id: key.string_value(LEGACY_PROGRAM_ID),
+        first_seen: key.last_write_time(),
+        last_modified: key.get_value(LEGACY_LAST_MODIFIED).ok().and_then(|v| v.as_filetime()),
+        created: key.get_value(LEGACY_CREATED).ok().and_then(|v| v.as_filetime()),
+        mft_entry: reference.map(|r| r & 0x0000_FFFF_FFFF_FFFF),
+        mft_sequence: reference.map(|r| (r >> 48) as u16),
+    }
+}
+
+fn parse_application_file(key: &RegistryKey) -> AmcacheFile {
+    AmcacheFile {
+        path: key.string_value("LowerCaseLongPath"),
+        sha1: key.string_value("FileId").map(|s| strip_sha1_padding(&s)),
+        publisher: key.string_value("Publisher"),
+        product_name: key.string_value("ProductName"),
+        description: None,
+        version: key.string_value("Version").or_else(|| key.string_value("BinFileVersion")),
+        size: key.integer_value("Size"),
+        link_date: key.string_value("LinkDate").and_then(|s| parse_inventory_date(&s)),
+        program_id: key.string_value("ProgramId"),
+        first_seen: key.last_write_time(),
+        last_modified: None,
+        created: None,
+        mft_entry: None,
+        mft_sequence: None,
+    }
+}
+
+fn parse_application(key: &RegistryKey) -> AmcacheApplication {
+    AmcacheApplication {
+        program_id: key.name().to_string(),
+        name: key.string_value("Name"),
+        version: key.string_value("Version"),
+        publisher: key.string_value("Publisher"),
+        install_date: key.string_value("InstallDate").and_then(|s| parse_inventory_date(&s)),
+        source: key.string_value("Source"),
+        root_dir_path: key.string_value("RootDirPath"),
+        uninstall_string: key.string_value("UninstallString"),
+        first_seen: key.last_write_time(),
+    }
+}
+
+fn parse_driver(key: &RegistryKey) -> AmcacheDriver {
+    AmcacheDriver {
+        path: key.name().to_string(),
+        name: key.string_value("DriverName"),
+        sha1: key.string_value("DriverId").map(|s| strip_sha1_padding(&s)),
+        company: key.string_value("DriverCompany"),
+        version: key.string_value("DriverVersion"),
+        product: key.string_value("Product"),
+        service: key.string_value("Service"),
+        signed: key.integer_value("DriverSigned").map(|v| v != 0),
+        link_date: key.integer_value("DriverTimeStamp").and_then(|secs| unix_to_datetime(secs as i64)),
+        last_write: key.string_value("DriverLastWriteTime").and_then(|s| parse_inventory_date(&s)),
+        first_seen: key.last_write_time(),
+    }
+}
+
+fn parse_device(key: &RegistryKey) -> AmcacheDevice {
+    AmcacheDevice {
+        id: key.name().to_string(),
+        description: key.string_value("Description"),
+        manufacturer: key.string_value("Manufacturer"),
+        model: key.string_value("Model"),
+        class: key.string_value("Class"),
+        driver_name: key.string_value("DriverName"),
+        driver_version: key.string_value("DriverVerVersion"),
+        service: key.string_value("Service"),
+        parent_id: key.string_value("ParentId"),
+        first_seen: key.last_write_time(),
+    }
+}
+
+fn subkeys(hive: &RegistryHive, path: &str) -> Vec<RegistryKey> {
+    hive.get_key(path)
+        .and_then(|key| key.enumerate_subkeys())
+        .unwrap_or_default()
+}
+
+/// SHA-1 values are stored as 44 hex digits, left-padded with `0000`
+pub fn strip_sha1_padding(value: &str) -> String {
+    let value = value.trim().to_lowercase();
+    match value.strip_prefix("0000") {
+        Some(sha1) if sha1.len() == 40 => sha1.to_string(),
+        _ => value,
+    }
+}
+
+/// Inventory keys store dates as `MM/DD/YYYY HH:MM:SS` strings
+fn parse_inventory_date(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value.trim(), "%m/%d/%Y %H:%M:%S")
+        .ok()
+        .map(|t| t.and_utc())
+        .or_else(|| {
+            // InstallDate is sometimes a bare date
+            NaiveDate::parse_from_str(value.trim(), "%m/%d/%Y").ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|t| t.and_utc())
+        })
+}
@@ -0,0 +1,106 @@
+// src/registry/credentials/crypto.rs
+use aes::{Aes128, Aes256};
+use aes::cipher::{BlockDecrypt, BlockDecryptMut, KeyInit, KeyIvInit};
+use aes::cipher::block_padding::NoPadding;
+use aes::cipher::generic_array::GenericArray;
+use des::Des;
+use md4::Md4;
+use md5::{Digest, Md5};
+use sha2::Sha256;
+
+pub(super) fn md5(parts: &[&[u8]]) -> [u8; 16] {
+    let mut hasher = Md5::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// NT hash of a password: MD4 over its UTF-16LE bytes
+pub(super) fn nt_hash(utf16: &[u8]) -> [u8; 16] {
+    Md4::digest(utf16).into()
+}
+
+pub(super) fn rc4(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut state: [u8; 256] = std::array::from_fn(|i| i as u8);
+    let mut j = 0u8;
+    for i in 0..256 {
+        j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
+        state.swap(i, j as usize);
+    }
+
+    let (mut i, mut j) = (0u8, 0u8);
+    data.iter()
+        .map(|byte| {
+            i = i.wrapping_add(1);
+            j = j.wrapping_add(state[i as usize]);
+            state.swap(i as usize, j as usize);
+            byte ^ state[state[i as usize].wrapping_add(state[j as usize]) as usize]
+        })
+        .collect()
+}
+
+/// AES-128-CBC without padding; trailing partial blocks are dropped
+pub(super) fn aes128_cbc_decrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+    let mut buffer = data[..data.len() / 16 * 16].to_vec();
+    let decryptor = cbc::Decryptor::<Aes128>::new_from_slices(key, iv).ok()?;
+    decryptor.decrypt_padded_mut::<NoPadding>(&mut buffer).ok()?;
+    Some(buffer)
+}
+
+/// The LSA decrypts each block independently (CBC with a zero IV that is
+/// reset per block), with a key of SHA-256 over the key and the first 32
+/// bytes of the data repeated 1000 times
+pub(super) fn lsa_decrypt(key: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+    let (salt, encrypted) = (data.get(..32)?, data.get(32..)?);
+
+    let mut hasher = Sha256::new();
+    hasher.update(key);
+    for _ in 0..1000 {
+        hasher.update(salt);
+    }
+    let cipher = Aes256::new(&hasher.finalize());
+
+    let mut plain = Vec::with_capacity(encrypted.len().div_ceil(16) * 16);
+    for chunk in encrypted.chunks(16) {
+        let mut block = [0u8; 16];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let mut block = GenericArray::from(block);
+        cipher.decrypt_block(&mut block);
+        plain.extend_from_slice(&block);
+    }
+    Some(plain)
+}
+
+/// Undo the per-RID DES obfuscation applied to SAM password hashes
+pub(super) fn des_unobfuscate(rid: u32, data: &[u8; 16]) -> [u8; 16] {
+    let r = rid.to_le_bytes();
+    let keys = [
+        des_key([r[0], r[1], r[2], r[3], r[0], r[1], r[2]]),
+        des_key([r[3], r[0], r[1], r[2], r[3], r[0], r[1]]),
+    ];
+
+    let mut hash = [0u8; 16];
+    for (i, key) in keys.iter().enumerate() {
+        let cipher = Des::new(GenericArray::from_slice(key));
+        let mut block = GenericArray::clone_from_slice(&data[i * 8..i * 8 + 8]);
+        cipher.decrypt_block(&mut block);
+        hash[i * 8..i * 8 + 8].copy_from_slice(&block);
+    }
+    hash
+}
+
+/// Spread 56 key bits over 8 bytes, leaving the low (parity) bit clear
+fn des_key(k: [u8; 7]) -> [u8; 8] {
+    [
+        k[0] >> 1,
+        ((k[0] & 0x01) << 6) | (k[1] >> 2),
+        ((k[1] & 0x03) << 5) | (k[2] >> 3),
+        ((k[2] & 0x07) << 4) | (k[3] >> 4),
+        ((k[3] & 0x0F) << 3) | (k[4] >> 5),
+        ((k[4] & 0x1F) << 2) | (k[5] >> 6),
+        ((k[5] & 0x3F) << 1) | (k[6] >> 7),
+        k[6] & 0x7F,
+    ]
+    .map(|b| b << 1)
+}
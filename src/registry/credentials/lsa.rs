@@ -0,0 +1,133 @@
+// src/registry/credentials/lsa.rs
+use anyhow::{Result, bail, Context};
+use chrono::{DateTime, Utc};
+use crate::registry::RegistryHive;
+use crate::utils::bytes::{hex, utf16};
+use crate::utils::time::filetime_to_datetime;
+use super::crypto::{aes128_cbc_decrypt, lsa_decrypt};
+use super::CachedLogon;
+
+const POL_EK_LIST: &str = "Policy\\PolEKList";
+const SECRETS: &str = "Policy\\Secrets";
+const CACHE: &str = "Cache";
+
+/// LSA_SECRET: version, key ID, algorithm and flags precede the data
+const SECRET_HEADER: usize = 28;
+/// LSA_SECRET_BLOB: a length and 12 unknown bytes precede the secret
+const BLOB_HEADER: usize = 16;
+/// The LSA key sits at this offset of the decrypted PolEKList blob
+const LSA_KEY_OFFSET: usize = 68;
+
+const DEFAULT_ITERATION_COUNT: u32 = 10240;
+
+/// Derive the LSA key from PolEKList (Vista and later)
+pub(super) fn lsa_key(security: &RegistryHive, boot_key: &[u8; 16]) -> Result<Vec<u8>> {
+    let Ok(key) = security.get_key(POL_EK_LIST) else {
+        bail!("No PolEKList; secrets from Windows XP and 2003 are not supported");
+    };
+    let value = key.get_value("")?;
+    let data = value.as_bytes().context("PolEKList is not binary")?;
+
+    let plain = lsa_decrypt(boot_key, data.get(SECRET_HEADER..).context("PolEKList truncated")?)
+        .context("PolEKList truncated")?;
+    Ok(plain.get(LSA_KEY_OFFSET..LSA_KEY_OFFSET + 32).context("PolEKList too short")?.to_vec())
+}
+
+/// A decrypted secret, before it is classified
+pub(super) struct RawSecret {
+    pub name: String,
+    pub last_write: Option<DateTime<Utc>>,
+    pub data: Vec<u8>,
+}
+
+/// Decrypt the current value of every secret under `Policy\Secrets`
+pub(super) fn secrets(security: &RegistryHive, lsa_key: &[u8]) -> Vec<RawSecret> {
+    let keys = security.get_key(SECRETS).and_then(|key| key.enumerate_subkeys()).unwrap_or_default();
+
+    keys.into_iter()
+        .filter(|key| key.name() != "NL$Control")
+        .filter_map(|key| {
+            let current = key.get_subkey("CurrVal").ok()?;
+            let value = current.get_value("").ok()?;
+            let Some(data) = decrypt_secret(lsa_key, value.as_bytes()?) else {
+                log::debug!("Failed to decrypt LSA secret {}", key.name());
+                return None;
+            };
+            Some(RawSecret {
+                name: key.name().to_string(),
+                last_write: current.last_write_time(),
+                data,
+            })
+        })
+        .collect()
+}
+
+fn decrypt_secret(lsa_key: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+    let plain = lsa_decrypt(lsa_key, data.get(SECRET_HEADER..)?)?;
+    let length = u32::from_le_bytes(plain.get(..4)?.try_into().ok()?) as usize;
+    Some(plain.get(BLOB_HEADER..BLOB_HEADER + length)?.to_vec())
+}
+
+/// Decrypt the MS-Cache v2 (DCC2) entries under `Cache` with the NL$KM secret
+pub(super) fn cached_logons(security: &RegistryHive, nlkm: &[u8]) -> Result<Vec<CachedLogon>> {
+    let key = security.get_key(CACHE)?;
+    let cache_key = nlkm.get(16..32).context("NL$KM secret too short")?;
+
+    // Small counts are in units of 1024; larger ones are literal
+    let iteration_count = key.integer_value("NL$IterationCount")
+        .map(|count| count as u32)
+        .map(|count| if count > 10240 { count & 0xFFFF_FC00 } else { count * 1024 })
+        .unwrap_or(DEFAULT_ITERATION_COUNT);
+
+    let mut logons = Vec::new();
+    for (name, value) in key.values()? {
+        if !name.starts_with("NL$") || name == "NL$Control" || name == "NL$IterationCount" {
+            continue;
+        }
+        let Some(record) = value.as_bytes() else {
+            continue;
+        };
+        if let Some(logon) = parse_cache_record(record, cache_key, iteration_count) {
+            logons.push(logon);
+        }
+    }
+
+    Ok(logons)
+}
+
+/// NL_RECORD: name lengths, IDs and flags, then the IV, a checksum and the
+/// encrypted hash and names
+fn parse_cache_record(record: &[u8], cache_key: &[u8], iteration_count: u32) -> Option<CachedLogon> {
+    let word = |offset: usize| Some(u16::from_le_bytes(record.get(offset..offset + 2)?.try_into().ok()?) as usize);
+    let user_length = word(0)?;
+    let domain_length = word(2)?;
+    let dns_domain_length = word(60)?;
+    let flags = u32::from_le_bytes(record.get(48..52)?.try_into().ok()?);
+    let iv = record.get(64..80)?;
+
+    // Unused slots have a zero IV; only encrypted entries are supported
+    if iv.iter().all(|&b| b == 0) || flags & 1 == 0 {
+        return None;
+    }
+
+    let plain = aes128_cbc_decrypt(cache_key, iv, record.get(96..)?)?;
+    let hash = plain.get(..16)?;
+
+    // Names are padded to 4-byte boundaries
+    let pad = |length: usize| (length + 3) & !3;
+    let names = plain.get(0x48..)?;
+    let username = utf16(names.get(..user_length)?);
+    let domain_start = pad(user_length);
+    let domain = utf16(names.get(domain_start..domain_start + domain_length)?);
+    let dns_start = domain_start + pad(domain_length);
+    let dns_domain = names.get(dns_start..dns_start + dns_domain_length).map(utf16);
+
+    Some(CachedLogon {
+        hash: format!("$DCC2${}#{}#{}", iteration_count, username, hex(hash)),
+        username,
+        domain,
+        dns_domain: dns_domain.filter(|d| !d.is_empty()),
+        last_logon: filetime_to_datetime(u64::from_le_bytes(record.get(32..40)?.try_into().ok()?)),
+        iteration_count,
+    })
+}
@@ -0,0 +1,166 @@
+// src/registry/credentials/mod.rs
+//! Offline decryption of SAM password hashes, LSA secrets and cached
+//! domain logons, for assessing credential exposure on a compromised host.
+//! Only built with the `credentials` feature. Plaintext secret values are
+//! withheld unless explicitly requested.
+mod crypto;
+mod syskey;
+mod lsa;
+
+use anyhow::{Result, Context};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use super::RegistryParser;
+use crate::utils::bytes::{hex, utf16};
+use crypto::nt_hash;
+use lsa::RawSecret;
+
+pub use syskey::boot_key;
+
+const SYSTEM: &str = "HKLM\\SYSTEM";
+const SOFTWARE: &str = "HKLM\\SOFTWARE";
+const SAM: &str = "HKLM\\SAM";
+const SECURITY: &str = "HKLM\\SECURITY";
+const WINLOGON: &str = "Microsoft\\Windows NT\\CurrentVersion\\Winlogon";
+
+/// Everything recovered from the SAM and SECURITY hives
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Credentials {
+    pub sam_hashes: Vec<SamHash>,
+    pub lsa_secrets: Vec<LsaSecret>,
+    pub cached_logons: Vec<CachedLogon>,
+}
+
+/// Password hashes of a local account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamHash {
+    pub rid: u32,
+    pub username: String,
+    /// Absent when LM hashes are disabled, as they are by default since Vista
+    pub lm_hash: Option<String>,
+    pub nt_hash: Option<String>,
+}
+
+/// What an LSA secret holds, judged by its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SecretKind {
+    /// `_SC_<service>`: password of a service's logon account
+    ServicePassword,
+    /// `DefaultPassword`: autologon password
+    AutoLogonPassword,
+    /// `$MACHINE.ACC`: domain computer account password
+    MachineAccount,
+    /// `NL$KM`: key protecting cached domain logons
+    CachedLogonKey,
+    /// `DPAPI_SYSTEM`: machine and user DPAPI master key material
+    DpapiSystem,
+    Other,
+}
+
+/// A secret from `SECURITY\Policy\Secrets`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LsaSecret {
+    pub name: String,
+    pub kind: SecretKind,
+    /// Service name, for service passwords
+    pub service: Option<String>,
+    /// Account the password belongs to, where it can be resolved
+    pub account: Option<String>,
+    /// NT hash of the password, for password secrets
+    pub nt_hash: Option<String>,
+    pub last_write: Option<DateTime<Utc>>,
+    /// Plaintext (passwords) or hex (everything else), only when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// A cached domain logon (MS-Cache v2 / DCC2)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedLogon {
+    pub username: String,
+    pub domain: String,
+    pub dns_domain: Option<String>,
+    pub last_logon: Option<DateTime<Utc>>,
+    pub iteration_count: u32,
+    /// In `$DCC2$<iterations>#<user>#<hash>` form
+    pub hash: String,
+}
+
+/// Decrypt the credentials in the loaded SYSTEM, SAM and SECURITY hives.
+/// Missing SAM or SECURITY hives leave their sections empty.
+pub fn extract_credentials(registry: &RegistryParser, reveal_secrets: bool) -> Result<Credentials> {
+    let system = registry.hive(SYSTEM).context("SYSTEM hive not found")?;
+    let boot_key = boot_key(system)?;
+    let mut credentials = Credentials::default();
+
+    if let Some(sam) = registry.hive(SAM) {
+        credentials.sam_hashes = syskey::sam_hashes(sam, &boot_key)?;
+    }
+
+    let Some(security) = registry.hive(SECURITY) else {
+        return Ok(credentials);
+    };
+    let lsa_key = lsa::lsa_key(security, &boot_key)?;
+    let secrets = lsa::secrets(security, &lsa_key);
+
+    if let Some(nlkm) = secrets.iter().find(|s| s.name == "NL$KM") {
+        credentials.cached_logons = lsa::cached_logons(security, &nlkm.data).unwrap_or_else(|e| {
+            log::debug!("No cached logons: {}", e);
+            Vec::new()
+        });
+    }
+
+    credentials.lsa_secrets = secrets.into_iter()
+        .map(|secret| classify_secret(registry, secret, reveal_secrets))
+        .collect();
+
+    Ok(credentials)
+}
+
+fn classify_secret(registry: &RegistryParser, secret: RawSecret, reveal: bool) -> LsaSecret {
+    let service = secret.name.strip_prefix("_SC_").map(str::to_string);
+    let kind = match secret.name.as_str() {
+        _ if service.is_some() => SecretKind::ServicePassword,
+        "DefaultPassword" => SecretKind::AutoLogonPassword,
+        "$MACHINE.ACC" => SecretKind::MachineAccount,
+        "NL$KM" => SecretKind::CachedLogonKey,
+        "DPAPI_SYSTEM" => SecretKind::DpapiSystem,
+        _ => SecretKind::Other,
+    };
+
+    let string = |hive: &str, path: &str, name: &str| registry.get_key(hive, path).ok()?.string_value(name);
+    let account = match kind {
+        SecretKind::ServicePassword => registry.hive(SYSTEM)
+            .and_then(|system| system.current_control_set().ok())
+            .and_then(|cs| string(SYSTEM, &format!("{}\\Services\\{}", cs, service.as_deref()?), "ObjectName")),
+        SecretKind::AutoLogonPassword => string(SOFTWARE, WINLOGON, "DefaultUserName").map(|user| {
+            match string(SOFTWARE, WINLOGON, "DefaultDomainName") {
+                Some(domain) => format!("{}\\{}", domain, user),
+                None => user,
+            }
+        }),
+        _ => None,
+    };
+
+    // Passwords are UTF-16, sometimes with a terminating NUL
+    let mut password = secret.data.as_slice();
+    while password.ends_with(&[0, 0]) {
+        password = &password[..password.len() - 2];
+    }
+    let is_password = matches!(kind, SecretKind::ServicePassword | SecretKind::AutoLogonPassword);
+
+    LsaSecret {
+        nt_hash: match kind {
+            _ if is_password => Some(hex(&nt_hash(password))),
+            // The machine password is random bytes, hashed as they are
+            SecretKind::MachineAccount => Some(hex(&nt_hash(&secret.data))),
+            _ => None,
+        },
+        value: reveal.then(|| if is_password { utf16(password) } else { hex(&secret.data) }),
+        name: secret.name,
+        kind,
+        service,
+        account,
+        last_write: secret.last_write,
+    }
+}
@@ -0,0 +1,127 @@
+// src/registry/credentials/syskey.rs
+use anyhow::{Result, bail, Context};
+use crate::registry::RegistryHive;
+use crate::registry::sam::{v_field, v_string, ACCOUNT_PATH, USERS_PATH, V_LM_HASH, V_NT_HASH, V_USERNAME};
+use crate::utils::bytes::hex;
+use super::crypto::{aes128_cbc_decrypt, des_unobfuscate, md5, rc4};
+use super::SamHash;
+
+/// The boot key is scattered across the class names of these keys
+const BOOT_KEY_PARTS: &[&str] = &["JD", "Skew1", "GBG", "Data"];
+const BOOT_KEY_PERMUTATION: [usize; 16] = [8, 5, 4, 2, 11, 9, 13, 3, 0, 6, 1, 12, 14, 10, 15, 7];
+
+const QWERTY: &[u8] = b"!@#$%^&*()qwertyUIOPAzxcvbnmQQQQQQQQQQQQ)(*@&%\0";
+const DIGITS: &[u8] = b"0123456789012345678901234567890123456789\0";
+const NTPASSWORD: &[u8] = b"NTPASSWORD\0";
+const LMPASSWORD: &[u8] = b"LMPASSWORD\0";
+
+/// Domain key structure in the Account F value
+const F_KEY_OFFSET: usize = 0x68;
+const KEY_REVISION_RC4: u32 = 1;
+const KEY_REVISION_AES: u32 = 2;
+
+/// Hash structures start with a 2-byte PEK ID and a 2-byte revision
+const HASH_REVISION_AES: u16 = 2;
+/// AES hash structures have a 24-byte header (IDs, data offset, salt)
+const AES_HASH_HEADER: usize = 0x18;
+
+/// Derive the boot key (SYSKEY) from the SYSTEM hive
+pub fn boot_key(system: &RegistryHive) -> Result<[u8; 16]> {
+    let lsa = format!("{}\\Control\\Lsa", system.current_control_set()?);
+
+    let mut scrambled = String::new();
+    for part in BOOT_KEY_PARTS {
+        let key = system.get_key(&format!("{}\\{}", lsa, part))?;
+        scrambled.push_str(&key.class_name().context(format!("Lsa\\{} has no class name", part))?);
+    }
+    // Checked before slicing, which would panic inside a multi-byte character
+    if !scrambled.is_ascii() {
+        bail!("Boot key class names are not hex");
+    }
+    if scrambled.len() != 32 {
+        bail!("Boot key class names have unexpected length {}", scrambled.len());
+    }
+
+    let mut bytes = [0u8; 16];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&scrambled[i * 2..i * 2 + 2], 16).context("Boot key is not hex")?;
+    }
+    Ok(BOOT_KEY_PERMUTATION.map(|i| bytes[i]))
+}
+
+/// Decrypt the domain key ("hashed boot key") from the Account F value
+fn hashed_boot_key(sam: &RegistryHive, boot_key: &[u8; 16]) -> Result<Vec<u8>> {
+    let f = sam.get_key(ACCOUNT_PATH)?.get_value("F")?;
+    let f = f.as_bytes().context("Account F value is not binary")?;
+    let dword = |offset: usize| -> Result<u32> {
+        Ok(u32::from_le_bytes(f.get(offset..offset + 4).context("Account F value truncated")?.try_into()?))
+    };
+
+    match dword(F_KEY_OFFSET)? {
+        KEY_REVISION_RC4 => {
+            // Revision, length, salt, key and checksum
+            let salt = f.get(0x70..0x80).context("Account F value truncated")?;
+            let encrypted = f.get(0x80..0xA0).context("Account F value truncated")?;
+
+            let key = rc4(&md5(&[salt, QWERTY, boot_key, DIGITS]), encrypted);
+            if md5(&[&key[..16], DIGITS, &key[..16], QWERTY])[..] != key[16..32] {
+                bail!("Boot key does not match this SAM hive");
+            }
+            Ok(key[..16].to_vec())
+        }
+        KEY_REVISION_AES => {
+            // Revision, length, checksum length, data length, salt, data
+            let length = dword(F_KEY_OFFSET + 12)? as usize;
+            let salt = f.get(0x78..0x88).context("Account F value truncated")?;
+            let data = f.get(0x88..0x88 + length).context("Account F value truncated")?;
+            let key = aes128_cbc_decrypt(boot_key, salt, data).context("Failed to decrypt domain key")?;
+            Ok(key.get(..16).context("Domain key too short")?.to_vec())
+        }
+        revision => bail!("Unsupported SAM key revision {}", revision),
+    }
+}
+
+/// Decrypt the LM and NT hashes of every local account
+pub(super) fn sam_hashes(sam: &RegistryHive, boot_key: &[u8; 16]) -> Result<Vec<SamHash>> {
+    let hashed_boot_key = hashed_boot_key(sam, boot_key)?;
+    let mut hashes = Vec::new();
+
+    for key in sam.get_key(USERS_PATH)?.enumerate_subkeys()? {
+        let Ok(rid) = u32::from_str_radix(key.name(), 16) else {
+            continue;
+        };
+        let Some(v) = key.get_value("V").ok().and_then(|v| v.as_bytes().map(<[u8]>::to_vec)) else {
+            continue;
+        };
+
+        hashes.push(SamHash {
+            rid,
+            username: v_string(&v, V_USERNAME).unwrap_or_default(),
+            lm_hash: v_field(&v, V_LM_HASH)
+                .and_then(|data| decrypt_hash(data, &hashed_boot_key, rid, LMPASSWORD))
+                .map(|hash| hex(&hash)),
+            nt_hash: v_field(&v, V_NT_HASH)
+                .and_then(|data| decrypt_hash(data, &hashed_boot_key, rid, NTPASSWORD))
+                .map(|hash| hex(&hash)),
+        });
+    }
+
+    Ok(hashes)
+}
+
+/// Decrypt one hash structure; `None` when the account has no such hash
+fn decrypt_hash(data: &[u8], hashed_boot_key: &[u8], rid: u32, constant: &[u8]) -> Option<[u8; 16]> {
+    let revision = u16::from_le_bytes(data.get(2..4)?.try_into().ok()?);
+
+    let obfuscated = if revision == HASH_REVISION_AES {
+        let salt = data.get(8..24)?;
+        let encrypted = data.get(AES_HASH_HEADER..).filter(|d| !d.is_empty())?;
+        aes128_cbc_decrypt(&hashed_boot_key[..16], salt, encrypted)?
+    } else {
+        let encrypted = data.get(4..20)?;
+        let key = md5(&[&hashed_boot_key[..16], &rid.to_le_bytes(), constant]);
+        rc4(&key, encrypted)
+    };
+
+    Some(des_unobfuscate(rid, obfuscated.get(..16)?.try_into().ok()?))
+}
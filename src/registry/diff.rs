@@ -0,0 +1,261 @@
+// src/registry/diff.rs
+use std::collections::{BTreeMap, HashSet};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use super::{RegistryHive, RegistryKey, RegistryValue};
+use super::export::{reg_key_line, reg_value_line, REG_FILE_HEADER};
+use super::hive::MAX_KEY_DEPTH;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+    /// Only the key's last write time changed
+    Touched,
+}
+
+/// Differences between two versions of the same hive
+#[derive(Debug, Clone, Serialize)]
+pub struct HiveDiff {
+    pub keys: Vec<KeyChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyChange {
+    /// Path relative to the hive root
+    pub path: String,
+    pub kind: ChangeKind,
+    pub old_last_write: Option<DateTime<Utc>>,
+    pub new_last_write: Option<DateTime<Utc>>,
+    pub values: Vec<ValueChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValueChange {
+    pub name: String,
+    pub kind: ChangeKind,
+    pub old: Option<RegistryValue>,
+    pub new: Option<RegistryValue>,
+}
+
+impl RegistryHive {
+    /// Compare this hive (the old version) against `other` (the new version).
+    /// Key and value names are compared case-insensitively.
+    pub fn diff(&self, other: &RegistryHive) -> Result<HiveDiff> {
+        let mut differ = Differ::default();
+        differ.keys(&self.get_root_key()?, &other.get_root_key()?, "", 0);
+        Ok(HiveDiff { keys: differ.changes })
+    }
+}
+
+impl HiveDiff {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Render the diff as `.reg` patch text that turns the old hive into the
+    /// new one when the hive is mounted at `mount`, e.g. `HKLM\SOFTWARE`
+    pub fn to_reg(&self, mount: &str) -> String {
+        let mut out = String::from(REG_FILE_HEADER);
+        let mut removed_root: Option<String> = None;
+
+        for change in &self.keys {
+            let path = join(mount, &change.path);
+
+            // Deleting a key deletes its subtree, so descendants need no lines
+            if let Some(root) = &removed_root
+                && path.to_lowercase().starts_with(&format!("{}\\", root.to_lowercase()))
+            {
+                continue;
+            }
+
+            match change.kind {
+                // .reg files cannot set last write times
+                ChangeKind::Touched => {}
+                ChangeKind::Removed => {
+                    out.push_str("\r\n");
+                    out.push_str(&reg_key_line(&path, true));
+                    removed_root = Some(path);
+                }
+                ChangeKind::Added | ChangeKind::Modified => {
+                    out.push_str("\r\n");
+                    out.push_str(&reg_key_line(&path, false));
+                    for value in &change.values {
+                        out.push_str(&reg_value_line(&value.name, value.new.as_ref()));
+                    }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Walks the old and new hives together. Keys that cannot be read are
+/// logged and skipped, as in queries, so that one corrupt cell does not lose
+/// the rest of the diff.
+#[derive(Default)]
+struct Differ {
+    /// Offsets of the keys already visited in each hive, so that subkey
+    /// cycles in a corrupt hive are followed only once
+    old_visited: HashSet<u32>,
+    new_visited: HashSet<u32>,
+    changes: Vec<KeyChange>,
+}
+
+impl Differ {
+    fn keys(&mut self, old: &RegistryKey, new: &RegistryKey, path: &str, depth: usize) {
+        let old_seen = !self.old_visited.insert(old.offset());
+        let new_seen = !self.new_visited.insert(new.offset());
+        if depth > MAX_KEY_DEPTH || old_seen || new_seen {
+            log::debug!("Skipping {}: subkey loop or nesting too deep", path);
+            return;
+        }
+
+        let kind = match diff_values(old, new) {
+            Ok(values) if !values.is_empty() => Some((ChangeKind::Modified, values)),
+            Ok(_) if old.timestamp() != new.timestamp() => Some((ChangeKind::Touched, Vec::new())),
+            Ok(_) => None,
+            Err(e) => {
+                log::debug!("Skipping values of {}: {}", path, e);
+                None
+            }
+        };
+        if let Some((kind, values)) = kind {
+            self.changes.push(KeyChange {
+                path: path.to_string(),
+                kind,
+                old_last_write: old.last_write_time(),
+                new_last_write: new.last_write_time(),
+                values,
+            });
+        }
+
+        // Without both lists, every subkey would look added or removed
+        let (old_subkeys, mut new_subkeys) = match (old.enumerate_subkeys(), new.enumerate_subkeys()) {
+            (Ok(old), Ok(new)) => (by_lowercase_name(old), by_lowercase_name(new)),
+            (Err(e), _) | (_, Err(e)) => {
+                log::debug!("Skipping subkeys of {}: {}", path, e);
+                return;
+            }
+        };
+
+        for (name, old_subkey) in old_subkeys {
+            let subpath = join(path, old_subkey.name());
+            match new_subkeys.remove(&name) {
+                Some(new_subkey) => self.keys(&old_subkey, &new_subkey, &subpath, depth + 1),
+                None => self.subtree(&old_subkey, &subpath, ChangeKind::Removed, depth + 1),
+            }
+        }
+
+        for new_subkey in new_subkeys.into_values() {
+            let subpath = join(path, new_subkey.name());
+            self.subtree(&new_subkey, &subpath, ChangeKind::Added, depth + 1);
+        }
+    }
+
+    /// Record a key and all of its descendants as wholly added or removed
+    fn subtree(&mut self, key: &RegistryKey, path: &str, kind: ChangeKind, depth: usize) {
+        let visited = match kind {
+            ChangeKind::Removed => &mut self.old_visited,
+            _ => &mut self.new_visited,
+        };
+        if depth > MAX_KEY_DEPTH || !visited.insert(key.offset()) {
+            log::debug!("Skipping {}: subkey loop or nesting too deep", path);
+            return;
+        }
+
+        let values = key.values()
+            .unwrap_or_else(|e| {
+                log::debug!("Skipping values of {}: {}", path, e);
+                Vec::new()
+            })
+            .into_iter()
+            .map(|(name, value)| match kind {
+                ChangeKind::Removed => ValueChange { name, kind, old: Some(value), new: None },
+                _ => ValueChange { name, kind, old: None, new: Some(value) },
+            })
+            .collect();
+
+        let (old_last_write, new_last_write) = match kind {
+            ChangeKind::Removed => (key.last_write_time(), None),
+            _ => (None, key.last_write_time()),
+        };
+
+        self.changes.push(KeyChange {
+            path: path.to_string(),
+            kind,
+            old_last_write,
+            new_last_write,
+            values,
+        });
+
+        let subkeys = key.enumerate_subkeys().unwrap_or_else(|e| {
+            log::debug!("Skipping subkeys of {}: {}", path, e);
+            Vec::new()
+        });
+        for subkey in subkeys {
+            self.subtree(&subkey, &join(path, subkey.name()), kind, depth + 1);
+        }
+    }
+}
+
+fn diff_values(old: &RegistryKey, new: &RegistryKey) -> Result<Vec<ValueChange>> {
+    let old_values: BTreeMap<String, (String, RegistryValue)> = old.values()?
+        .into_iter()
+        .map(|(name, value)| (name.to_lowercase(), (name, value)))
+        .collect();
+    let mut new_values: BTreeMap<String, (String, RegistryValue)> = new.values()?
+        .into_iter()
+        .map(|(name, value)| (name.to_lowercase(), (name, value)))
+        .collect();
+
+    let mut changes = Vec::new();
+
+    for (lower, (name, old_value)) in old_values {
+        match new_values.remove(&lower) {
+            Some((_, new_value)) if new_value == old_value => {}
+            Some((_, new_value)) => changes.push(ValueChange {
+                name,
+                kind: ChangeKind::Modified,
+                old: Some(old_value),
+                new: Some(new_value),
+            }),
+            None => changes.push(ValueChange {
+                name,
+                kind: ChangeKind::Removed,
+                old: Some(old_value),
+                new: None,
+            }),
+        }
+    }
+
+    for (name, new_value) in new_values.into_values() {
+        changes.push(ValueChange {
+            name,
+            kind: ChangeKind::Added,
+            old: None,
+            new: Some(new_value),
+        });
+    }
+
+    Ok(changes)
+}
+
+fn by_lowercase_name(keys: Vec<RegistryKey>) -> BTreeMap<String, RegistryKey> {
+    keys.into_iter()
+        .map(|key| (key.name().to_lowercase(), key))
+        .collect()
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else if name.is_empty() {
+        parent.to_string()
+    } else {
+        format!("{}\\{}", parent, name)
+    }
+}
@@ -0,0 +1,131 @@
+// src/registry/export.rs
+use super::RegistryValue;
+use super::query::QueryMatch;
+
+pub const REG_FILE_HEADER: &str = "Windows Registry Editor Version 5.00\r\n";
+
+/// Expand a mount-style path (`HKLM\SOFTWARE\...`) to the root names regedit expects
+pub fn reg_key_path(path: &str) -> String {
+    const ROOTS: &[(&str, &str)] = &[
+        ("HKLM", "HKEY_LOCAL_MACHINE"),
+        ("HKU", "HKEY_USERS"),
+    ];
+
+    for (short, long) in ROOTS {
+        if let Some(rest) = path.strip_prefix(short)
+            && (rest.is_empty() || rest.starts_with('\\'))
+        {
+            return format!("{}{}", long, rest);
+        }
+    }
+    path.to_string()
+}
+
+/// `[key]` line opening a key section; `deleted` emits the `[-key]` form
+pub fn reg_key_line(path: &str, deleted: bool) -> String {
+    let marker = if deleted { "-" } else { "" };
+    format!("[{}{}]\r\n", marker, reg_key_path(path))
+}
+
+/// `"name"=data` line for a value; `None` data emits a deletion (`"name"=-`)
+pub fn reg_value_line(name: &str, value: Option<&RegistryValue>) -> String {
+    let name = if name.is_empty() {
+        "@".to_string()
+    } else {
+        format!("\"{}\"", escape(name))
+    };
+
+    let data = match value {
+        None => "-".to_string(),
+        Some(value) => reg_data(value),
+    };
+
+    format!("{}={}\r\n", name, data)
+}
+
+fn reg_data(value: &RegistryValue) -> String {
+    match value {
+        RegistryValue::String(s) => format!("\"{}\"", escape(s)),
+        RegistryValue::Dword(v) => format!("dword:{:08x}", v),
+        RegistryValue::Binary(bytes) => format!("hex:{}", hex_list(bytes)),
+        // Everything else, including malformed data kept raw, round-trips
+        // through the explicit hex(type) form
+        other => format!("hex({:x}):{}", other.value_type(), hex_list(&other.to_raw_bytes())),
+    }
+}
+
+fn hex_list(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Render query results as a `.reg` file
+pub fn matches_to_reg(matches: &[QueryMatch]) -> String {
+    let mut out = String::from(REG_FILE_HEADER);
+    let mut current_key = None;
+
+    for m in matches {
+        let path = m.full_path();
+        if current_key.as_ref() != Some(&path) {
+            out.push_str("\r\n");
+            out.push_str(&reg_key_line(&path, false));
+            current_key = Some(path);
+        }
+        if let (Some(name), Some(value)) = (&m.value_name, &m.value) {
+            out.push_str(&reg_value_line(name, Some(value)));
+        }
+    }
+
+    out
+}
+
+/// Render query results as an aligned text table
+pub fn matches_to_table(matches: &[QueryMatch]) -> String {
+    let headers = ["Last Write", "Key", "Owner", "Writable By", "Value", "Type", "Data"];
+    let rows: Vec<[String; 7]> = matches.iter()
+        .map(|m| {
+            let security = m.security.as_ref();
+            // Low-privileged principals granted write access
+            let writable_by: Vec<&str> = security
+                .map(|s| s.weak_write_aces.iter().map(|ace| ace.sid.as_str()).collect())
+                .unwrap_or_default();
+            [
+                m.last_write.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default(),
+                m.full_path(),
+                security.and_then(|s| s.owner.clone()).unwrap_or_default(),
+                writable_by.join(","),
+                m.value_name.as_deref().map(|n| if n.is_empty() { "(default)" } else { n }).unwrap_or_default().to_string(),
+                m.value.as_ref().map(|v| v.type_name()).unwrap_or_default(),
+                m.value.as_ref().map(|v| v.to_string()).unwrap_or_default(),
+            ]
+        })
+        .collect();
+
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: &[String]| {
+        let padded: Vec<String> = cells.iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+
+    let mut out = format_row(&headers.map(String::from));
+    out.push_str(&format_row(&widths.map(|w| "-".repeat(w))));
+    for row in &rows {
+        out.push_str(&format_row(row));
+    }
+    out
+}
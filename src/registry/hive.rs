@@ -1,14 +1,35 @@
 // src/registry/hive.rs
 use std::path::Path;
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::fmt;
+use std::io::Read;
+use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
 use byteorder::{LittleEndian, ReadBytesExt};
-use anyhow::{Result, bail};
+use anyhow::{Result, bail, Context};
+use chrono::{DateTime, Utc};
+use super::{KeySecurity, RegistryValue, SecurityDescriptor};
+use crate::utils::bytes::utf16;
+use crate::utils::time::filetime_to_datetime;
+
+/// Cell offsets are relative to the first hive bin, which follows the base block
+const HBIN_START: usize = 0x1000;
+/// Largest chunk of value data stored in a single big data ("db") segment
+const BIG_DATA_SEGMENT_SIZE: usize = 16344;
+/// Key names stored as ASCII/Latin-1 rather than UTF-16
+const KEY_COMP_NAME: u16 = 0x0020;
+/// Value names stored as ASCII/Latin-1 rather than UTF-16
+const VALUE_COMP_NAME: u16 = 0x0001;
+/// Guards against cycles in corrupt "ri" index chains
+const MAX_INDEX_DEPTH: usize = 8;
+/// Windows does not create keys more than 512 levels deep, so recursion
+/// past this is following a subkey cycle in a corrupt hive
+pub(crate) const MAX_KEY_DEPTH: usize = 512;
 
 #[derive(Debug, Clone)]
 pub struct RegistryHive {
     /// Raw hive data
-    data: Vec<u8>,
+    data: HiveData,
     /// Offset to root key
     root_offset: u32,
     /// Hive header information
@@ -17,12 +38,23 @@ pub struct RegistryHive {
 
 #[derive(Debug, Clone)]
 pub struct RegistryKey {
+    /// Hive the key was parsed from
+    hive: HiveData,
     /// Offset in the hive data
     offset: u32,
     /// Number of subkeys
     subkey_count: u32,
+    /// Offset of the subkey index
+    subkeys_offset: u32,
     /// Number of values
     value_count: u32,
+    /// Offset of the value list
+    values_offset: u32,
+    /// Offset of the security (sk) cell
+    security_offset: u32,
+    /// Offset and byte length of the class name, if any
+    class_offset: u32,
+    class_length: u16,
     /// Name of the key
     name: String,
     /// Last write timestamp
@@ -43,16 +75,74 @@ struct HiveHeader {
     length: u32,
 }
 
+/// Raw hive bytes, shared between a hive and every key parsed from it
+#[derive(Clone)]
+pub(crate) struct HiveData {
+    bytes: Arc<Vec<u8>>,
+    minor_version: u32,
+}
+
+impl fmt::Debug for HiveData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HiveData({} bytes)", self.bytes.len())
+    }
+}
+
+impl HiveData {
+    /// Return the payload of the cell at `offset`, excluding the size field
+    pub(crate) fn cell(&self, offset: u32) -> Result<&[u8]> {
+        let start = HBIN_START + offset as usize;
+        let size_field = self.bytes.get(start..start + 4)
+            .context(format!("Cell offset {:#x} out of bounds", offset))?;
+
+        // Allocated cells have a negative size
+        let size = i32::from_le_bytes(size_field.try_into()?).unsigned_abs() as usize;
+        if size < 4 {
+            bail!("Invalid cell size at offset {:#x}", offset);
+        }
+
+        self.bytes.get(start + 4..start + size)
+            .context(format!("Cell at offset {:#x} exceeds hive length", offset))
+    }
+}
+
+pub(crate) fn read_u16(buf: &[u8], offset: usize) -> Result<u16> {
+    let bytes = buf.get(offset..offset + 2).context("Unexpected end of cell")?;
+    Ok(u16::from_le_bytes(bytes.try_into()?))
+}
+
+pub(crate) fn read_u32(buf: &[u8], offset: usize) -> Result<u32> {
+    let bytes = buf.get(offset..offset + 4).context("Unexpected end of cell")?;
+    Ok(u32::from_le_bytes(bytes.try_into()?))
+}
+
+/// Decode a key or value name, which is either Latin-1 or UTF-16LE
+fn decode_name(raw: &[u8], compressed: bool) -> String {
+    if compressed {
+        raw.iter().map(|&b| b as char).collect()
+    } else {
+        utf16(raw)
+    }
+}
+
 impl RegistryHive {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let mut file = File::open(path)?;
         let mut data = Vec::new();
         file.read_to_end(&mut data)?;
 
+        Self::from_bytes(data)
+    }
+
+    /// Parse a hive already read into memory, e.g. extracted from an image
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
         let header = Self::parse_header(&data)?;
-        
+
         Ok(Self {
-            data,
+            data: HiveData {
+                bytes: Arc::new(data),
+                minor_version: header.minor_version,
+            },
             root_offset: header.root_cell_offset,
             header,
         })
@@ -64,10 +154,10 @@ impl RegistryHive {
         }
 
         let mut cursor = std::io::Cursor::new(data);
-        
+
         let mut signature = [0u8; 4];
         cursor.read_exact(&mut signature)?;
-        
+
         if &signature != b"regf" {
             bail!("Invalid hive signature");
         }
@@ -88,36 +178,39 @@ impl RegistryHive {
 
     pub fn get_key(&self, path: &str) -> Result<RegistryKey> {
         let mut current_key = self.get_root_key()?;
-        
+
         if path.is_empty() {
             return Ok(current_key);
         }
 
-        for component in path.split('\\') {
-            let subkeys = current_key.enumerate_subkeys()?;
-            current_key = subkeys.into_iter()
-                .find(|k| k.name.eq_ignore_ascii_case(component))
-                .context(format!("Subkey '{}' not found", component))?;
+        for component in path.split('\\').filter(|c| !c.is_empty()) {
+            current_key = current_key.get_subkey(component)?;
         }
 
         Ok(current_key)
     }
 
+    /// Name of the control set marked current in a SYSTEM hive, e.g. `ControlSet001`
+    pub fn current_control_set(&self) -> Result<String> {
+        let current = self.get_key("Select")?
+            .get_value("Current")?
+            .as_integer()
+            .context("Select\\Current is not an integer")?;
+        Ok(format!("ControlSet{:03}", current))
+    }
+
     pub fn get_root_key(&self) -> Result<RegistryKey> {
         self.parse_key(self.root_offset)
     }
 
     fn parse_key(&self, offset: u32) -> Result<RegistryKey> {
-        // Implementation of registry key parsing
-        // This would involve reading the binary structure of the registry key
-        // and parsing its metadata, name, and other attributes
-        unimplemented!("Registry key parsing not yet implemented")
+        RegistryKey::parse(&self.data, offset)
     }
 
     pub fn search_value(&self, value_name: &str) -> Result<Vec<(String, RegistryValue)>> {
         let mut results = Vec::new();
         let root = self.get_root_key()?;
-        self.search_value_recursive(&root, String::new(), value_name, &mut results)?;
+        self.search_value_recursive(&root, String::new(), value_name, 0, &mut HashSet::new(), &mut results)?;
         Ok(results)
     }
 
@@ -126,13 +219,18 @@ impl RegistryHive {
         key: &RegistryKey,
         path: String,
         value_name: &str,
+        depth: usize,
+        visited: &mut HashSet<u32>,
         results: &mut Vec<(String, RegistryValue)>
     ) -> Result<()> {
+        if depth > MAX_KEY_DEPTH || !visited.insert(key.offset) {
+            log::debug!("Skipping {}: subkey loop or nesting too deep", path);
+            return Ok(());
+        }
+
         // Check values in current key
-        if let Ok(values) = key.enumerate_values() {
-            if let Some(value) = values.get(value_name) {
-                results.push((path.clone(), value.clone()));
-            }
+        if let Ok(value) = key.get_value(value_name) {
+            results.push((path.clone(), value));
         }
 
         // Recurse into subkeys
@@ -142,9 +240,275 @@ impl RegistryHive {
             } else {
                 format!("{}\\{}", path, subkey.name)
             };
-            self.search_value_recursive(&subkey, new_path, value_name, results)?;
+            self.search_value_recursive(&subkey, new_path, value_name, depth + 1, visited, results)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl RegistryKey {
+    fn parse(hive: &HiveData, offset: u32) -> Result<Self> {
+        let cell = hive.cell(offset)?;
+        if !cell.starts_with(b"nk") {
+            bail!("Expected key cell at offset {:#x}", offset);
+        }
+
+        let flags = read_u16(cell, 2)?;
+        let name_length = read_u16(cell, 72)? as usize;
+        let raw_name = cell.get(76..76 + name_length)
+            .context(format!("Key name at offset {:#x} truncated", offset))?;
+
+        Ok(Self {
+            hive: hive.clone(),
+            offset,
+            timestamp: i64::from_le_bytes(cell[4..12].try_into()?),
+            subkey_count: read_u32(cell, 20)?,
+            subkeys_offset: read_u32(cell, 28)?,
+            value_count: read_u32(cell, 36)?,
+            values_offset: read_u32(cell, 40)?,
+            security_offset: read_u32(cell, 44)?,
+            class_offset: read_u32(cell, 48)?,
+            class_length: read_u16(cell, 74)?,
+            name: decode_name(raw_name, flags & KEY_COMP_NAME != 0),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    pub fn subkey_count(&self) -> u32 {
+        self.subkey_count
+    }
+
+    pub fn value_count(&self) -> u32 {
+        self.value_count
+    }
+
+    /// Raw last write FILETIME
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    pub fn last_write_time(&self) -> Option<DateTime<Utc>> {
+        filetime_to_datetime(self.timestamp as u64)
+    }
+
+    /// The key's class name, a UTF-16 string most keys do not have
+    pub fn class_name(&self) -> Option<String> {
+        if self.class_length == 0 || self.class_offset == u32::MAX {
+            return None;
+        }
+
+        let cell = self.hive.cell(self.class_offset).ok()?;
+        let raw = cell.get(..self.class_length as usize)?;
+        Some(utf16(raw))
+    }
+
+    /// Parse the security descriptor from the key's sk cell
+    pub fn security_descriptor(&self) -> Result<SecurityDescriptor> {
+        let cell = self.hive.cell(self.security_offset)?;
+        if !cell.starts_with(b"sk") {
+            bail!("Expected security cell at offset {:#x}", self.security_offset);
+        }
+
+        let size = read_u32(cell, 16)? as usize;
+        let descriptor = cell.get(20..20 + size)
+            .context(format!("Security descriptor at offset {:#x} truncated", self.security_offset))?;
+
+        SecurityDescriptor::parse(descriptor)
+    }
+
+    /// Owner, SDDL and weak ACEs of the key, or `None` if its security
+    /// descriptor cannot be read
+    pub fn security(&self) -> Option<KeySecurity> {
+        self.security_descriptor()
+            .map_err(|e| log::debug!("No security descriptor for '{}': {}", self.name, e))
+            .ok()
+            .map(|descriptor| KeySecurity::new(&descriptor))
+    }
+
+    pub fn enumerate_subkeys(&self) -> Result<Vec<RegistryKey>> {
+        if self.subkey_count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut offsets = Vec::new();
+        self.collect_subkey_offsets(self.subkeys_offset, 0, &mut offsets)?;
+
+        // Like values, an unreadable subkey is skipped rather than failing
+        // the whole listing
+        Ok(offsets.into_iter()
+            .filter_map(|offset| {
+                RegistryKey::parse(&self.hive, offset)
+                    .map_err(|e| log::debug!("Skipping subkey of '{}': {}", self.name, e))
+                    .ok()
+            })
+            .collect())
+    }
+
+    fn collect_subkey_offsets(&self, list_offset: u32, depth: usize, out: &mut Vec<u32>) -> Result<()> {
+        if depth > MAX_INDEX_DEPTH {
+            bail!("Subkey index nesting too deep");
+        }
+
+        let cell = self.hive.cell(list_offset)?;
+        let count = read_u16(cell, 2)? as usize;
+
+        match cell.get(..2) {
+            // Fast leaf and hash leaf: offset followed by a name hint or hash
+            Some(b"lf") | Some(b"lh") => {
+                for i in 0..count {
+                    out.push(read_u32(cell, 4 + i * 8)?);
+                }
+            }
+            Some(b"li") => {
+                for i in 0..count {
+                    out.push(read_u32(cell, 4 + i * 4)?);
+                }
+            }
+            // Index root: a list of further leaf lists
+            Some(b"ri") => {
+                for i in 0..count {
+                    self.collect_subkey_offsets(read_u32(cell, 4 + i * 4)?, depth + 1, out)?;
+                }
+            }
+            _ => bail!("Unknown subkey index at offset {:#x}", list_offset),
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Get a subkey by name, or by a backslash-separated relative path
+    pub fn get_subkey(&self, name: &str) -> Result<RegistryKey> {
+        if let Some((first, rest)) = name.split_once('\\') {
+            return self.get_subkey(first)?.get_subkey(rest);
+        }
+
+        self.enumerate_subkeys()?
+            .into_iter()
+            .find(|k| k.name.eq_ignore_ascii_case(name))
+            .context(format!("Subkey '{}' not found", name))
+    }
+
+    /// Values in on-disk order, which some artifacts (MRU lists) depend on
+    pub fn values(&self) -> Result<Vec<(String, RegistryValue)>> {
+        Ok(self.raw_values()?
+            .into_iter()
+            .map(|(name, value_type, data)| (name, RegistryValue::from_raw(value_type, &data)))
+            .collect())
+    }
+
+    /// Type code and undecoded data of a value. Decoded strings stop at the
+    /// first NUL, so this is where data hidden after one can be found.
+    pub fn raw_value(&self, value_name: &str) -> Result<(u32, Vec<u8>)> {
+        self.raw_values()?
+            .into_iter()
+            .find(|(name, _, _)| name.eq_ignore_ascii_case(value_name))
+            .map(|(_, value_type, data)| (value_type, data))
+            .context(format!("Value '{}' not found", value_name))
+    }
+
+    fn raw_values(&self) -> Result<Vec<(String, u32, Vec<u8>)>> {
+        if self.value_count == 0 {
+            return Ok(Vec::new());
+        }
+
+        // The count comes from the key cell, so bound it by the list cell
+        let list = self.hive.cell(self.values_offset)?;
+        let count = (self.value_count as usize).min(list.len() / 4);
+        let mut values = Vec::with_capacity(count);
+
+        for i in 0..count {
+            let vk_offset = read_u32(list, i * 4)?;
+            match self.parse_value(vk_offset) {
+                Ok(value) => values.push(value),
+                Err(e) => log::debug!("Skipping value in key '{}': {}", self.name, e),
+            }
+        }
+
+        Ok(values)
+    }
+
+    pub fn enumerate_values(&self) -> Result<HashMap<String, RegistryValue>> {
+        Ok(self.values()?.into_iter().collect())
+    }
+
+    /// Look up a value by name, case-insensitively. The default value has an empty name.
+    pub fn get_value(&self, value_name: &str) -> Result<RegistryValue> {
+        self.values()?
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(value_name))
+            .map(|(_, value)| value)
+            .context(format!("Value '{}' not found", value_name))
+    }
+
+    /// String data of a value, or `None` if it is missing, empty or not a string
+    pub fn string_value(&self, value_name: &str) -> Option<String> {
+        self.get_value(value_name).ok()
+            .and_then(|v| v.as_string_lossy())
+            .filter(|s| !s.is_empty())
+    }
+
+    /// Integer data of a REG_DWORD or REG_QWORD value
+    pub fn integer_value(&self, value_name: &str) -> Option<u64> {
+        self.get_value(value_name).ok().and_then(|v| v.as_integer())
+    }
+
+    fn parse_value(&self, offset: u32) -> Result<(String, u32, Vec<u8>)> {
+        let cell = self.hive.cell(offset)?;
+        if !cell.starts_with(b"vk") {
+            bail!("Expected value cell at offset {:#x}", offset);
+        }
+
+        let name_length = read_u16(cell, 2)? as usize;
+        let value_type = read_u32(cell, 12)?;
+        let flags = read_u16(cell, 16)?;
+        let raw_name = cell.get(20..20 + name_length)
+            .context(format!("Value name at offset {:#x} truncated", offset))?;
+
+        let name = decode_name(raw_name, flags & VALUE_COMP_NAME != 0);
+        let data = self.read_value_data(cell)?;
+
+        Ok((name, value_type, data))
+    }
+
+    fn read_value_data(&self, vk: &[u8]) -> Result<Vec<u8>> {
+        let raw_size = read_u32(vk, 4)?;
+
+        // Small values are stored directly in the data offset field
+        if raw_size & 0x8000_0000 != 0 {
+            let size = ((raw_size & 0x7fff_ffff) as usize).min(4);
+            return Ok(vk[8..8 + size].to_vec());
+        }
+
+        let size = raw_size as usize;
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+
+        let cell = self.hive.cell(read_u32(vk, 8)?)?;
+
+        // Large values in hive format 1.4+ are split into big data segments
+        if size > BIG_DATA_SEGMENT_SIZE && self.hive.minor_version > 3 && cell.starts_with(b"db") {
+            let segment_count = read_u16(cell, 2)? as usize;
+            let segments = self.hive.cell(read_u32(cell, 4)?)?;
+            let mut data = Vec::with_capacity(size.min(segment_count * BIG_DATA_SEGMENT_SIZE));
+
+            for i in 0..segment_count {
+                let segment = self.hive.cell(read_u32(segments, i * 4)?)?;
+                let take = (size - data.len()).min(BIG_DATA_SEGMENT_SIZE).min(segment.len());
+                data.extend_from_slice(&segment[..take]);
+            }
+
+            return Ok(data);
+        }
+
+        Ok(cell[..size.min(cell.len())].to_vec())
+    }
+}
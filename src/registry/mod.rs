@@ -2,7 +2,42 @@
 mod parser;
 mod hive;
 mod value;
+mod security;
+mod query;
+mod export;
+mod diff;
+mod sam;
+mod shimcache;
+mod amcache;
+mod persistence;
+mod usb;
+mod networklist;
+mod taskcache;
+mod software;
+mod plugins;
+#[cfg(feature = "credentials")]
+mod credentials;
 
 pub use parser::RegistryParser;
 pub use hive::{RegistryHive, RegistryKey};
-pub use value::RegistryValue;
\ No newline at end of file
+pub use value::RegistryValue;
+pub use security::{SecurityDescriptor, KeySecurity, Acl, Ace, AceType};
+pub use query::{RegistryQuery, QueryMatch};
+pub use export::{matches_to_reg, matches_to_table};
+pub use diff::{HiveDiff, KeyChange, ValueChange, ChangeKind};
+pub use sam::{parse_sam, SamAccount};
+pub use shimcache::{parse_shimcache, decode_shimcache, ShimCache, ShimCacheEntry, ShimCacheFormat};
+pub use amcache::{parse_amcache, strip_sha1_padding, Amcache, AmcacheFile, AmcacheApplication, AmcacheDriver, AmcacheDevice};
+pub use persistence::{analyze_persistence, PersistenceEntry, PersistenceReason};
+pub use usb::{reconstruct_usb_history, UsbDevice, UsbUserMount};
+pub use networklist::{parse_network_list, NetworkProfile};
+pub use taskcache::{parse_task_cache, TaskCacheEntry};
+pub use software::{collect_installed_software, InstalledProgram, SoftwareSource};
+pub use plugins::{
+    parse_userassist, UserAssistEntry, parse_mru, MruEntry, MruSource, parse_shellbags, ShellBagEntry,
+    parse_rdp_connections, RdpConnection, parse_mapped_drives, MappedDrive,
+};
+#[cfg(feature = "credentials")]
+pub use credentials::{
+    boot_key, extract_credentials, CachedLogon, Credentials, LsaSecret, SamHash, SecretKind,
+};
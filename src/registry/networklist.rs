@@ -0,0 +1,98 @@
+// src/registry/networklist.rs
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+use super::{RegistryHive, RegistryKey};
+
+const NETWORK_LIST: &str = "Microsoft\\Windows NT\\CurrentVersion\\NetworkList";
+
+/// A network the system has connected to, from `NetworkList\Profiles`
+/// joined with its `Signatures` entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkProfile {
+    pub guid: String,
+    /// SSID for wireless networks, otherwise the name Windows assigned
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// `public`, `private` or `domain`
+    pub category: Option<String>,
+    /// `wired`, `wireless`, `ppp` or `mobile broadband`
+    pub network_type: Option<String>,
+    /// True when the network was identified by a domain controller
+    pub managed: bool,
+    pub first_connected: Option<DateTime<Utc>>,
+    pub last_connected: Option<DateTime<Utc>>,
+    /// Default gateway MAC, e.g. `00-11-22-AA-BB-CC`
+    pub gateway_mac: Option<String>,
+    pub dns_suffix: Option<String>,
+    /// SSID or name recorded in the signature
+    pub first_network: Option<String>,
+    pub last_write: Option<DateTime<Utc>>,
+}
+
+/// Parse the network profiles in a SOFTWARE hive. `DateCreated` and
+/// `DateLastConnected` are local SYSTEMTIMEs; `bias_minutes` converts
+/// them to UTC (UTC = local + bias).
+pub fn parse_network_list(hive: &RegistryHive, bias_minutes: i32) -> Vec<NetworkProfile> {
+    let profiles = hive.get_key(&format!("{}\\Profiles", NETWORK_LIST))
+        .and_then(|key| key.enumerate_subkeys())
+        .unwrap_or_default();
+    let signatures = signatures(hive);
+    let local = |key: &RegistryKey, name: &str| key.get_value(name).ok()
+        .and_then(|v| v.as_systemtime())
+        .map(|t| t + Duration::minutes(bias_minutes as i64));
+
+    profiles.iter()
+        .map(|key| {
+            let signature = signatures.iter()
+                .find(|(_, s)| s.string_value("ProfileGuid").is_some_and(|g| g.eq_ignore_ascii_case(key.name())));
+
+            NetworkProfile {
+                guid: key.name().to_string(),
+                name: key.string_value("ProfileName"),
+                description: key.string_value("Description"),
+                category: key.integer_value("Category").map(|c| match c {
+                    0 => "public".to_string(),
+                    1 => "private".to_string(),
+                    2 => "domain".to_string(),
+                    other => other.to_string(),
+                }),
+                // IANA ifType values
+                network_type: key.integer_value("NameType").map(|t| match t {
+                    6 => "wired".to_string(),
+                    23 => "ppp".to_string(),
+                    71 => "wireless".to_string(),
+                    243 => "mobile broadband".to_string(),
+                    other => other.to_string(),
+                }),
+                managed: signature.map(|(managed, _)| *managed)
+                    .unwrap_or_else(|| key.integer_value("Managed").is_some_and(|m| m != 0)),
+                first_connected: local(key, "DateCreated"),
+                last_connected: local(key, "DateLastConnected"),
+                gateway_mac: signature
+                    .and_then(|(_, s)| s.get_value("DefaultGatewayMac").ok())
+                    .and_then(|v| v.as_bytes().filter(|b| b.len() == 6).map(format_mac)),
+                dns_suffix: signature.and_then(|(_, s)| s.string_value("DnsSuffix")).filter(|s| s != "<none>"),
+                first_network: signature.and_then(|(_, s)| s.string_value("FirstNetwork")),
+                last_write: key.last_write_time(),
+            }
+        })
+        .collect()
+}
+
+/// Signature keys, flagged with whether they are under `Managed`
+fn signatures(hive: &RegistryHive) -> Vec<(bool, RegistryKey)> {
+    let mut signatures = Vec::new();
+
+    for (managed, kind) in [(true, "Managed"), (false, "Unmanaged")] {
+        let keys = hive.get_key(&format!("{}\\Signatures\\{}", NETWORK_LIST, kind))
+            .and_then(|key| key.enumerate_subkeys())
+            .unwrap_or_default();
+        signatures.extend(keys.into_iter().map(|key| (managed, key)));
+    }
+
+    signatures
+}
+
+fn format_mac(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join("-")
+}
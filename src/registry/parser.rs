@@ -1,57 +1,176 @@
 // src/registry/parser.rs
 use std::path::Path;
 use std::collections::HashMap;
-use std::io::{Read, Seek};
+use std::num::NonZeroUsize;
 use anyhow::{Result, bail, Context};
+use parking_lot::Mutex;
+use crate::image::{TskImage, FileType};
 use super::{RegistryHive, RegistryKey, RegistryValue};
+use super::query::{QueryMatch, RegistryQuery};
+
+/// Number of recently accessed keys kept in the cache
+const KEY_CACHE_SIZE: usize = 1000;
+
+/// Hives under `Windows\System32\config`, mounted below HKLM
+const SYSTEM_HIVES: &[&str] = &["SYSTEM", "SOFTWARE", "SAM", "SECURITY"];
 
 #[derive(Debug)]
 pub struct RegistryParser {
     /// Holds loaded registry hives indexed by their name (e.g., "SYSTEM", "SOFTWARE")
     hives: HashMap<String, RegistryHive>,
-    /// Cache of recently accessed keys to improve performance
-    key_cache: lru::LruCache<String, RegistryKey>,
+    /// Cache of recently accessed keys to improve performance. Lookups
+    /// reorder the LRU, so it sits behind a lock to keep `get_key` usable
+    /// through a shared reference from several threads.
+    key_cache: Mutex<lru::LruCache<String, RegistryKey>>,
+}
+
+impl Default for RegistryParser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl RegistryParser {
     pub fn new() -> Self {
         Self {
             hives: HashMap::new(),
-            key_cache: lru::LruCache::new(1000), // Cache last 1000 accessed keys
+            key_cache: Mutex::new(lru::LruCache::new(
+                NonZeroUsize::new(KEY_CACHE_SIZE).unwrap(),
+            )),
         }
     }
 
     /// Load a registry hive from a file
     pub fn load_hive<P: AsRef<Path>>(&mut self, path: P, name: &str) -> Result<()> {
         let hive = RegistryHive::from_file(path)?;
-        self.hives.insert(name.to_string(), hive);
+        self.insert_hive(name, hive);
         Ok(())
     }
 
+    /// Load a registry hive from memory, e.g. a file read out of an image
+    pub fn load_hive_bytes(&mut self, data: Vec<u8>, name: &str) -> Result<()> {
+        let hive = RegistryHive::from_bytes(data)?;
+        self.insert_hive(name, hive);
+        Ok(())
+    }
+
+    fn insert_hive(&mut self, name: &str, hive: RegistryHive) {
+        // Replacing a hive invalidates any keys cached from the old one
+        if self.hives.insert(name.to_string(), hive).is_some() {
+            self.key_cache.lock().clear();
+        }
+    }
+
+    /// Load the system hives and each user's NTUSER.DAT and UsrClass.dat from
+    /// an image. Hives are named after where Windows mounts them:
+    /// `HKLM\SYSTEM`, `HKU\<profile>` and `HKU\<profile>_Classes`.
+    /// Returns the number of hives loaded.
+    pub fn load_from_image(&mut self, image: &TskImage) -> Result<usize> {
+        let mut loaded = 0;
+
+        let config = Path::new("/Windows/System32/config");
+        for name in SYSTEM_HIVES {
+            loaded += self.load_image_hive(image, &config.join(name), &format!("HKLM\\{}", name));
+        }
+
+        if let Ok(profiles) = image.list_directory(Path::new("/Users")) {
+            for profile in profiles {
+                if !matches!(profile.file_type, FileType::Directory) || profile.name.starts_with('.') {
+                    continue;
+                }
+
+                let home = Path::new("/Users").join(&profile.name);
+                loaded += self.load_image_hive(
+                    image,
+                    &home.join("NTUSER.DAT"),
+                    &format!("HKU\\{}", profile.name),
+                );
+                loaded += self.load_image_hive(
+                    image,
+                    &home.join("AppData/Local/Microsoft/Windows/UsrClass.dat"),
+                    &format!("HKU\\{}_Classes", profile.name),
+                );
+            }
+        }
+
+        if loaded == 0 {
+            bail!("No registry hives found in image");
+        }
+
+        Ok(loaded)
+    }
+
+    fn load_image_hive(&mut self, image: &TskImage, path: &Path, name: &str) -> usize {
+        let result = image.read_file(path)
+            .and_then(|data| self.load_hive_bytes(data, name));
+
+        match result {
+            Ok(()) => 1,
+            Err(e) => {
+                log::debug!("Skipping hive {}: {}", path.display(), e);
+                0
+            }
+        }
+    }
+
+    /// Names of all loaded hives
+    pub fn hive_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.hives.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Each user's NTUSER.DAT hive, keyed by profile name
+    pub fn user_hives(&self) -> Vec<(&str, &RegistryHive)> {
+        self.hive_names()
+            .into_iter()
+            .filter_map(|name| {
+                let user = name.strip_prefix("HKU\\")?;
+                if user.ends_with("_Classes") {
+                    return None;
+                }
+                Some((user, &self.hives[name]))
+            })
+            .collect()
+    }
+
+    /// Each user's UsrClass.dat hive, keyed by profile name
+    pub fn user_class_hives(&self) -> Vec<(&str, &RegistryHive)> {
+        self.hive_names()
+            .into_iter()
+            .filter_map(|name| {
+                let user = name.strip_prefix("HKU\\")?.strip_suffix("_Classes")?;
+                Some((user, &self.hives[name]))
+            })
+            .collect()
+    }
+
+    /// Get a loaded hive by name
+    pub fn hive(&self, name: &str) -> Option<&RegistryHive> {
+        self.hives.get(name)
+    }
+
     /// Get a registry value by its full path
     pub fn get_value(&self, hive: &str, key_path: &str, value_name: &str) -> Result<RegistryValue> {
-        let hive = self.hives.get(hive)
-            .context(format!("Hive '{}' not found", hive))?;
-        
         let key = self.get_key(hive, key_path)?;
         key.get_value(value_name)
     }
 
     /// Get a registry key by its path
     pub fn get_key(&self, hive: &str, path: &str) -> Result<RegistryKey> {
-        let cache_key = format!("{}\\{}", hive, path);
-        
+        let cache_key = format!("{}\\{}", hive, path).to_lowercase();
+
         // Check cache first
-        if let Some(key) = self.key_cache.get(&cache_key) {
+        if let Some(key) = self.key_cache.lock().get(&cache_key) {
             return Ok(key.clone());
         }
 
         let hive = self.hives.get(hive)
             .context(format!("Hive '{}' not found", hive))?;
-        
+
         let key = hive.get_key(path)?;
-        self.key_cache.put(cache_key, key.clone());
-        
+        self.key_cache.lock().put(cache_key, key.clone());
+
         Ok(key)
     }
 
@@ -70,7 +189,7 @@ impl RegistryParser {
     /// Search for a value across all loaded hives
     pub fn search_value(&self, value_name: &str) -> Vec<(String, String, RegistryValue)> {
         let mut results = Vec::new();
-        
+
         for (hive_name, hive) in &self.hives {
             if let Ok(matches) = hive.search_value(value_name) {
                 for (key_path, value) in matches {
@@ -78,7 +197,25 @@ impl RegistryParser {
                 }
             }
         }
-        
+
         results
     }
-}
\ No newline at end of file
+
+    /// Run a query against every loaded hive whose mount name it matches
+    pub fn query(&self, query: &RegistryQuery) -> Result<Vec<QueryMatch>> {
+        let mut results = Vec::new();
+
+        for hive_name in self.hive_names() {
+            for pattern in query.remaining_after_mount(hive_name) {
+                // Jump straight to the deepest literal key through the cache
+                let (prefix, rest) = pattern.split_literal_prefix();
+                let Ok(start) = self.get_key(hive_name, &prefix) else {
+                    continue;
+                };
+                query.walk(hive_name, &start, &prefix, rest, &mut results);
+            }
+        }
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,379 @@
+// src/registry/persistence.rs
+use std::collections::BTreeMap;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use super::{KeySecurity, RegistryKey, RegistryParser};
+use crate::utils::time::filetime_to_datetime;
+
+const SYSTEM: &str = "HKLM\\SYSTEM";
+const SOFTWARE: &str = "HKLM\\SOFTWARE";
+
+/// Prefixes under SOFTWARE holding 64-bit and 32-bit registrations
+const SOFTWARE_VIEWS: &[&str] = &["", "Wow6432Node\\"];
+
+const MACHINE_RUN_KEYS: &[(&str, PersistenceReason)] = &[
+    ("Microsoft\\Windows\\CurrentVersion\\Run", PersistenceReason::Run),
+    ("Microsoft\\Windows\\CurrentVersion\\RunOnce", PersistenceReason::RunOnce),
+    ("Microsoft\\Windows\\CurrentVersion\\RunServices", PersistenceReason::RunServices),
+    ("Microsoft\\Windows\\CurrentVersion\\RunServicesOnce", PersistenceReason::RunServices),
+    ("Microsoft\\Windows\\CurrentVersion\\Policies\\Explorer\\Run", PersistenceReason::PolicyRun),
+];
+
+const USER_RUN_KEYS: &[(&str, PersistenceReason)] = &[
+    ("Software\\Microsoft\\Windows\\CurrentVersion\\Run", PersistenceReason::Run),
+    ("Software\\Microsoft\\Windows\\CurrentVersion\\RunOnce", PersistenceReason::RunOnce),
+    ("Software\\Microsoft\\Windows\\CurrentVersion\\RunServices", PersistenceReason::RunServices),
+    ("Software\\Microsoft\\Windows\\CurrentVersion\\RunServicesOnce", PersistenceReason::RunServices),
+    ("Software\\Microsoft\\Windows\\CurrentVersion\\Policies\\Explorer\\Run", PersistenceReason::PolicyRun),
+];
+
+const WINLOGON: &str = "Microsoft\\Windows NT\\CurrentVersion\\Winlogon";
+const IFEO: &str = "Microsoft\\Windows NT\\CurrentVersion\\Image File Execution Options";
+const SILENT_PROCESS_EXIT: &str = "Microsoft\\Windows NT\\CurrentVersion\\SilentProcessExit";
+const WINDOWS: &str = "Microsoft\\Windows NT\\CurrentVersion\\Windows";
+const ACTIVE_SETUP: &str = "Microsoft\\Active Setup\\Installed Components";
+
+/// BAM and DAM moved under `State` in Windows 10 1809
+const BAM_PATHS: &[&str] = &["Services\\bam\\State\\UserSettings", "Services\\bam\\UserSettings"];
+const DAM_PATHS: &[&str] = &["Services\\dam\\State\\UserSettings", "Services\\dam\\UserSettings"];
+
+const LSA_PACKAGES: &[(&str, &str, PersistenceReason)] = &[
+    ("Control\\Lsa", "Authentication Packages", PersistenceReason::LsaAuthenticationPackage),
+    ("Control\\Lsa", "Security Packages", PersistenceReason::LsaSecurityPackage),
+    ("Control\\Lsa\\OSConfig", "Security Packages", PersistenceReason::LsaSecurityPackage),
+    ("Control\\Lsa", "Notification Packages", PersistenceReason::LsaNotificationPackage),
+];
+
+/// Why an entry was reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PersistenceReason {
+    BamExecution,
+    DamExecution,
+    Service,
+    Run,
+    RunOnce,
+    RunServices,
+    PolicyRun,
+    WinlogonUserinit,
+    WinlogonShell,
+    IfeoDebugger,
+    SilentProcessExit,
+    AppInitDlls,
+    ComHijack,
+    LsaAuthenticationPackage,
+    LsaSecurityPackage,
+    LsaNotificationPackage,
+    PrintMonitor,
+    ActiveSetup,
+}
+
+/// One autostart or execution location, in the spirit of Autoruns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistenceEntry {
+    pub reason: PersistenceReason,
+    /// Mounted hive name, e.g. `HKLM\SOFTWARE` or `HKU\alice`
+    pub hive: String,
+    pub key_path: String,
+    /// Value or subkey name identifying the entry
+    pub name: String,
+    /// Command line, image path or DLL that runs
+    pub command: String,
+    /// Profile or SID the entry applies to, for per-user locations
+    pub user: Option<String>,
+    pub last_write: Option<DateTime<Utc>>,
+    /// Time recorded by the entry itself, such as a BAM last execution
+    pub timestamp: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub details: BTreeMap<String, String>,
+    /// Permissions of the key holding the entry, to spot locations that
+    /// unprivileged users can change
+    pub security: Option<KeySecurity>,
+}
+
+/// Enumerate autostart and execution locations across all loaded hives
+pub fn analyze_persistence(registry: &RegistryParser) -> Vec<PersistenceEntry> {
+    let mut analyzer = Analyzer { registry, entries: Vec::new() };
+
+    if let Some(control_set) = registry.hive(SYSTEM).and_then(|h| h.current_control_set().ok()) {
+        analyzer.bam(&control_set, BAM_PATHS, PersistenceReason::BamExecution);
+        analyzer.bam(&control_set, DAM_PATHS, PersistenceReason::DamExecution);
+        analyzer.services(&control_set);
+        analyzer.lsa(&control_set);
+        analyzer.print_monitors(&control_set);
+    }
+
+    for view in SOFTWARE_VIEWS {
+        for (path, reason) in MACHINE_RUN_KEYS {
+            analyzer.run_key(SOFTWARE, &format!("{}{}", view, path), *reason, None);
+        }
+        analyzer.winlogon(SOFTWARE, &format!("{}{}", view, WINLOGON), None);
+        analyzer.ifeo(&format!("{}{}", view, IFEO));
+        analyzer.silent_process_exit(&format!("{}{}", view, SILENT_PROCESS_EXIT));
+        analyzer.appinit(&format!("{}{}", view, WINDOWS));
+        analyzer.active_setup(&format!("{}{}", view, ACTIVE_SETUP));
+    }
+
+    for (user, _) in registry.user_hives() {
+        let hive = format!("HKU\\{}", user);
+        for (path, reason) in USER_RUN_KEYS {
+            analyzer.run_key(&hive, path, *reason, Some(user));
+        }
+        analyzer.winlogon(&hive, &format!("Software\\{}", WINLOGON), Some(user));
+        analyzer.com_hijacks(&hive, "Software\\Classes\\CLSID", user);
+    }
+    for (user, _) in registry.user_class_hives() {
+        analyzer.com_hijacks(&format!("HKU\\{}_Classes", user), "CLSID", user);
+    }
+
+    analyzer.entries
+}
+
+struct Analyzer<'a> {
+    registry: &'a RegistryParser,
+    entries: Vec<PersistenceEntry>,
+}
+
+impl Analyzer<'_> {
+    fn push(
+        &mut self,
+        reason: PersistenceReason,
+        hive: &str,
+        key_path: &str,
+        key: &RegistryKey,
+        name: &str,
+        command: String,
+    ) -> &mut PersistenceEntry {
+        self.entries.push(PersistenceEntry {
+            reason,
+            hive: hive.to_string(),
+            key_path: key_path.to_string(),
+            name: name.to_string(),
+            command,
+            user: None,
+            last_write: key.last_write_time(),
+            timestamp: None,
+            details: BTreeMap::new(),
+            security: key.security(),
+        });
+        self.entries.last_mut().unwrap()
+    }
+
+    /// Background/Desktop Activity Moderator: one key per SID whose values
+    /// are executable paths with the last execution FILETIME
+    fn bam(&mut self, control_set: &str, paths: &[&str], reason: PersistenceReason) {
+        for path in paths {
+            let root = format!("{}\\{}", control_set, path);
+            let Ok(sids) = self.registry.enumerate_subkeys(SYSTEM, &root) else {
+                continue;
+            };
+
+            for sid in sids {
+                let key_path = format!("{}\\{}", root, sid.name());
+                for (name, value) in sid.values().unwrap_or_default() {
+                    let Some(data) = value.as_bytes().filter(|d| d.len() >= 8) else {
+                        continue;
+                    };
+                    let timestamp = filetime_to_datetime(u64::from_le_bytes(data[0..8].try_into().unwrap()));
+
+                    let entry = self.push(reason, SYSTEM, &key_path, &sid, &name, name.clone());
+                    entry.user = Some(sid.name().to_string());
+                    entry.timestamp = timestamp;
+                }
+            }
+        }
+    }
+
+    fn services(&mut self, control_set: &str) {
+        let root = format!("{}\\Services", control_set);
+        let Ok(services) = self.registry.enumerate_subkeys(SYSTEM, &root) else {
+            return;
+        };
+
+        for service in services {
+            let Some(image_path) = service.string_value("ImagePath") else {
+                continue;
+            };
+            let key_path = format!("{}\\{}", root, service.name());
+            let service_dll = service.get_subkey("Parameters").ok()
+                .and_then(|p| p.string_value("ServiceDll"))
+                .or_else(|| service.string_value("ServiceDll"));
+            let start = service.integer_value("Start");
+            let account = service.string_value("ObjectName");
+            let display_name = service.string_value("DisplayName");
+            let service_type = service.integer_value("Type");
+
+            let entry = self.push(PersistenceReason::Service, SYSTEM, &key_path, &service, service.name(), image_path);
+            if let Some(dll) = service_dll {
+                entry.details.insert("service_dll".into(), dll);
+            }
+            if let Some(start) = start {
+                entry.details.insert("start_type".into(), start_type_name(start).to_string());
+            }
+            if let Some(account) = account {
+                entry.details.insert("account".into(), account);
+            }
+            if let Some(display_name) = display_name {
+                entry.details.insert("display_name".into(), display_name);
+            }
+            if let Some(service_type) = service_type {
+                entry.details.insert("type".into(), format!("{:#x}", service_type));
+            }
+        }
+    }
+
+    fn lsa(&mut self, control_set: &str) {
+        for (path, value, reason) in LSA_PACKAGES {
+            let key_path = format!("{}\\{}", control_set, path);
+            let Ok(key) = self.registry.get_key(SYSTEM, &key_path) else {
+                continue;
+            };
+            let Ok(packages) = key.get_value(value) else {
+                continue;
+            };
+
+            let packages: Vec<String> = match packages.as_multi_string() {
+                Some(list) => list.to_vec(),
+                None => packages.as_string_lossy().into_iter().collect(),
+            };
+            for package in packages.into_iter().filter(|p| !p.is_empty() && p != "\"\"") {
+                self.push(*reason, SYSTEM, &key_path, &key, value, package);
+            }
+        }
+    }
+
+    fn print_monitors(&mut self, control_set: &str) {
+        let root = format!("{}\\Control\\Print\\Monitors", control_set);
+        for monitor in self.registry.enumerate_subkeys(SYSTEM, &root).unwrap_or_default() {
+            if let Some(driver) = monitor.string_value("Driver") {
+                let key_path = format!("{}\\{}", root, monitor.name());
+                self.push(PersistenceReason::PrintMonitor, SYSTEM, &key_path, &monitor, monitor.name(), driver);
+            }
+        }
+    }
+
+    fn run_key(&mut self, hive: &str, path: &str, reason: PersistenceReason, user: Option<&str>) {
+        let Ok(key) = self.registry.get_key(hive, path) else {
+            return;
+        };
+
+        for (name, value) in key.values().unwrap_or_default() {
+            let Some(command) = value.as_string_lossy().filter(|c| !c.is_empty()) else {
+                continue;
+            };
+            let entry = self.push(reason, hive, path, &key, &name, command);
+            entry.user = user.map(str::to_string);
+        }
+    }
+
+    fn winlogon(&mut self, hive: &str, path: &str, user: Option<&str>) {
+        let Ok(key) = self.registry.get_key(hive, path) else {
+            return;
+        };
+
+        for (name, reason) in [("Userinit", PersistenceReason::WinlogonUserinit), ("Shell", PersistenceReason::WinlogonShell)] {
+            if let Some(command) = key.string_value(name) {
+                let entry = self.push(reason, hive, path, &key, name, command);
+                entry.user = user.map(str::to_string);
+            }
+        }
+    }
+
+    /// A Debugger value replaces the target program with another command
+    fn ifeo(&mut self, path: &str) {
+        for image in self.registry.enumerate_subkeys(SOFTWARE, path).unwrap_or_default() {
+            if let Some(debugger) = image.string_value("Debugger") {
+                let key_path = format!("{}\\{}", path, image.name());
+                self.push(PersistenceReason::IfeoDebugger, SOFTWARE, &key_path, &image, image.name(), debugger);
+            }
+        }
+    }
+
+    /// MonitorProcess runs when the target exits, if the IFEO GlobalFlag
+    /// has FLG_MONITOR_SILENT_PROCESS_EXIT (0x200) set
+    fn silent_process_exit(&mut self, path: &str) {
+        let ifeo = path.replace(SILENT_PROCESS_EXIT, IFEO);
+
+        for image in self.registry.enumerate_subkeys(SOFTWARE, path).unwrap_or_default() {
+            let Some(monitor) = image.string_value("MonitorProcess") else {
+                continue;
+            };
+            let global_flag = self.registry
+                .get_value(SOFTWARE, &format!("{}\\{}", ifeo, image.name()), "GlobalFlag").ok()
+                .and_then(|v| v.as_integer().or_else(|| {
+                    v.as_string_lossy().and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+                }));
+
+            let key_path = format!("{}\\{}", path, image.name());
+            let entry = self.push(PersistenceReason::SilentProcessExit, SOFTWARE, &key_path, &image, image.name(), monitor);
+            entry.details.insert(
+                "monitoring_enabled".into(),
+                global_flag.is_some_and(|flag| flag & 0x200 != 0).to_string(),
+            );
+        }
+    }
+
+    fn appinit(&mut self, path: &str) {
+        let Ok(key) = self.registry.get_key(SOFTWARE, path) else {
+            return;
+        };
+        let Some(dlls) = key.string_value("AppInit_DLLs") else {
+            return;
+        };
+
+        let load = key.integer_value("LoadAppInit_DLLs");
+        let entry = self.push(PersistenceReason::AppInitDlls, SOFTWARE, path, &key, "AppInit_DLLs", dlls);
+        entry.details.insert("enabled".into(), load.is_some_and(|v| v != 0).to_string());
+    }
+
+    fn active_setup(&mut self, path: &str) {
+        for component in self.registry.enumerate_subkeys(SOFTWARE, path).unwrap_or_default() {
+            if let Some(stub) = component.string_value("StubPath") {
+                let key_path = format!("{}\\{}", path, component.name());
+                let display_name = component.string_value("");
+                let entry = self.push(PersistenceReason::ActiveSetup, SOFTWARE, &key_path, &component, component.name(), stub);
+                if let Some(display_name) = display_name {
+                    entry.details.insert("display_name".into(), display_name);
+                }
+            }
+        }
+    }
+
+    /// Per-user CLSID registrations take precedence over HKLM ones, so a
+    /// user-level server for a CLSID that also exists machine-wide hijacks it
+    fn com_hijacks(&mut self, hive: &str, path: &str, user: &str) {
+        for clsid in self.registry.enumerate_subkeys(hive, path).unwrap_or_default() {
+            for server in ["InprocServer32", "LocalServer32"] {
+                let Ok(server_key) = clsid.get_subkey(server) else {
+                    continue;
+                };
+                let Some(command) = server_key.string_value("") else {
+                    continue;
+                };
+
+                let machine_path = format!("Classes\\CLSID\\{}\\{}", clsid.name(), server);
+                let machine = self.registry.get_key(SOFTWARE, &machine_path).ok()
+                    .and_then(|key| key.string_value(""));
+
+                let key_path = format!("{}\\{}\\{}", path, clsid.name(), server);
+                let entry = self.push(PersistenceReason::ComHijack, hive, &key_path, &server_key, clsid.name(), command);
+                entry.user = Some(user.to_string());
+                entry.details.insert("server".into(), server.to_string());
+                entry.details.insert("overrides_machine".into(), machine.is_some().to_string());
+                if let Some(machine) = machine {
+                    entry.details.insert("machine_server".into(), machine);
+                }
+            }
+        }
+    }
+}
+
+fn start_type_name(start: u64) -> &'static str {
+    match start {
+        0 => "boot",
+        1 => "system",
+        2 => "automatic",
+        3 => "manual",
+        4 => "disabled",
+        _ => "unknown",
+    }
+}
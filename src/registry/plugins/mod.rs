@@ -0,0 +1,11 @@
+// src/registry/plugins/mod.rs
+//! Per-user plugins that turn NTUSER.DAT keys into timeline-ready records
+mod userassist;
+mod mru;
+mod shellbags;
+mod network;
+
+pub use userassist::{parse_userassist, UserAssistEntry};
+pub use mru::{parse_mru, MruEntry, MruSource};
+pub use shellbags::{parse_shellbags, ShellBagEntry};
+pub use network::{parse_rdp_connections, RdpConnection, parse_mapped_drives, MappedDrive};
@@ -0,0 +1,265 @@
+// src/registry/plugins/mru.rs
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::registry::{RegistryHive, RegistryKey, RegistryValue};
+use crate::shellitem::{id_list_path, parse_id_list, read_utf16, ShellItem};
+use crate::utils::time::filetime_to_datetime;
+
+const EXPLORER: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer";
+const TYPED_URLS: &str = "Software\\Microsoft\\Internet Explorer\\TypedURLs";
+const TYPED_URLS_TIME: &str = "Software\\Microsoft\\Internet Explorer\\TypedURLsTime";
+const OFFICE: &str = "Software\\Microsoft\\Office";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MruSource {
+    RecentDocs,
+    OpenSaveMru,
+    OpenSavePidlMru,
+    LastVisitedMru,
+    LastVisitedPidlMru,
+    RunMru,
+    TypedPaths,
+    TypedUrls,
+    WordWheelQuery,
+    OfficeFileMru,
+}
+
+/// One entry of a most-recently-used list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MruEntry {
+    pub user: String,
+    pub source: MruSource,
+    pub key_path: String,
+    /// The entry at position 0 was added or moved up at this time
+    pub key_last_write: Option<DateTime<Utc>>,
+    /// Position in the list, 0 being the most recent
+    pub position: usize,
+    /// File name, path, command or search term
+    pub value: String,
+    /// Extension subkey (RecentDocs, OpenSave) or program (LastVisited)
+    pub category: Option<String>,
+    /// Per-entry timestamp, where the list records one
+    pub timestamp: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shell_items: Vec<ShellItem>,
+}
+
+/// Parse every supported MRU list in a user's NTUSER.DAT
+pub fn parse_mru(hive: &RegistryHive, user: &str) -> Vec<MruEntry> {
+    let mut entries = Vec::new();
+    let mut mru = MruCollector { hive, user, entries: &mut entries };
+
+    mru.with_subkeys(MruSource::RecentDocs, &format!("{}\\RecentDocs", EXPLORER), recent_doc);
+    mru.with_subkeys(MruSource::OpenSavePidlMru, &format!("{}\\ComDlg32\\OpenSavePidlMRU", EXPLORER), pidl);
+    mru.with_subkeys(MruSource::OpenSaveMru, &format!("{}\\ComDlg32\\OpenSaveMRU", EXPLORER), string);
+    mru.list(MruSource::LastVisitedPidlMru, &format!("{}\\ComDlg32\\LastVisitedPidlMRU", EXPLORER), None, last_visited_pidl);
+    mru.list(MruSource::LastVisitedMru, &format!("{}\\ComDlg32\\LastVisitedMRU", EXPLORER), None, last_visited);
+    mru.list(MruSource::RunMru, &format!("{}\\RunMRU", EXPLORER), None, run_command);
+    mru.with_subkeys(MruSource::WordWheelQuery, &format!("{}\\WordWheelQuery", EXPLORER), string);
+    mru.numbered(MruSource::TypedPaths, &format!("{}\\TypedPaths", EXPLORER), None);
+    mru.numbered(MruSource::TypedUrls, TYPED_URLS, Some(TYPED_URLS_TIME));
+    mru.office();
+
+    entries
+}
+
+/// Decoded value: text, optional category override and shell items
+type Decoded = (String, Option<String>, Vec<ShellItem>);
+type Decoder = fn(&RegistryValue) -> Option<Decoded>;
+
+struct MruCollector<'a> {
+    hive: &'a RegistryHive,
+    user: &'a str,
+    entries: &'a mut Vec<MruEntry>,
+}
+
+impl MruCollector<'_> {
+    /// A list key plus one list per subkey, named after the category
+    fn with_subkeys(&mut self, source: MruSource, path: &str, decode: Decoder) {
+        self.list(source, path, None, decode);
+
+        let Ok(subkeys) = self.hive.get_key(path).and_then(|key| key.enumerate_subkeys()) else {
+            return;
+        };
+        for subkey in subkeys {
+            self.list(source, &format!("{}\\{}", path, subkey.name()), Some(subkey.name()), decode);
+        }
+    }
+
+    /// A key ordered by MRUListEx or MRUList
+    fn list(&mut self, source: MruSource, path: &str, category: Option<&str>, decode: Decoder) {
+        let Ok(key) = self.hive.get_key(path) else {
+            return;
+        };
+
+        for (position, name) in mru_order(&key).into_iter().enumerate() {
+            let Some((value, entry_category, shell_items)) = key.get_value(&name).ok().as_ref().and_then(decode) else {
+                continue;
+            };
+
+            self.entries.push(MruEntry {
+                user: self.user.to_string(),
+                source,
+                key_path: path.to_string(),
+                key_last_write: key.last_write_time(),
+                position,
+                value,
+                category: entry_category.or_else(|| category.map(str::to_string)),
+                timestamp: None,
+                shell_items,
+            });
+        }
+    }
+
+    /// A key of `url1`, `url2`, ... values, with optional per-entry
+    /// FILETIMEs under the same names in `times_path`
+    fn numbered(&mut self, source: MruSource, path: &str, times_path: Option<&str>) {
+        let Ok(key) = self.hive.get_key(path) else {
+            return;
+        };
+        let times = times_path.and_then(|p| self.hive.get_key(p).ok());
+
+        let mut numbered: Vec<(usize, String, String)> = key.values().unwrap_or_default()
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let index = name.to_lowercase().strip_prefix("url")?.parse().ok()?;
+                Some((index, name, value.as_string_lossy()?))
+            })
+            .collect();
+        numbered.sort_by_key(|(index, _, _)| *index);
+
+        for (position, (_, name, value)) in numbered.into_iter().enumerate() {
+            self.entries.push(MruEntry {
+                user: self.user.to_string(),
+                source,
+                key_path: path.to_string(),
+                key_last_write: key.last_write_time(),
+                position,
+                value,
+                category: None,
+                timestamp: times.as_ref()
+                    .and_then(|t| t.get_value(&name).ok())
+                    .and_then(|v| v.as_filetime()),
+                shell_items: Vec::new(),
+            });
+        }
+    }
+
+    /// `Office\<version>\<app>\File MRU`, and the per-account
+    /// `User MRU\<id>\File MRU` lists of Office 2013 and later
+    fn office(&mut self) {
+        let Ok(versions) = self.hive.get_key(OFFICE).and_then(|key| key.enumerate_subkeys()) else {
+            return;
+        };
+
+        for version in versions {
+            for app in version.enumerate_subkeys().unwrap_or_default() {
+                let app_path = format!("{}\\{}\\{}", OFFICE, version.name(), app.name());
+                self.office_list(&format!("{}\\File MRU", app_path), app.name());
+
+                let accounts = app.get_subkey("User MRU")
+                    .and_then(|key| key.enumerate_subkeys())
+                    .unwrap_or_default();
+                for account in accounts {
+                    self.office_list(&format!("{}\\User MRU\\{}\\File MRU", app_path, account.name()), app.name());
+                }
+            }
+        }
+    }
+
+    /// Values are `Item 1`, `Item 2`, ... holding `[F00000000][T01D9...][O00000000]*path`
+    fn office_list(&mut self, path: &str, app: &str) {
+        let Ok(key) = self.hive.get_key(path) else {
+            return;
+        };
+
+        let mut items: Vec<(usize, String)> = key.values().unwrap_or_default()
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let index = name.strip_prefix("Item ")?.parse().ok()?;
+                Some((index, value.as_string_lossy()?))
+            })
+            .collect();
+        items.sort_by_key(|(index, _)| *index);
+
+        for (position, (_, data)) in items.into_iter().enumerate() {
+            let (header, file) = data.split_once('*').unwrap_or(("", data.as_str()));
+
+            self.entries.push(MruEntry {
+                user: self.user.to_string(),
+                source: MruSource::OfficeFileMru,
+                key_path: path.to_string(),
+                key_last_write: key.last_write_time(),
+                position,
+                value: file.to_string(),
+                category: Some(app.to_string()),
+                timestamp: office_timestamp(header),
+                shell_items: Vec::new(),
+            });
+        }
+    }
+}
+
+/// Value names in MRU order, from MRUListEx (DWORD indexes ending with
+/// 0xFFFFFFFF) or MRUList (one letter per value)
+pub(super) fn mru_order(key: &RegistryKey) -> Vec<String> {
+    if let Some(list) = key.get_value("MRUListEx").ok().as_ref().and_then(RegistryValue::as_bytes) {
+        return list.chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .take_while(|&index| index != u32::MAX)
+            .map(|index| index.to_string())
+            .collect();
+    }
+
+    key.get_value("MRUList").ok()
+        .and_then(|v| v.as_string_lossy())
+        .map(|list| list.chars().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Office stores the last-open time as a hex FILETIME, e.g. `[T01D9A4B2C3D4E5F6]`
+fn office_timestamp(header: &str) -> Option<DateTime<Utc>> {
+    let start = header.find("[T")? + 2;
+    let end = start + header[start..].find(']')?;
+    filetime_to_datetime(u64::from_str_radix(&header[start..end], 16).ok()?)
+}
+
+fn string(value: &RegistryValue) -> Option<Decoded> {
+    let text = value.as_string_lossy().or_else(|| value.as_bytes().map(|b| read_utf16(b).0))?;
+    Some((text, None, Vec::new()))
+}
+
+/// A UTF-16 file name followed by the shell items of its shortcut
+fn recent_doc(value: &RegistryValue) -> Option<Decoded> {
+    let data = value.as_bytes()?;
+    let (name, consumed) = read_utf16(data);
+    Some((name, None, parse_id_list(&data[consumed..])))
+}
+
+fn pidl(value: &RegistryValue) -> Option<Decoded> {
+    let items = parse_id_list(value.as_bytes()?);
+    Some((id_list_path(&items), None, items))
+}
+
+/// A UTF-16 program name followed by the ID list of the folder last used
+fn last_visited_pidl(value: &RegistryValue) -> Option<Decoded> {
+    let data = value.as_bytes()?;
+    let (program, consumed) = read_utf16(data);
+    let items = parse_id_list(&data[consumed..]);
+    Some((id_list_path(&items), Some(program), items))
+}
+
+/// A UTF-16 program name followed by the UTF-16 folder last used
+fn last_visited(value: &RegistryValue) -> Option<Decoded> {
+    let data = value.as_bytes()?;
+    let (program, consumed) = read_utf16(data);
+    let (folder, _) = read_utf16(&data[consumed..]);
+    Some((folder, Some(program), Vec::new()))
+}
+
+/// Commands carry a trailing `\1`
+fn run_command(value: &RegistryValue) -> Option<Decoded> {
+    let command = value.as_string_lossy()?;
+    let command = command.strip_suffix("\\1").unwrap_or(&command).to_string();
+    Some((command, None, Vec::new()))
+}
@@ -0,0 +1,117 @@
+// src/registry/plugins/network.rs
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::registry::RegistryHive;
+use super::mru::mru_order;
+
+const TERMINAL_SERVER_CLIENT: &str = "Software\\Microsoft\\Terminal Server Client";
+const MAP_NETWORK_DRIVE_MRU: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\Map Network Drive MRU";
+
+/// A host the user connected to with the Remote Desktop client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RdpConnection {
+    pub user: String,
+    pub server: String,
+    /// Account name saved for the server
+    pub username_hint: Option<String>,
+    /// Position in `Default\MRU0..MRU9`, 0 being the most recent
+    pub mru_position: Option<usize>,
+    /// Last write of the `Servers\<host>` key, roughly the last connection
+    pub last_write: Option<DateTime<Utc>>,
+}
+
+/// A network share mapped by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappedDrive {
+    pub user: String,
+    /// Drive letter for persistent mappings under `Network`
+    pub drive_letter: Option<String>,
+    pub remote_path: String,
+    pub username: Option<String>,
+    pub provider: Option<String>,
+    /// Position in the Map Network Drive MRU, for shares mapped before
+    pub mru_position: Option<usize>,
+    pub last_write: Option<DateTime<Utc>>,
+}
+
+/// Parse a user's Remote Desktop client history
+pub fn parse_rdp_connections(hive: &RegistryHive, user: &str) -> Vec<RdpConnection> {
+    let mut connections: Vec<RdpConnection> = hive.get_key(&format!("{}\\Servers", TERMINAL_SERVER_CLIENT))
+        .and_then(|key| key.enumerate_subkeys())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|server| RdpConnection {
+            user: user.to_string(),
+            server: server.name().to_string(),
+            username_hint: server.string_value("UsernameHint"),
+            mru_position: None,
+            last_write: server.last_write_time(),
+        })
+        .collect();
+
+    let Ok(default) = hive.get_key(&format!("{}\\Default", TERMINAL_SERVER_CLIENT)) else {
+        return connections;
+    };
+
+    for position in 0..10 {
+        let Some(server) = default.string_value(&format!("MRU{}", position)) else {
+            continue;
+        };
+
+        match connections.iter_mut().find(|c| c.server.eq_ignore_ascii_case(&server)) {
+            Some(connection) => connection.mru_position = Some(position),
+            None => connections.push(RdpConnection {
+                user: user.to_string(),
+                server,
+                username_hint: None,
+                mru_position: Some(position),
+                last_write: None,
+            }),
+        }
+    }
+
+    connections
+}
+
+/// Parse a user's persistent drive mappings and Map Network Drive MRU
+pub fn parse_mapped_drives(hive: &RegistryHive, user: &str) -> Vec<MappedDrive> {
+    let mut drives: Vec<MappedDrive> = hive.get_key("Network")
+        .and_then(|key| key.enumerate_subkeys())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|drive| Some(MappedDrive {
+            user: user.to_string(),
+            drive_letter: Some(format!("{}:", drive.name().to_uppercase())),
+            remote_path: drive.string_value("RemotePath")?,
+            username: drive.string_value("UserName"),
+            provider: drive.string_value("ProviderName"),
+            mru_position: None,
+            last_write: drive.last_write_time(),
+        }))
+        .collect();
+
+    let Ok(mru) = hive.get_key(MAP_NETWORK_DRIVE_MRU) else {
+        return drives;
+    };
+
+    for (position, name) in mru_order(&mru).into_iter().enumerate() {
+        let Some(path) = mru.string_value(&name) else {
+            continue;
+        };
+
+        match drives.iter_mut().find(|d| d.remote_path.eq_ignore_ascii_case(&path)) {
+            Some(drive) => drive.mru_position = Some(position),
+            None => drives.push(MappedDrive {
+                user: user.to_string(),
+                drive_letter: None,
+                remote_path: path,
+                username: None,
+                provider: None,
+                mru_position: Some(position),
+                last_write: (position == 0).then(|| mru.last_write_time()).flatten(),
+            }),
+        }
+    }
+
+    drives
+}
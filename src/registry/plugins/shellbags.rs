@@ -0,0 +1,107 @@
+// src/registry/plugins/shellbags.rs
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::registry::{RegistryHive, RegistryKey};
+use crate::shellitem::{parse_item, ShellItem};
+use super::mru::mru_order;
+
+/// BagMRU roots: NTUSER.DAT on XP, UsrClass.dat from Vista on
+const BAG_MRU_PATHS: &[&str] = &[
+    "Software\\Microsoft\\Windows\\Shell\\BagMRU",
+    "Software\\Microsoft\\Windows\\ShellNoRoam\\BagMRU",
+    "Local Settings\\Software\\Microsoft\\Windows\\Shell\\BagMRU",
+    "Wow6432Node\\Local Settings\\Software\\Microsoft\\Windows\\Shell\\BagMRU",
+];
+
+/// Corrupt hives can link keys into cycles
+const MAX_DEPTH: usize = 64;
+
+/// A folder the user browsed, rebuilt from the BagMRU tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellBagEntry {
+    pub user: String,
+    /// BagMRU key holding the entry's children, e.g. `...\BagMRU\0\2`
+    pub key_path: String,
+    /// Last write of that key: when the folder's children last changed
+    pub key_last_write: Option<DateTime<Utc>>,
+    /// Full folder path, e.g. `My Computer\C:\Users\alice\Documents`
+    pub path: String,
+    /// Position in the parent's MRU list, 0 being the most recent
+    pub mru_position: Option<usize>,
+    /// Bag number under `Shell\Bags` holding the folder's view settings
+    pub node_slot: Option<u32>,
+    pub mft_entry: Option<u64>,
+    pub mft_sequence: Option<u16>,
+    pub created: Option<DateTime<Utc>>,
+    pub modified: Option<DateTime<Utc>>,
+    pub accessed: Option<DateTime<Utc>>,
+    pub item: ShellItem,
+}
+
+/// Parse the ShellBags of a user's NTUSER.DAT or UsrClass.dat
+pub fn parse_shellbags(hive: &RegistryHive, user: &str) -> Vec<ShellBagEntry> {
+    let mut entries = Vec::new();
+
+    for root_path in BAG_MRU_PATHS {
+        if let Ok(root) = hive.get_key(root_path) {
+            walk(&root, root_path, "", user, 0, &mut entries);
+        }
+    }
+
+    entries
+}
+
+fn walk(key: &RegistryKey, key_path: &str, parent: &str, user: &str, depth: usize, out: &mut Vec<ShellBagEntry>) {
+    if depth >= MAX_DEPTH {
+        log::debug!("ShellBags nested too deep at {}", key_path);
+        return;
+    }
+
+    let order = mru_order(key);
+    let Ok(values) = key.values() else {
+        return;
+    };
+
+    for (name, value) in values {
+        // Numbered values hold one shell item each; subkeys of the same
+        // name hold that item's children
+        if name.parse::<u32>().is_err() {
+            continue;
+        }
+        let Some(data) = value.as_bytes() else {
+            continue;
+        };
+
+        let item = parse_item(data);
+        let path = if parent.is_empty() {
+            item.name()
+        } else {
+            format!("{}\\{}", parent, item.name())
+        };
+        let child_path = format!("{}\\{}", key_path, name);
+        let child = key.get_subkey(&name).ok();
+        let entry = item.file_entry();
+
+        out.push(ShellBagEntry {
+            user: user.to_string(),
+            key_path: child_path.clone(),
+            key_last_write: child.as_ref().and_then(RegistryKey::last_write_time),
+            path: path.clone(),
+            mru_position: order.iter().position(|n| *n == name),
+            node_slot: child.as_ref()
+                .and_then(|c| c.get_value("NodeSlot").ok())
+                .and_then(|v| v.as_integer())
+                .map(|v| v as u32),
+            mft_entry: entry.and_then(|e| e.mft_entry),
+            mft_sequence: entry.and_then(|e| e.mft_sequence),
+            created: entry.and_then(|e| e.created),
+            modified: entry.and_then(|e| e.modified),
+            accessed: entry.and_then(|e| e.accessed),
+            item,
+        });
+
+        if let Some(child) = child {
+            walk(&child, &child_path, &path, user, depth + 1, out);
+        }
+    }
+}
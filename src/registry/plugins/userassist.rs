@@ -0,0 +1,124 @@
+// src/registry/plugins/userassist.rs
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::registry::RegistryHive;
+use crate::utils::guid::known_folder_name;
+use crate::utils::time::filetime_to_datetime;
+
+const USERASSIST_PATH: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\UserAssist";
+
+/// Windows 7 and later store a 72-byte record per entry
+const WIN7_RECORD_SIZE: usize = 72;
+/// XP and Vista store a 16-byte record per entry
+const XP_RECORD_SIZE: usize = 16;
+
+/// A program or shortcut launched through Explorer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserAssistEntry {
+    pub user: String,
+    /// GUID of the UserAssist subkey, which identifies the item kind
+    pub guid: String,
+    pub key_path: String,
+    pub key_last_write: Option<DateTime<Utc>>,
+    /// ROT13-decoded value name, with a leading known folder GUID resolved
+    pub name: String,
+    pub run_count: u32,
+    pub focus_count: Option<u32>,
+    pub focus_time_ms: Option<u32>,
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+/// Parse every UserAssist entry in a user's NTUSER.DAT
+pub fn parse_userassist(hive: &RegistryHive, user: &str) -> Vec<UserAssistEntry> {
+    let mut entries = Vec::new();
+
+    let Ok(guids) = hive.get_key(USERASSIST_PATH).and_then(|key| key.enumerate_subkeys()) else {
+        return entries;
+    };
+
+    for guid in guids {
+        let Ok(count) = guid.get_subkey("Count") else {
+            continue;
+        };
+        let key_path = format!("{}\\{}\\Count", USERASSIST_PATH, guid.name());
+        let Ok(values) = count.values() else {
+            continue;
+        };
+
+        for (name, value) in values {
+            let Some(data) = value.as_bytes() else {
+                continue;
+            };
+            let Some(record) = parse_record(data) else {
+                log::debug!("Unrecognized UserAssist record of {} bytes in {}", data.len(), key_path);
+                continue;
+            };
+
+            entries.push(UserAssistEntry {
+                user: user.to_string(),
+                guid: guid.name().to_string(),
+                key_path: key_path.clone(),
+                key_last_write: count.last_write_time(),
+                name: resolve_known_folder(&rot13(&name)),
+                run_count: record.run_count,
+                focus_count: record.focus_count,
+                focus_time_ms: record.focus_time_ms,
+                last_run: record.last_run,
+            });
+        }
+    }
+
+    entries
+}
+
+struct Record {
+    run_count: u32,
+    focus_count: Option<u32>,
+    focus_time_ms: Option<u32>,
+    last_run: Option<DateTime<Utc>>,
+}
+
+fn parse_record(data: &[u8]) -> Option<Record> {
+    let dword = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+    let filetime = |offset: usize| {
+        filetime_to_datetime(u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap()))
+    };
+
+    match data.len() {
+        WIN7_RECORD_SIZE => Some(Record {
+            run_count: dword(4),
+            focus_count: Some(dword(8)),
+            focus_time_ms: Some(dword(12)),
+            last_run: filetime(60),
+        }),
+        XP_RECORD_SIZE => Some(Record {
+            // XP counts start at 5
+            run_count: dword(4).saturating_sub(5),
+            focus_count: None,
+            focus_time_ms: None,
+            last_run: filetime(8),
+        }),
+        _ => None,
+    }
+}
+
+fn rot13(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' => (((c as u8 - b'a') + 13) % 26 + b'a') as char,
+            'A'..='Z' => (((c as u8 - b'A') + 13) % 26 + b'A') as char,
+            _ => c,
+        })
+        .collect()
+}
+
+/// Replace a leading `{GUID}` with its folder name, e.g.
+/// `{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\cmd.exe` becomes `System32\cmd.exe`
+fn resolve_known_folder(name: &str) -> String {
+    if let Some(end) = name.find('}').filter(|_| name.starts_with('{'))
+        && let Some(folder) = known_folder_name(&name[..=end])
+    {
+        return format!("{}{}", folder, &name[end + 1..]);
+    }
+    name.to_string()
+}
@@ -0,0 +1,325 @@
+// src/registry/query.rs
+use anyhow::{Result, bail, Context};
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::collections::HashSet;
+use super::{KeySecurity, RegistryKey, RegistryValue};
+use super::hive::MAX_KEY_DEPTH;
+
+/// An ad hoc registry lookup across loaded hives.
+///
+/// Key paths are matched component by component against the hive mount
+/// name followed by the key path, e.g. `HKU\*\Software\Microsoft\Windows\CurrentVersion\Run`.
+/// `*` and `?` glob within a single component and `**` spans any number of components.
+#[derive(Debug, Clone)]
+pub struct RegistryQuery {
+    pattern: Vec<PathComponent>,
+    value_name: Option<Regex>,
+    data: Option<Regex>,
+    modified_after: Option<DateTime<Utc>>,
+    modified_before: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+enum PathComponent {
+    /// A component without wildcards, which can be looked up directly
+    Literal(String),
+    Glob(Regex),
+    /// `**`: zero or more components
+    AnyDepth,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryMatch {
+    pub hive: String,
+    pub key_path: String,
+    pub last_write: Option<DateTime<Utc>>,
+    pub value_name: Option<String>,
+    pub value: Option<RegistryValue>,
+    /// Permissions of the matched key
+    pub security: Option<KeySecurity>,
+}
+
+impl QueryMatch {
+    /// Full path including the hive mount name
+    pub fn full_path(&self) -> String {
+        if self.key_path.is_empty() {
+            self.hive.clone()
+        } else {
+            format!("{}\\{}", self.hive, self.key_path)
+        }
+    }
+}
+
+impl RegistryQuery {
+    pub fn new(path: &str) -> Result<Self> {
+        let pattern = normalize_root(path)
+            .split('\\')
+            .filter(|c| !c.is_empty())
+            .map(PathComponent::parse)
+            .collect::<Result<Vec<_>>>()?;
+
+        if pattern.is_empty() {
+            bail!("Empty registry query");
+        }
+
+        Ok(Self {
+            pattern,
+            value_name: None,
+            data: None,
+            modified_after: None,
+            modified_before: None,
+        })
+    }
+
+    /// Only report values whose name matches `pattern` (case-insensitive)
+    pub fn value_name(mut self, pattern: &str) -> Result<Self> {
+        self.value_name = Some(case_insensitive(pattern)?);
+        Ok(self)
+    }
+
+    /// Only report values whose data, rendered as text, matches `pattern`
+    pub fn data(mut self, pattern: &str) -> Result<Self> {
+        self.data = Some(case_insensitive(pattern)?);
+        Ok(self)
+    }
+
+    /// Only report keys last written within the given range
+    pub fn modified_between(mut self, after: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>) -> Self {
+        self.modified_after = after;
+        self.modified_before = before;
+        self
+    }
+
+    /// Pattern components left to match inside a hive mounted at `mount`.
+    /// Several remainders are possible when `**` straddles the mount name.
+    pub(crate) fn remaining_after_mount(&self, mount: &str) -> Vec<QueryPattern<'_>> {
+        let mount: Vec<&str> = mount.split('\\').collect();
+        let mut out = Vec::new();
+        match_mount(&self.pattern, &mount, &mut out);
+        out.into_iter().map(|components| QueryPattern { components }).collect()
+    }
+
+    /// Recursively match `pattern` below `key`, collecting hits into `out`.
+    /// Keys that cannot be read are logged and skipped so that one corrupt
+    /// cell does not lose the matches found elsewhere.
+    pub(crate) fn walk(
+        &self,
+        hive: &str,
+        key: &RegistryKey,
+        path: &str,
+        pattern: QueryPattern<'_>,
+        out: &mut Vec<QueryMatch>,
+    ) {
+        let mut walk = Walk { query: self, hive, visited: HashSet::new(), out };
+        walk.key(key, path, pattern, 0);
+    }
+
+    fn emit(&self, hive: &str, key: &RegistryKey, path: &str, out: &mut Vec<QueryMatch>) {
+        let last_write = key.last_write_time();
+
+        if let Some(after) = self.modified_after
+            && last_write.is_none_or(|t| t < after)
+        {
+            return;
+        }
+        if let Some(before) = self.modified_before
+            && last_write.is_none_or(|t| t > before)
+        {
+            return;
+        }
+
+        let mut values = key.values().unwrap_or_else(|e| {
+            log::debug!("Skipping values of {}\\{}: {}", hive, path, e);
+            Vec::new()
+        });
+        values.retain(|(name, value)| {
+            self.value_name.as_ref().is_none_or(|re| re.is_match(name))
+                && self.data.as_ref().is_none_or(|re| re.is_match(&value.to_string()))
+        });
+
+        let security = key.security();
+        let filtered = self.value_name.is_some() || self.data.is_some();
+        if values.is_empty() && !filtered {
+            out.push(QueryMatch {
+                hive: hive.to_string(),
+                key_path: path.to_string(),
+                last_write,
+                value_name: None,
+                value: None,
+                security,
+            });
+            return;
+        }
+
+        for (name, value) in values {
+            out.push(QueryMatch {
+                hive: hive.to_string(),
+                key_path: path.to_string(),
+                last_write,
+                value_name: Some(name),
+                value: Some(value),
+                security: security.clone(),
+            });
+        }
+    }
+}
+
+/// State of a single query walk through one hive
+struct Walk<'a> {
+    query: &'a RegistryQuery,
+    hive: &'a str,
+    /// Key offsets already matched against each remaining pattern length, so
+    /// that subkey cycles in a corrupt hive are followed only once
+    visited: HashSet<(u32, usize)>,
+    out: &'a mut Vec<QueryMatch>,
+}
+
+impl Walk<'_> {
+    fn key(&mut self, key: &RegistryKey, path: &str, pattern: QueryPattern<'_>, depth: usize) {
+        if depth > MAX_KEY_DEPTH || !self.visited.insert((key.offset(), pattern.components.len())) {
+            log::debug!("Skipping {}\\{}: subkey loop or nesting too deep", self.hive, path);
+            return;
+        }
+
+        let Some((first, rest)) = pattern.components.split_first() else {
+            return self.query.emit(self.hive, key, path, self.out);
+        };
+
+        let child_path = |name: &str| {
+            if path.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}\\{}", path, name)
+            }
+        };
+        let hive = self.hive;
+        let subkeys = || key.enumerate_subkeys().unwrap_or_else(|e| {
+            log::debug!("Skipping subkeys of {}\\{}: {}", hive, path, e);
+            Vec::new()
+        });
+
+        match first {
+            PathComponent::Literal(name) => {
+                if let Ok(subkey) = key.get_subkey(name) {
+                    let next = child_path(subkey.name());
+                    self.key(&subkey, &next, QueryPattern { components: rest }, depth + 1);
+                }
+            }
+            PathComponent::Glob(re) => {
+                for subkey in subkeys() {
+                    if re.is_match(subkey.name()) {
+                        let next = child_path(subkey.name());
+                        self.key(&subkey, &next, QueryPattern { components: rest }, depth + 1);
+                    }
+                }
+            }
+            PathComponent::AnyDepth => {
+                self.key(key, path, QueryPattern { components: rest }, depth);
+                for subkey in subkeys() {
+                    let next = child_path(subkey.name());
+                    self.key(&subkey, &next, pattern, depth + 1);
+                }
+            }
+        }
+    }
+}
+
+/// The part of a query still to be matched inside a hive
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct QueryPattern<'a> {
+    components: &'a [PathComponent],
+}
+
+impl<'a> QueryPattern<'a> {
+    /// Split off the leading literal components, which can be resolved with a
+    /// single (cached) key lookup instead of a walk
+    pub(crate) fn split_literal_prefix(self) -> (String, QueryPattern<'a>) {
+        let count = self.components.iter()
+            .take_while(|c| matches!(c, PathComponent::Literal(_)))
+            .count();
+
+        let prefix: Vec<&str> = self.components[..count].iter()
+            .filter_map(|c| match c {
+                PathComponent::Literal(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        (prefix.join("\\"), QueryPattern { components: &self.components[count..] })
+    }
+}
+
+impl PathComponent {
+    fn parse(component: &str) -> Result<Self> {
+        if component == "**" {
+            return Ok(PathComponent::AnyDepth);
+        }
+        if !component.contains(['*', '?']) {
+            return Ok(PathComponent::Literal(component.to_string()));
+        }
+
+        let mut pattern = String::from("^");
+        for c in component.chars() {
+            match c {
+                '*' => pattern.push_str(".*"),
+                '?' => pattern.push('.'),
+                c => pattern.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        pattern.push('$');
+
+        Ok(PathComponent::Glob(case_insensitive(&pattern)?))
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            PathComponent::Literal(literal) => literal.eq_ignore_ascii_case(name),
+            PathComponent::Glob(re) => re.is_match(name),
+            PathComponent::AnyDepth => true,
+        }
+    }
+}
+
+fn match_mount<'a>(pattern: &'a [PathComponent], mount: &[&str], out: &mut Vec<&'a [PathComponent]>) {
+    let Some((mount_first, mount_rest)) = mount.split_first() else {
+        out.push(pattern);
+        return;
+    };
+
+    match pattern.first() {
+        None => {}
+        Some(PathComponent::AnyDepth) => {
+            match_mount(&pattern[1..], mount, out);
+            match_mount(pattern, mount_rest, out);
+        }
+        Some(component) if component.matches(mount_first) => {
+            match_mount(&pattern[1..], mount_rest, out);
+        }
+        Some(_) => {}
+    }
+}
+
+/// Accept the long root key names as aliases for the short mount names
+fn normalize_root(path: &str) -> String {
+    const ROOTS: &[(&str, &str)] = &[
+        ("HKEY_LOCAL_MACHINE", "HKLM"),
+        ("HKEY_USERS", "HKU"),
+    ];
+
+    let path = path.trim_start_matches('\\');
+    for (long, short) in ROOTS {
+        if path.len() >= long.len() && path[..long.len()].eq_ignore_ascii_case(long) {
+            return format!("{}{}", short, &path[long.len()..]);
+        }
+    }
+    path.to_string()
+}
+
+fn case_insensitive(pattern: &str) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .context(format!("Invalid pattern '{}'", pattern))
+}
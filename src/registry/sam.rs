@@ -0,0 +1,225 @@
+// src/registry/sam.rs
+use std::collections::HashMap;
+use anyhow::{Result, bail, Context};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use super::{RegistryHive, RegistryKey};
+use crate::utils::bytes::utf16;
+use crate::utils::sid::parse_sid;
+use crate::utils::time::filetime_to_datetime;
+
+pub(crate) const USERS_PATH: &str = "SAM\\Domains\\Account\\Users";
+const ALIASES_PATH: &str = "SAM\\Domains\\Builtin\\Aliases";
+pub(crate) const ACCOUNT_PATH: &str = "SAM\\Domains\\Account";
+
+/// User account control (ACB) flags from the F value
+pub const ACB_DISABLED: u16 = 0x0001;
+pub const ACB_PASSWORD_NOT_REQUIRED: u16 = 0x0004;
+pub const ACB_PASSWORD_DOES_NOT_EXPIRE: u16 = 0x0200;
+pub const ACB_LOCKED: u16 = 0x0400;
+
+/// Stored in the F value's expiry field by accounts that never expire
+const NEVER_EXPIRES: u64 = 0x7FFF_FFFF_FFFF_FFFF;
+
+/// Variable-length fields in the V value are described by 12-byte
+/// (offset, length, unknown) entries; data offsets are relative to the end
+/// of this header
+const V_DATA_START: usize = 0xCC;
+pub(crate) const V_USERNAME: usize = 1;
+const V_FULL_NAME: usize = 2;
+const V_COMMENT: usize = 3;
+#[cfg(feature = "credentials")]
+pub(crate) const V_LM_HASH: usize = 13;
+#[cfg(feature = "credentials")]
+pub(crate) const V_NT_HASH: usize = 14;
+
+/// Alias C values have a fixed header, with offsets relative to its end
+const C_DATA_START: usize = 0x34;
+
+/// A local account from the SAM hive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamAccount {
+    pub rid: u32,
+    pub sid: Option<String>,
+    pub username: String,
+    pub full_name: Option<String>,
+    pub comment: Option<String>,
+    /// Approximated by the last write time of the account's `Names` key
+    pub created: Option<DateTime<Utc>>,
+    pub last_login: Option<DateTime<Utc>>,
+    pub last_password_change: Option<DateTime<Utc>>,
+    pub last_failed_login: Option<DateTime<Utc>>,
+    pub account_expires: Option<DateTime<Utc>>,
+    pub login_count: u16,
+    pub failed_login_count: u16,
+    /// Raw ACB flags
+    pub flags: u16,
+    pub disabled: bool,
+    pub password_not_required: bool,
+    pub password_never_expires: bool,
+    pub locked: bool,
+    /// Names of the Builtin aliases (local groups) the account belongs to
+    pub groups: Vec<String>,
+}
+
+/// Parse every local account from a SAM hive
+pub fn parse_sam(hive: &RegistryHive) -> Result<Vec<SamAccount>> {
+    let domain_sid = account_domain_sid(hive);
+    let created = creation_times(hive);
+    let memberships = alias_memberships(hive);
+
+    let users = hive.get_key(USERS_PATH)?;
+    let mut accounts = Vec::new();
+
+    for key in users.enumerate_subkeys()? {
+        // Subkeys other than Names are the hex RID of each account
+        let Ok(rid) = u32::from_str_radix(key.name(), 16) else {
+            continue;
+        };
+
+        match parse_account(&key, rid) {
+            Ok(mut account) => {
+                account.sid = domain_sid.as_ref().map(|domain| format!("{}-{}", domain, rid));
+                account.created = created.get(&account.username.to_lowercase()).copied().flatten();
+                account.groups = account.sid.as_ref()
+                    .and_then(|sid| memberships.get(sid))
+                    .cloned()
+                    .unwrap_or_default();
+                accounts.push(account);
+            }
+            Err(e) => log::debug!("Skipping SAM account {:#x}: {}", rid, e),
+        }
+    }
+
+    Ok(accounts)
+}
+
+fn parse_account(key: &RegistryKey, rid: u32) -> Result<SamAccount> {
+    let f = key.get_value("F")?;
+    let f = f.as_bytes().context("F value is not binary")?;
+    let v = key.get_value("V")?;
+    let v = v.as_bytes().context("V value is not binary")?;
+
+    if f.len() < 0x44 {
+        bail!("F value too short");
+    }
+
+    let qword = |offset: usize| u64::from_le_bytes(f[offset..offset + 8].try_into().unwrap());
+    let filetime = |offset: usize| filetime_to_datetime(qword(offset));
+    let word = |offset: usize| u16::from_le_bytes([f[offset], f[offset + 1]]);
+    let flags = word(0x38);
+
+    Ok(SamAccount {
+        rid,
+        sid: None,
+        username: v_string(v, V_USERNAME).context("V value has no username")?,
+        full_name: v_string(v, V_FULL_NAME).filter(|s| !s.is_empty()),
+        comment: v_string(v, V_COMMENT).filter(|s| !s.is_empty()),
+        created: None,
+        last_login: filetime(0x08),
+        last_password_change: filetime(0x18),
+        // Accounts that never expire store 0 or 0x7FFFFFFFFFFFFFFF (year 30828)
+        account_expires: match qword(0x20) {
+            0 | NEVER_EXPIRES => None,
+            expires => filetime_to_datetime(expires),
+        },
+        last_failed_login: filetime(0x28),
+        login_count: word(0x42),
+        failed_login_count: word(0x40),
+        flags,
+        disabled: flags & ACB_DISABLED != 0,
+        password_not_required: flags & ACB_PASSWORD_NOT_REQUIRED != 0,
+        password_never_expires: flags & ACB_PASSWORD_DOES_NOT_EXPIRE != 0,
+        locked: flags & ACB_LOCKED != 0,
+        groups: Vec::new(),
+    })
+}
+
+/// Read the data described by header entry `index` of a V value
+pub(crate) fn v_field(v: &[u8], index: usize) -> Option<&[u8]> {
+    let entry = index * 12;
+    let offset = u32::from_le_bytes(v.get(entry..entry + 4)?.try_into().ok()?) as usize;
+    let length = u32::from_le_bytes(v.get(entry + 4..entry + 8)?.try_into().ok()?) as usize;
+    let start = V_DATA_START + offset;
+    v.get(start..start + length)
+}
+
+/// Read the UTF-16 string described by header entry `index` of a V value
+pub(crate) fn v_string(v: &[u8], index: usize) -> Option<String> {
+    Some(utf16(v_field(v, index)?))
+}
+
+/// The machine's account domain SID, stored as the last three
+/// sub-authorities at the end of the Account key's V value
+fn account_domain_sid(hive: &RegistryHive) -> Option<String> {
+    let v = hive.get_key(ACCOUNT_PATH).ok()?.get_value("V").ok()?;
+    let v = v.as_bytes()?;
+    let tail = v.get(v.len().checked_sub(12)?..)?;
+
+    let subs: Vec<String> = tail.chunks_exact(4)
+        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]).to_string())
+        .collect();
+    Some(format!("S-1-5-21-{}", subs.join("-")))
+}
+
+/// Map lowercase usernames to the last write time of their `Names` subkey,
+/// which is set when the account is created
+fn creation_times(hive: &RegistryHive) -> HashMap<String, Option<DateTime<Utc>>> {
+    hive.get_key(&format!("{}\\Names", USERS_PATH))
+        .and_then(|names| names.enumerate_subkeys())
+        .map(|keys| {
+            keys.into_iter()
+                .map(|key| (key.name().to_lowercase(), key.last_write_time()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Map member SIDs to the names of the Builtin aliases they belong to
+fn alias_memberships(hive: &RegistryHive) -> HashMap<String, Vec<String>> {
+    let mut memberships: HashMap<String, Vec<String>> = HashMap::new();
+
+    let Ok(aliases) = hive.get_key(ALIASES_PATH).and_then(|key| key.enumerate_subkeys()) else {
+        return memberships;
+    };
+
+    for alias in aliases {
+        let Ok(c) = alias.get_value("C") else {
+            continue;
+        };
+        let Some(c) = c.as_bytes() else {
+            continue;
+        };
+
+        if let Some((name, members)) = parse_alias(c) {
+            for member in members {
+                memberships.entry(member).or_default().push(name.clone());
+            }
+        }
+    }
+
+    memberships
+}
+
+/// Parse an alias C value into the alias name and its member SIDs
+fn parse_alias(c: &[u8]) -> Option<(String, Vec<String>)> {
+    let field = |offset: usize| -> Option<usize> {
+        Some(u32::from_le_bytes(c.get(offset..offset + 4)?.try_into().ok()?) as usize)
+    };
+
+    let name_start = C_DATA_START + field(0x10)?;
+    let name = utf16(c.get(name_start..name_start + field(0x14)?)?);
+
+    let mut pos = C_DATA_START + field(0x28)?;
+    // Each member SID takes at least 12 bytes, which bounds a corrupt count
+    let count = field(0x30)?.min(c.len().saturating_sub(pos) / 12);
+    let mut members = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let (sid, length) = parse_sid(c.get(pos..)?)?;
+        members.push(sid);
+        pos += length;
+    }
+
+    Some((name, members))
+}
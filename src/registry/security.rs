@@ -0,0 +1,435 @@
+// src/registry/security.rs
+use anyhow::{Result, bail, Context};
+use serde::{Serialize, Deserialize};
+use crate::utils::guid::format_guid;
+use crate::utils::sid::parse_sid;
+
+// Security descriptor control flags
+const SE_DACL_PRESENT: u16 = 0x0004;
+const SE_SACL_PRESENT: u16 = 0x0010;
+const SE_DACL_AUTO_INHERIT_REQ: u16 = 0x0100;
+const SE_SACL_AUTO_INHERIT_REQ: u16 = 0x0200;
+const SE_DACL_AUTO_INHERITED: u16 = 0x0400;
+const SE_SACL_AUTO_INHERITED: u16 = 0x0800;
+const SE_DACL_PROTECTED: u16 = 0x1000;
+const SE_SACL_PROTECTED: u16 = 0x2000;
+
+/// Object ACEs carry optional object type GUIDs before the SID
+const ACE_OBJECT_TYPE_PRESENT: u32 = 0x1;
+const ACE_INHERITED_OBJECT_TYPE_PRESENT: u32 = 0x2;
+
+/// Rights that let a principal change a key's values, subkeys or permissions
+const WRITE_RIGHTS: u32 = 0x0000_0002 // KEY_SET_VALUE
+    | 0x0000_0004 // KEY_CREATE_SUB_KEY
+    | 0x0001_0000 // DELETE
+    | 0x0004_0000 // WRITE_DAC
+    | 0x0008_0000 // WRITE_OWNER
+    | 0x1000_0000 // GENERIC_ALL
+    | 0x4000_0000; // GENERIC_WRITE
+
+/// Registry access rights by SDDL abbreviation, largest composites first
+const RIGHTS: &[(u32, &str)] = &[
+    (0x000F_003F, "KA"),
+    (0x0002_0019, "KR"),
+    (0x0002_0006, "KW"),
+    (0x1000_0000, "GA"),
+    (0x2000_0000, "GX"),
+    (0x4000_0000, "GW"),
+    (0x8000_0000, "GR"),
+    (0x0008_0000, "WO"),
+    (0x0004_0000, "WD"),
+    (0x0002_0000, "RC"),
+    (0x0001_0000, "SD"),
+    (0x0000_0001, "CC"),
+    (0x0000_0002, "DC"),
+    (0x0000_0004, "LC"),
+    (0x0000_0008, "SW"),
+    (0x0000_0010, "RP"),
+    (0x0000_0020, "WP"),
+];
+
+/// Well-known SIDs and their SDDL aliases
+const SID_ALIASES: &[(&str, &str)] = &[
+    ("S-1-1-0", "WD"),
+    ("S-1-3-0", "CO"),
+    ("S-1-3-1", "CG"),
+    ("S-1-5-2", "NU"),
+    ("S-1-5-4", "IU"),
+    ("S-1-5-6", "SU"),
+    ("S-1-5-7", "AN"),
+    ("S-1-5-11", "AU"),
+    ("S-1-5-12", "RC"),
+    ("S-1-5-18", "SY"),
+    ("S-1-5-19", "LS"),
+    ("S-1-5-20", "NS"),
+    ("S-1-5-32-544", "BA"),
+    ("S-1-5-32-545", "BU"),
+    ("S-1-5-32-546", "BG"),
+    ("S-1-5-32-547", "PU"),
+    ("S-1-5-32-551", "BO"),
+    ("S-1-15-2-1", "AC"),
+];
+
+/// Principals that any local or domain user belongs to
+const LOW_PRIVILEGE_SIDS: &[&str] = &[
+    "S-1-1-0",
+    "S-1-5-2",
+    "S-1-5-4",
+    "S-1-5-7",
+    "S-1-5-11",
+    "S-1-5-32-545",
+    "S-1-5-32-546",
+    "S-1-15-2-1",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityDescriptor {
+    pub revision: u8,
+    pub control: u16,
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    pub dacl: Option<Acl>,
+    pub sacl: Option<Acl>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Acl {
+    pub revision: u8,
+    pub aces: Vec<Ace>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ace {
+    pub ace_type: AceType,
+    pub flags: u8,
+    pub access_mask: u32,
+    /// Access mask broken down into SDDL right abbreviations
+    pub rights: Vec<String>,
+    pub sid: String,
+    pub object_type: Option<String>,
+    pub inherited_object_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AceType {
+    AccessAllowed,
+    AccessDenied,
+    SystemAudit,
+    SystemAlarm,
+    AccessAllowedObject,
+    AccessDeniedObject,
+    SystemAuditObject,
+    SystemAlarmObject,
+    AccessAllowedCallback,
+    AccessDeniedCallback,
+    SystemMandatoryLabel,
+    Unknown(u8),
+}
+
+impl AceType {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0x00 => AceType::AccessAllowed,
+            0x01 => AceType::AccessDenied,
+            0x02 => AceType::SystemAudit,
+            0x03 => AceType::SystemAlarm,
+            0x05 => AceType::AccessAllowedObject,
+            0x06 => AceType::AccessDeniedObject,
+            0x07 => AceType::SystemAuditObject,
+            0x08 => AceType::SystemAlarmObject,
+            0x09 => AceType::AccessAllowedCallback,
+            0x0A => AceType::AccessDeniedCallback,
+            0x11 => AceType::SystemMandatoryLabel,
+            other => AceType::Unknown(other),
+        }
+    }
+
+    fn is_object(&self) -> bool {
+        matches!(
+            self,
+            AceType::AccessAllowedObject
+                | AceType::AccessDeniedObject
+                | AceType::SystemAuditObject
+                | AceType::SystemAlarmObject
+        )
+    }
+
+    fn sddl(&self) -> String {
+        match self {
+            AceType::AccessAllowed => "A".to_string(),
+            AceType::AccessDenied => "D".to_string(),
+            AceType::SystemAudit => "AU".to_string(),
+            AceType::SystemAlarm => "AL".to_string(),
+            AceType::AccessAllowedObject => "OA".to_string(),
+            AceType::AccessDeniedObject => "OD".to_string(),
+            AceType::SystemAuditObject => "OU".to_string(),
+            AceType::SystemAlarmObject => "OL".to_string(),
+            AceType::AccessAllowedCallback => "XA".to_string(),
+            AceType::AccessDeniedCallback => "XD".to_string(),
+            AceType::SystemMandatoryLabel => "ML".to_string(),
+            AceType::Unknown(value) => format!("{:#x}", value),
+        }
+    }
+}
+
+/// The parts of a key's security descriptor reported with query matches and
+/// persistence entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeySecurity {
+    pub owner: Option<String>,
+    pub sddl: String,
+    /// Allow ACEs that let broad, low-privileged groups modify the key
+    pub weak_write_aces: Vec<Ace>,
+}
+
+impl KeySecurity {
+    pub fn new(descriptor: &SecurityDescriptor) -> Self {
+        Self {
+            owner: descriptor.owner.clone(),
+            sddl: descriptor.to_sddl(),
+            weak_write_aces: descriptor.weak_write_aces().into_iter().cloned().collect(),
+        }
+    }
+}
+
+impl SecurityDescriptor {
+    /// Parse a self-relative SECURITY_DESCRIPTOR
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < 20 {
+            bail!("Security descriptor too short");
+        }
+
+        let control = u16::from_le_bytes([data[2], data[3]]);
+        let offset = |i: usize| u32::from_le_bytes(data[i..i + 4].try_into().unwrap()) as usize;
+        let (owner_offset, group_offset) = (offset(4), offset(8));
+        let (sacl_offset, dacl_offset) = (offset(12), offset(16));
+
+        let sid_at = |off: usize| -> Option<String> {
+            if off == 0 {
+                return None;
+            }
+            data.get(off..).and_then(parse_sid).map(|(sid, _)| sid)
+        };
+
+        let dacl = if control & SE_DACL_PRESENT != 0 && dacl_offset != 0 {
+            Some(Acl::parse(data.get(dacl_offset..).context("DACL offset out of bounds")?)?)
+        } else {
+            None
+        };
+
+        let sacl = if control & SE_SACL_PRESENT != 0 && sacl_offset != 0 {
+            Some(Acl::parse(data.get(sacl_offset..).context("SACL offset out of bounds")?)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            revision: data[0],
+            control,
+            owner: sid_at(owner_offset),
+            group: sid_at(group_offset),
+            dacl,
+            sacl,
+        })
+    }
+
+    /// Render the descriptor in Security Descriptor Definition Language
+    pub fn to_sddl(&self) -> String {
+        let mut sddl = String::new();
+
+        if let Some(owner) = &self.owner {
+            sddl.push_str(&format!("O:{}", sid_to_sddl(owner)));
+        }
+        if let Some(group) = &self.group {
+            sddl.push_str(&format!("G:{}", sid_to_sddl(group)));
+        }
+        if let Some(dacl) = &self.dacl {
+            sddl.push_str("D:");
+            sddl.push_str(&acl_flags(
+                self.control,
+                SE_DACL_PROTECTED,
+                SE_DACL_AUTO_INHERIT_REQ,
+                SE_DACL_AUTO_INHERITED,
+            ));
+            sddl.push_str(&dacl.to_sddl());
+        }
+        if let Some(sacl) = &self.sacl {
+            sddl.push_str("S:");
+            sddl.push_str(&acl_flags(
+                self.control,
+                SE_SACL_PROTECTED,
+                SE_SACL_AUTO_INHERIT_REQ,
+                SE_SACL_AUTO_INHERITED,
+            ));
+            sddl.push_str(&sacl.to_sddl());
+        }
+
+        sddl
+    }
+
+    /// Allow ACEs granting write, delete or permission-change rights to
+    /// broad, low-privileged groups such as Everyone or Users
+    pub fn weak_write_aces(&self) -> Vec<&Ace> {
+        self.dacl
+            .iter()
+            .flat_map(|acl| acl.aces.iter())
+            .filter(|ace| {
+                matches!(ace.ace_type, AceType::AccessAllowed | AceType::AccessAllowedObject)
+                    && ace.access_mask & WRITE_RIGHTS != 0
+                    && is_low_privilege(&ace.sid)
+            })
+            .collect()
+    }
+}
+
+impl Acl {
+    fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 {
+            bail!("ACL too short");
+        }
+
+        let size = u16::from_le_bytes([data[2], data[3]]) as usize;
+        let count = u16::from_le_bytes([data[4], data[5]]) as usize;
+        let data = data.get(..size).context("ACL exceeds descriptor")?;
+
+        let mut aces = Vec::with_capacity(count);
+        let mut pos = 8;
+        for _ in 0..count {
+            let header = data.get(pos..pos + 4).context("ACE header truncated")?;
+            let ace_size = u16::from_le_bytes([header[2], header[3]]) as usize;
+            if ace_size < 4 {
+                bail!("Invalid ACE size");
+            }
+            let ace = data.get(pos..pos + ace_size).context("ACE truncated")?;
+            aces.push(Ace::parse(ace)?);
+            pos += ace_size;
+        }
+
+        Ok(Self { revision: data[0], aces })
+    }
+
+    fn to_sddl(&self) -> String {
+        self.aces.iter().map(Ace::to_sddl).collect()
+    }
+}
+
+impl Ace {
+    fn parse(data: &[u8]) -> Result<Self> {
+        let ace_type = AceType::from_u8(data[0]);
+        let access_mask = u32::from_le_bytes(data.get(4..8).context("ACE mask truncated")?.try_into()?);
+
+        let mut pos = 8;
+        let mut object_type = None;
+        let mut inherited_object_type = None;
+
+        if ace_type.is_object() {
+            let flags = u32::from_le_bytes(data.get(8..12).context("Object ACE truncated")?.try_into()?);
+            pos = 12;
+            if flags & ACE_OBJECT_TYPE_PRESENT != 0 {
+                object_type = data.get(pos..).and_then(format_guid);
+                pos += 16;
+            }
+            if flags & ACE_INHERITED_OBJECT_TYPE_PRESENT != 0 {
+                inherited_object_type = data.get(pos..).and_then(format_guid);
+                pos += 16;
+            }
+        }
+
+        let (sid, _) = data.get(pos..)
+            .and_then(parse_sid)
+            .context("ACE SID invalid")?;
+
+        Ok(Self {
+            ace_type,
+            flags: data[1],
+            access_mask,
+            rights: rights_to_strings(access_mask),
+            sid,
+            object_type,
+            inherited_object_type,
+        })
+    }
+
+    fn to_sddl(&self) -> String {
+        let strip = |guid: &Option<String>| {
+            guid.as_deref()
+                .map(|g| g.trim_matches(|c| c == '{' || c == '}').to_lowercase())
+                .unwrap_or_default()
+        };
+
+        format!(
+            "({};{};{};{};{};{})",
+            self.ace_type.sddl(),
+            ace_flags_to_sddl(self.flags),
+            self.rights.concat(),
+            strip(&self.object_type),
+            strip(&self.inherited_object_type),
+            sid_to_sddl(&self.sid),
+        )
+    }
+}
+
+fn acl_flags(control: u16, protected: u16, auto_inherit_req: u16, auto_inherited: u16) -> String {
+    let mut flags = String::new();
+    if control & protected != 0 {
+        flags.push('P');
+    }
+    if control & auto_inherit_req != 0 {
+        flags.push_str("AR");
+    }
+    if control & auto_inherited != 0 {
+        flags.push_str("AI");
+    }
+    flags
+}
+
+fn ace_flags_to_sddl(flags: u8) -> String {
+    const FLAGS: &[(u8, &str)] = &[
+        (0x01, "OI"),
+        (0x02, "CI"),
+        (0x04, "NP"),
+        (0x08, "IO"),
+        (0x10, "ID"),
+        (0x40, "SA"),
+        (0x80, "FA"),
+    ];
+
+    FLAGS.iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// Break an access mask into SDDL right abbreviations, falling back to hex
+/// for any bits without a name
+fn rights_to_strings(mask: u32) -> Vec<String> {
+    let mut remaining = mask;
+    let mut rights = Vec::new();
+
+    for (bits, name) in RIGHTS {
+        if remaining & bits == *bits {
+            rights.push(name.to_string());
+            remaining &= !bits;
+        }
+    }
+
+    if remaining != 0 {
+        // SDDL cannot mix names and hex, so use the full mask
+        return vec![format!("{:#x}", mask)];
+    }
+
+    rights
+}
+
+fn sid_to_sddl(sid: &str) -> String {
+    SID_ALIASES.iter()
+        .find(|(full, _)| *full == sid)
+        .map(|(_, alias)| alias.to_string())
+        .unwrap_or_else(|| sid.to_string())
+}
+
+fn is_low_privilege(sid: &str) -> bool {
+    // Domain Users and Domain Guests
+    let domain_group = sid.starts_with("S-1-5-21-") && (sid.ends_with("-513") || sid.ends_with("-514"));
+    domain_group || LOW_PRIVILEGE_SIDS.contains(&sid)
+}
@@ -0,0 +1,318 @@
+// src/registry/shimcache.rs
+use anyhow::{Result, bail, Context};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use super::RegistryHive;
+use crate::utils::bytes::{u32_at, u64_at, utf16z};
+use crate::utils::time::filetime_to_datetime;
+
+/// Value locations under the current control set, newest first
+const SHIMCACHE_PATHS: &[&str] = &[
+    "Control\\Session Manager\\AppCompatCache",
+    "Control\\Session Manager\\AppCompatibility",
+];
+const SHIMCACHE_VALUE: &str = "AppCompatCache";
+
+const XP_MAGIC: u32 = 0xDEAD_BEEF;
+/// Shared by Server 2003 and Vista/2008
+const NT5_2_MAGIC: u32 = 0xBADC_0FFE;
+const WIN7_MAGIC: u32 = 0xBADC_0FEE;
+const WIN8_SIGNATURE: &[u8] = b"00ts";
+const WIN81_SIGNATURE: &[u8] = b"10ts";
+
+const XP_HEADER_SIZE: usize = 400;
+const XP_ENTRY_SIZE: usize = 552;
+const NT5_2_HEADER_SIZE: usize = 8;
+const WIN7_HEADER_SIZE: usize = 128;
+const WIN8_HEADER_SIZE: usize = 128;
+
+/// Set in the insert flags when the CSRSS marked the file as executed
+const INSERT_FLAG_EXECUTED: u32 = 0x2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShimCacheFormat {
+    WindowsXp,
+    Server2003,
+    Vista,
+    Windows7,
+    Windows8,
+    Windows81,
+    Windows10,
+}
+
+/// Decoded AppCompatCache value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShimCache {
+    pub format: ShimCacheFormat,
+    pub is_64bit: bool,
+    pub entries: Vec<ShimCacheEntry>,
+}
+
+/// A file the shim engine examined; on most versions this is evidence of
+/// presence rather than execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShimCacheEntry {
+    /// Position in the cache, 0 being the most recently inserted
+    pub position: usize,
+    pub path: String,
+    /// The file's own last modification time
+    pub last_modified: Option<DateTime<Utc>>,
+    /// When the entry was last updated (XP only)
+    pub last_update: Option<DateTime<Utc>>,
+    /// XP and Server 2003 only
+    pub file_size: Option<u64>,
+    pub insert_flags: Option<u32>,
+    pub shim_flags: Option<u32>,
+    /// Whether the insert flags mark the file as executed, where present
+    pub executed: Option<bool>,
+    /// Packaged app name for Windows 8.1 Store apps
+    pub package: Option<String>,
+}
+
+/// Decode the ShimCache of the current control set in a SYSTEM hive
+pub fn parse_shimcache(hive: &RegistryHive) -> Result<ShimCache> {
+    let control_set = hive.current_control_set()?;
+
+    for path in SHIMCACHE_PATHS {
+        let Ok(key) = hive.get_key(&format!("{}\\{}", control_set, path)) else {
+            continue;
+        };
+        let Ok(value) = key.get_value(SHIMCACHE_VALUE) else {
+            continue;
+        };
+        let data = value.as_bytes().context("AppCompatCache value is not binary")?;
+        return decode_shimcache(data);
+    }
+
+    bail!("No AppCompatCache value found")
+}
+
+/// Decode a raw AppCompatCache value, detecting its format
+pub fn decode_shimcache(data: &[u8]) -> Result<ShimCache> {
+    let header = u32_at(data, 0).context("AppCompatCache value too short")? as usize;
+
+    // Windows 10 starts with its header size, followed by 10ts entries
+    if matches!(header, 0x30 | 0x34) && data.get(header..header + 4) == Some(WIN81_SIGNATURE) {
+        return decode_win10(data, header);
+    }
+    match data.get(WIN8_HEADER_SIZE..WIN8_HEADER_SIZE + 4) {
+        Some(WIN8_SIGNATURE) => return decode_win8(data, ShimCacheFormat::Windows8),
+        Some(WIN81_SIGNATURE) => return decode_win8(data, ShimCacheFormat::Windows81),
+        _ => {}
+    }
+
+    match header as u32 {
+        XP_MAGIC => decode_xp(data),
+        NT5_2_MAGIC => decode_nt5_2(data),
+        WIN7_MAGIC => decode_win7(data),
+        magic => bail!("Unknown AppCompatCache format {:#010x}", magic),
+    }
+}
+
+fn decode_xp(data: &[u8]) -> Result<ShimCache> {
+    let count = u32_at(data, 8).context("Truncated XP header")? as usize;
+    let mut entries = Vec::new();
+
+    for position in 0..count {
+        let offset = XP_HEADER_SIZE + position * XP_ENTRY_SIZE;
+        let Some(entry) = data.get(offset..offset + XP_ENTRY_SIZE) else {
+            break;
+        };
+
+        entries.push(ShimCacheEntry {
+            last_modified: filetime_at(entry, 528),
+            file_size: u64_at(entry, 536),
+            last_update: filetime_at(entry, 544),
+            ..ShimCacheEntry::new(position, utf16z(&entry[..520]))
+        });
+    }
+
+    Ok(ShimCache { format: ShimCacheFormat::WindowsXp, is_64bit: false, entries })
+}
+
+/// Server 2003 and Vista share a magic; Vista stores small insert flags
+/// where 2003 stores the file size
+fn decode_nt5_2(data: &[u8]) -> Result<ShimCache> {
+    let count = u32_at(data, 4).context("Truncated header")? as usize;
+    let is_64bit = is_64bit_layout(data, NT5_2_HEADER_SIZE);
+    let entry_size = if is_64bit { 32 } else { 24 };
+    let fields = if is_64bit { 24 } else { 16 };
+
+    let records: Vec<&[u8]> = (0..count)
+        .map_while(|i| {
+            let offset = NT5_2_HEADER_SIZE + i * entry_size;
+            data.get(offset..offset + entry_size)
+        })
+        .collect();
+
+    let has_file_size = records.iter().any(|r| u32_at(r, fields).unwrap_or(0) > 3);
+    let format = if has_file_size { ShimCacheFormat::Server2003 } else { ShimCacheFormat::Vista };
+
+    let entries = records.iter().enumerate()
+        .map(|(position, record)| {
+            let mut entry = ShimCacheEntry::new(position, unicode_string_at(data, record, is_64bit));
+            entry.last_modified = filetime_at(record, fields - 8);
+            if has_file_size {
+                entry.file_size = u64_at(record, fields);
+            } else {
+                entry.set_insert_flags(u32_at(record, fields), u32_at(record, fields + 4));
+            }
+            entry
+        })
+        .collect();
+
+    Ok(ShimCache { format, is_64bit, entries })
+}
+
+fn decode_win7(data: &[u8]) -> Result<ShimCache> {
+    let count = u32_at(data, 4).context("Truncated header")? as usize;
+    let is_64bit = is_64bit_layout(data, WIN7_HEADER_SIZE);
+    let entry_size = if is_64bit { 48 } else { 32 };
+    let fields = if is_64bit { 24 } else { 16 };
+
+    let entries = (0..count)
+        .map_while(|i| {
+            let offset = WIN7_HEADER_SIZE + i * entry_size;
+            data.get(offset..offset + entry_size)
+        })
+        .enumerate()
+        .map(|(position, record)| {
+            let mut entry = ShimCacheEntry::new(position, unicode_string_at(data, record, is_64bit));
+            entry.last_modified = filetime_at(record, fields - 8);
+            entry.set_insert_flags(u32_at(record, fields), u32_at(record, fields + 4));
+            entry
+        })
+        .collect();
+
+    Ok(ShimCache { format: ShimCacheFormat::Windows7, is_64bit, entries })
+}
+
+/// Windows 8 and 8.1 entries: signature, unknown, entry size, then the
+/// path, the 8.1 package name, flags, modification time and shim data
+fn decode_win8(data: &[u8], format: ShimCacheFormat) -> Result<ShimCache> {
+    let mut entries = Vec::new();
+    let mut offset = WIN8_HEADER_SIZE;
+
+    while let Some(body) = next_ts_entry(data, &mut offset) {
+        let mut reader = Reader { data: body, pos: 0 };
+        let Some(path) = reader.string() else {
+            break;
+        };
+        let mut entry = ShimCacheEntry::new(entries.len(), path);
+
+        if format == ShimCacheFormat::Windows81 {
+            entry.package = reader.string().filter(|p| !p.is_empty());
+        }
+        entry.set_insert_flags(reader.u32(), reader.u32());
+        entry.last_modified = reader.filetime();
+
+        entries.push(entry);
+    }
+
+    Ok(ShimCache { format, is_64bit: true, entries })
+}
+
+/// Windows 10 and 11 entries have a path, modification time and shim
+/// data, with no insert flags
+fn decode_win10(data: &[u8], header: usize) -> Result<ShimCache> {
+    let mut entries = Vec::new();
+    let mut offset = header;
+
+    while let Some(body) = next_ts_entry(data, &mut offset) {
+        let mut reader = Reader { data: body, pos: 0 };
+        let Some(path) = reader.string() else {
+            break;
+        };
+        let mut entry = ShimCacheEntry::new(entries.len(), path);
+        entry.last_modified = reader.filetime();
+
+        entries.push(entry);
+    }
+
+    Ok(ShimCache { format: ShimCacheFormat::Windows10, is_64bit: true, entries })
+}
+
+impl ShimCacheEntry {
+    fn new(position: usize, path: String) -> Self {
+        Self {
+            position,
+            path,
+            last_modified: None,
+            last_update: None,
+            file_size: None,
+            insert_flags: None,
+            shim_flags: None,
+            executed: None,
+            package: None,
+        }
+    }
+
+    fn set_insert_flags(&mut self, insert_flags: Option<u32>, shim_flags: Option<u32>) {
+        self.insert_flags = insert_flags;
+        self.shim_flags = shim_flags;
+        self.executed = insert_flags.map(|flags| flags & INSERT_FLAG_EXECUTED != 0);
+    }
+}
+
+/// Return the body of the `00ts`/`10ts` entry at `offset` and advance past it
+fn next_ts_entry<'a>(data: &'a [u8], offset: &mut usize) -> Option<&'a [u8]> {
+    let signature = data.get(*offset..*offset + 4)?;
+    if signature != WIN8_SIGNATURE && signature != WIN81_SIGNATURE {
+        return None;
+    }
+    let size = u32_at(data, *offset + 8)? as usize;
+    let body = data.get(*offset + 12..*offset + 12 + size)?;
+    *offset += 12 + size;
+    Some(body)
+}
+
+/// 64-bit records pad the 32-bit path offset slot with zeros
+fn is_64bit_layout(data: &[u8], first_entry: usize) -> bool {
+    u32_at(data, first_entry + 4) == Some(0)
+}
+
+/// Read the UNICODE_STRING path a fixed-size record points to
+fn unicode_string_at(data: &[u8], record: &[u8], is_64bit: bool) -> String {
+    let length = u16::from_le_bytes([record[0], record[1]]) as usize;
+    let offset = if is_64bit {
+        u64_at(record, 8).unwrap_or(0) as usize
+    } else {
+        u32_at(record, 4).unwrap_or(0) as usize
+    };
+
+    data.get(offset..offset.saturating_add(length))
+        .map(utf16z)
+        .unwrap_or_default()
+}
+
+/// Sequential reader over a Windows 8+ entry body
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    /// A u16 byte length followed by UTF-16 text
+    fn string(&mut self) -> Option<String> {
+        let length = u16::from_le_bytes(self.data.get(self.pos..self.pos + 2)?.try_into().ok()?) as usize;
+        let text = self.data.get(self.pos + 2..self.pos + 2 + length)?;
+        self.pos += 2 + length;
+        Some(utf16z(text))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let value = u32_at(self.data, self.pos)?;
+        self.pos += 4;
+        Some(value)
+    }
+
+    fn filetime(&mut self) -> Option<DateTime<Utc>> {
+        let value = filetime_at(self.data, self.pos);
+        self.pos += 8;
+        value
+    }
+}
+
+fn filetime_at(data: &[u8], offset: usize) -> Option<DateTime<Utc>> {
+    filetime_to_datetime(u64_at(data, offset)?)
+}
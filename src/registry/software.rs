@@ -0,0 +1,184 @@
+// src/registry/software.rs
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Serialize, Deserialize};
+use super::{RegistryKey, RegistryParser};
+
+const SOFTWARE: &str = "HKLM\\SOFTWARE";
+
+const UNINSTALL: &str = "Microsoft\\Windows\\CurrentVersion\\Uninstall";
+const UNINSTALL_WOW64: &str = "Wow6432Node\\Microsoft\\Windows\\CurrentVersion\\Uninstall";
+const USER_UNINSTALL: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall";
+const INSTALLER_USER_DATA: &str = "Microsoft\\Windows\\CurrentVersion\\Installer\\UserData";
+const APPX_ALL_USER_STORE: &str = "Microsoft\\Windows\\CurrentVersion\\Appx\\AppxAllUserStore\\Applications";
+/// Per-user package repository in UsrClass.dat
+const APPX_REPOSITORY: &str = "Local Settings\\Software\\Microsoft\\Windows\\CurrentVersion\\AppModel\\Repository\\Packages";
+
+/// Where an installed program was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoftwareSource {
+    /// `SOFTWARE\...\Uninstall`, the native view
+    Uninstall,
+    /// `SOFTWARE\Wow6432Node\...\Uninstall`, 32-bit programs on 64-bit Windows
+    UninstallWow64,
+    /// A user's `NTUSER.DAT\Software\...\Uninstall`
+    UserUninstall,
+    /// `Installer\UserData\<SID>\Products`, Windows Installer products
+    /// without an Uninstall entry
+    MsiProduct,
+    /// An AppX (Microsoft Store) package
+    Appx,
+}
+
+/// One entry of the installed software inventory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledProgram {
+    pub name: String,
+    pub version: Option<String>,
+    pub publisher: Option<String>,
+    pub install_date: Option<DateTime<Utc>>,
+    pub install_location: Option<String>,
+    pub uninstall_string: Option<String>,
+    pub source: SoftwareSource,
+    /// Profile or SID for per-user installs
+    pub user: Option<String>,
+    /// Uninstall subkey, MSI product code or AppX package full name
+    pub id: String,
+    pub last_write: Option<DateTime<Utc>>,
+}
+
+/// Build the installed software inventory from SOFTWARE and the user hives
+pub fn collect_installed_software(registry: &RegistryParser) -> Vec<InstalledProgram> {
+    let mut programs = Vec::new();
+
+    for (path, source) in [(UNINSTALL, SoftwareSource::Uninstall), (UNINSTALL_WOW64, SoftwareSource::UninstallWow64)] {
+        for key in registry.enumerate_subkeys(SOFTWARE, path).unwrap_or_default() {
+            programs.extend(uninstall_entry(&key, source, None));
+        }
+    }
+
+    for (user, hive) in registry.user_hives() {
+        let keys = hive.get_key(USER_UNINSTALL).and_then(|key| key.enumerate_subkeys()).unwrap_or_default();
+        for key in keys {
+            programs.extend(uninstall_entry(&key, SoftwareSource::UserUninstall, Some(user)));
+        }
+    }
+
+    msi_products(registry, &mut programs);
+
+    for key in registry.enumerate_subkeys(SOFTWARE, APPX_ALL_USER_STORE).unwrap_or_default() {
+        programs.extend(appx_package(&key, None));
+    }
+    for (user, hive) in registry.user_class_hives() {
+        let keys = hive.get_key(APPX_REPOSITORY).and_then(|key| key.enumerate_subkeys()).unwrap_or_default();
+        for key in keys {
+            programs.extend(appx_package(&key, Some(user)));
+        }
+    }
+
+    programs
+}
+
+/// Entries without a display name are patches and components that
+/// Programs and Features does not list either
+fn uninstall_entry(key: &RegistryKey, source: SoftwareSource, user: Option<&str>) -> Option<InstalledProgram> {
+    Some(InstalledProgram {
+        name: key.string_value("DisplayName")?,
+        version: key.string_value("DisplayVersion"),
+        publisher: key.string_value("Publisher"),
+        install_date: key.string_value("InstallDate").and_then(|s| parse_install_date(&s)),
+        install_location: key.string_value("InstallLocation"),
+        uninstall_string: key.string_value("UninstallString"),
+        source,
+        user: user.map(str::to_string),
+        id: key.name().to_string(),
+        last_write: key.last_write_time(),
+    })
+}
+
+/// Windows Installer products, skipping those already listed under an
+/// Uninstall key by product code
+fn msi_products(registry: &RegistryParser, programs: &mut Vec<InstalledProgram>) {
+    for sid in registry.enumerate_subkeys(SOFTWARE, INSTALLER_USER_DATA).unwrap_or_default() {
+        let products = sid.get_subkey("Products").and_then(|key| key.enumerate_subkeys()).unwrap_or_default();
+
+        for product in products {
+            let Some(product_code) = unpack_guid(product.name()) else {
+                continue;
+            };
+            if programs.iter().any(|p| p.id.eq_ignore_ascii_case(&product_code)) {
+                continue;
+            }
+            let Ok(properties) = product.get_subkey("InstallProperties") else {
+                continue;
+            };
+
+            if let Some(mut program) = uninstall_entry(&properties, SoftwareSource::MsiProduct, None) {
+                program.id = product_code;
+                // S-1-5-18 holds per-machine installs
+                program.user = Some(sid.name().to_string()).filter(|s| s != "S-1-5-18");
+                programs.push(program);
+            }
+        }
+    }
+}
+
+/// Package full names look like `Microsoft.WindowsCalculator_10.2103.8.0_x64__8wekyb3d8bbwe`
+fn appx_package(key: &RegistryKey, user: Option<&str>) -> Option<InstalledProgram> {
+    let full_name = key.name();
+    let mut parts = full_name.split('_');
+    let name = parts.next().filter(|n| !n.is_empty())?;
+    let version = parts.next().map(str::to_string);
+
+    // DisplayName is often an `@{...}` resource reference, which is not
+    // readable offline
+    let display_name = key.string_value("DisplayName").filter(|n| !n.starts_with("@{") && !n.starts_with("ms-resource:"));
+
+    Some(InstalledProgram {
+        name: display_name.unwrap_or_else(|| name.to_string()),
+        version,
+        publisher: key.string_value("Publisher"),
+        install_date: None,
+        install_location: key.string_value("PackageRootFolder").or_else(|| key.string_value("Path")),
+        uninstall_string: None,
+        source: SoftwareSource::Appx,
+        user: user.map(str::to_string),
+        id: full_name.to_string(),
+        last_write: key.last_write_time(),
+    })
+}
+
+/// Windows Installer packs a product code by reversing the first three
+/// groups and swapping the nibbles of each remaining byte, so
+/// `00006109C80000000000000000F01FEC` is `{90160000-008C-0000-0000-0000000FF1CE}`
+fn unpack_guid(packed: &str) -> Option<String> {
+    if packed.len() != 32 || !packed.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let reversed = |range: std::ops::Range<usize>| packed[range].chars().rev().collect::<String>();
+    let swapped = |range: std::ops::Range<usize>| {
+        packed[range].as_bytes()
+            .chunks(2)
+            .map(|pair| format!("{}{}", pair[1] as char, pair[0] as char))
+            .collect::<String>()
+    };
+
+    Some(format!(
+        "{{{}-{}-{}-{}-{}}}",
+        reversed(0..8),
+        reversed(8..12),
+        reversed(12..16),
+        swapped(16..20),
+        swapped(20..32),
+    ).to_uppercase())
+}
+
+/// `InstallDate` is usually `YYYYMMDD`, occasionally `MM/DD/YYYY`
+fn parse_install_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%m/%d/%Y"))
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|t| t.and_utc())
+}
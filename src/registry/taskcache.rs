@@ -0,0 +1,100 @@
+// src/registry/taskcache.rs
+use std::collections::HashSet;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use super::{RegistryHive, RegistryKey};
+use super::hive::MAX_KEY_DEPTH;
+use crate::parsers::{parse_task_actions, ScheduledTaskAction};
+use crate::utils::time::filetime_to_datetime;
+
+const TASK_CACHE: &str = "Microsoft\\Windows NT\\CurrentVersion\\Schedule\\TaskCache";
+
+/// A task registered with the scheduler, from `TaskCache\Tree` joined with
+/// its `TaskCache\Tasks\{GUID}` entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskCacheEntry {
+    /// Task path, e.g. `\Microsoft\Windows\Defrag\ScheduledDefrag`
+    pub path: String,
+    /// GUID of the `Tasks` entry
+    pub id: String,
+    /// Zero hides the task from enumeration
+    pub index: Option<u64>,
+    /// The `SD` value is missing, which also hides the task from
+    /// `schtasks` and the Task Scheduler UI
+    pub sd_removed: bool,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub created: Option<DateTime<Utc>>,
+    pub last_run: Option<DateTime<Utc>>,
+    pub last_successful_run: Option<DateTime<Utc>>,
+    /// HRESULT or exit code of the last run
+    pub last_result: Option<u32>,
+    pub actions: Vec<ScheduledTaskAction>,
+    pub last_write: Option<DateTime<Utc>>,
+}
+
+/// Parse the scheduled task cache in a SOFTWARE hive
+pub fn parse_task_cache(hive: &RegistryHive) -> Vec<TaskCacheEntry> {
+    let Ok(tree) = hive.get_key(&format!("{}\\Tree", TASK_CACHE)) else {
+        return Vec::new();
+    };
+    let tasks = hive.get_key(&format!("{}\\Tasks", TASK_CACHE)).ok();
+
+    let mut entries = Vec::new();
+    walk_tree(&tree, "", tasks.as_ref(), 0, &mut HashSet::new(), &mut entries);
+    entries
+}
+
+/// Folders in the tree have no `Id`; tasks do
+fn walk_tree(
+    key: &RegistryKey,
+    path: &str,
+    tasks: Option<&RegistryKey>,
+    depth: usize,
+    visited: &mut HashSet<u32>,
+    entries: &mut Vec<TaskCacheEntry>,
+) {
+    if depth > MAX_KEY_DEPTH || !visited.insert(key.offset()) {
+        log::debug!("Skipping {}: subkey loop or nesting too deep", path);
+        return;
+    }
+
+    for subkey in key.enumerate_subkeys().unwrap_or_default() {
+        let path = format!("{}\\{}", path, subkey.name());
+        match subkey.string_value("Id") {
+            Some(id) => entries.push(entry(&subkey, path, id, tasks)),
+            None => walk_tree(&subkey, &path, tasks, depth + 1, visited, entries),
+        }
+    }
+}
+
+fn entry(key: &RegistryKey, path: String, id: String, tasks: Option<&RegistryKey>) -> TaskCacheEntry {
+    let task = tasks.and_then(|tasks| tasks.get_subkey(&id).ok());
+    let dynamic_info = task.as_ref()
+        .and_then(|task| task.get_value("DynamicInfo").ok())
+        .and_then(|v| v.as_bytes().map(<[u8]>::to_vec))
+        .unwrap_or_default();
+    // Version, created, last run, state, last result, then (Windows 10)
+    // last successful run
+    let filetime = |offset: usize| dynamic_info.get(offset..offset + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .and_then(filetime_to_datetime);
+
+    TaskCacheEntry {
+        path,
+        id,
+        index: key.integer_value("Index"),
+        sd_removed: key.get_value("SD").is_err(),
+        author: task.as_ref().and_then(|task| task.string_value("Author")),
+        description: task.as_ref().and_then(|task| task.string_value("Description")),
+        created: filetime(4),
+        last_run: filetime(12),
+        last_successful_run: filetime(28),
+        last_result: dynamic_info.get(24..28).map(|b| u32::from_le_bytes(b.try_into().unwrap())),
+        actions: task.as_ref()
+            .and_then(|task| task.get_value("Actions").ok())
+            .and_then(|v| v.as_bytes().map(parse_task_actions))
+            .unwrap_or_default(),
+        last_write: key.last_write_time(),
+    }
+}
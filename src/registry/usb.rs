@@ -0,0 +1,281 @@
+// src/registry/usb.rs
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use super::{RegistryKey, RegistryParser};
+use crate::parsers::SetupApiInstall;
+use crate::utils::bytes::utf16;
+
+const SYSTEM: &str = "HKLM\\SYSTEM";
+const SOFTWARE: &str = "HKLM\\SOFTWARE";
+const WPD_DEVICES: &str = "Microsoft\\Windows Portable Devices\\Devices";
+const EMDMGMT: &str = "Microsoft\\Windows NT\\CurrentVersion\\EMDMgmt";
+const MOUNT_POINTS2: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\MountPoints2";
+
+/// Device property set holding install, arrival and removal times
+const DEVICE_PROPERTIES: &str = "Properties\\{83da6326-97a6-4088-9453-a1923f573b29}";
+const PROPERTY_FIRST_INSTALL: &str = "0064";
+const PROPERTY_INSTALL_DATE: &str = "0065";
+const PROPERTY_LAST_ARRIVAL: &str = "0066";
+const PROPERTY_LAST_REMOVAL: &str = "0067";
+
+/// A USB mass storage device, correlated across every source that mentions it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsbDevice {
+    pub vendor: Option<String>,
+    pub product: Option<String>,
+    pub revision: Option<String>,
+    /// USBSTOR instance ID without the trailing `&0` LUN suffix
+    pub serial: String,
+    /// False when Windows generated the ID because the device has no serial
+    pub serial_is_unique: bool,
+    pub friendly_name: Option<String>,
+    pub vid: Option<String>,
+    pub pid: Option<String>,
+    pub volume_guids: Vec<String>,
+    pub drive_letter: Option<String>,
+    /// Volume label from Windows Portable Devices or EMDMgmt
+    pub volume_name: Option<String>,
+    /// Volume serial number from EMDMgmt, e.g. `1234-ABCD`
+    pub volume_serial: Option<String>,
+    pub users: Vec<UsbUserMount>,
+    pub first_install: Option<DateTime<Utc>>,
+    pub install_date: Option<DateTime<Utc>>,
+    pub last_arrival: Option<DateTime<Utc>>,
+    pub last_removal: Option<DateTime<Utc>>,
+    pub setupapi_install: Option<DateTime<Utc>>,
+    /// Earliest of the install times
+    pub first_connected: Option<DateTime<Utc>>,
+    /// Last arrival, or the instance key's last write before Windows 8
+    pub last_connected: Option<DateTime<Utc>>,
+}
+
+/// A user whose MountPoints2 key references one of the device's volumes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsbUserMount {
+    pub user: String,
+    pub volume_guid: String,
+    /// Last write of the user's MountPoints2 subkey for the volume
+    pub last_mounted: Option<DateTime<Utc>>,
+}
+
+/// Reconstruct USB storage history from the loaded hives and the device
+/// installs parsed from setupapi.dev.log
+pub fn reconstruct_usb_history(registry: &RegistryParser, setupapi: &[SetupApiInstall]) -> Vec<UsbDevice> {
+    let Some(control_set) = registry.hive(SYSTEM).and_then(|h| h.current_control_set().ok()) else {
+        return Vec::new();
+    };
+
+    let usb_ids = usb_vid_pids(registry, &control_set);
+    let mounted = mounted_devices(registry);
+    let wpd = registry.enumerate_subkeys(SOFTWARE, WPD_DEVICES).unwrap_or_default();
+    let emd = registry.enumerate_subkeys(SOFTWARE, EMDMGMT).unwrap_or_default();
+    let mounts = user_mount_points(registry);
+
+    let mut devices = Vec::new();
+    let usbstor = format!("{}\\Enum\\USBSTOR", control_set);
+
+    for class in registry.enumerate_subkeys(SYSTEM, &usbstor).unwrap_or_default() {
+        let (vendor, product, revision) = parse_class_name(class.name());
+
+        for instance in class.enumerate_subkeys().unwrap_or_default() {
+            let instance_id = instance.name().to_string();
+            let serial = strip_lun(&instance_id).to_string();
+            // Parent ID prefixes look like `7&2A6B1F3D&0`
+            let serial_is_unique = instance_id.chars().nth(1) != Some('&');
+            let needle = format!("#{}#", instance_id.to_lowercase());
+
+            let mut device = UsbDevice {
+                vendor: vendor.clone(),
+                product: product.clone(),
+                revision: revision.clone(),
+                serial: serial.clone(),
+                serial_is_unique,
+                friendly_name: instance.string_value("FriendlyName"),
+                vid: None,
+                pid: None,
+                volume_guids: Vec::new(),
+                drive_letter: None,
+                volume_name: None,
+                volume_serial: None,
+                users: Vec::new(),
+                first_install: device_property(&instance, PROPERTY_FIRST_INSTALL),
+                install_date: device_property(&instance, PROPERTY_INSTALL_DATE),
+                last_arrival: device_property(&instance, PROPERTY_LAST_ARRIVAL),
+                last_removal: device_property(&instance, PROPERTY_LAST_REMOVAL),
+                setupapi_install: None,
+                first_connected: None,
+                last_connected: None,
+            };
+
+            if let Some((vid, pid)) = usb_ids.get(&serial.to_lowercase()) {
+                device.vid = Some(vid.clone());
+                device.pid = Some(pid.clone());
+            }
+
+            // MountedDevices maps drive letters and volume GUIDs to the
+            // device's symbolic link, which embeds the instance ID
+            for (name, link) in &mounted {
+                if !link.contains(&needle) {
+                    continue;
+                }
+                if let Some(letter) = name.strip_prefix("\\DosDevices\\") {
+                    device.drive_letter = Some(letter.to_string());
+                } else if let Some(guid) = name.strip_prefix("\\??\\Volume") {
+                    device.volume_guids.push(guid.to_string());
+                }
+            }
+
+            for key in &wpd {
+                if key.name().to_lowercase().contains(&needle) {
+                    device.volume_name = key.string_value("FriendlyName").or(device.volume_name);
+                }
+            }
+
+            for key in &emd {
+                if key.name().to_lowercase().contains(&needle)
+                    && let Some((label, volume_serial)) = parse_emdmgmt_name(key.name())
+                {
+                    device.volume_serial = Some(volume_serial);
+                    if !label.is_empty() && device.volume_name.is_none() {
+                        device.volume_name = Some(label);
+                    }
+                }
+            }
+
+            for guid in &device.volume_guids {
+                if let Some(users) = mounts.get(&guid.to_lowercase()) {
+                    device.users.extend(users.iter().cloned());
+                }
+            }
+
+            let device_id = format!("usbstor\\{}\\{}", class.name(), instance_id).to_lowercase();
+            device.setupapi_install = setupapi.iter()
+                .filter(|install| install.device_id.to_lowercase() == device_id)
+                .map(|install| install.timestamp)
+                .min();
+
+            device.first_connected = [device.first_install, device.install_date, device.setupapi_install]
+                .into_iter()
+                .flatten()
+                .min();
+            device.last_connected = device.last_arrival.or_else(|| instance.last_write_time());
+
+            devices.push(device);
+        }
+    }
+
+    devices
+}
+
+/// Map lowercase serial numbers to the VID and PID of their `Enum\USB` entry
+fn usb_vid_pids(registry: &RegistryParser, control_set: &str) -> HashMap<String, (String, String)> {
+    let mut ids = HashMap::new();
+    let usb = format!("{}\\Enum\\USB", control_set);
+
+    for device in registry.enumerate_subkeys(SYSTEM, &usb).unwrap_or_default() {
+        // VID_0781&PID_5567
+        let upper = device.name().to_uppercase();
+        let Some((vid, pid)) = upper.split_once('&') else {
+            continue;
+        };
+        let (Some(vid), Some(pid)) = (vid.strip_prefix("VID_"), pid.strip_prefix("PID_")) else {
+            continue;
+        };
+
+        for instance in device.enumerate_subkeys().unwrap_or_default() {
+            ids.insert(instance.name().to_lowercase(), (vid.to_string(), pid.to_string()));
+        }
+    }
+
+    ids
+}
+
+/// MountedDevices values whose data is a device path, lowercased
+fn mounted_devices(registry: &RegistryParser) -> Vec<(String, String)> {
+    let Ok(key) = registry.get_key(SYSTEM, "MountedDevices") else {
+        return Vec::new();
+    };
+
+    key.values().unwrap_or_default()
+        .into_iter()
+        .filter_map(|(name, value)| {
+            // MBR disks store a 12-byte signature and offset instead
+            let data = value.as_bytes().filter(|d| d.len() > 12)?;
+            Some((name, utf16(data).to_lowercase()))
+        })
+        .collect()
+}
+
+/// Map lowercase volume GUIDs to the users whose MountPoints2 lists them
+fn user_mount_points(registry: &RegistryParser) -> HashMap<String, Vec<UsbUserMount>> {
+    let mut mounts: HashMap<String, Vec<UsbUserMount>> = HashMap::new();
+
+    for (user, hive) in registry.user_hives() {
+        let Ok(points) = hive.get_key(MOUNT_POINTS2).and_then(|k| k.enumerate_subkeys()) else {
+            continue;
+        };
+        for point in points {
+            if point.name().starts_with('{') {
+                mounts.entry(point.name().to_lowercase())
+                    .or_default()
+                    .push(UsbUserMount {
+                        user: user.to_string(),
+                        volume_guid: point.name().to_string(),
+                        last_mounted: point.last_write_time(),
+                    });
+            }
+        }
+    }
+
+    mounts
+}
+
+/// Split `Disk&Ven_SanDisk&Prod_Cruzer_Blade&Rev_1.00` into its parts
+fn parse_class_name(name: &str) -> (Option<String>, Option<String>, Option<String>) {
+    let mut vendor = None;
+    let mut product = None;
+    let mut revision = None;
+
+    for part in name.split('&') {
+        if let Some(v) = part.strip_prefix("Ven_") {
+            vendor = Some(v.replace('_', " ").trim().to_string()).filter(|s| !s.is_empty());
+        } else if let Some(p) = part.strip_prefix("Prod_") {
+            product = Some(p.replace('_', " ").trim().to_string()).filter(|s| !s.is_empty());
+        } else if let Some(r) = part.strip_prefix("Rev_") {
+            revision = Some(r.to_string()).filter(|s| !s.is_empty());
+        }
+    }
+
+    (vendor, product, revision)
+}
+
+/// Drop the `&0` logical unit suffix from an instance ID
+fn strip_lun(instance_id: &str) -> &str {
+    match instance_id.rsplit_once('&') {
+        Some((serial, lun)) if lun.chars().all(|c| c.is_ascii_digit()) => serial,
+        _ => instance_id,
+    }
+}
+
+/// EMDMgmt key names end with `}<label>_<decimal volume serial>`
+fn parse_emdmgmt_name(name: &str) -> Option<(String, String)> {
+    let tail = &name[name.rfind('}')? + 1..];
+    let (label, serial) = tail.rsplit_once('_')?;
+    let serial: u32 = serial.parse().ok()?;
+    Some((label.to_string(), format!("{:04X}-{:04X}", serial >> 16, serial & 0xFFFF)))
+}
+
+/// Read a FILETIME device property. Windows 8 and later store it as the
+/// default value of `Properties\{guid}\0064`; Windows 7 as the `Data`
+/// value of `Properties\{guid}\00000064\00000000`.
+fn device_property(instance: &RegistryKey, id: &str) -> Option<DateTime<Utc>> {
+    let properties = instance.get_subkey(DEVICE_PROPERTIES).ok()?;
+
+    if let Ok(key) = properties.get_subkey(id) {
+        return key.get_value("").ok()?.as_filetime();
+    }
+    properties.get_subkey(&format!("0000{}\\00000000", id)).ok()?
+        .get_value("Data").ok()?
+        .as_filetime()
+}
@@ -1,28 +1,275 @@
 // src/registry/value.rs
-#[derive(Debug, Clone)]
+use std::fmt;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use crate::utils::guid::format_guid;
+use crate::utils::sid::format_sid;
+use crate::utils::time::{filetime_to_datetime, systemtime_to_datetime};
+
+pub const REG_NONE: u32 = 0;
+pub const REG_SZ: u32 = 1;
+pub const REG_EXPAND_SZ: u32 = 2;
+pub const REG_BINARY: u32 = 3;
+pub const REG_DWORD: u32 = 4;
+pub const REG_DWORD_BIG_ENDIAN: u32 = 5;
+pub const REG_LINK: u32 = 6;
+pub const REG_MULTI_SZ: u32 = 7;
+pub const REG_RESOURCE_LIST: u32 = 8;
+pub const REG_FULL_RESOURCE_DESCRIPTOR: u32 = 9;
+pub const REG_RESOURCE_REQUIREMENTS_LIST: u32 = 10;
+pub const REG_QWORD: u32 = 11;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "data")]
 pub enum RegistryValue {
     String(String),
     ExpandString(String),
     Binary(Vec<u8>),
     Dword(u32),
+    DwordBigEndian(u32),
+    Link(String),
     Qword(u64),
     MultiString(Vec<String>),
+    ResourceList(Vec<u8>),
+    FullResourceDescriptor(Vec<u8>),
+    ResourceRequirementsList(Vec<u8>),
     None,
+    /// Data of an unknown type, or data that does not decode as its declared
+    /// type (odd-length or unpaired UTF-16, short DWORDs, REG_NONE with a payload).
+    /// The raw bytes are kept untouched alongside the original type code.
+    Raw { value_type: u32, data: Vec<u8> },
 }
 
 impl RegistryValue {
+    /// Decode raw value data according to its registry type code
+    pub fn from_raw(value_type: u32, data: &[u8]) -> Self {
+        let raw = || RegistryValue::Raw { value_type, data: data.to_vec() };
+
+        match value_type {
+            REG_NONE if data.is_empty() => RegistryValue::None,
+            REG_SZ => decode_utf16(data).map(RegistryValue::String).unwrap_or_else(raw),
+            REG_EXPAND_SZ => decode_utf16(data).map(RegistryValue::ExpandString).unwrap_or_else(raw),
+            REG_LINK => decode_utf16(data).map(RegistryValue::Link).unwrap_or_else(raw),
+            REG_MULTI_SZ => decode_multi_utf16(data).map(RegistryValue::MultiString).unwrap_or_else(raw),
+            REG_BINARY => RegistryValue::Binary(data.to_vec()),
+            REG_DWORD => match <[u8; 4]>::try_from(data) {
+                Ok(bytes) => RegistryValue::Dword(u32::from_le_bytes(bytes)),
+                Err(_) => raw(),
+            },
+            REG_DWORD_BIG_ENDIAN => match <[u8; 4]>::try_from(data) {
+                Ok(bytes) => RegistryValue::DwordBigEndian(u32::from_be_bytes(bytes)),
+                Err(_) => raw(),
+            },
+            REG_QWORD => match <[u8; 8]>::try_from(data) {
+                Ok(bytes) => RegistryValue::Qword(u64::from_le_bytes(bytes)),
+                Err(_) => raw(),
+            },
+            REG_RESOURCE_LIST => RegistryValue::ResourceList(data.to_vec()),
+            REG_FULL_RESOURCE_DESCRIPTOR => RegistryValue::FullResourceDescriptor(data.to_vec()),
+            REG_RESOURCE_REQUIREMENTS_LIST => RegistryValue::ResourceRequirementsList(data.to_vec()),
+            _ => raw(),
+        }
+    }
+
+    /// The numeric registry type code of this value
+    pub fn value_type(&self) -> u32 {
+        match self {
+            RegistryValue::String(_) => REG_SZ,
+            RegistryValue::ExpandString(_) => REG_EXPAND_SZ,
+            RegistryValue::Binary(_) => REG_BINARY,
+            RegistryValue::Dword(_) => REG_DWORD,
+            RegistryValue::DwordBigEndian(_) => REG_DWORD_BIG_ENDIAN,
+            RegistryValue::Link(_) => REG_LINK,
+            RegistryValue::Qword(_) => REG_QWORD,
+            RegistryValue::MultiString(_) => REG_MULTI_SZ,
+            RegistryValue::ResourceList(_) => REG_RESOURCE_LIST,
+            RegistryValue::FullResourceDescriptor(_) => REG_FULL_RESOURCE_DESCRIPTOR,
+            RegistryValue::ResourceRequirementsList(_) => REG_RESOURCE_REQUIREMENTS_LIST,
+            RegistryValue::None => REG_NONE,
+            RegistryValue::Raw { value_type, .. } => *value_type,
+        }
+    }
+
+    /// The symbolic name of the registry type, e.g. `REG_SZ`
+    pub fn type_name(&self) -> String {
+        match self.value_type() {
+            REG_NONE => "REG_NONE".to_string(),
+            REG_SZ => "REG_SZ".to_string(),
+            REG_EXPAND_SZ => "REG_EXPAND_SZ".to_string(),
+            REG_BINARY => "REG_BINARY".to_string(),
+            REG_DWORD => "REG_DWORD".to_string(),
+            REG_DWORD_BIG_ENDIAN => "REG_DWORD_BIG_ENDIAN".to_string(),
+            REG_LINK => "REG_LINK".to_string(),
+            REG_MULTI_SZ => "REG_MULTI_SZ".to_string(),
+            REG_RESOURCE_LIST => "REG_RESOURCE_LIST".to_string(),
+            REG_FULL_RESOURCE_DESCRIPTOR => "REG_FULL_RESOURCE_DESCRIPTOR".to_string(),
+            REG_RESOURCE_REQUIREMENTS_LIST => "REG_RESOURCE_REQUIREMENTS_LIST".to_string(),
+            REG_QWORD => "REG_QWORD".to_string(),
+            other => format!("REG_UNKNOWN({:#x})", other),
+        }
+    }
+
+    /// Re-encode the value as it would be stored in a hive
+    pub fn to_raw_bytes(&self) -> Vec<u8> {
+        match self {
+            RegistryValue::String(s) | RegistryValue::ExpandString(s) | RegistryValue::Link(s) => {
+                encode_utf16(s)
+            }
+            RegistryValue::MultiString(strings) => {
+                let mut data: Vec<u8> = strings.iter().flat_map(|s| encode_utf16(s)).collect();
+                data.extend_from_slice(&[0, 0]);
+                data
+            }
+            RegistryValue::Dword(v) => v.to_le_bytes().to_vec(),
+            RegistryValue::DwordBigEndian(v) => v.to_be_bytes().to_vec(),
+            RegistryValue::Qword(v) => v.to_le_bytes().to_vec(),
+            RegistryValue::None => Vec::new(),
+            _ => self.as_bytes().unwrap_or_default().to_vec(),
+        }
+    }
+
     pub fn as_string(&self) -> Option<&str> {
         match self {
-            RegistryValue::String(s) | RegistryValue::ExpandString(s) => Some(s),
+            RegistryValue::String(s) | RegistryValue::ExpandString(s) | RegistryValue::Link(s) => Some(s),
             _ => None,
         }
     }
 
+    /// Like `as_string`, but also decodes string-typed `Raw` data, replacing
+    /// invalid UTF-16 sequences
+    pub fn as_string_lossy(&self) -> Option<String> {
+        match self {
+            RegistryValue::Raw { value_type: REG_SZ | REG_EXPAND_SZ | REG_LINK, data } => {
+                let units: Vec<u16> = data
+                    .chunks_exact(2)
+                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                    .take_while(|&u| u != 0)
+                    .collect();
+                Some(String::from_utf16_lossy(&units))
+            }
+            _ => self.as_string().map(str::to_string),
+        }
+    }
+
     pub fn as_integer(&self) -> Option<u64> {
         match self {
-            RegistryValue::Dword(v) => Some(*v as u64),
+            RegistryValue::Dword(v) | RegistryValue::DwordBigEndian(v) => Some(*v as u64),
             RegistryValue::Qword(v) => Some(*v),
             _ => None,
         }
     }
-}
\ No newline at end of file
+
+    pub fn as_multi_string(&self) -> Option<&[String]> {
+        match self {
+            RegistryValue::MultiString(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Raw bytes for binary-like values
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            RegistryValue::Binary(b)
+            | RegistryValue::ResourceList(b)
+            | RegistryValue::FullResourceDescriptor(b)
+            | RegistryValue::ResourceRequirementsList(b)
+            | RegistryValue::Raw { data: b, .. } => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Interpret the value as a FILETIME, from a QWORD or an 8-byte blob
+    pub fn as_filetime(&self) -> Option<DateTime<Utc>> {
+        match self {
+            RegistryValue::Qword(v) => filetime_to_datetime(*v),
+            _ => {
+                let bytes = <[u8; 8]>::try_from(self.as_bytes()?).ok()?;
+                filetime_to_datetime(u64::from_le_bytes(bytes))
+            }
+        }
+    }
+
+    /// Interpret a 16-byte blob as a SYSTEMTIME structure
+    pub fn as_systemtime(&self) -> Option<DateTime<Utc>> {
+        let bytes = self.as_bytes().filter(|b| b.len() == 16)?;
+        systemtime_to_datetime(bytes)
+    }
+
+    /// Interpret a 16-byte blob as a GUID
+    pub fn as_guid(&self) -> Option<String> {
+        let bytes = self.as_bytes().filter(|b| b.len() == 16)?;
+        format_guid(bytes)
+    }
+
+    /// Interpret a blob as a binary SID
+    pub fn as_sid(&self) -> Option<String> {
+        format_sid(self.as_bytes()?)
+    }
+}
+
+impl fmt::Display for RegistryValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryValue::String(s) | RegistryValue::ExpandString(s) | RegistryValue::Link(s) => {
+                write!(f, "{}", s)
+            }
+            RegistryValue::Dword(v) | RegistryValue::DwordBigEndian(v) => write!(f, "{:#010x} ({})", v, v),
+            RegistryValue::Qword(v) => write!(f, "{:#018x} ({})", v, v),
+            RegistryValue::MultiString(strings) => write!(f, "{}", strings.join(", ")),
+            RegistryValue::None => Ok(()),
+            _ => {
+                let bytes = self.as_bytes().unwrap_or_default();
+                let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                write!(f, "{}", hex.join(" "))
+            }
+        }
+    }
+}
+
+fn encode_utf16(s: &str) -> Vec<u8> {
+    s.encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(|u| u.to_le_bytes())
+        .collect()
+}
+
+/// Decode UTF-16LE string data, stopping at the first NUL as reg.exe and
+/// regedit do. Anything hidden after it is only available from
+/// `RegistryKey::raw_value`. Returns `None` when the data is not valid UTF-16.
+fn decode_utf16(data: &[u8]) -> Option<String> {
+    let units = utf16_units(data)?;
+    let end = units.iter().position(|&u| u == 0).unwrap_or(units.len());
+    String::from_utf16(&units[..end]).ok()
+}
+
+/// Decode REG_MULTI_SZ data into its component strings
+fn decode_multi_utf16(data: &[u8]) -> Option<Vec<String>> {
+    let mut units = utf16_units(data)?;
+    while units.last() == Some(&0) {
+        units.pop();
+    }
+
+    if units.is_empty() {
+        return Some(Vec::new());
+    }
+
+    units
+        .split(|&u| u == 0)
+        .map(|s| String::from_utf16(s).ok())
+        .collect()
+}
+
+fn utf16_units(data: &[u8]) -> Option<Vec<u16>> {
+    // Writers frequently size strings as strlen + 1 bytes, leaving a stray NUL
+    let data = match data.len() % 2 {
+        0 => data,
+        _ if data.last() == Some(&0) => &data[..data.len() - 1],
+        _ => return None,
+    };
+
+    Some(
+        data.chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect(),
+    )
+}
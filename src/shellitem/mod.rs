@@ -0,0 +1,417 @@
+// src/shellitem/mod.rs
+//! Shell item (PIDL) decoding, shared by registry MRU lists, ShellBags and
+//! shortcut files.
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::utils::bytes::utf16_units;
+use crate::utils::guid::{format_guid, known_folder_name};
+use crate::utils::time::{dos_datetime_to_datetime, filetime_to_datetime};
+
+/// Signature of the file entry extension block carrying the long name
+const BEEF0004: u32 = 0xBEEF_0004;
+/// Extension block with FILETIME-precision creation, modification and
+/// access times
+const BEEF0026: u32 = 0xBEEF_0026;
+/// Control panel category items carry this signature at offset 4
+const CONTROL_PANEL_CATEGORY: u32 = 0x39DE_2184;
+/// Delegate items wrap a file entry behind this signature
+const DELEGATE_SIGNATURE: &[u8] = b"CFSF";
+/// Portable device (MTP) items
+const MTP_DEVICE: u32 = 0x1031_2005;
+const MTP_FILE_ENTRY: u32 = 0x1014_1981;
+
+const CONTROL_PANEL_CATEGORIES: &[&str] = &[
+    "All Control Panel Items",
+    "Appearance and Personalization",
+    "Hardware and Sound",
+    "Network and Internet",
+    "Sounds, Speech, and Audio Devices",
+    "System and Security",
+    "Clock, Language, and Region",
+    "Ease of Access",
+    "Programs",
+    "User Accounts",
+    "Security Center",
+    "Mobile PC",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ShellItem {
+    /// Root folder such as My Computer or a known folder (class 0x1F)
+    RootFolder {
+        guid: String,
+        name: Option<String>,
+    },
+    /// Drive or volume (class 0x20-0x2F)
+    Volume {
+        name: String,
+    },
+    /// File or directory (class 0x30-0x3F, or wrapped in a delegate item)
+    FileEntry(FileEntry),
+    /// Network location such as a domain, server or share (class 0x40-0x4F)
+    Network {
+        location: String,
+        description: Option<String>,
+        comments: Option<String>,
+    },
+    /// URI such as an FTP site (class 0x61)
+    Uri {
+        uri: String,
+        connected: Option<DateTime<Utc>>,
+    },
+    /// Control panel applet (class 0x71)
+    ControlPanel {
+        guid: String,
+        name: Option<String>,
+    },
+    /// Control panel category view
+    ControlPanelCategory {
+        id: u32,
+        name: Option<String>,
+    },
+    /// Portable device, storage or file reached over MTP
+    Mtp {
+        name: String,
+    },
+    /// Anything not decoded; kept raw for later analysis
+    Unknown {
+        class_type: u8,
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub is_directory: bool,
+    /// 8.3 name from the item itself
+    pub short_name: String,
+    /// Full name from the BEEF0004 extension block
+    pub long_name: Option<String>,
+    pub file_size: u32,
+    pub attributes: u16,
+    pub modified: Option<DateTime<Utc>>,
+    pub created: Option<DateTime<Utc>>,
+    pub accessed: Option<DateTime<Utc>>,
+    pub mft_entry: Option<u64>,
+    pub mft_sequence: Option<u16>,
+}
+
+impl ShellItem {
+    /// Display name of the item, as it would appear in a path
+    pub fn name(&self) -> String {
+        match self {
+            ShellItem::RootFolder { guid, name } => name.clone().unwrap_or_else(|| guid.clone()),
+            ShellItem::Volume { name } => name.trim_end_matches('\\').to_string(),
+            ShellItem::FileEntry(entry) => entry.long_name.clone().unwrap_or_else(|| entry.short_name.clone()),
+            ShellItem::Network { location, .. } => location.clone(),
+            ShellItem::Uri { uri, .. } => uri.clone(),
+            ShellItem::ControlPanel { guid, name } => name.clone().unwrap_or_else(|| guid.clone()),
+            ShellItem::ControlPanelCategory { id, name } => {
+                name.clone().unwrap_or_else(|| format!("Control Panel category {}", id))
+            }
+            ShellItem::Mtp { name } => name.clone(),
+            ShellItem::Unknown { class_type, .. } => format!("<unknown item {:#04x}>", class_type),
+        }
+    }
+
+    pub fn file_entry(&self) -> Option<&FileEntry> {
+        match self {
+            ShellItem::FileEntry(entry) => Some(entry),
+            _ => None,
+        }
+    }
+}
+
+/// Parse an ID list: a sequence of size-prefixed items ending with a zero size
+pub fn parse_id_list(data: &[u8]) -> Vec<ShellItem> {
+    let mut items = Vec::new();
+    let mut pos = 0;
+
+    while let Some(size) = data.get(pos..pos + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize) {
+        if size < 3 || pos + size > data.len() {
+            break;
+        }
+        items.push(parse_item(&data[pos..pos + size]));
+        pos += size;
+    }
+
+    items
+}
+
+/// Parse a single item, including its two-byte size prefix
+pub fn parse_item(item: &[u8]) -> ShellItem {
+    let class_type = item.get(2).copied().unwrap_or(0);
+
+    // Some items are identified by a signature rather than their class
+    let signature = |offset: usize| {
+        item.get(offset..offset + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+    };
+    if class_type == 0x01
+        && signature(4) == Some(CONTROL_PANEL_CATEGORY)
+        && let Some(parsed) = parse_control_panel_category(item)
+    {
+        return parsed;
+    }
+    if item.get(6..10) == Some(DELEGATE_SIGNATURE)
+        && let Some(entry) = parse_delegate(item)
+    {
+        return ShellItem::FileEntry(entry);
+    }
+    if matches!(signature(6), Some(MTP_DEVICE | MTP_FILE_ENTRY))
+        && let Some(parsed) = parse_mtp(item)
+    {
+        return parsed;
+    }
+
+    let parsed = match class_type {
+        0x1F => parse_root_folder(item),
+        0x20..=0x2F => parse_volume(item),
+        0x30..=0x3F => parse_file_entry(item, class_type).map(ShellItem::FileEntry),
+        0x40..=0x4F => parse_network(item),
+        0x61 => parse_uri(item),
+        0x71 => parse_control_panel(item),
+        _ => None,
+    };
+
+    parsed.unwrap_or_else(|| ShellItem::Unknown {
+        class_type,
+        data: item.to_vec(),
+    })
+}
+
+/// Join item names into a path, e.g. `My Computer\C:\Users\alice`
+pub fn id_list_path(items: &[ShellItem]) -> String {
+    items.iter()
+        .map(ShellItem::name)
+        .collect::<Vec<_>>()
+        .join("\\")
+}
+
+fn parse_root_folder(item: &[u8]) -> Option<ShellItem> {
+    let guid = format_guid(item.get(4..20)?)?;
+    let name = known_folder_name(&guid).map(str::to_string);
+    Some(ShellItem::RootFolder { guid, name })
+}
+
+fn parse_volume(item: &[u8]) -> Option<ShellItem> {
+    let name = read_ascii(item.get(3..)?);
+    if name.len() >= 2 && name.as_bytes()[1] == b':' {
+        return Some(ShellItem::Volume { name });
+    }
+
+    // Volume-class items without a drive letter identify a shell folder by GUID
+    let guid = format_guid(item.get(4..20)?)?;
+    let name = known_folder_name(&guid).map(str::to_string);
+    Some(ShellItem::RootFolder { guid, name })
+}
+
+fn parse_file_entry(item: &[u8], class_type: u8) -> Option<FileEntry> {
+    if item.len() < 14 {
+        return None;
+    }
+
+    let unicode = class_type & 0x04 != 0;
+    let word = |offset: usize| u16::from_le_bytes([item[offset], item[offset + 1]]);
+
+    let mut entry = FileEntry {
+        is_directory: class_type & 0x01 != 0,
+        short_name: String::new(),
+        long_name: None,
+        file_size: u32::from_le_bytes(item[4..8].try_into().ok()?),
+        attributes: word(12),
+        modified: dos_datetime_to_datetime(word(8), word(10)),
+        created: None,
+        accessed: None,
+        mft_entry: None,
+        mft_sequence: None,
+    };
+
+    let (short_name, name_end) = if unicode {
+        read_utf16(&item[14..])
+    } else {
+        let name = read_ascii(&item[14..]);
+        let length = name.len() + 1;
+        (name, length)
+    };
+    entry.short_name = short_name;
+
+    // Extension blocks start on a two-byte boundary after the name
+    let mut pos = 14 + name_end;
+    pos += pos % 2;
+
+    while let Some(block) = next_extension_block(item, pos) {
+        parse_extension_block(block, &mut entry);
+        pos += block.len();
+    }
+
+    Some(entry)
+}
+
+/// Delegate items (class 0x74) embed a file entry after the `CFSF`
+/// signature, followed by two GUIDs and the usual extension blocks
+fn parse_delegate(item: &[u8]) -> Option<FileEntry> {
+    let inner_size = u16::from_le_bytes(item.get(10..12)?.try_into().ok()?) as usize;
+    let inner = item.get(10..10 + inner_size)?;
+    let mut entry = parse_file_entry(inner, inner.get(2).copied()? | 0x30)?;
+
+    // The delegate and item class GUIDs sit between the inner item and
+    // its extension blocks
+    let mut pos = 10 + inner_size + 2 + 32;
+    while let Some(block) = next_extension_block(item, pos) {
+        parse_extension_block(block, &mut entry);
+        pos += block.len();
+    }
+
+    Some(entry)
+}
+
+fn parse_extension_block(block: &[u8], entry: &mut FileEntry) {
+    match u32::from_le_bytes(block[4..8].try_into().unwrap()) {
+        BEEF0004 => parse_beef0004(block, entry),
+        BEEF0026 => parse_beef0026(block, entry),
+        _ => {}
+    }
+}
+
+/// BEEF0026 repeats the timestamps at full FILETIME precision
+fn parse_beef0026(block: &[u8], entry: &mut FileEntry) {
+    let filetime = |offset: usize| {
+        block.get(offset..offset + 8)
+            .and_then(|b| filetime_to_datetime(u64::from_le_bytes(b.try_into().unwrap())))
+    };
+
+    entry.created = filetime(12).or(entry.created);
+    entry.modified = filetime(20).or(entry.modified);
+    entry.accessed = filetime(28).or(entry.accessed);
+}
+
+/// Flags at offset 4 say whether a description and comments follow the
+/// ASCII location
+fn parse_network(item: &[u8]) -> Option<ShellItem> {
+    let flags = *item.get(4)?;
+    let mut pos = 5;
+
+    let mut next = || {
+        let text = read_ascii(item.get(pos..)?);
+        pos += text.len() + 1;
+        Some(text)
+    };
+
+    let location = next()?;
+    let description = if flags & 0x80 != 0 { next() } else { None };
+    let comments = if flags & 0x40 != 0 { next() } else { None };
+
+    Some(ShellItem::Network {
+        location,
+        description: description.filter(|s| !s.is_empty()),
+        comments: comments.filter(|s| !s.is_empty()),
+    })
+}
+
+/// The URI follows a variable-size data block that holds the connection
+/// time; flag 0x80 marks a UTF-16 string
+fn parse_uri(item: &[u8]) -> Option<ShellItem> {
+    let flags = *item.get(3)?;
+    let data_size = u16::from_le_bytes(item.get(4..6)?.try_into().ok()?) as usize;
+
+    let connected = item.get(14..22)
+        .filter(|_| data_size >= 16)
+        .and_then(|b| filetime_to_datetime(u64::from_le_bytes(b.try_into().unwrap())));
+
+    let start = 6 + data_size;
+    let uri = if flags & 0x80 != 0 {
+        read_utf16(item.get(start..)?).0
+    } else {
+        read_ascii(item.get(start..)?)
+    };
+
+    Some(ShellItem::Uri { uri, connected })
+}
+
+fn parse_control_panel(item: &[u8]) -> Option<ShellItem> {
+    let guid = format_guid(item.get(14..30)?)?;
+    let name = known_folder_name(&guid).map(str::to_string);
+    Some(ShellItem::ControlPanel { guid, name })
+}
+
+fn parse_control_panel_category(item: &[u8]) -> Option<ShellItem> {
+    let id = u32::from_le_bytes(item.get(8..12)?.try_into().ok()?);
+    let name = CONTROL_PANEL_CATEGORIES.get(id as usize).map(|s| s.to_string());
+    Some(ShellItem::ControlPanelCategory { id, name })
+}
+
+/// MTP items have no documented fixed layout for their names, so take the
+/// first printable ASCII string stored as UTF-16 after the header
+fn parse_mtp(item: &[u8]) -> Option<ShellItem> {
+    let mut pos = 10;
+    while pos + 4 <= item.len() {
+        let (text, consumed) = read_utf16(&item[pos..]);
+        if text.chars().count() >= 2 && text.chars().all(|c| c.is_ascii_graphic() || c == ' ') {
+            return Some(ShellItem::Mtp { name: text });
+        }
+        pos += consumed.max(2);
+    }
+    None
+}
+
+/// Return the extension block starting at `pos`, if there is a valid one
+fn next_extension_block(item: &[u8], pos: usize) -> Option<&[u8]> {
+    let size = u16::from_le_bytes(item.get(pos..pos + 2)?.try_into().ok()?) as usize;
+    if size < 8 {
+        return None;
+    }
+    let block = item.get(pos..pos + size)?;
+    // Every extension block signature has the form 0xBEEFxxxx
+    (u16::from_le_bytes([block[6], block[7]]) == 0xBEEF).then_some(block)
+}
+
+fn parse_beef0004(block: &[u8], entry: &mut FileEntry) {
+    if block.len() < 18 {
+        return;
+    }
+
+    let word = |offset: usize| u16::from_le_bytes([block[offset], block[offset + 1]]);
+    let version = word(2);
+
+    entry.created = dos_datetime_to_datetime(word(8), word(10));
+    entry.accessed = dos_datetime_to_datetime(word(12), word(14));
+
+    let mut pos = 18;
+    if version >= 7 && block.len() >= 36 {
+        let reference = u64::from_le_bytes(block[20..28].try_into().unwrap());
+        entry.mft_entry = Some(reference & 0x0000_FFFF_FFFF_FFFF);
+        entry.mft_sequence = Some((reference >> 48) as u16);
+        pos = 36;
+    }
+    if version >= 3 {
+        // Size of the localized name that follows the long name
+        pos += 2;
+    }
+    if version >= 9 {
+        pos += 4;
+    }
+    if version >= 8 {
+        pos += 4;
+    }
+
+    if let Some(data) = block.get(pos..) {
+        let (name, _) = read_utf16(data);
+        if !name.is_empty() {
+            entry.long_name = Some(name);
+        }
+    }
+}
+
+/// Read a NUL-terminated ASCII string
+fn read_ascii(data: &[u8]) -> String {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    data[..end].iter().map(|&b| b as char).collect()
+}
+
+/// Read a NUL-terminated UTF-16LE string, returning it with the number of
+/// bytes consumed including the terminator
+pub(crate) fn read_utf16(data: &[u8]) -> (String, usize) {
+    let units: Vec<u16> = utf16_units(data).take_while(|&u| u != 0).collect();
+    let consumed = (units.len() * 2 + 2).min(data.len());
+    (String::from_utf16_lossy(&units), consumed)
+}
@@ -0,0 +1,175 @@
+// src/sigma/condition.rs
+use anyhow::{Result, bail};
+
+/// A parsed `condition` expression
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Condition {
+    Search(String),
+    Not(Box<Condition>),
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+    /// `1 of <pattern>`; `them` is the pattern `*`
+    AnyOf(String),
+    /// `all of <pattern>`
+    AllOf(String),
+}
+
+impl Condition {
+    /// Evaluate with `search` deciding each named search
+    pub fn evaluate(&self, names: &[&str], search: &dyn Fn(&str) -> bool) -> bool {
+        match self {
+            Condition::Search(name) => search(name),
+            Condition::Not(inner) => !inner.evaluate(names, search),
+            Condition::And(items) => items.iter().all(|c| c.evaluate(names, search)),
+            Condition::Or(items) => items.iter().any(|c| c.evaluate(names, search)),
+            Condition::AnyOf(pattern) => matching(names, pattern).any(search),
+            Condition::AllOf(pattern) => {
+                let mut matched = matching(names, pattern).peekable();
+                matched.peek().is_some() && matched.all(search)
+            }
+        }
+    }
+
+    /// Search names referenced directly, for validation
+    pub fn references(&self) -> Vec<&str> {
+        match self {
+            Condition::Search(name) => vec![name.as_str()],
+            Condition::Not(inner) => inner.references(),
+            Condition::And(items) | Condition::Or(items) => items.iter().flat_map(Condition::references).collect(),
+            Condition::AnyOf(_) | Condition::AllOf(_) => Vec::new(),
+        }
+    }
+}
+
+/// Searches matching a quantifier pattern. `them` excludes searches whose
+/// name starts with an underscore.
+fn matching<'a>(names: &'a [&'a str], pattern: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    names.iter().copied().filter(move |name| match pattern {
+        "them" => !name.starts_with('_'),
+        _ => glob_match(pattern, name),
+    })
+}
+
+/// `*` wildcards only, as used in `1 of selection_*`
+fn glob_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(remaining) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=remaining.len())
+                .filter(|&i| remaining.is_char_boundary(i))
+                .any(|i| glob_match(rest, &remaining[i..]))
+        }
+    }
+}
+
+/// Parse a condition. Aggregations (`| count() > 5`) are not supported.
+pub(super) fn parse_condition(text: &str) -> Result<Condition> {
+    if text.contains('|') {
+        bail!("Aggregation conditions are not supported: {}", text);
+    }
+
+    let tokens = tokenize(text);
+    let mut parser = Parser { tokens: &tokens, pos: 0 };
+    let condition = parser.or()?;
+    if let Some(token) = tokens.get(parser.pos) {
+        bail!("Unexpected '{}' in condition '{}'", token, text);
+    }
+    Ok(condition)
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+
+    for c in text.chars() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let matched = self.peek().is_some_and(|t| t.eq_ignore_ascii_case(keyword));
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn or(&mut self) -> Result<Condition> {
+        let mut items = vec![self.and()?];
+        while self.keyword("or") {
+            items.push(self.and()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { Condition::Or(items) })
+    }
+
+    fn and(&mut self) -> Result<Condition> {
+        let mut items = vec![self.not()?];
+        while self.keyword("and") {
+            items.push(self.not()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { Condition::And(items) })
+    }
+
+    fn not(&mut self) -> Result<Condition> {
+        if self.keyword("not") {
+            return Ok(Condition::Not(Box::new(self.not()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Condition> {
+        let Some(token) = self.peek().map(str::to_string) else {
+            bail!("Condition ends unexpectedly");
+        };
+        self.pos += 1;
+
+        match token.to_lowercase().as_str() {
+            "(" => {
+                let inner = self.or()?;
+                if !self.keyword(")") {
+                    bail!("Unbalanced parentheses in condition");
+                }
+                Ok(inner)
+            }
+            "1" | "any" | "all" => {
+                if !self.keyword("of") {
+                    bail!("Expected 'of' after '{}'", token);
+                }
+                let Some(pattern) = self.peek().map(str::to_string) else {
+                    bail!("Expected a search pattern after '{} of'", token);
+                };
+                self.pos += 1;
+                Ok(if token.eq_ignore_ascii_case("all") { Condition::AllOf(pattern) } else { Condition::AnyOf(pattern) })
+            }
+            ")" | "and" | "or" | "of" => bail!("Unexpected '{}' in condition", token),
+            _ => Ok(Condition::Search(token)),
+        }
+    }
+}
@@ -0,0 +1,271 @@
+// src/sigma/detection.rs
+//! Compiled search identifiers: field/value maps, lists of maps, and keyword
+//! lists, with value modifiers applied at load time.
+use anyhow::{Result, bail, Context};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use regex::{Regex, RegexBuilder};
+use serde_yaml::Value;
+
+/// Alternatives for dashes in command line flags (`windash`)
+const DASHES: &[char] = &['-', '/', '\u{2013}', '\u{2014}', '\u{2015}'];
+
+/// One named search of a rule's `detection` section
+#[derive(Debug)]
+pub(super) enum Search {
+    /// Any of the maps must match; every field of a map must match
+    Maps(Vec<Vec<FieldMatch>>),
+    /// Any keyword must appear in any field
+    Keywords(Vec<Pattern>),
+}
+
+#[derive(Debug)]
+pub(super) struct FieldMatch {
+    pub field: String,
+    pub test: FieldTest,
+}
+
+#[derive(Debug)]
+pub(super) enum FieldTest {
+    /// Any pattern (or every pattern, with `|all`) must match
+    Patterns { patterns: Vec<Pattern>, all: bool },
+    /// `null`: the field is absent or empty
+    Null,
+    /// `|exists`
+    Exists(bool),
+    /// `|gt`, `|gte`, `|lt`, `|lte`
+    Compare { op: Comparison, value: f64 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+#[derive(Debug)]
+pub(super) enum Pattern {
+    /// Lowercased literal compared for equality, unless `cased`
+    Exact { value: String, cased: bool },
+    Regex(Regex),
+}
+
+impl Search {
+    pub fn matches(&self, lookup: &dyn Fn(&str) -> Option<String>, values: &dyn Fn() -> Vec<String>) -> bool {
+        match self {
+            Search::Maps(maps) => maps.iter().any(|map| map.iter().all(|m| m.matches(lookup(&m.field)))),
+            Search::Keywords(patterns) => {
+                let values = values();
+                patterns.iter().any(|p| values.iter().any(|v| p.matches(v)))
+            }
+        }
+    }
+}
+
+impl FieldMatch {
+    fn matches(&self, value: Option<String>) -> bool {
+        match (&self.test, value) {
+            (FieldTest::Null, value) => value.is_none_or(|v| v.is_empty()),
+            (FieldTest::Exists(expected), value) => value.is_some() == *expected,
+            (_, None) => false,
+            (FieldTest::Patterns { patterns, all: true }, Some(value)) => patterns.iter().all(|p| p.matches(&value)),
+            (FieldTest::Patterns { patterns, all: false }, Some(value)) => patterns.iter().any(|p| p.matches(&value)),
+            (FieldTest::Compare { op, value: limit }, Some(value)) => {
+                let Ok(value) = value.trim().parse::<f64>() else {
+                    return false;
+                };
+                match op {
+                    Comparison::Greater => value > *limit,
+                    Comparison::GreaterOrEqual => value >= *limit,
+                    Comparison::Less => value < *limit,
+                    Comparison::LessOrEqual => value <= *limit,
+                }
+            }
+        }
+    }
+}
+
+impl Pattern {
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            Pattern::Exact { value: expected, cased: true } => value == expected,
+            Pattern::Exact { value: expected, cased: false } => value.to_lowercase() == *expected,
+            Pattern::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// Compile a search identifier's definition
+pub(super) fn compile_search(definition: &Value) -> Result<Search> {
+    match definition {
+        Value::Mapping(_) => Ok(Search::Maps(vec![compile_map(definition)?])),
+        Value::Sequence(items) if items.iter().all(|i| matches!(i, Value::Mapping(_))) => {
+            Ok(Search::Maps(items.iter().map(compile_map).collect::<Result<_>>()?))
+        }
+        // Keywords are full-text searches
+        Value::Sequence(items) => {
+            let patterns = items.iter()
+                .map(|item| compile_pattern(&scalar(item)?, &["contains"]))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Search::Keywords(patterns.into_iter().flatten().collect()))
+        }
+        other => Ok(Search::Keywords(compile_pattern(&scalar(other)?, &["contains"])?)),
+    }
+}
+
+fn compile_map(map: &Value) -> Result<Vec<FieldMatch>> {
+    let Value::Mapping(map) = map else {
+        bail!("Expected a map of fields");
+    };
+
+    let mut fields = Vec::new();
+    for (key, value) in map {
+        let key = key.as_str().context("Field names must be strings")?;
+        let mut parts = key.split('|');
+        let field = parts.next().unwrap_or_default().to_string();
+        let modifiers: Vec<&str> = parts.collect();
+        fields.push(FieldMatch { field, test: compile_test(value, &modifiers)? });
+    }
+    Ok(fields)
+}
+
+fn compile_test(value: &Value, modifiers: &[&str]) -> Result<FieldTest> {
+    if value.is_null() {
+        return Ok(FieldTest::Null);
+    }
+
+    let values = match value {
+        Value::Sequence(items) => items.clone(),
+        other => vec![other.clone()],
+    };
+
+    if modifiers.contains(&"exists") {
+        return Ok(FieldTest::Exists(value.as_bool().context("exists takes true or false")?));
+    }
+    let comparison = modifiers.iter().find_map(|m| match *m {
+        "gt" => Some(Comparison::Greater),
+        "gte" => Some(Comparison::GreaterOrEqual),
+        "lt" => Some(Comparison::Less),
+        "lte" => Some(Comparison::LessOrEqual),
+        _ => None,
+    });
+    if let Some(op) = comparison {
+        let value = scalar(values.first().context("Comparison without a value")?)?;
+        return Ok(FieldTest::Compare { op, value: value.parse().context("Comparison value is not a number")? });
+    }
+
+    let mut patterns = Vec::new();
+    for value in &values {
+        if value.is_null() {
+            continue;
+        }
+        patterns.extend(compile_pattern(&scalar(value)?, modifiers)?);
+    }
+    Ok(FieldTest::Patterns { patterns, all: modifiers.contains(&"all") })
+}
+
+/// Apply the modifiers to a value. Encoding modifiers can turn one value
+/// into several alternatives.
+fn compile_pattern(value: &str, modifiers: &[&str]) -> Result<Vec<Pattern>> {
+    let mut kind = "equals";
+    let mut cased = false;
+    let mut variants = vec![value.as_bytes().to_vec()];
+
+    for modifier in modifiers {
+        match *modifier {
+            "contains" | "startswith" | "endswith" | "re" => kind = modifier,
+            "all" => {}
+            "cased" => cased = true,
+            "wide" | "utf16le" => {
+                variants = variants.iter()
+                    .map(|v| String::from_utf8_lossy(v).encode_utf16().flat_map(u16::to_le_bytes).collect())
+                    .collect();
+            }
+            "windash" => {
+                variants = variants.iter()
+                    .flat_map(|v| {
+                        let text = String::from_utf8_lossy(v).into_owned();
+                        DASHES.iter().map(move |dash| text.replace('-', &dash.to_string()).into_bytes())
+                    })
+                    .collect();
+                variants.dedup();
+            }
+            "base64" => variants = variants.iter().map(|v| STANDARD.encode(v).into_bytes()).collect(),
+            "base64offset" => variants = variants.iter().flat_map(|v| base64_offsets(v)).collect(),
+            other => bail!("Unsupported modifier '{}'", other),
+        }
+    }
+
+    variants.into_iter()
+        .map(|variant| {
+            let text = String::from_utf8_lossy(&variant).into_owned();
+            if kind == "re" {
+                return Ok(Pattern::Regex(Regex::new(&text).context(format!("Invalid regex '{}'", text))?));
+            }
+            if kind == "equals" && !text.contains(['*', '?']) {
+                let value = if cased { text } else { text.to_lowercase() };
+                return Ok(Pattern::Exact { value, cased });
+            }
+
+            let glob = glob_to_regex(&text);
+            let pattern = match kind {
+                "contains" => glob,
+                "startswith" => format!("^{}", glob),
+                "endswith" => format!("{}$", glob),
+                _ => format!("^{}$", glob),
+            };
+            let regex = RegexBuilder::new(&pattern)
+                .case_insensitive(!cased)
+                .dot_matches_new_line(true)
+                .build()?;
+            Ok(Pattern::Regex(regex))
+        })
+        .collect()
+}
+
+/// The three encodings of a value at each possible offset within a base64
+/// stream, trimmed to the characters that do not depend on its neighbours
+fn base64_offsets(value: &[u8]) -> Vec<Vec<u8>> {
+    const START: [usize; 3] = [0, 2, 3];
+    const END_TRIM: [usize; 3] = [0, 3, 2];
+
+    (0..3)
+        .map(|offset| {
+            let mut padded = vec![b' '; offset];
+            padded.extend_from_slice(value);
+            let encoded = STANDARD.encode(&padded).into_bytes();
+            let end = encoded.len().saturating_sub(END_TRIM[(value.len() + offset) % 3]);
+            encoded.get(START[offset]..end).unwrap_or_default().to_vec()
+        })
+        .collect()
+}
+
+/// Sigma wildcards: `*` and `?`, with a backslash escaping either
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '\\' if matches!(chars.peek(), Some('*' | '?')) => {
+                regex.push_str(&regex::escape(&chars.next().unwrap().to_string()));
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    regex
+}
+
+fn scalar(value: &Value) -> Result<String> {
+    Ok(match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        other => bail!("Expected a scalar value, found {:?}", other),
+    })
+}
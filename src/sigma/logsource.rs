@@ -0,0 +1,126 @@
+// src/sigma/logsource.rs
+//! Mapping of Sigma `logsource` definitions to EVTX channels and event IDs
+use anyhow::{Result, bail};
+
+const SECURITY: &str = "Security";
+const SYSMON: &str = "Microsoft-Windows-Sysmon/Operational";
+const POWERSHELL: &str = "Microsoft-Windows-PowerShell/Operational";
+const POWERSHELL_CLASSIC: &str = "Windows PowerShell";
+
+/// Sigma's Sysmon field names as logged by Security 4688
+const PROCESS_CREATION_4688: &[(&str, &str)] = &[
+    ("Image", "NewProcessName"),
+    ("ParentImage", "ParentProcessName"),
+];
+
+const SERVICES: &[(&str, &[&str])] = &[
+    ("security", &[SECURITY]),
+    ("system", &["System"]),
+    ("application", &["Application"]),
+    ("sysmon", &[SYSMON]),
+    ("powershell", &[POWERSHELL]),
+    ("powershell-classic", &[POWERSHELL_CLASSIC]),
+    ("taskscheduler", &["Microsoft-Windows-TaskScheduler/Operational"]),
+    ("wmi", &["Microsoft-Windows-WMI-Activity/Operational"]),
+    ("windefend", &["Microsoft-Windows-Windows Defender/Operational"]),
+    ("bits-client", &["Microsoft-Windows-Bits-Client/Operational"]),
+    ("codeintegrity-operational", &["Microsoft-Windows-CodeIntegrity/Operational"]),
+    ("dns-server", &["DNS Server"]),
+    ("driver-framework", &["Microsoft-Windows-DriverFrameworks-UserMode/Operational"]),
+    ("firewall-as", &["Microsoft-Windows-Windows Firewall With Advanced Security/Firewall"]),
+    ("ntlm", &["Microsoft-Windows-NTLM/Operational"]),
+    ("openssh", &["OpenSSH/Operational"]),
+    ("printservice-admin", &["Microsoft-Windows-PrintService/Admin"]),
+    ("printservice-operational", &["Microsoft-Windows-PrintService/Operational"]),
+    ("security-mitigations", &["Microsoft-Windows-Security-Mitigations/Kernel Mode", "Microsoft-Windows-Security-Mitigations/User Mode"]),
+    ("smbclient-security", &["Microsoft-Windows-SmbClient/Security"]),
+    ("terminalservices-localsessionmanager", &["Microsoft-Windows-TerminalServices-LocalSessionManager/Operational"]),
+    ("applocker", &[
+        "Microsoft-Windows-AppLocker/EXE and DLL",
+        "Microsoft-Windows-AppLocker/MSI and Script",
+        "Microsoft-Windows-AppLocker/Packaged app-Deployment",
+        "Microsoft-Windows-AppLocker/Packaged app-Execution",
+    ]),
+];
+
+/// Sysmon event IDs of the generic Windows categories
+const SYSMON_CATEGORIES: &[(&str, &[u32])] = &[
+    ("process_termination", &[5]),
+    ("network_connection", &[3]),
+    ("driver_load", &[6]),
+    ("image_load", &[7]),
+    ("create_remote_thread", &[8]),
+    ("raw_access_thread", &[9]),
+    ("process_access", &[10]),
+    ("file_event", &[11]),
+    ("registry_event", &[12, 13, 14]),
+    ("registry_add", &[12]),
+    ("registry_delete", &[12]),
+    ("registry_set", &[13]),
+    ("registry_rename", &[14]),
+    ("create_stream_hash", &[15]),
+    ("pipe_created", &[17, 18]),
+    ("wmi_event", &[19, 20, 21]),
+    ("dns_query", &[22]),
+    ("file_delete", &[23, 26]),
+    ("clipboard_change", &[24]),
+    ("process_tampering", &[25]),
+    ("file_block_executable", &[27]),
+    ("file_block_shredding", &[28]),
+    ("file_executable_detected", &[29]),
+];
+
+/// One channel (and optionally event IDs) a rule applies to, with the
+/// field names that differ from the rule's
+#[derive(Debug, Clone)]
+pub(super) struct Source {
+    pub channel: &'static str,
+    pub event_ids: &'static [u32],
+    pub fields: &'static [(&'static str, &'static str)],
+}
+
+impl Source {
+    fn channel(channel: &'static str) -> Self {
+        Self { channel, event_ids: &[], fields: &[] }
+    }
+
+    fn events(channel: &'static str, event_ids: &'static [u32]) -> Self {
+        Self { channel, event_ids, fields: &[] }
+    }
+}
+
+/// Resolve a logsource. `None` means any Windows channel; rules for other
+/// products, or logsources without a known mapping, are rejected.
+pub(super) fn resolve(product: Option<&str>, category: Option<&str>, service: Option<&str>) -> Result<Option<Vec<Source>>> {
+    if let Some(product) = product
+        && !product.eq_ignore_ascii_case("windows")
+    {
+        bail!("Logsource product '{}' does not apply to event logs", product);
+    }
+
+    if let Some(service) = service {
+        let Some((_, channels)) = SERVICES.iter().find(|(name, _)| name.eq_ignore_ascii_case(service)) else {
+            bail!("No channel mapping for logsource service '{}'", service);
+        };
+        return Ok(Some(channels.iter().map(|c| Source::channel(c)).collect()));
+    }
+
+    let Some(category) = category else {
+        return Ok(None);
+    };
+    let sources = match category {
+        "process_creation" => vec![
+            Source::events(SYSMON, &[1]),
+            Source { channel: SECURITY, event_ids: &[4688], fields: PROCESS_CREATION_4688 },
+        ],
+        "ps_script" => vec![Source::events(POWERSHELL, &[4104])],
+        "ps_module" => vec![Source::events(POWERSHELL, &[4103])],
+        "ps_classic_start" => vec![Source::events(POWERSHELL_CLASSIC, &[400])],
+        "ps_classic_provider_start" => vec![Source::events(POWERSHELL_CLASSIC, &[600])],
+        _ => match SYSMON_CATEGORIES.iter().find(|(name, _)| *name == category) {
+            Some((_, event_ids)) => vec![Source::events(SYSMON, event_ids)],
+            None => bail!("No channel mapping for logsource category '{}'", category),
+        },
+    };
+    Ok(Some(sources))
+}
@@ -0,0 +1,196 @@
+// src/sigma/mod.rs
+//! Evaluation of Sigma detection rules against parsed event log records.
+//! Rules are compiled once at load time; rules using unsupported features
+//! (aggregations, unknown modifiers or logsources) are skipped with a warning.
+mod condition;
+mod detection;
+mod logsource;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use anyhow::{Result, bail, Context};
+use serde::{Serialize, Deserialize};
+use serde_yaml::Value;
+use crate::parsers::EvtxRecord;
+use condition::{parse_condition, Condition};
+use detection::{compile_search, Search};
+use logsource::Source;
+
+/// A compiled Sigma rule
+#[derive(Debug)]
+pub struct SigmaRule {
+    pub id: Option<String>,
+    pub title: String,
+    pub level: Option<String>,
+    pub status: Option<String>,
+    pub tags: Vec<String>,
+    /// `None` applies the rule to every channel
+    sources: Option<Vec<Source>>,
+    searches: BTreeMap<String, Search>,
+    conditions: Vec<Condition>,
+}
+
+/// A record matched by a rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigmaHit {
+    pub rule_id: Option<String>,
+    pub title: String,
+    pub level: Option<String>,
+    pub tags: Vec<String>,
+    pub record: EvtxRecord,
+}
+
+impl SigmaRule {
+    /// Parse and compile a single-document rule
+    pub fn parse(yaml: &str) -> Result<Self> {
+        let rule: Value = serde_yaml::from_str(yaml).context("Invalid YAML")?;
+        let text = |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+
+        let title = text(&rule, "title").context("Rule has no title")?;
+        let logsource = rule.get("logsource").context("Rule has no logsource")?;
+        let sources = logsource::resolve(
+            logsource.get("product").and_then(Value::as_str),
+            logsource.get("category").and_then(Value::as_str),
+            logsource.get("service").and_then(Value::as_str),
+        )?;
+
+        let Some(Value::Mapping(detection)) = rule.get("detection") else {
+            bail!("Rule has no detection section");
+        };
+
+        let mut searches = BTreeMap::new();
+        let mut conditions = Vec::new();
+        for (key, value) in detection {
+            match key.as_str() {
+                Some("condition") => match value {
+                    Value::String(condition) => conditions.push(parse_condition(condition)?),
+                    // A list of conditions is a deprecated form of `or`
+                    Value::Sequence(items) => {
+                        for item in items {
+                            conditions.push(parse_condition(item.as_str().context("Condition is not a string")?)?);
+                        }
+                    }
+                    _ => bail!("Condition is not a string"),
+                },
+                Some("timeframe") => bail!("Timeframe correlations are not supported"),
+                Some(name) => {
+                    let search = compile_search(value).context(format!("Search '{}'", name))?;
+                    searches.insert(name.to_string(), search);
+                }
+                None => bail!("Search names must be strings"),
+            }
+        }
+
+        if conditions.is_empty() {
+            bail!("Rule has no condition");
+        }
+        for name in conditions.iter().flat_map(Condition::references) {
+            if !searches.contains_key(name) {
+                bail!("Condition references undefined search '{}'", name);
+            }
+        }
+
+        Ok(Self {
+            id: text(&rule, "id"),
+            title,
+            level: text(&rule, "level"),
+            status: text(&rule, "status"),
+            tags: rule.get("tags")
+                .and_then(Value::as_sequence)
+                .map(|tags| tags.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                .unwrap_or_default(),
+            sources,
+            searches,
+            conditions,
+        })
+    }
+
+    pub fn matches(&self, record: &EvtxRecord) -> bool {
+        let source = match &self.sources {
+            None => None,
+            Some(sources) => {
+                let found = sources.iter().find(|source| {
+                    record.channel.as_deref() == Some(source.channel)
+                        && (source.event_ids.is_empty() || record.event_id.is_some_and(|id| source.event_ids.contains(&id)))
+                });
+                match found {
+                    Some(source) => Some(source),
+                    None => return false,
+                }
+            }
+        };
+
+        let fields = source.map(|s| s.fields).unwrap_or_default();
+        let lookup = |name: &str| {
+            let name = fields.iter().find(|(sigma, _)| *sigma == name).map_or(name, |(_, logged)| *logged);
+            field_value(record, name)
+        };
+        let values = || -> Vec<String> { record.event_data.values().cloned().collect() };
+        let names: Vec<&str> = self.searches.keys().map(String::as_str).collect();
+        let search = |name: &str| self.searches.get(name).is_some_and(|s| s.matches(&lookup, &values));
+
+        self.conditions.iter().any(|condition| condition.evaluate(&names, &search))
+    }
+}
+
+/// Look up a field: System fields by their Sigma names, then event data
+fn field_value(record: &EvtxRecord, name: &str) -> Option<String> {
+    match name {
+        "EventID" => record.event_id.map(|id| id.to_string()),
+        "Channel" => record.channel.clone(),
+        "Computer" => record.computer.clone(),
+        "Provider_Name" => record.provider.clone(),
+        "Level" => record.level.map(|level| level.to_string()),
+        _ => record.event_data.get(name).cloned().or_else(|| {
+            record.event_data.iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+        }),
+    }
+}
+
+/// Load every `.yml` and `.yaml` rule under a directory, recursively
+pub fn load_sigma_rules(dir: &Path) -> Result<Vec<SigmaRule>> {
+    let mut rules = Vec::new();
+    let entries = std::fs::read_dir(dir).context(format!("Failed to read {}", dir.display()))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            rules.extend(load_sigma_rules(&path)?);
+            continue;
+        }
+        let is_rule = path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("yml") || e.eq_ignore_ascii_case("yaml"));
+        if !is_rule {
+            continue;
+        }
+
+        match std::fs::read_to_string(&path).map_err(Into::into).and_then(|yaml| SigmaRule::parse(&yaml)) {
+            Ok(rule) => rules.push(rule),
+            Err(e) => log::warn!("Skipping Sigma rule {}: {:#}", path.display(), e),
+        }
+    }
+
+    Ok(rules)
+}
+
+/// Evaluate every rule against every record
+pub fn evaluate_sigma<'a>(rules: &[SigmaRule], records: impl IntoIterator<Item = &'a EvtxRecord>) -> Vec<SigmaHit> {
+    let mut hits = Vec::new();
+
+    for record in records {
+        for rule in rules.iter().filter(|rule| rule.matches(record)) {
+            hits.push(SigmaHit {
+                rule_id: rule.id.clone(),
+                title: rule.title.clone(),
+                level: rule.level.clone(),
+                tags: rule.tags.clone(),
+                record: record.clone(),
+            });
+        }
+    }
+
+    hits
+}
@@ -0,0 +1,36 @@
+// src/utils/bytes.rs
+
+/// Little-endian `u16` at `offset`, or `None` past the end of `data`
+pub fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(offset..offset.checked_add(2)?)?.try_into().ok()?))
+}
+
+/// Little-endian `u32` at `offset`, or `None` past the end of `data`
+pub fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(offset..offset.checked_add(4)?)?.try_into().ok()?))
+}
+
+/// Little-endian `u64` at `offset`, or `None` past the end of `data`
+pub fn u64_at(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(offset..offset.checked_add(8)?)?.try_into().ok()?))
+}
+
+/// UTF-16LE code units, ignoring a trailing odd byte
+pub fn utf16_units(data: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]]))
+}
+
+/// Decode all of `data` as UTF-16LE, replacing invalid sequences
+pub fn utf16(data: &[u8]) -> String {
+    String::from_utf16_lossy(&utf16_units(data).collect::<Vec<_>>())
+}
+
+/// Decode UTF-16LE up to the first NUL, replacing invalid sequences
+pub fn utf16z(data: &[u8]) -> String {
+    String::from_utf16_lossy(&utf16_units(data).take_while(|&u| u != 0).collect::<Vec<_>>())
+}
+
+/// Lowercase hex digits, two per byte
+pub fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
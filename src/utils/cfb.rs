@@ -0,0 +1,282 @@
+// src/utils/cfb.rs
+//! Read-only Compound File Binary ([MS-CFB]) reader, for OLE containers
+//! such as automatic jump lists
+use std::collections::HashSet;
+use anyhow::{Result, bail, Context};
+use chrono::{DateTime, Utc};
+use crate::utils::bytes::{u16_at, u32_at, u64_at, utf16};
+use crate::utils::time::filetime_to_datetime;
+
+const SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+const HEADER_SIZE: usize = 512;
+const HEADER_DIFAT_ENTRIES: usize = 109;
+const DIRECTORY_ENTRY_SIZE: usize = 128;
+
+const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+const NO_STREAM: u32 = 0xFFFF_FFFF;
+/// Sector IDs at or above this are markers, not sectors
+const MAX_SECTOR: u32 = 0xFFFF_FFFA;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryType {
+    Storage,
+    Stream,
+    Root,
+}
+
+/// A storage or stream in the directory
+#[derive(Debug, Clone)]
+pub struct CfbEntry {
+    /// Full path from the root, separated by `/`
+    pub path: String,
+    pub name: String,
+    pub entry_type: EntryType,
+    pub size: u64,
+    pub created: Option<DateTime<Utc>>,
+    pub modified: Option<DateTime<Utc>>,
+    start_sector: u32,
+}
+
+/// A parsed compound file, holding the allocation tables and directory
+pub struct CompoundFile<'a> {
+    data: &'a [u8],
+    sector_size: usize,
+    mini_sector_size: usize,
+    mini_stream_cutoff: u64,
+    fat: Vec<u32>,
+    mini_fat: Vec<u32>,
+    mini_stream: Vec<u8>,
+    entries: Vec<CfbEntry>,
+}
+
+/// Raw directory entry before the tree is resolved into paths
+struct RawEntry {
+    name: String,
+    object_type: u8,
+    left: u32,
+    right: u32,
+    child: u32,
+    created: Option<DateTime<Utc>>,
+    modified: Option<DateTime<Utc>>,
+    start_sector: u32,
+    size: u64,
+}
+
+impl<'a> CompoundFile<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        if data.len() < HEADER_SIZE || data[..8] != SIGNATURE {
+            bail!("Not a compound file");
+        }
+        let major_version = u16_at(data, 0x1A).unwrap_or_default();
+        let sector_shift = u16_at(data, 0x1E).unwrap_or_default();
+        let mini_sector_shift = u16_at(data, 0x20).unwrap_or_default();
+        if !matches!((major_version, sector_shift), (3, 9) | (4, 12)) || mini_sector_shift != 6 {
+            bail!("Unsupported compound file version {} (sector shift {})", major_version, sector_shift);
+        }
+
+        let mut file = Self {
+            data,
+            sector_size: 1 << sector_shift,
+            mini_sector_size: 1 << mini_sector_shift,
+            mini_stream_cutoff: u32_at(data, 0x38).unwrap_or(4096) as u64,
+            fat: Vec::new(),
+            mini_fat: Vec::new(),
+            mini_stream: Vec::new(),
+            entries: Vec::new(),
+        };
+
+        // FAT sector IDs: 109 in the header, the rest in the DIFAT chain.
+        // Neither can hold more sectors than the file has.
+        let file_sectors = data.len() / file.sector_size;
+        let fat_sectors = (u32_at(data, 0x2C).unwrap_or_default() as usize).min(file_sectors);
+        let mut difat: Vec<u32> = (0..HEADER_DIFAT_ENTRIES)
+            .filter_map(|i| u32_at(data, 0x4C + i * 4))
+            .collect();
+        let mut next = u32_at(data, 0x44).unwrap_or(END_OF_CHAIN);
+        let per_sector = file.sector_size / 4 - 1;
+        let mut visited = HashSet::new();
+        while next < MAX_SECTOR && difat.len() < fat_sectors {
+            if !visited.insert(next) {
+                bail!("DIFAT chain loops at sector {}", next);
+            }
+            let sector = file.sector(next).context("DIFAT sector out of range")?;
+            difat.extend((0..per_sector).filter_map(|i| u32_at(sector, i * 4)));
+            next = u32_at(sector, per_sector * 4).unwrap_or(END_OF_CHAIN);
+        }
+        for &id in difat.iter().take(fat_sectors).filter(|&&id| id < MAX_SECTOR) {
+            let sector = file.sector(id).context("FAT sector out of range")?;
+            file.fat.extend(sector.chunks_exact(4).map(|c| u32::from_le_bytes(c.try_into().unwrap())));
+        }
+
+        let directory = file.read_chain(u32_at(data, 0x30).unwrap_or(END_OF_CHAIN), None)?;
+        let raw: Vec<RawEntry> = directory.chunks_exact(DIRECTORY_ENTRY_SIZE).map(|e| raw_entry(e, major_version)).collect();
+        let Some(root) = raw.first().filter(|root| root.object_type == 5) else {
+            bail!("Compound file has no root entry");
+        };
+
+        let mini_fat = file.read_chain(u32_at(data, 0x3C).unwrap_or(END_OF_CHAIN), None)?;
+        file.mini_fat = mini_fat.chunks_exact(4).map(|c| u32::from_le_bytes(c.try_into().unwrap())).collect();
+        file.mini_stream = file.read_chain(root.start_sector, Some(root.size))?;
+
+        file.entries.push(CfbEntry {
+            path: String::new(),
+            name: root.name.clone(),
+            entry_type: EntryType::Root,
+            size: root.size,
+            created: root.created,
+            modified: root.modified,
+            start_sector: root.start_sector,
+        });
+        walk_tree(&raw, root.child, &mut file.entries);
+
+        Ok(file)
+    }
+
+    /// Every storage and stream, the root first
+    pub fn entries(&self) -> &[CfbEntry] {
+        &self.entries
+    }
+
+    /// Read a stream by path, ignoring case as Windows does
+    pub fn read_stream(&self, path: &str) -> Result<Vec<u8>> {
+        let Some(entry) = self.entries.iter()
+            .find(|e| e.entry_type == EntryType::Stream && e.path.eq_ignore_ascii_case(path))
+        else {
+            bail!("No stream named {}", path);
+        };
+        self.read_entry(entry)
+    }
+
+    pub fn read_entry(&self, entry: &CfbEntry) -> Result<Vec<u8>> {
+        if entry.size < self.mini_stream_cutoff {
+            self.read_mini_chain(entry.start_sector, entry.size)
+        } else {
+            self.read_chain(entry.start_sector, Some(entry.size))
+        }
+    }
+
+    fn sector(&self, id: u32) -> Option<&'a [u8]> {
+        let start = (id as usize + 1).checked_mul(self.sector_size)?;
+        self.data.get(start..start + self.sector_size)
+    }
+
+    /// Follow a FAT chain, truncating to `size` when given
+    fn read_chain(&self, start: u32, size: Option<u64>) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut next = start;
+        // A chain can be no longer than the FAT; more means a loop
+        for _ in 0..=self.fat.len() {
+            if next >= MAX_SECTOR {
+                break;
+            }
+            out.extend_from_slice(self.sector(next).context("Sector chain runs past the end of the file")?);
+            if size.is_some_and(|size| out.len() as u64 >= size) {
+                break;
+            }
+            next = self.fat.get(next as usize).copied().unwrap_or(END_OF_CHAIN);
+        }
+        if let Some(size) = size {
+            if (out.len() as u64) < size {
+                bail!("Stream truncated: {} of {} bytes", out.len(), size);
+            }
+            out.truncate(size as usize);
+        }
+        Ok(out)
+    }
+
+    fn read_mini_chain(&self, start: u32, size: u64) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut next = start;
+        for _ in 0..=self.mini_fat.len() {
+            if next >= MAX_SECTOR || out.len() as u64 >= size {
+                break;
+            }
+            let offset = next as usize * self.mini_sector_size;
+            let Some(sector) = self.mini_stream.get(offset..offset + self.mini_sector_size) else {
+                bail!("Mini sector {} out of range", next);
+            };
+            out.extend_from_slice(sector);
+            next = self.mini_fat.get(next as usize).copied().unwrap_or(END_OF_CHAIN);
+        }
+        if (out.len() as u64) < size {
+            bail!("Mini stream truncated: {} of {} bytes", out.len(), size);
+        }
+        out.truncate(size as usize);
+        Ok(out)
+    }
+}
+
+fn raw_entry(entry: &[u8], major_version: u16) -> RawEntry {
+    let name_length = (u16_at(entry, 64).unwrap_or_default() as usize).min(64);
+    let size = u64_at(entry, 120).unwrap_or_default();
+
+    RawEntry {
+        name: utf16(&entry[..name_length.saturating_sub(2)]),
+        object_type: entry[66],
+        left: u32_at(entry, 68).unwrap_or(NO_STREAM),
+        right: u32_at(entry, 72).unwrap_or(NO_STREAM),
+        child: u32_at(entry, 76).unwrap_or(NO_STREAM),
+        created: u64_at(entry, 100).and_then(filetime_to_datetime),
+        modified: u64_at(entry, 108).and_then(filetime_to_datetime),
+        start_sector: u32_at(entry, 116).unwrap_or(END_OF_CHAIN),
+        // Version 3 writers may leave garbage in the high half
+        size: if major_version == 3 { size & 0xFFFF_FFFF } else { size },
+    }
+}
+
+/// In-order walk of each storage's red-black tree of children. The trees
+/// of corrupt files can be arbitrarily deep or cyclic, so this keeps an
+/// explicit stack and visits each entry at most once.
+fn walk_tree(raw: &[RawEntry], root_child: u32, out: &mut Vec<CfbEntry>) {
+    enum Step {
+        /// Walk the subtree rooted at an entry
+        Walk(u32, String),
+        /// Output an entry whose left subtree is done
+        Emit(usize, String),
+    }
+
+    // The root is entry 0
+    let mut visited = vec![false; raw.len()];
+    if let Some(root) = visited.first_mut() {
+        *root = true;
+    }
+    let mut stack = vec![Step::Walk(root_child, String::new())];
+
+    while let Some(step) = stack.pop() {
+        match step {
+            Step::Walk(id, parent) => {
+                let Some(entry) = raw.get(id as usize) else {
+                    continue;
+                };
+                if std::mem::replace(&mut visited[id as usize], true) {
+                    continue;
+                }
+                stack.push(Step::Walk(entry.right, parent.clone()));
+                stack.push(Step::Emit(id as usize, parent.clone()));
+                stack.push(Step::Walk(entry.left, parent));
+            }
+            Step::Emit(index, parent) => {
+                let entry = &raw[index];
+                let entry_type = match entry.object_type {
+                    1 => EntryType::Storage,
+                    2 => EntryType::Stream,
+                    _ => continue,
+                };
+                let path = if parent.is_empty() { entry.name.clone() } else { format!("{}/{}", parent, entry.name) };
+                out.push(CfbEntry {
+                    path: path.clone(),
+                    name: entry.name.clone(),
+                    entry_type,
+                    size: entry.size,
+                    created: entry.created,
+                    modified: entry.modified,
+                    start_sector: entry.start_sector,
+                });
+                // Children come before the right siblings
+                if entry_type == EntryType::Storage {
+                    stack.push(Step::Walk(entry.child, path));
+                }
+            }
+        }
+    }
+}
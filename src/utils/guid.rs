@@ -0,0 +1,93 @@
+// src/utils/guid.rs
+
+/// Format a 16-byte little-endian GUID as `{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}`
+pub fn format_guid(data: &[u8]) -> Option<String> {
+    if data.len() < 16 {
+        return None;
+    }
+
+    let d1 = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+    let d2 = u16::from_le_bytes([data[4], data[5]]);
+    let d3 = u16::from_le_bytes([data[6], data[7]]);
+
+    Some(format!(
+        "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+        d1, d2, d3,
+        data[8], data[9],
+        data[10], data[11], data[12], data[13], data[14], data[15],
+    ))
+}
+
+/// Well-known shell folder and known folder GUIDs, as they appear in shell
+/// items, UserAssist value names and shortcut KnownFolder blocks
+const KNOWN_FOLDERS: &[(&str, &str)] = &[
+    ("{20D04FE0-3AEA-1069-A2D8-08002B30309D}", "My Computer"),
+    ("{59031A47-3F72-44A7-89C5-5595FE6B30EE}", "Users Files"),
+    ("{F02C1A0D-BE21-4350-88B0-7367FC96EF3C}", "Network"),
+    ("{208D2C60-3AEA-1069-A2D7-08002B30309D}", "My Network Places"),
+    ("{21EC2020-3AEA-1069-A2DD-08002B30309D}", "Control Panel"),
+    ("{26EE0668-A00A-44D7-9371-BEB064C98683}", "All Control Panel Items"),
+    ("{645FF040-5081-101B-9F08-00AA002F954E}", "Recycle Bin"),
+    ("{031E4825-7B94-4DC3-B131-E946B44C8DD5}", "Libraries"),
+    ("{871C5380-42A0-1069-A2EA-08002B30309D}", "Internet Explorer"),
+    ("{450D8FBA-AD25-11D0-98A8-0800361B1103}", "My Documents"),
+    ("{679F85CB-0220-4080-B29B-5540CC05AAB6}", "Quick Access"),
+    ("{B4BFCC3A-DB2C-424C-B029-7FE99A87C641}", "Desktop"),
+    ("{374DE290-123F-4565-9164-39C4925E467B}", "Downloads"),
+    ("{088E3905-0323-4B02-9826-5D99428E115F}", "Downloads"),
+    ("{FDD39AD0-238F-46AF-ADB4-6C85480369C7}", "Documents"),
+    ("{D3162B92-9365-467A-956B-92703ACA08AF}", "Documents"),
+    ("{A8CDFF1C-4878-43BE-B5FD-F8091C1C60D0}", "Documents"),
+    ("{33E28130-4E1E-4676-835A-98395C3BC3BB}", "Pictures"),
+    ("{24AD3AD4-A569-4530-98E1-AB02F9417AA8}", "Pictures"),
+    ("{4BD8D571-6D19-48D3-BE97-422220080E43}", "Music"),
+    ("{3DFDF296-DBEC-4FB4-81D1-6A3438BCF4DE}", "Music"),
+    ("{18989B1D-99B5-455B-841C-AB7C74E4DDFC}", "Videos"),
+    ("{F86FA3AB-70D2-4FC7-9C99-FCBF05467F3A}", "Videos"),
+    ("{F38BF404-1D43-42F2-9305-67DE0B28FC23}", "Windows"),
+    ("{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}", "System32"),
+    ("{D65231B0-B2F1-4857-A4CE-A8E7C6EA7D27}", "SysWOW64"),
+    ("{905E63B6-C1BF-494E-B29C-65B732D3D21A}", "Program Files"),
+    ("{6D809377-6AF0-444B-8957-A3773F02200E}", "Program Files"),
+    ("{7C5A40EF-A0FB-4BFC-874A-C0F2E0B9FA8E}", "Program Files (x86)"),
+    ("{F7F1ED05-9F6D-47A2-AAAE-29D317C6F066}", "Common Files"),
+    ("{6365D5A7-0F0D-45E5-87F6-0DA56B6A4F7D}", "Common Files"),
+    ("{DE974D24-D9C6-4D3E-BF91-F4455120B917}", "Common Files (x86)"),
+    ("{A77F5D77-2E2B-44C3-A6A2-ABA601054A51}", "Programs"),
+    ("{0139D44E-6AFE-49F2-8690-3DAFCAE6FFB8}", "Common Programs"),
+    ("{625B53C3-AB48-4EC1-BA1F-A1EF4146FC19}", "Start Menu"),
+    ("{A4115719-D62E-491D-AA7C-E74B8BE3B067}", "Common Start Menu"),
+    ("{B97D20BB-F46A-4C97-BA10-5E3608430854}", "Startup"),
+    ("{82A5EA35-D9CD-47C5-9629-E15D2F714E6E}", "Common Startup"),
+    ("{9E3995AB-1F9C-4F13-B827-48B24B6C7174}", "User Pinned"),
+    ("{5E6C858F-0E22-4760-9AFE-EA3317B67173}", "User Profile"),
+    ("{62AB5D82-FDC1-4DC3-A9DD-070D1D495D97}", "ProgramData"),
+    ("{3EB685DB-65F9-4CF6-A03A-E3EF65729F3D}", "AppData"),
+    ("{F1B32785-6FBA-4FCF-9D55-7B8E7F157091}", "Local AppData"),
+    ("{A520A1A4-1780-4FF6-BD18-167343C5AF16}", "LocalLow AppData"),
+    ("{AE50C081-EBD2-438A-8655-8A092E34987A}", "Recent"),
+    ("{8983036C-27C0-404B-8F08-102D10DCFD74}", "SendTo"),
+    ("{1777F761-68AD-4D8A-87BD-30B759FA33DD}", "Favorites"),
+    ("{2B0F765D-C0E9-4171-908E-08A611B84FF6}", "Cookies"),
+    ("{352481E8-33BE-4251-BA85-6007CAEDCF9D}", "Temporary Internet Files"),
+    ("{D9DC8A3B-B784-432E-A781-5A1130A75963}", "History"),
+    ("{4C5C32FF-BB9D-43B0-B5B4-2D72E54EAAA4}", "Saved Games"),
+    ("{56784854-C6CB-462B-8169-88E350ACB882}", "Contacts"),
+    ("{BFB9D5E0-C6A9-404C-B2B2-AE6DB6AF4968}", "Links"),
+    ("{7D1D3A04-DEBB-4115-95CF-2F29DA2920DA}", "Searches"),
+    ("{A63293E8-664E-48DB-A079-DF759E0509F7}", "Templates"),
+    ("{C4AA340D-F20F-4863-AFEF-F87EF2E6BA25}", "Public Desktop"),
+    ("{DFDF76A2-C82A-4D63-906A-5644AC457385}", "Public"),
+    ("{ED4824AF-DCE4-45A8-81E2-FC7965083634}", "Public Documents"),
+    ("{3D644C9B-1FB8-4F30-9B45-F670235F79C0}", "Public Downloads"),
+    ("{018D5C66-4533-4307-9B53-224DE2ED1FE6}", "OneDrive"),
+    ("{0AC0837C-BBF8-452A-850D-79D08E667CA7}", "Computer"),
+    ("{4234D49B-0245-4DF3-B780-3893943456E1}", "Applications"),
+];
+
+/// Friendly name of a well-known folder GUID (braced, any case)
+pub fn known_folder_name(guid: &str) -> Option<&'static str> {
+    KNOWN_FOLDERS.iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(guid))
+        .map(|(_, name)| *name)
+}
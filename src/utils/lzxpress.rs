@@ -0,0 +1,227 @@
+// src/utils/lzxpress.rs
+//! LZXPRESS decompression ([MS-XCA]): the Huffman variant used by Windows 10
+//! prefetch files and the plain LZ77 variant used by ESE databases
+use anyhow::{Result, bail};
+
+/// Literals, then 16 match lengths for each of 16 offset bit counts
+const SYMBOLS: usize = 512;
+const TABLE_SIZE: usize = SYMBOLS / 2;
+const MAX_CODE_LENGTH: u32 = 15;
+/// Each Huffman table covers this much output
+const BLOCK_SIZE: usize = 65536;
+
+/// Decompress `data` into exactly `output_size` bytes
+pub fn decompress_lzxpress_huffman(data: &[u8], output_size: usize) -> Result<Vec<u8>> {
+    // Every block starts with its own table, which bounds the expansion and
+    // rejects implausible sizes before anything is allocated
+    if output_size.div_ceil(BLOCK_SIZE) * TABLE_SIZE > data.len() {
+        bail!("LZXPRESS data too short for {} bytes of output", output_size);
+    }
+
+    let mut output = Vec::with_capacity(output_size);
+    let mut input = Input { data, pos: 0 };
+
+    while output.len() < output_size {
+        let Some(table) = data.get(input.pos..input.pos + TABLE_SIZE) else {
+            bail!("LZXPRESS data truncated before a Huffman table");
+        };
+        input.pos += TABLE_SIZE;
+        let (decoding, lengths) = decoding_table(table)?;
+
+        let mut bits = Bits::new(&mut input);
+        let block_end = (output.len() + BLOCK_SIZE).min(output_size);
+
+        while output.len() < block_end {
+            let symbol = decoding[bits.peek(MAX_CODE_LENGTH) as usize] as usize;
+            bits.consume(lengths[symbol] as u32);
+
+            if symbol < 256 {
+                output.push(symbol as u8);
+                continue;
+            }
+
+            let symbol = symbol - 256;
+            let offset_bits = (symbol >> 4) as u32;
+            let mut length = symbol & 0x0F;
+            if length == 15 {
+                length = bits.input.byte() as usize;
+                if length == 255 {
+                    length = bits.input.u16() as usize;
+                    if length == 0 {
+                        length = bits.input.u32() as usize;
+                    }
+                    if length < 15 {
+                        bail!("Corrupt LZXPRESS match length");
+                    }
+                    length -= 15;
+                }
+                length += 15;
+            }
+            length += 3;
+
+            let offset = (bits.peek(offset_bits) as usize) + (1 << offset_bits);
+            bits.consume(offset_bits);
+
+            if offset > output.len() {
+                bail!("LZXPRESS match offset {} before start of output", offset);
+            }
+            // Copy bytewise: matches may overlap their own output
+            let start = output.len() - offset;
+            for i in 0..length.min(output_size - output.len()) {
+                output.push(output[start + i]);
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Decompress plain LZ77 LZXPRESS ([MS-XCA] 2.4): 32-bit flag words
+/// select literals or 16-bit match descriptors, with long match lengths
+/// spread over shared half-bytes and extra bytes. Decoding stops after
+/// `max_size` bytes, as a single match may claim up to 4 GiB.
+pub fn decompress_lzxpress(data: &[u8], max_size: usize) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut input = Input { data, pos: 0 };
+    let (mut flags, mut flag_count) = (0u32, 0);
+    let mut half_byte: Option<usize> = None;
+
+    while output.len() < max_size {
+        if flag_count == 0 {
+            if input.pos + 4 > data.len() {
+                break;
+            }
+            flags = input.u32();
+            flag_count = 32;
+        }
+        flag_count -= 1;
+
+        if input.pos >= data.len() {
+            break;
+        }
+        if flags & (1 << flag_count) == 0 {
+            output.push(input.byte());
+            continue;
+        }
+
+        let match_bytes = input.u16() as usize;
+        let offset = (match_bytes >> 3) + 1;
+        let mut length = match_bytes & 7;
+        if length == 7 {
+            length = match half_byte.take() {
+                Some(pos) => (data[pos] >> 4) as usize,
+                None => {
+                    half_byte = Some(input.pos);
+                    (input.byte() & 0x0F) as usize
+                }
+            };
+            if length == 15 {
+                length = input.byte() as usize;
+                if length == 255 {
+                    length = input.u16() as usize;
+                    if length == 0 {
+                        length = input.u32() as usize;
+                    }
+                    if length < 15 + 7 {
+                        bail!("Corrupt LZXPRESS match length");
+                    }
+                    length -= 15 + 7;
+                }
+                length += 15;
+            }
+            length += 7;
+        }
+        length += 3;
+
+        if offset > output.len() {
+            bail!("LZXPRESS match offset {} before start of output", offset);
+        }
+        let start = output.len() - offset;
+        for i in 0..length.min(max_size - output.len()) {
+            output.push(output[start + i]);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Canonical Huffman decoding table indexed by the next 15 bits
+fn decoding_table(table: &[u8]) -> Result<(Vec<u16>, [u8; SYMBOLS])> {
+    let mut lengths = [0u8; SYMBOLS];
+    for (i, byte) in table.iter().enumerate() {
+        lengths[i * 2] = byte & 0x0F;
+        lengths[i * 2 + 1] = byte >> 4;
+    }
+
+    let mut decoding = vec![0u16; 1 << MAX_CODE_LENGTH];
+    let mut entry = 0usize;
+    for length in 1..=MAX_CODE_LENGTH as u8 {
+        for symbol in (0..SYMBOLS).filter(|&s| lengths[s] == length) {
+            let count = 1usize << (MAX_CODE_LENGTH as u8 - length);
+            let Some(entries) = decoding.get_mut(entry..entry + count) else {
+                bail!("Invalid LZXPRESS Huffman table");
+            };
+            entries.fill(symbol as u16);
+            entry += count;
+        }
+    }
+    if entry != decoding.len() {
+        bail!("Incomplete LZXPRESS Huffman table");
+    }
+
+    Ok((decoding, lengths))
+}
+
+/// Byte-level input; reads past the end yield zeros, which only matters
+/// for corrupt data since the output size bounds decoding
+struct Input<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Input<'_> {
+    fn byte(&mut self) -> u8 {
+        let value = self.data.get(self.pos).copied().unwrap_or(0);
+        self.pos += 1;
+        value
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes([self.byte(), self.byte()])
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes([self.byte(), self.byte(), self.byte(), self.byte()])
+    }
+}
+
+/// Bit stream read MSB-first from 16-bit little-endian words, interleaved
+/// with the raw bytes of long match lengths
+struct Bits<'a, 'b> {
+    input: &'a mut Input<'b>,
+    next: u32,
+    extra: i32,
+}
+
+impl<'a, 'b> Bits<'a, 'b> {
+    fn new(input: &'a mut Input<'b>) -> Self {
+        let next = ((input.u16() as u32) << 16) | input.u16() as u32;
+        Self { input, next, extra: 16 }
+    }
+
+    fn peek(&self, count: u32) -> u32 {
+        if count == 0 { 0 } else { self.next >> (32 - count) }
+    }
+
+    fn consume(&mut self, count: u32) {
+        if count == 0 {
+            return;
+        }
+        self.next <<= count;
+        self.extra -= count as i32;
+        if self.extra < 0 {
+            self.next |= (self.input.u16() as u32) << (-self.extra);
+            self.extra += 16;
+        }
+    }
+}
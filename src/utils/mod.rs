@@ -0,0 +1,9 @@
+// src/utils/mod.rs
+pub mod bytes;
+pub mod cfb;
+pub mod guid;
+pub mod lzxpress;
+pub mod sid;
+pub mod sqlite;
+pub mod time;
+pub mod xml;
@@ -0,0 +1,35 @@
+// src/utils/sid.rs
+
+/// Parse a binary SID, returning its string form and the number of bytes it occupies
+pub fn parse_sid(data: &[u8]) -> Option<(String, usize)> {
+    if data.len() < 8 || data[0] != 1 {
+        return None;
+    }
+
+    let sub_count = data[1] as usize;
+    let length = 8 + sub_count * 4;
+    if data.len() < length {
+        return None;
+    }
+
+    // The identifier authority is a 48-bit big-endian value
+    let authority = data[2..8]
+        .iter()
+        .fold(0u64, |acc, b| (acc << 8) | *b as u64);
+
+    let mut sid = format!("S-1-{}", authority);
+    for i in 0..sub_count {
+        let offset = 8 + i * 4;
+        let sub = u32::from_le_bytes([
+            data[offset], data[offset + 1], data[offset + 2], data[offset + 3],
+        ]);
+        sid.push_str(&format!("-{}", sub));
+    }
+
+    Some((sid, length))
+}
+
+/// Format a binary SID as `S-1-...`
+pub fn format_sid(data: &[u8]) -> Option<String> {
+    parse_sid(data).map(|(sid, _)| sid)
+}
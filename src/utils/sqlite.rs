@@ -0,0 +1,460 @@
+// src/utils/sqlite.rs
+//! Read-only SQLite database reader. Tables are read by walking their
+//! B-trees, with the committed frames of a write-ahead log applied on top;
+//! indexes and WITHOUT ROWID tables are not read.
+use std::collections::{BTreeMap, HashMap, HashSet};
+use anyhow::{Result, bail, Context};
+use serde::{Serialize, Deserialize};
+
+const SIGNATURE: &[u8] = b"SQLite format 3\0";
+const HEADER_SIZE: usize = 100;
+
+/// The low bit selects big-endian checksums
+const WAL_MAGIC: u32 = 0x377F_0682;
+const WAL_HEADER_SIZE: usize = 32;
+const WAL_FRAME_HEADER_SIZE: usize = 24;
+
+const PAGE_INTERIOR_TABLE: u8 = 0x05;
+const PAGE_LEAF_TABLE: u8 = 0x0D;
+
+const TEXT_UTF16LE: u32 = 2;
+const TEXT_UTF16BE: u32 = 3;
+
+/// B-tree depth beyond which pages are assumed to loop
+const MAX_TREE_DEPTH: usize = 32;
+
+/// A stored value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SqliteValue {
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+/// Column values by name; null columns are absent
+pub type SqliteRow = BTreeMap<String, SqliteValue>;
+
+impl SqliteValue {
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            SqliteValue::Integer(value) => Some(*value),
+            SqliteValue::Real(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SqliteValue::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            SqliteValue::Blob(data) => Some(data),
+            SqliteValue::Text(text) => Some(text.as_bytes()),
+            _ => None,
+        }
+    }
+}
+
+/// A rowid table from `sqlite_master`
+#[derive(Debug, Clone)]
+pub struct SqliteTable {
+    pub name: String,
+    pub root_page: u32,
+    pub columns: Vec<String>,
+    /// The `INTEGER PRIMARY KEY` column, stored as the rowid
+    rowid_column: Option<usize>,
+}
+
+pub struct SqliteDatabase<'a> {
+    data: &'a [u8],
+    page_size: usize,
+    /// Page size less the bytes reserved at the end of each page
+    usable_size: usize,
+    encoding: u32,
+    /// The latest committed copy of each page in the WAL
+    wal_pages: HashMap<u32, &'a [u8]>,
+    tables: Vec<SqliteTable>,
+}
+
+impl<'a> SqliteDatabase<'a> {
+    /// Parse a database and, if given, the contents of its `-wal` file
+    pub fn parse(data: &'a [u8], wal: Option<&'a [u8]>) -> Result<Self> {
+        if data.len() < HEADER_SIZE || !data.starts_with(SIGNATURE) {
+            bail!("Not a SQLite database");
+        }
+        let page_size = match u16::from_be_bytes([data[16], data[17]]) {
+            1 => 65536,
+            size if size.is_power_of_two() && size >= 512 => size as usize,
+            size => bail!("Invalid SQLite page size {}", size),
+        };
+        let reserved = data[20] as usize;
+        let encoding = u32::from_be_bytes(data[56..60].try_into().unwrap());
+
+        let wal_pages = match wal {
+            Some(wal) if !wal.is_empty() => read_wal(wal, page_size).unwrap_or_else(|e| {
+                log::debug!("Ignoring SQLite WAL: {}", e);
+                HashMap::new()
+            }),
+            _ => HashMap::new(),
+        };
+
+        let mut database = Self {
+            data,
+            page_size,
+            usable_size: page_size.saturating_sub(reserved),
+            encoding,
+            wal_pages,
+            tables: Vec::new(),
+        };
+        database.tables = database.read_schema().context("Failed to read SQLite schema")?;
+        Ok(database)
+    }
+
+    pub fn tables(&self) -> &[SqliteTable] {
+        &self.tables
+    }
+
+    /// Find a table by name, ignoring case
+    pub fn table(&self, name: &str) -> Option<&SqliteTable> {
+        self.tables.iter().find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Every row of a table, in rowid order
+    pub fn rows(&self, name: &str) -> Result<Vec<SqliteRow>> {
+        let Some(table) = self.table(name) else {
+            bail!("SQLite table '{}' not found", name);
+        };
+
+        let mut rows = Vec::new();
+        for (rowid, payload) in self.cells(table.root_page)? {
+            let Some(values) = self.decode_record(&payload) else {
+                log::debug!("Skipping corrupt row {} in {}", rowid, table.name);
+                continue;
+            };
+            let mut row = SqliteRow::new();
+            for (i, column) in table.columns.iter().enumerate() {
+                let value = match values.get(i).cloned().flatten() {
+                    None if table.rowid_column == Some(i) => Some(SqliteValue::Integer(rowid)),
+                    value => value,
+                };
+                if let Some(value) = value {
+                    row.insert(column.clone(), value);
+                }
+            }
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
+    fn page(&self, number: u32) -> Result<&'a [u8]> {
+        if let Some(page) = self.wal_pages.get(&number) {
+            return Ok(page);
+        }
+        let offset = (number as usize).checked_sub(1).context("Invalid SQLite page 0")? * self.page_size;
+        self.data.get(offset..offset + self.page_size)
+            .with_context(|| format!("SQLite page {} is beyond the end of the file", number))
+    }
+
+    /// (rowid, payload) of each cell in the table B-tree rooted at `root`
+    fn cells(&self, root: u32) -> Result<Vec<(i64, Vec<u8>)>> {
+        let mut cells = Vec::new();
+        let mut visited = HashSet::new();
+        self.walk(root, 0, &mut visited, &mut cells)?;
+        Ok(cells)
+    }
+
+    fn walk(&self, number: u32, depth: usize, visited: &mut HashSet<u32>, cells: &mut Vec<(i64, Vec<u8>)>) -> Result<()> {
+        if depth > MAX_TREE_DEPTH || !visited.insert(number) {
+            bail!("SQLite B-tree loop at page {}", number);
+        }
+        let page = self.page(number)?;
+        // Page 1 starts with the database header
+        let header = if number == 1 { HEADER_SIZE } else { 0 };
+        let Some(&kind) = page.get(header) else {
+            bail!("Truncated SQLite page {}", number);
+        };
+        let cell_count = be16(page, header + 3).unwrap_or_default() as usize;
+        let pointers = header + if kind == PAGE_INTERIOR_TABLE { 12 } else { 8 };
+        let cell_offsets = (0..cell_count).filter_map(|i| be16(page, pointers + i * 2).map(|o| o as usize));
+
+        match kind {
+            PAGE_LEAF_TABLE => {
+                for offset in cell_offsets {
+                    match self.leaf_cell(page, offset) {
+                        Some(cell) => cells.push(cell),
+                        None => log::debug!("Skipping corrupt cell on SQLite page {}", number),
+                    }
+                }
+            }
+            PAGE_INTERIOR_TABLE => {
+                let children = cell_offsets.filter_map(|offset| be32(page, offset));
+                let right_most = be32(page, header + 8);
+                for child in children.chain(right_most).collect::<Vec<_>>() {
+                    if let Err(e) = self.walk(child, depth + 1, visited, cells) {
+                        log::debug!("Skipping SQLite page {}: {}", child, e);
+                    }
+                }
+            }
+            other => bail!("Unexpected SQLite page type {:#04x} in a table B-tree", other),
+        }
+        Ok(())
+    }
+
+    /// A leaf cell's rowid and payload, following any overflow pages
+    fn leaf_cell(&self, page: &[u8], offset: usize) -> Option<(i64, Vec<u8>)> {
+        let (size, n) = varint(page.get(offset..)?)?;
+        let (rowid, m) = varint(page.get(offset + n..)?)?;
+        let size = size as usize;
+        let start = offset + n + m;
+
+        // Payload beyond what fits on the page spills into a chain of
+        // overflow pages, each starting with the next page's number
+        let usable = self.usable_size;
+        let max_local = usable - 35;
+        let local = if size <= max_local {
+            size
+        } else {
+            let min_local = (usable - 12) * 32 / 255 - 23;
+            let local = min_local + (size - min_local) % (usable - 4);
+            if local <= max_local { local } else { min_local }
+        };
+
+        let mut payload = page.get(start..start + local)?.to_vec();
+        let mut next = if local < size { be32(page, start + local)? } else { 0 };
+        let mut visited = HashSet::new();
+        while payload.len() < size {
+            if next == 0 || !visited.insert(next) {
+                return None;
+            }
+            let overflow = self.page(next).ok()?;
+            let take = (size - payload.len()).min(usable - 4);
+            payload.extend_from_slice(overflow.get(4..4 + take)?);
+            next = be32(overflow, 0)?;
+        }
+        Some((rowid as i64, payload))
+    }
+
+    /// Values of a record, `None` for NULL
+    fn decode_record(&self, payload: &[u8]) -> Option<Vec<Option<SqliteValue>>> {
+        let (header_size, mut pos) = varint(payload)?;
+        let header_size = header_size as usize;
+        let mut body = header_size;
+        let mut values = Vec::new();
+
+        while pos < header_size {
+            let (serial_type, n) = varint(payload.get(pos..header_size)?)?;
+            pos += n;
+            let int = |size: usize| -> Option<i64> {
+                let bytes = payload.get(body..body + size)?;
+                // Sign-extend from the top byte
+                let first = (bytes[0] as i8) as i64;
+                Some(bytes[1..].iter().fold(first, |value, &b| (value << 8) | b as i64))
+            };
+            let (value, size) = match serial_type {
+                0 => (None, 0),
+                1..=4 => {
+                    let size = serial_type as usize;
+                    (Some(SqliteValue::Integer(int(size)?)), size)
+                }
+                5 => (Some(SqliteValue::Integer(int(6)?)), 6),
+                6 => (Some(SqliteValue::Integer(int(8)?)), 8),
+                7 => (Some(SqliteValue::Real(f64::from_bits(int(8)? as u64))), 8),
+                8 => (Some(SqliteValue::Integer(0)), 0),
+                9 => (Some(SqliteValue::Integer(1)), 0),
+                n if n >= 12 && n % 2 == 0 => {
+                    let size = (n as usize - 12) / 2;
+                    (Some(SqliteValue::Blob(payload.get(body..body + size)?.to_vec())), size)
+                }
+                n if n >= 13 => {
+                    let size = (n as usize - 13) / 2;
+                    (Some(SqliteValue::Text(self.text(payload.get(body..body + size)?))), size)
+                }
+                _ => return None,
+            };
+            values.push(value);
+            body += size;
+        }
+        Some(values)
+    }
+
+    fn text(&self, data: &[u8]) -> String {
+        let units = |convert: fn([u8; 2]) -> u16| -> Vec<u16> {
+            data.chunks_exact(2).map(|c| convert([c[0], c[1]])).collect()
+        };
+        match self.encoding {
+            TEXT_UTF16LE => String::from_utf16_lossy(&units(u16::from_le_bytes)),
+            TEXT_UTF16BE => String::from_utf16_lossy(&units(u16::from_be_bytes)),
+            _ => String::from_utf8_lossy(data).into_owned(),
+        }
+    }
+
+    /// Rowid tables from `sqlite_master`, which is rooted at page 1
+    fn read_schema(&self) -> Result<Vec<SqliteTable>> {
+        let mut tables = Vec::new();
+        for (_, payload) in self.cells(1)? {
+            let Some(values) = self.decode_record(&payload) else {
+                continue;
+            };
+            let text = |i: usize| match values.get(i) {
+                Some(Some(SqliteValue::Text(text))) => Some(text.as_str()),
+                _ => None,
+            };
+            let root_page = match values.get(3) {
+                Some(Some(SqliteValue::Integer(page))) => *page as u32,
+                _ => 0,
+            };
+            let (Some("table"), Some(name), Some(sql)) = (text(0), text(1), text(4)) else {
+                continue;
+            };
+            if root_page == 0 || sql.to_uppercase().contains("WITHOUT ROWID") {
+                continue;
+            }
+
+            let definitions = column_definitions(sql);
+            let rowid_column = definitions.iter().position(|(_, rest)| {
+                let rest = rest.to_uppercase();
+                rest.starts_with("INTEGER") && rest.split_whitespace().collect::<Vec<_>>().join(" ").contains("PRIMARY KEY")
+            });
+            tables.push(SqliteTable {
+                name: name.to_string(),
+                root_page,
+                columns: definitions.into_iter().map(|(name, _)| name).collect(),
+                rowid_column,
+            });
+        }
+        Ok(tables)
+    }
+}
+
+/// Committed frames of a WAL file, by page number. Frames are read while
+/// their salts and running checksum hold; those after the last commit
+/// frame are discarded.
+fn read_wal(wal: &[u8], page_size: usize) -> Result<HashMap<u32, &[u8]>> {
+    if wal.len() < WAL_HEADER_SIZE {
+        bail!("Truncated WAL header");
+    }
+    let magic = u32::from_be_bytes(wal[0..4].try_into().unwrap());
+    if magic & !1 != WAL_MAGIC {
+        bail!("Invalid WAL signature");
+    }
+    let big_endian = magic & 1 != 0;
+    if be32(wal, 8) != Some(page_size as u32) {
+        bail!("WAL page size does not match the database");
+    }
+    let salts = &wal[16..24];
+    let mut checksum = wal_checksum(&wal[..24], big_endian, (0, 0));
+    if checksum != (be32(wal, 24).unwrap(), be32(wal, 28).unwrap()) {
+        bail!("WAL header checksum mismatch");
+    }
+
+    let mut committed = HashMap::new();
+    let mut pending = Vec::new();
+    let frame_size = WAL_FRAME_HEADER_SIZE + page_size;
+    for frame in wal[WAL_HEADER_SIZE..].chunks_exact(frame_size) {
+        if &frame[8..16] != salts {
+            break;
+        }
+        checksum = wal_checksum(&frame[..8], big_endian, checksum);
+        checksum = wal_checksum(&frame[WAL_FRAME_HEADER_SIZE..], big_endian, checksum);
+        if checksum != (be32(frame, 16).unwrap(), be32(frame, 20).unwrap()) {
+            break;
+        }
+
+        pending.push((be32(frame, 0).unwrap(), &frame[WAL_FRAME_HEADER_SIZE..]));
+        // A non-zero database size marks the last frame of a transaction
+        if be32(frame, 4) != Some(0) {
+            committed.extend(pending.drain(..));
+        }
+    }
+    Ok(committed)
+}
+
+fn wal_checksum(data: &[u8], big_endian: bool, (mut s0, mut s1): (u32, u32)) -> (u32, u32) {
+    let word = |b: &[u8]| {
+        let b = b.try_into().unwrap();
+        if big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) }
+    };
+    for chunk in data.chunks_exact(8) {
+        s0 = s0.wrapping_add(word(&chunk[..4])).wrapping_add(s1);
+        s1 = s1.wrapping_add(word(&chunk[4..])).wrapping_add(s0);
+    }
+    (s0, s1)
+}
+
+/// Column names and the rest of their definitions from a CREATE TABLE
+/// statement, skipping table constraints
+fn column_definitions(sql: &str) -> Vec<(String, String)> {
+    let Some(start) = sql.find('(') else {
+        return Vec::new();
+    };
+
+    // Split on commas outside parentheses and quotes
+    let mut definitions = Vec::new();
+    let (mut depth, mut quote, mut current) = (0, None, String::new());
+    for c in sql[start + 1..].chars() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '[') => quote = Some(']'),
+            (None, '(') => depth += 1,
+            (None, ')') if depth == 0 => break,
+            (None, ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                definitions.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    definitions.push(current);
+
+    definitions.iter()
+        .filter_map(|definition| {
+            let definition = definition.trim();
+            let (name, rest) = match definition.chars().next()? {
+                open @ ('"' | '`' | '[') => {
+                    let close = if open == '[' { ']' } else { open };
+                    let end = definition[1..].find(close)? + 1;
+                    (&definition[1..end], &definition[end + 1..])
+                }
+                _ => definition.split_once(char::is_whitespace).unwrap_or((definition, "")),
+            };
+            let keyword = name.to_uppercase();
+            if ["CONSTRAINT", "PRIMARY", "UNIQUE", "CHECK", "FOREIGN"].contains(&keyword.as_str()) {
+                return None;
+            }
+            Some((name.to_string(), rest.trim().to_string()))
+        })
+        .collect()
+}
+
+/// A big-endian variable-length integer and its size
+fn varint(data: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in data.iter().take(9).enumerate() {
+        if i == 8 {
+            return Some(((value << 8) | byte as u64, 9));
+        }
+        value = (value << 7) | (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+fn be16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn be32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+}
@@ -0,0 +1,90 @@
+// src/utils/time.rs
+use anyhow::{Result, bail};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Seconds between the FILETIME epoch (1601-01-01) and the Unix epoch
+const FILETIME_UNIX_DIFF: i64 = 11_644_473_600;
+
+/// Convert a Windows FILETIME (100ns intervals since 1601) to UTC.
+/// Zero and out-of-range values yield `None`.
+pub fn filetime_to_datetime(filetime: u64) -> Option<DateTime<Utc>> {
+    if filetime == 0 {
+        return None;
+    }
+
+    let secs = (filetime / 10_000_000) as i64 - FILETIME_UNIX_DIFF;
+    let nanos = (filetime % 10_000_000) as u32 * 100;
+    DateTime::from_timestamp(secs, nanos)
+}
+
+/// Convert a 16-byte SYSTEMTIME structure to UTC
+pub fn systemtime_to_datetime(data: &[u8]) -> Option<DateTime<Utc>> {
+    if data.len() < 16 {
+        return None;
+    }
+
+    let field = |i: usize| u16::from_le_bytes([data[i * 2], data[i * 2 + 1]]) as u32;
+    // Field 2 is the day of the week, which is redundant
+    let date = NaiveDate::from_ymd_opt(field(0) as i32, field(1), field(3))?;
+    let time = date.and_hms_milli_opt(field(4), field(5), field(6), field(7))?;
+    Some(time.and_utc())
+}
+
+/// Convert an MS-DOS (FAT) date and time pair to a timestamp, which has
+/// two-second resolution and no time zone
+pub fn dos_datetime_to_datetime(date: u16, time: u16) -> Option<DateTime<Utc>> {
+    if date == 0 {
+        return None;
+    }
+
+    let day = NaiveDate::from_ymd_opt(
+        1980 + (date >> 9) as i32,
+        ((date >> 5) & 0x0F) as u32,
+        (date & 0x1F) as u32,
+    )?;
+    let time = day.and_hms_opt(
+        (time >> 11) as u32,
+        ((time >> 5) & 0x3F) as u32,
+        ((time & 0x1F) * 2) as u32,
+    )?;
+    Some(time.and_utc())
+}
+
+/// Convert Unix epoch seconds to UTC, treating zero as unset
+pub fn unix_to_datetime(secs: i64) -> Option<DateTime<Utc>> {
+    if secs == 0 {
+        return None;
+    }
+
+    DateTime::from_timestamp(secs, 0)
+}
+
+/// Convert a WebKit/Chromium timestamp (microseconds since 1601) to UTC
+pub fn webkit_to_datetime(micros: i64) -> Option<DateTime<Utc>> {
+    if micros <= 0 {
+        return None;
+    }
+
+    filetime_to_datetime((micros as u64).checked_mul(10)?)
+}
+
+/// Convert a Mozilla PRTime (microseconds since the Unix epoch) to UTC
+pub fn prtime_to_datetime(micros: i64) -> Option<DateTime<Utc>> {
+    if micros == 0 {
+        return None;
+    }
+
+    DateTime::from_timestamp_micros(micros)
+}
+
+/// Parse a user-supplied timestamp, either RFC 3339 or a bare `YYYY-MM-DD` date (midnight UTC)
+pub fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        Ok(date) => Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc()),
+        Err(_) => bail!("Invalid timestamp '{}', expected RFC 3339 or YYYY-MM-DD", s),
+    }
+}
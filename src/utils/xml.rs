@@ -0,0 +1,110 @@
+// src/utils/xml.rs
+//! Minimal helpers for the small, well-formed XML documents Windows keeps
+//! on disk (WLAN profiles, scheduled tasks). Not a general XML parser.
+
+/// Contents of the first `<tag>` element at any depth. Element names are
+/// matched exactly, so documents using namespace prefixes need them in `tag`.
+pub fn element<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}", tag);
+    let mut from = 0;
+
+    // Skip longer names that share the prefix, e.g. <nameX>
+    while let Some(found) = xml[from..].find(&open) {
+        let start = from + found + open.len();
+        match xml[start..].chars().next() {
+            Some('>') => {
+                let close = format!("</{}>", tag);
+                let end = start + 1 + xml[start + 1..].find(&close)?;
+                return Some(&xml[start + 1..end]);
+            }
+            Some(c) if c.is_whitespace() => {
+                let body = start + xml[start..].find('>')? + 1;
+                if xml[..body].ends_with("/>") {
+                    return Some("");
+                }
+                let close = format!("</{}>", tag);
+                let end = body + xml[body..].find(&close)?;
+                return Some(&xml[body..end]);
+            }
+            _ => from = start,
+        }
+    }
+
+    None
+}
+
+/// Trimmed, unescaped text of the first `<tag>` element, if not empty
+pub fn element_text(xml: &str, tag: &str) -> Option<String> {
+    element(xml, tag)
+        .map(|text| unescape(text.trim()))
+        .filter(|text| !text.is_empty())
+}
+
+pub fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Child elements of an element's content, in order, as (name, content).
+/// Comments, processing instructions and text between elements are skipped.
+pub fn children(xml: &str) -> Vec<(&str, &str)> {
+    let mut children = Vec::new();
+    let mut pos = 0;
+
+    while let Some(found) = xml[pos..].find('<') {
+        let start = pos + found;
+        let rest = &xml[start..];
+        if rest.starts_with("<!--") {
+            pos = start + rest.find("-->").map_or(rest.len(), |end| end + 3);
+            continue;
+        }
+        if rest.starts_with("<?") || rest.starts_with("<!") || rest.starts_with("</") {
+            pos = start + rest.find('>').map_or(rest.len(), |end| end + 1);
+            continue;
+        }
+
+        let name_end = rest[1..].find(|c: char| c.is_whitespace() || c == '>' || c == '/').map_or(rest.len(), |n| n + 1);
+        let name = &rest[1..name_end];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        if rest[..tag_end].ends_with('/') {
+            children.push((name, ""));
+            pos = start + tag_end + 1;
+            continue;
+        }
+
+        // Find the matching close tag, allowing for nested same-name elements
+        let body = start + tag_end + 1;
+        let (open, close) = (format!("<{}", name), format!("</{}>", name));
+        let (mut depth, mut at) = (1, body);
+        while depth > 0 {
+            let next_close = xml[at..].find(&close).map(|n| at + n);
+            let next_open = xml[at..].find(&open).map(|n| at + n)
+                .filter(|&n| xml[n + open.len()..].starts_with(|c: char| c.is_whitespace() || c == '>'));
+            match (next_open, next_close) {
+                (Some(open_at), Some(close_at)) if open_at < close_at => {
+                    let self_closing = xml[open_at..].find('>').is_some_and(|end| xml[..open_at + end].ends_with('/'));
+                    if !self_closing {
+                        depth += 1;
+                    }
+                    at = open_at + open.len();
+                }
+                (_, Some(close_at)) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        children.push((name, &xml[body..close_at]));
+                    }
+                    at = close_at + close.len();
+                }
+                (_, None) => return children,
+            }
+        }
+        pos = at;
+    }
+
+    children
+}
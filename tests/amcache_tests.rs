@@ -0,0 +1,149 @@
+// tests/amcache_tests.rs
+mod common;
+
+use common::{build_hive, Key};
+use forensic_triage::registry::{parse_amcache, strip_sha1_padding, Amcache, RegistryHive};
+
+/// 2020-01-01 as a FILETIME
+const JAN_2020: u64 = 132_223_104_000_000_000;
+const DAY: u64 = 864_000_000_000;
+const SHA1: &str = "a94a8fe5ccb19ba61c4c0873d391e987982fbbd3";
+const VOLUME: &str = "{5b4ec1b1-0000-0000-0000-100000000000}";
+
+fn parse(root: Key) -> Amcache {
+    parse_amcache(&RegistryHive::from_bytes(build_hive(root)).unwrap())
+}
+
+fn date(time: Option<chrono::DateTime<chrono::Utc>>) -> String {
+    time.unwrap().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+#[test]
+fn test_legacy_file_layout() {
+    // File reference 0x0003_0000_0000_04D2: sequence 3, MFT entry 1234
+    let amcache = parse(Key::new("ROOT")
+        .subkey(&format!("Root\\File\\{}\\30000000004d2", VOLUME), Key::new("30000000004d2")
+            .last_write(JAN_2020)
+            .string("0", "Windows Tools")
+            .string("1", "Example Corp")
+            .string("5", "1.2.3.4")
+            .dword("6", 40_960)
+            .string("c", "Example tool")
+            .dword("f", 1_577_836_800)
+            .qword("11", JAN_2020 - DAY)
+            .qword("12", JAN_2020 - 2 * DAY)
+            .string("15", "C:\\Tools\\tool.exe")
+            .string("100", "0000f4ad8b4c2b9c")
+            .string("101", &format!("0000{}", SHA1)))
+        // A file reference that is not hex still yields an entry
+        .subkey(&format!("Root\\File\\{}\\bogus", VOLUME), Key::new("bogus")
+            .string("15", "C:\\Tools\\other.exe")));
+
+    assert_eq!(amcache.files.len(), 2);
+    assert!(amcache.applications.is_empty() && amcache.drivers.is_empty() && amcache.devices.is_empty());
+
+    let file = amcache.files.iter().find(|f| f.mft_entry.is_some()).unwrap();
+    assert_eq!(file.path.as_deref(), Some("C:\\Tools\\tool.exe"));
+    assert_eq!(file.sha1.as_deref(), Some(SHA1));
+    assert_eq!(file.publisher.as_deref(), Some("Example Corp"));
+    assert_eq!(file.product_name.as_deref(), Some("Windows Tools"));
+    assert_eq!(file.description.as_deref(), Some("Example tool"));
+    assert_eq!(file.version.as_deref(), Some("1.2.3.4"));
+    assert_eq!(file.size, Some(40_960));
+    assert_eq!(file.program_id.as_deref(), Some("0000f4ad8b4c2b9c"));
+    assert_eq!(date(file.link_date), "2020-01-01 00:00:00");
+    assert_eq!(date(file.first_seen), "2020-01-01 00:00:00");
+    assert_eq!(date(file.last_modified), "2019-12-31 00:00:00");
+    assert_eq!(date(file.created), "2019-12-30 00:00:00");
+    assert_eq!((file.mft_entry, file.mft_sequence), (Some(1234), Some(3)));
+
+    let other = amcache.files.iter().find(|f| f.mft_entry.is_none()).unwrap();
+    assert_eq!(other.path.as_deref(), Some("C:\\Tools\\other.exe"));
+    assert_eq!(other.mft_sequence, None);
+}
+
+#[test]
+fn test_inventory_layouts() {
+    let amcache = parse(Key::new("ROOT")
+        .subkey("Root\\InventoryApplicationFile\\tool.exe|1a2b3c4d", Key::new("tool.exe|1a2b3c4d")
+            .last_write(JAN_2020)
+            .string("LowerCaseLongPath", "c:\\tools\\tool.exe")
+            .string("FileId", &format!("0000{}", SHA1.to_uppercase()))
+            .string("Publisher", "example corp")
+            .string("ProductName", "windows tools")
+            .string("BinFileVersion", "1.2.3.4")
+            .qword("Size", 40_960)
+            .string("LinkDate", "03/05/2024 14:30:00")
+            .string("ProgramId", "0000f4ad8b4c2b9c"))
+        .subkey("Root\\InventoryApplication\\0000f4ad8b4c2b9c", Key::new("0000f4ad8b4c2b9c")
+            .string("Name", "Windows Tools")
+            .string("Version", "1.2")
+            .string("Publisher", "Example Corp")
+            // Installers sometimes record only the date
+            .string("InstallDate", "03/05/2024")
+            .string("Source", "AddRemoveProgram")
+            .string("RootDirPath", "C:\\Tools")
+            .string("UninstallString", "C:\\Tools\\uninstall.exe"))
+        .subkey("Root\\InventoryDriverBinary\\c:/windows/system32/drivers/example.sys", Key::new("c:/windows/system32/drivers/example.sys")
+            .string("DriverName", "example.sys")
+            .string("DriverId", &format!("0000{}", SHA1))
+            .string("DriverCompany", "Example Corp")
+            .string("DriverVersion", "10.0.1")
+            .string("Service", "example")
+            .dword("DriverSigned", 1)
+            .dword("DriverTimeStamp", 1_577_836_800)
+            .string("DriverLastWriteTime", "01/02/2020 08:00:00"))
+        .subkey("Root\\InventoryDevicePnp\\usb#vid_0781&pid_5567#serial", Key::new("usb#vid_0781&pid_5567#serial")
+            .string("Description", "USB Mass Storage Device")
+            .string("Manufacturer", "Compatible USB storage device")
+            .string("Class", "USB")
+            .string("DriverVerVersion", "10.0.19041.1")
+            .string("Service", "USBSTOR")
+            .string("ParentId", "usb\\root_hub30")));
+
+    let [file] = amcache.files.as_slice() else {
+        panic!("expected one file, got {:?}", amcache.files);
+    };
+    assert_eq!(file.path.as_deref(), Some("c:\\tools\\tool.exe"));
+    assert_eq!(file.sha1.as_deref(), Some(SHA1));
+    // BinFileVersion stands in for a missing Version
+    assert_eq!(file.version.as_deref(), Some("1.2.3.4"));
+    assert_eq!(file.size, Some(40_960));
+    assert_eq!(date(file.link_date), "2024-03-05 14:30:00");
+    assert_eq!(date(file.first_seen), "2020-01-01 00:00:00");
+    assert_eq!((file.created, file.mft_entry), (None, None));
+
+    let [application] = amcache.applications.as_slice() else {
+        panic!("expected one application, got {:?}", amcache.applications);
+    };
+    assert_eq!(application.program_id, "0000f4ad8b4c2b9c");
+    assert_eq!(application.name.as_deref(), Some("Windows Tools"));
+    assert_eq!(date(application.install_date), "2024-03-05 00:00:00");
+    assert_eq!(application.root_dir_path.as_deref(), Some("C:\\Tools"));
+    assert_eq!(application.uninstall_string.as_deref(), Some("C:\\Tools\\uninstall.exe"));
+
+    let [driver] = amcache.drivers.as_slice() else {
+        panic!("expected one driver, got {:?}", amcache.drivers);
+    };
+    assert_eq!(driver.path, "c:/windows/system32/drivers/example.sys");
+    assert_eq!(driver.sha1.as_deref(), Some(SHA1));
+    assert_eq!(driver.signed, Some(true));
+    assert_eq!(date(driver.link_date), "2020-01-01 00:00:00");
+    assert_eq!(date(driver.last_write), "2020-01-02 08:00:00");
+
+    let [device] = amcache.devices.as_slice() else {
+        panic!("expected one device, got {:?}", amcache.devices);
+    };
+    assert_eq!(device.id, "usb#vid_0781&pid_5567#serial");
+    assert_eq!(device.class.as_deref(), Some("USB"));
+    assert_eq!(device.driver_version.as_deref(), Some("10.0.19041.1"));
+    assert_eq!(device.service.as_deref(), Some("USBSTOR"));
+    assert_eq!(device.parent_id.as_deref(), Some("usb\\root_hub30"));
+}
+
+#[test]
+fn test_strip_sha1_padding() {
+    assert_eq!(strip_sha1_padding(&format!(" 0000{} ", SHA1.to_uppercase())), SHA1);
+    // Only 44-digit values are padded
+    assert_eq!(strip_sha1_padding("0000abcd"), "0000abcd");
+}
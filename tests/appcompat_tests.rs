@@ -0,0 +1,36 @@
+// tests/appcompat_tests.rs
+use forensic_triage::parsers::parse_recentfilecache;
+use forensic_triage::registry::strip_sha1_padding;
+
+fn recentfilecache(paths: &[&str]) -> Vec<u8> {
+    let mut data = vec![0xFE, 0xFF, 0xEE, 0xFF, 0x11, 0x22, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00];
+    data.resize(20, 0);
+    for path in paths {
+        data.extend((path.encode_utf16().count() as u32).to_le_bytes());
+        data.extend(path.encode_utf16().chain([0]).flat_map(|u| u.to_le_bytes()));
+    }
+    data
+}
+
+#[test]
+fn test_recentfilecache_paths() {
+    let data = recentfilecache(&["c:\\windows\\system32\\cmd.exe", "c:\\temp\\tool.exe"]);
+    let paths = parse_recentfilecache(&data).unwrap();
+
+    assert_eq!(paths, vec!["c:\\windows\\system32\\cmd.exe", "c:\\temp\\tool.exe"]);
+}
+
+#[test]
+fn test_recentfilecache_rejects_other_files() {
+    assert!(parse_recentfilecache(b"regf\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0").is_err());
+}
+
+#[test]
+fn test_sha1_padding_stripped() {
+    assert_eq!(
+        strip_sha1_padding("0000A94A8FE5CCB19BA61C4C0873D391E987982FBBD3"),
+        "a94a8fe5ccb19ba61c4c0873d391e987982fbbd3",
+    );
+    // Values that are not padded SHA-1 hashes are kept as they are
+    assert_eq!(strip_sha1_padding("0000abc"), "0000abc");
+}
@@ -0,0 +1,242 @@
+// tests/browser_tests.rs
+use forensic_triage::parsers::{
+    parse_chromium_history, parse_firefox_form_history, parse_firefox_places, BrowserActivityKind,
+};
+
+const PAGE_SIZE: usize = 4096;
+
+/// 2024-01-02 03:04:05 UTC as WebKit and PRTime microseconds
+const WEBKIT: i64 = 13_348_638_245_000_000;
+const PRTIME: i64 = 1_704_164_645_000_000;
+const TIME: &str = "2024-01-02T03:04:05+00:00";
+
+enum V<'a> {
+    I(i64),
+    T(&'a str),
+    N,
+}
+use V::*;
+
+/// Varints of up to two bytes are all these tests need
+fn varint(n: usize) -> Vec<u8> {
+    assert!(n < 0x4000);
+    if n < 0x80 { vec![n as u8] } else { vec![0x80 | (n >> 7) as u8, (n & 0x7F) as u8] }
+}
+
+fn record(values: &[V]) -> Vec<u8> {
+    let mut types = Vec::new();
+    let mut body = Vec::new();
+    for value in values {
+        match value {
+            I(n) => {
+                types.push(6);
+                body.extend(n.to_be_bytes());
+            }
+            T(text) => {
+                types.extend(varint(13 + 2 * text.len()));
+                body.extend(text.as_bytes());
+            }
+            N => types.push(0),
+        }
+    }
+    let header_size = if types.len() + 1 < 0x80 { types.len() + 1 } else { types.len() + 2 };
+    let mut data = varint(header_size);
+    data.extend(types);
+    data.extend(body);
+    data
+}
+
+type Rows = Vec<(i64, Vec<u8>)>;
+
+/// A leaf table page of (rowid, record) cells; page 1 leaves room for the
+/// database header
+fn leaf(number: u32, rows: &[(i64, Vec<u8>)]) -> Vec<u8> {
+    let mut page = vec![0u8; PAGE_SIZE];
+    let header = if number == 1 { 100 } else { 0 };
+    page[header] = 0x0D;
+    page[header + 3..header + 5].copy_from_slice(&(rows.len() as u16).to_be_bytes());
+
+    let mut end = PAGE_SIZE;
+    for (i, (rowid, payload)) in rows.iter().enumerate() {
+        let mut cell = varint(payload.len());
+        cell.push(*rowid as u8);
+        cell.extend(payload);
+        end -= cell.len();
+        page[end..end + cell.len()].copy_from_slice(&cell);
+        let pointer = header + 8 + i * 2;
+        page[pointer..pointer + 2].copy_from_slice(&(end as u16).to_be_bytes());
+    }
+    page[header + 5..header + 7].copy_from_slice(&(end as u16).to_be_bytes());
+    page
+}
+
+/// A database of one leaf page per (CREATE TABLE, rows) after the schema
+fn database(tables: &[(&str, Rows)]) -> Vec<u8> {
+    let schema: Rows = tables.iter().enumerate()
+        .map(|(i, (sql, _))| {
+            let name = sql.split_whitespace().nth(2).unwrap().split('(').next().unwrap();
+            (i as i64 + 1, record(&[T("table"), T(name), T(name), I(i as i64 + 2), T(sql)]))
+        })
+        .collect();
+
+    let mut data = leaf(1, &schema);
+    data[..16].copy_from_slice(b"SQLite format 3\0");
+    data[16..18].copy_from_slice(&(PAGE_SIZE as u16).to_be_bytes());
+    data[18] = 2;
+    data[19] = 2;
+    data[28..32].copy_from_slice(&(tables.len() as u32 + 1).to_be_bytes());
+    data[56..60].copy_from_slice(&1u32.to_be_bytes());
+    for (i, (_, rows)) in tables.iter().enumerate() {
+        data.extend(leaf(i as u32 + 2, rows));
+    }
+    data
+}
+
+fn checksum(data: &[u8], (mut s0, mut s1): (u32, u32)) -> (u32, u32) {
+    for chunk in data.chunks_exact(8) {
+        s0 = s0.wrapping_add(u32::from_le_bytes(chunk[..4].try_into().unwrap())).wrapping_add(s1);
+        s1 = s1.wrapping_add(u32::from_le_bytes(chunk[4..].try_into().unwrap())).wrapping_add(s0);
+    }
+    (s0, s1)
+}
+
+/// A WAL with little-endian checksums holding (page number, commit, page)
+fn wal(frames: &[(u32, bool, Vec<u8>)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for value in [0x377F_0682u32, 3_007_000, PAGE_SIZE as u32, 0, 0x1234_5678, 0x9ABC_DEF0] {
+        data.extend(value.to_be_bytes());
+    }
+    let mut sum = checksum(&data, (0, 0));
+    data.extend(sum.0.to_be_bytes());
+    data.extend(sum.1.to_be_bytes());
+
+    for (number, commit, page) in frames {
+        let mut header = number.to_be_bytes().to_vec();
+        header.extend(if *commit { 10u32 } else { 0 }.to_be_bytes());
+        header.extend_from_slice(&data[16..24]);
+        sum = checksum(&header[..8], sum);
+        sum = checksum(page, sum);
+        header.extend(sum.0.to_be_bytes());
+        header.extend(sum.1.to_be_bytes());
+        data.extend(header);
+        data.extend(page);
+    }
+    data
+}
+
+#[test]
+fn test_chromium_history_with_wal() {
+    let visits = "CREATE TABLE visits(id INTEGER PRIMARY KEY,url INTEGER NOT NULL,visit_time INTEGER NOT NULL,from_visit INTEGER)";
+    let data = database(&[
+        (
+            "CREATE TABLE urls(id INTEGER PRIMARY KEY AUTOINCREMENT,url LONGVARCHAR,title LONGVARCHAR,visit_count INTEGER DEFAULT 0 NOT NULL,last_visit_time INTEGER NOT NULL)",
+            vec![
+                (1, record(&[N, T("https://example.com/"), T("Example"), I(2), I(WEBKIT)])),
+                (2, record(&[N, T("https://www.google.com/search?q=triage"), T("triage - Google Search"), I(1), I(WEBKIT)])),
+            ],
+        ),
+        (visits, vec![(1, record(&[N, I(1), I(WEBKIT - 60_000_000), N]))]),
+        (
+            "CREATE TABLE downloads (id INTEGER PRIMARY KEY,guid VARCHAR NOT NULL,current_path LONGVARCHAR NOT NULL,target_path LONGVARCHAR NOT NULL,start_time INTEGER NOT NULL,received_bytes INTEGER NOT NULL,total_bytes INTEGER NOT NULL)",
+            vec![(1, record(&[N, T("guid"), T("C:\\Users\\alice\\Downloads\\x.crdownload"), T("C:\\Users\\alice\\Downloads\\x.exe"), I(WEBKIT), I(4096), I(4096)]))],
+        ),
+        (
+            "CREATE TABLE downloads_url_chains (id INTEGER NOT NULL,chain_index INTEGER NOT NULL,url LONGVARCHAR NOT NULL, PRIMARY KEY (id, chain_index))",
+            vec![
+                (1, record(&[I(1), I(0), T("https://example.com/get")])),
+                (2, record(&[I(1), I(1), T("https://cdn.example.com/x.exe")])),
+            ],
+        ),
+        (
+            "CREATE TABLE keyword_search_terms (keyword_id INTEGER NOT NULL,url_id INTEGER NOT NULL,term LONGVARCHAR NOT NULL,normalized_term LONGVARCHAR NOT NULL)",
+            vec![(1, record(&[I(2), I(2), T("triage"), T("triage")]))],
+        ),
+    ]);
+
+    let activity = parse_chromium_history(&data, None).unwrap();
+    assert_eq!(activity.len(), 3);
+    let visit = &activity[0];
+    assert_eq!(visit.kind, BrowserActivityKind::Visit);
+    assert_eq!(visit.url.as_deref(), Some("https://example.com/"));
+    assert_eq!((visit.title.as_deref(), visit.count), (Some("Example"), Some(2)));
+    assert_eq!(visit.timestamp.unwrap().to_rfc3339(), "2024-01-02T03:03:05+00:00");
+
+    let download = &activity[1];
+    assert_eq!(download.kind, BrowserActivityKind::Download);
+    assert_eq!(download.url.as_deref(), Some("https://cdn.example.com/x.exe"));
+    assert_eq!(download.value.as_deref(), Some("C:\\Users\\alice\\Downloads\\x.exe"));
+    assert_eq!(download.size, Some(4096));
+
+    let search = &activity[2];
+    assert_eq!(search.kind, BrowserActivityKind::Search);
+    assert_eq!(search.value.as_deref(), Some("triage"));
+    assert_eq!(search.timestamp.unwrap().to_rfc3339(), TIME);
+
+    // A committed frame adds a visit; a trailing uncommitted one is ignored
+    let committed = leaf(3, &[(1, record(&[N, I(1), I(WEBKIT - 60_000_000), N])), (2, record(&[N, I(2), I(WEBKIT), I(1)]))]);
+    let uncommitted = leaf(3, &[]);
+    let log = wal(&[(3, true, committed), (3, false, uncommitted)]);
+    let activity = parse_chromium_history(&data, Some(&log)).unwrap();
+    let visits: Vec<_> = activity.iter().filter(|a| a.kind == BrowserActivityKind::Visit).collect();
+    assert_eq!(visits.len(), 2);
+    assert_eq!(visits[1].url.as_deref(), Some("https://www.google.com/search?q=triage"));
+
+    // A corrupt WAL is ignored rather than failing the database
+    let mut corrupt = log.clone();
+    corrupt[40 + PAGE_SIZE / 2] ^= 0xFF;
+    assert_eq!(parse_chromium_history(&data, Some(&corrupt)).unwrap().len(), 3);
+}
+
+#[test]
+fn test_firefox_places_and_form_history() {
+    let places = database(&[
+        (
+            "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR, rev_host LONGVARCHAR, visit_count INTEGER DEFAULT 0)",
+            vec![(1, record(&[N, T("https://mozilla.org/file.zip"), T("Files"), T("gro.allizom."), I(3)]))],
+        ),
+        (
+            "CREATE TABLE moz_historyvisits (id INTEGER PRIMARY KEY, from_visit INTEGER, place_id INTEGER, visit_date INTEGER, visit_type INTEGER, session INTEGER)",
+            vec![(1, record(&[N, I(0), I(1), I(PRTIME), I(1), I(0)]))],
+        ),
+        (
+            "CREATE TABLE moz_anno_attributes (id INTEGER PRIMARY KEY, name VARCHAR(32) UNIQUE NOT NULL)",
+            vec![
+                (1, record(&[N, T("downloads/destinationFileURI")])),
+                (2, record(&[N, T("downloads/metaData")])),
+            ],
+        ),
+        (
+            "CREATE TABLE moz_annos (id INTEGER PRIMARY KEY,place_id INTEGER NOT NULL,anno_attribute_id INTEGER,content LONGVARCHAR, flags INTEGER DEFAULT 0,expiration INTEGER DEFAULT 0,type INTEGER DEFAULT 0,dateAdded INTEGER DEFAULT 0,lastModified INTEGER DEFAULT 0)",
+            vec![
+                (1, record(&[N, I(1), I(1), T("file:///C:/Users/bob/Downloads/file.zip"), I(0), I(4), I(3), I(PRTIME), I(PRTIME)])),
+                (2, record(&[N, I(1), I(2), T("{\"state\":1,\"fileSize\":2048}"), I(0), I(4), I(3), I(PRTIME), I(PRTIME)])),
+            ],
+        ),
+    ]);
+
+    let activity = parse_firefox_places(&places, None).unwrap();
+    assert_eq!(activity.len(), 2);
+    assert_eq!(activity[0].kind, BrowserActivityKind::Visit);
+    assert_eq!(activity[0].timestamp.unwrap().to_rfc3339(), TIME);
+    assert_eq!(activity[0].count, Some(3));
+    assert_eq!(activity[1].kind, BrowserActivityKind::Download);
+    assert_eq!(activity[1].url.as_deref(), Some("https://mozilla.org/file.zip"));
+    assert_eq!(activity[1].value.as_deref(), Some("file:///C:/Users/bob/Downloads/file.zip"));
+    assert_eq!(activity[1].size, Some(2048));
+
+    let form_history = database(&[(
+        "CREATE TABLE moz_formhistory (id INTEGER PRIMARY KEY, fieldname TEXT NOT NULL, value TEXT NOT NULL, timesUsed INTEGER, firstUsed INTEGER, lastUsed INTEGER, guid TEXT)",
+        vec![
+            (1, record(&[N, T("searchbar-history"), T("mimikatz"), I(2), I(PRTIME), I(PRTIME), T("a")])),
+            (2, record(&[N, T("email"), T("bob@example.com"), I(1), I(PRTIME), I(PRTIME), T("b")])),
+        ],
+    )]);
+    let activity = parse_firefox_form_history(&form_history, None).unwrap();
+    assert_eq!(activity[0].kind, BrowserActivityKind::Search);
+    assert_eq!(activity[0].value.as_deref(), Some("mimikatz"));
+    assert_eq!(activity[1].kind, BrowserActivityKind::FormEntry);
+    assert_eq!(activity[1].name.as_deref(), Some("email"));
+    assert_eq!(activity[1].count, Some(1));
+
+    assert!(parse_firefox_places(b"not a database", None).is_err());
+}
@@ -0,0 +1,294 @@
+// tests/common/mod.rs
+//! Builds minimal registry hives in memory for the registry tests
+#![allow(dead_code)]
+
+pub mod shell_items;
+
+pub const REG_SZ: u32 = 1;
+pub const REG_EXPAND_SZ: u32 = 2;
+pub const REG_BINARY: u32 = 3;
+pub const REG_DWORD: u32 = 4;
+pub const REG_MULTI_SZ: u32 = 7;
+pub const REG_QWORD: u32 = 11;
+
+const HBIN_SIZE: usize = 4096;
+
+/// A key to be written into a hive, with its values and subkeys
+#[derive(Debug, Clone, Default)]
+pub struct Key {
+    name: String,
+    last_write: u64,
+    class: Option<String>,
+    security: Option<Vec<u8>>,
+    values: Vec<(String, u32, Vec<u8>)>,
+    subkeys: Vec<Key>,
+}
+
+impl Key {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), ..Default::default() }
+    }
+
+    /// Last write time as a FILETIME
+    pub fn last_write(mut self, filetime: u64) -> Self {
+        self.last_write = filetime;
+        self
+    }
+
+    pub fn class(mut self, class: &str) -> Self {
+        self.class = Some(class.to_string());
+        self
+    }
+
+    /// Self-relative security descriptor, stored in the key's sk cell
+    pub fn security(mut self, descriptor: &[u8]) -> Self {
+        self.security = Some(descriptor.to_vec());
+        self
+    }
+
+    pub fn value(mut self, name: &str, value_type: u32, data: &[u8]) -> Self {
+        self.values.push((name.to_string(), value_type, data.to_vec()));
+        self
+    }
+
+    pub fn string(self, name: &str, text: &str) -> Self {
+        self.value(name, REG_SZ, &utf16z(text))
+    }
+
+    pub fn dword(self, name: &str, value: u32) -> Self {
+        self.value(name, REG_DWORD, &value.to_le_bytes())
+    }
+
+    pub fn qword(self, name: &str, value: u64) -> Self {
+        self.value(name, REG_QWORD, &value.to_le_bytes())
+    }
+
+    pub fn multi_string(self, name: &str, strings: &[&str]) -> Self {
+        let mut data: Vec<u8> = strings.iter().flat_map(|s| utf16z(s)).collect();
+        data.extend([0, 0]);
+        self.value(name, REG_MULTI_SZ, &data)
+    }
+
+    /// Add `key` at a backslash-separated `path` below this key, creating
+    /// intermediate keys and merging into ones that already exist
+    pub fn subkey(mut self, path: &str, key: Key) -> Self {
+        self.insert(path, key);
+        self
+    }
+
+    fn insert(&mut self, path: &str, key: Key) {
+        let (first, rest) = path.split_once('\\').unwrap_or((path, ""));
+        let index = match self.subkeys.iter().position(|k| k.name.eq_ignore_ascii_case(first)) {
+            Some(index) => index,
+            None => {
+                self.subkeys.push(Key::new(first));
+                self.subkeys.len() - 1
+            }
+        };
+        let child = &mut self.subkeys[index];
+        if rest.is_empty() {
+            child.last_write = key.last_write.max(child.last_write);
+            child.class = key.class.or(child.class.take());
+            child.security = key.security.or(child.security.take());
+            child.values.extend(key.values);
+            for subkey in key.subkeys {
+                let name = subkey.name.clone();
+                child.insert(&name, subkey);
+            }
+        } else {
+            child.insert(rest, key);
+        }
+    }
+}
+
+/// A hive with `root` as its root key
+pub fn build_hive(root: Key) -> Vec<u8> {
+    let mut cells = Cells { data: b"hbin".to_vec() };
+    cells.data.resize(32, 0);
+    let root_offset = cells.key(&root);
+
+    let mut hbin = cells.data;
+    hbin.resize(hbin.len().div_ceil(HBIN_SIZE) * HBIN_SIZE, 0);
+    let hbin_size = hbin.len() as u32;
+    hbin[8..12].copy_from_slice(&hbin_size.to_le_bytes());
+
+    let mut hive = vec![0u8; HBIN_SIZE];
+    hive[0..4].copy_from_slice(b"regf");
+    hive[20..24].copy_from_slice(&1u32.to_le_bytes());
+    hive[24..28].copy_from_slice(&5u32.to_le_bytes());
+    hive[36..40].copy_from_slice(&root_offset.to_le_bytes());
+    hive[40..44].copy_from_slice(&hbin_size.to_le_bytes());
+    hive.extend(hbin);
+    hive
+}
+
+/// Self-relative security descriptor with an auto-inherited DACL of
+/// (ACE flags, access mask, SID) allow entries
+pub fn security_descriptor(owner: &str, group: &str, entries: &[(u8, u32, &str)]) -> Vec<u8> {
+    let aces: Vec<u8> = entries.iter()
+        .flat_map(|&(flags, mask, sid)| {
+            let sid = sid_bytes(sid);
+            let mut ace = vec![0, flags];
+            ace.extend(((8 + sid.len()) as u16).to_le_bytes());
+            ace.extend(mask.to_le_bytes());
+            ace.extend(sid);
+            ace
+        })
+        .collect();
+
+    let mut acl = vec![2, 0];
+    acl.extend(((8 + aces.len()) as u16).to_le_bytes());
+    acl.extend((entries.len() as u16).to_le_bytes());
+    acl.extend([0, 0]);
+    acl.extend(aces);
+
+    let (owner, group) = (sid_bytes(owner), sid_bytes(group));
+    let owner_offset = 20u32;
+    let group_offset = owner_offset + owner.len() as u32;
+    let dacl_offset = group_offset + group.len() as u32;
+
+    // Self-relative, DACL present and auto-inherited
+    let mut data = vec![1, 0];
+    data.extend(0x8404u16.to_le_bytes());
+    data.extend(owner_offset.to_le_bytes());
+    data.extend(group_offset.to_le_bytes());
+    data.extend(0u32.to_le_bytes());
+    data.extend(dacl_offset.to_le_bytes());
+    data.extend(owner);
+    data.extend(group);
+    data.extend(acl);
+    data
+}
+
+/// Binary form of a SID string such as `S-1-5-32-544`
+pub fn sid_bytes(sid: &str) -> Vec<u8> {
+    let parts: Vec<u64> = sid.split('-').skip(2).map(|p| p.parse().unwrap()).collect();
+    let mut data = vec![1, parts.len() as u8 - 1];
+    data.extend(&parts[0].to_be_bytes()[2..]);
+    for sub in &parts[1..] {
+        data.extend((*sub as u32).to_le_bytes());
+    }
+    data
+}
+
+/// Offset of the cell of the key named `name` in a built hive
+pub fn key_offset(hive: &[u8], name: &str) -> u32 {
+    let name_pos = (76..hive.len())
+        .find(|&pos| {
+            hive[pos..].starts_with(name.as_bytes())
+                && &hive[pos - 76..pos - 74] == b"nk"
+                && u16::from_le_bytes([hive[pos - 4], hive[pos - 3]]) as usize == name.len()
+        })
+        .unwrap_or_else(|| panic!("No key named {}", name));
+    (name_pos - 80 - HBIN_SIZE) as u32
+}
+
+/// Point the subkey list entry for the key named `child` at the key named
+/// `target`, as a corrupt hive with a subkey cycle would
+pub fn redirect_subkey(hive: &mut [u8], child: &str, target: &str) {
+    let mut hint = child.as_bytes().to_vec();
+    hint.resize(4, 0);
+    let mut entry = key_offset(hive, child).to_le_bytes().to_vec();
+    entry.extend(&hint[..4]);
+
+    let target = key_offset(hive, target);
+    let pos = hive.windows(8).position(|w| w == entry).unwrap();
+    hive[pos..pos + 4].copy_from_slice(&target.to_le_bytes());
+}
+
+/// UTF-16LE with a terminating NUL, as REG_SZ data is stored
+pub fn utf16z(text: &str) -> Vec<u8> {
+    text.encode_utf16().chain([0]).flat_map(u16::to_le_bytes).collect()
+}
+
+struct Cells {
+    data: Vec<u8>,
+}
+
+impl Cells {
+    /// Append an allocated cell, returning its offset from the first hbin
+    fn add(&mut self, payload: &[u8]) -> u32 {
+        let offset = self.data.len() as u32;
+        let size = (payload.len() + 4).div_ceil(8) * 8;
+        self.data.extend((-(size as i32)).to_le_bytes());
+        self.data.extend(payload);
+        self.data.resize(offset as usize + size, 0);
+        offset
+    }
+
+    fn key(&mut self, key: &Key) -> u32 {
+        let subkeys: Vec<u32> = key.subkeys.iter().map(|subkey| self.key(subkey)).collect();
+        let values: Vec<u32> = key.values.iter().map(|(name, value_type, data)| self.value(name, *value_type, data)).collect();
+
+        let subkey_list = if subkeys.is_empty() {
+            u32::MAX
+        } else {
+            let mut list = b"lf".to_vec();
+            list.extend((subkeys.len() as u16).to_le_bytes());
+            for (offset, subkey) in subkeys.iter().zip(&key.subkeys) {
+                let mut hint = subkey.name.as_bytes().to_vec();
+                hint.resize(4, 0);
+                list.extend(offset.to_le_bytes());
+                list.extend(&hint[..4]);
+            }
+            self.add(&list)
+        };
+        let value_list = if values.is_empty() {
+            u32::MAX
+        } else {
+            let list: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+            self.add(&list)
+        };
+        let security = key.security.as_ref().map_or(u32::MAX, |descriptor| {
+            let mut sk = b"sk".to_vec();
+            // Reserved, list links to itself and reference count
+            sk.extend([0; 14]);
+            sk.extend((descriptor.len() as u32).to_le_bytes());
+            sk.extend(descriptor);
+            self.add(&sk)
+        });
+        let class = key.class.as_ref().map(|class| {
+            let data: Vec<u8> = class.encode_utf16().flat_map(u16::to_le_bytes).collect();
+            (self.add(&data), data.len() as u16)
+        });
+
+        // Compressed (ASCII) name
+        let mut nk = vec![0u8; 76];
+        nk[0..2].copy_from_slice(b"nk");
+        nk[2..4].copy_from_slice(&0x20u16.to_le_bytes());
+        nk[4..12].copy_from_slice(&key.last_write.to_le_bytes());
+        nk[20..24].copy_from_slice(&(subkeys.len() as u32).to_le_bytes());
+        nk[28..32].copy_from_slice(&subkey_list.to_le_bytes());
+        nk[36..40].copy_from_slice(&(values.len() as u32).to_le_bytes());
+        nk[40..44].copy_from_slice(&value_list.to_le_bytes());
+        nk[44..48].copy_from_slice(&security.to_le_bytes());
+        let (class_offset, class_length) = class.unwrap_or((u32::MAX, 0));
+        nk[48..52].copy_from_slice(&class_offset.to_le_bytes());
+        nk[72..74].copy_from_slice(&(key.name.len() as u16).to_le_bytes());
+        nk[74..76].copy_from_slice(&class_length.to_le_bytes());
+        nk.extend(key.name.as_bytes());
+        self.add(&nk)
+    }
+
+    fn value(&mut self, name: &str, value_type: u32, data: &[u8]) -> u32 {
+        let mut vk = b"vk".to_vec();
+        vk.extend((name.len() as u16).to_le_bytes());
+        if data.len() <= 4 {
+            // Small data is stored in the offset field
+            vk.extend((data.len() as u32 | 0x8000_0000).to_le_bytes());
+            let mut inline = data.to_vec();
+            inline.resize(4, 0);
+            vk.extend(inline);
+        } else {
+            let offset = self.add(data);
+            vk.extend((data.len() as u32).to_le_bytes());
+            vk.extend(offset.to_le_bytes());
+        }
+        vk.extend(value_type.to_le_bytes());
+        // ASCII name flag
+        vk.extend(1u16.to_le_bytes());
+        vk.extend(0u16.to_le_bytes());
+        vk.extend(name.as_bytes());
+        self.add(&vk)
+    }
+}
@@ -0,0 +1,52 @@
+// tests/common/shell_items.rs
+//! Builds shell items, as stored in ShellBags, MRU lists and shortcuts
+use super::utf16z;
+
+/// Set the leading size field to the item's length
+pub fn with_size(mut item: Vec<u8>) -> Vec<u8> {
+    let size = item.len() as u16;
+    item[0..2].copy_from_slice(&size.to_le_bytes());
+    item
+}
+
+pub fn root_item() -> Vec<u8> {
+    // My Computer: {20D04FE0-3AEA-1069-A2D8-08002B30309D}
+    let mut item = vec![0, 0, 0x1F, 0x50];
+    item.extend_from_slice(&[
+        0xE0, 0x4F, 0xD0, 0x20, 0xEA, 0x3A, 0x69, 0x10,
+        0xA2, 0xD8, 0x08, 0x00, 0x2B, 0x30, 0x30, 0x9D,
+    ]);
+    with_size(item)
+}
+
+pub fn volume_item() -> Vec<u8> {
+    let mut item = vec![0, 0, 0x2F];
+    item.extend_from_slice(b"C:\\\0");
+    item.resize(25, 0);
+    with_size(item)
+}
+
+pub fn directory_item(short_name: &str, long_name: &str) -> Vec<u8> {
+    let mut item = vec![0u8; 14];
+    item[2] = 0x31;
+    // 2025-01-01 12:00:00
+    item[8..10].copy_from_slice(&0x5A21u16.to_le_bytes());
+    item[10..12].copy_from_slice(&0x6000u16.to_le_bytes());
+    item.extend_from_slice(short_name.as_bytes());
+    item.push(0);
+    if item.len() % 2 == 1 {
+        item.push(0);
+    }
+
+    // BEEF0004 version 9 with MFT entry 1234, sequence 3
+    let mut block = vec![0u8; 36];
+    block[2..4].copy_from_slice(&9u16.to_le_bytes());
+    block[4..8].copy_from_slice(&0xBEEF_0004u32.to_le_bytes());
+    block[20..28].copy_from_slice(&((3u64 << 48) | 1234).to_le_bytes());
+    block.extend_from_slice(&[0u8; 10]);
+    block.extend(utf16z(long_name));
+    block.extend_from_slice(&[0u8; 2]);
+    item.extend(with_size(block));
+
+    with_size(item)
+}
@@ -0,0 +1,244 @@
+// tests/credentials_tests.rs
+//! Known-answer tests for credential decryption. The encrypted values were
+//! produced with an independent implementation from published plaintexts:
+//! the NT and LM hashes of "password", the FIPS-197 appendix C.1 AES vector
+//! as the domain key, and hashcat's example DCC2 hash for "tom".
+#![cfg(feature = "credentials")]
+mod common;
+
+use common::{build_hive, Key, REG_BINARY};
+use forensic_triage::registry::{boot_key, extract_credentials, Credentials, RegistryParser, SecretKind};
+
+/// Boot key 000102..0F scrambled across the Lsa subkeys' class names
+const CLASS_NAMES: [(&str, &str); 4] = [("JD", "080A0307"), ("Skew1", "0201090F"), ("GBG", "00050D04"), ("Data", "0B060C0E")];
+
+/// FIPS-197 C.1: AES-128 with key 000102..0F encrypts 00112233..FF to this,
+/// making 00112233..FF the domain key under the boot key
+const AES_DOMAIN_KEY: &str = "69c4e0d86a7b0430d8cdb78070b4c55a";
+/// The same domain key and its checksum, RC4-encrypted with salt 11..11
+const RC4_DOMAIN_KEY: &str = "7eef46d05fcb28c6c2fd64fc0338d734c7b7b791cb57035be19f61d9afe01987";
+/// NT hash of "password" for RID 500, AES-encrypted with IV 22..22
+const AES_NT_HASH: &str = "066db2aedc2ae6b1b01652db0832021f";
+/// LM and NT hashes of "password" for RID 1001, RC4-encrypted
+const RC4_LM_HASH: &str = "c8b55471fa2351457c91b699966ed2d0";
+const RC4_NT_HASH: &str = "42acd5efeeff3f9614d1b3c40247207d";
+
+const NT_PASSWORD: &str = "8846f7eaee8fb117ad06bdd830b7586c";
+const LM_PASSWORD: &str = "e52cac67419a9a224a3b108f3fa6cb6d";
+
+/// PolEKList holding LSA key 33..33, encrypted under the boot key with salt 44..44
+const POL_EK_LIST: &str = "09c1d944fb8d8f138605c6c515e7f45f9b359d06db366096bb6e62c3bc9c07279b359d06db366096bb6e62c3bc9c07279b359d06db366096bb6e62c3bc9c0727993a9ed84dc8c28d0ecff87fbdce2faa9f766578fe13c9d9b5a627513a5be08586a0b3d2bff4efe6ee360c61fcb5e524";
+/// "password" as a service password secret, with salt 55..55
+const SERVICE_SECRET: &str = "8f7e9fa026a902ae763dbf8b074ad74d629512dc84db681b35c0622ecd55eeb54d3cf698f2d72479d8df2e86ce17de8a";
+/// NL$KM of bytes 00..3F, with salt 66..66
+const NLKM_SECRET: &str = "367f5ff77aefd9ac94042c96e096d48f03f9447504e4241e4390fcf9593160d4b375bfbe170f9ba0e61fa01c9100af4e0a7ece3120b15639fedf20b1eb3d2f289a55f9abdd475e24a6b64ca881db9e15";
+/// Cached logon of CORP\tom (corp.local), encrypted with IV 77..77
+const CACHE_RECORD: &str = "0ff495af9274035c6ee131cf81772a11177a7d47dbb2fec91141a24ebcb1ed12370bc7913230cf9fbffb3b8869a7cb35cde4fd8fd47f888a439c73221c58d6d55457391e3088c1c971df401916e61d8613d0c78373997d7ed1ea0f4889aef83d3b60b03a4fdd68b65f300e51374b43ef";
+
+fn unhex(text: &str) -> Vec<u8> {
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap()).collect()
+}
+
+fn utf16(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn system_hive(class_names: &[(&str, &str)]) -> Vec<u8> {
+    let mut root = Key::new("ROOT")
+        .subkey("Select", Key::new("Select").dword("Current", 1))
+        .subkey("ControlSet001\\Services\\MSSQL", Key::new("MSSQL").string("ObjectName", "CORP\\svc_sql"));
+    for (name, class) in class_names {
+        root = root.subkey(&format!("ControlSet001\\Control\\Lsa\\{}", name), Key::new(name).class(class));
+    }
+    build_hive(root)
+}
+
+/// A V value holding the username and the LM and NT hash structures
+fn v_value(username: &str, lm_hash: &[u8], nt_hash: &[u8]) -> Vec<u8> {
+    let mut v = vec![0u8; 0xCC];
+    let mut data = Vec::new();
+    for (index, field) in [(1, utf16(username)), (13, lm_hash.to_vec()), (14, nt_hash.to_vec())] {
+        v[index * 12..index * 12 + 4].copy_from_slice(&(data.len() as u32).to_le_bytes());
+        v[index * 12 + 4..index * 12 + 8].copy_from_slice(&(field.len() as u32).to_le_bytes());
+        data.extend(field);
+        data.resize(data.len().div_ceil(4) * 4, 0);
+    }
+    v.extend(data);
+    v
+}
+
+fn sam_hive(f: Vec<u8>, users: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut root = Key::new("ROOT")
+        .subkey("SAM\\Domains\\Account", Key::new("Account").value("F", REG_BINARY, &f));
+    for (rid, v) in users {
+        root = root.subkey(&format!("SAM\\Domains\\Account\\Users\\{:08X}", rid), Key::new("").value("V", REG_BINARY, v));
+    }
+    build_hive(root)
+}
+
+/// Revision 2 domain key and an AES hash structure for RID 500
+fn aes_sam_hive() -> Vec<u8> {
+    let mut f = vec![0u8; 0x68];
+    f.extend(2u32.to_le_bytes());
+    f.extend(0u32.to_le_bytes());
+    f.extend(0u32.to_le_bytes());
+    f.extend(16u32.to_le_bytes());
+    f.extend([0u8; 16]);
+    f.extend(unhex(AES_DOMAIN_KEY));
+
+    let header = |length: usize| {
+        let mut header = vec![0, 0, 2, 0];
+        header.extend((length as u32).to_le_bytes());
+        header
+    };
+    // No LM hash: the structure has a header and salt but no data
+    let mut lm = header(0);
+    lm.extend([0u8; 16]);
+    let mut nt = header(16);
+    nt.extend([0x22u8; 16]);
+    nt.extend(unhex(AES_NT_HASH));
+
+    sam_hive(f, &[(500, v_value("Administrator", &lm, &nt))])
+}
+
+/// Revision 1 domain key and RC4 hash structures for RID 1001
+fn rc4_sam_hive() -> Vec<u8> {
+    let mut f = vec![0u8; 0x68];
+    f.extend(1u32.to_le_bytes());
+    f.extend([0u8; 4]);
+    f.extend([0x11u8; 16]);
+    f.extend(unhex(RC4_DOMAIN_KEY));
+    f.extend([0u8; 8]);
+
+    let hash = |encrypted: &str| {
+        let mut hash = vec![0, 0, 1, 0];
+        hash.extend(unhex(encrypted));
+        hash
+    };
+    sam_hive(f, &[(1001, v_value("jdoe", &hash(RC4_LM_HASH), &hash(RC4_NT_HASH)))])
+}
+
+/// LSA_SECRET with a zeroed 28-byte header and the salt before the data
+fn lsa_secret(salt: u8, encrypted: &str) -> Vec<u8> {
+    let mut secret = vec![0u8; 28];
+    secret.extend([salt; 32]);
+    secret.extend(unhex(encrypted));
+    secret
+}
+
+fn security_hive() -> Vec<u8> {
+    let mut record = vec![0u8; 96];
+    record[0..2].copy_from_slice(&6u16.to_le_bytes());
+    record[2..4].copy_from_slice(&8u16.to_le_bytes());
+    record[32..40].copy_from_slice(&132_223_104_000_000_000u64.to_le_bytes());
+    record[48..52].copy_from_slice(&1u32.to_le_bytes());
+    record[60..62].copy_from_slice(&20u16.to_le_bytes());
+    record[64..80].copy_from_slice(&[0x77; 16]);
+    record.extend(unhex(CACHE_RECORD));
+
+    build_hive(Key::new("ROOT")
+        .subkey("Policy\\PolEKList", Key::new("PolEKList").value("", REG_BINARY, &lsa_secret(0x44, POL_EK_LIST)))
+        .subkey("Policy\\Secrets\\_SC_MSSQL\\CurrVal", Key::new("CurrVal").value("", REG_BINARY, &lsa_secret(0x55, SERVICE_SECRET)))
+        .subkey("Policy\\Secrets\\NL$KM\\CurrVal", Key::new("CurrVal").value("", REG_BINARY, &lsa_secret(0x66, NLKM_SECRET)))
+        .subkey("Cache", Key::new("Cache")
+            .value("NL$1", REG_BINARY, &record)
+            // An unused slot has a zero IV
+            .value("NL$2", REG_BINARY, &[0u8; 200])
+            .value("NL$Control", REG_BINARY, &[0u8; 8])))
+}
+
+fn extract(sam: Vec<u8>, reveal: bool) -> Credentials {
+    let mut registry = RegistryParser::new();
+    registry.load_hive_bytes(system_hive(&CLASS_NAMES), "HKLM\\SYSTEM").unwrap();
+    registry.load_hive_bytes(sam, "HKLM\\SAM").unwrap();
+    registry.load_hive_bytes(security_hive(), "HKLM\\SECURITY").unwrap();
+    extract_credentials(&registry, reveal).unwrap()
+}
+
+#[test]
+fn test_boot_key() {
+    let mut registry = RegistryParser::new();
+    registry.load_hive_bytes(system_hive(&CLASS_NAMES), "HKLM\\SYSTEM").unwrap();
+    let key = boot_key(registry.hive("HKLM\\SYSTEM").unwrap()).unwrap();
+    assert_eq!(key, std::array::from_fn::<u8, 16, _>(|i| i as u8));
+}
+
+#[test]
+fn test_boot_key_rejects_non_ascii_class_names() {
+    // 32 bytes in total, with a two-byte character straddling a hex pair
+    let mut class_names = CLASS_NAMES;
+    class_names[0].1 = "080A0\u{e9}7";
+
+    let mut registry = RegistryParser::new();
+    registry.load_hive_bytes(system_hive(&class_names), "HKLM\\SYSTEM").unwrap();
+    assert!(boot_key(registry.hive("HKLM\\SYSTEM").unwrap()).is_err());
+}
+
+#[test]
+fn test_sam_hashes_aes() {
+    let credentials = extract(aes_sam_hive(), false);
+    assert_eq!(credentials.sam_hashes.len(), 1);
+
+    let admin = &credentials.sam_hashes[0];
+    assert_eq!(admin.rid, 500);
+    assert_eq!(admin.username, "Administrator");
+    assert_eq!(admin.nt_hash.as_deref(), Some(NT_PASSWORD));
+    assert_eq!(admin.lm_hash, None);
+}
+
+#[test]
+fn test_sam_hashes_rc4() {
+    let credentials = extract(rc4_sam_hive(), false);
+    assert_eq!(credentials.sam_hashes.len(), 1);
+
+    let user = &credentials.sam_hashes[0];
+    assert_eq!(user.rid, 1001);
+    assert_eq!(user.username, "jdoe");
+    assert_eq!(user.lm_hash.as_deref(), Some(LM_PASSWORD));
+    assert_eq!(user.nt_hash.as_deref(), Some(NT_PASSWORD));
+}
+
+#[test]
+fn test_sam_rejects_wrong_boot_key() {
+    let mut registry = RegistryParser::new();
+    let class_names = [("JD", "00000000"), ("Skew1", "00000000"), ("GBG", "00000000"), ("Data", "00000000")];
+    registry.load_hive_bytes(system_hive(&class_names), "HKLM\\SYSTEM").unwrap();
+    registry.load_hive_bytes(rc4_sam_hive(), "HKLM\\SAM").unwrap();
+    assert!(extract_credentials(&registry, false).is_err());
+}
+
+#[test]
+fn test_lsa_secrets() {
+    let credentials = extract(aes_sam_hive(), false);
+    assert_eq!(credentials.lsa_secrets.len(), 2);
+
+    let service = credentials.lsa_secrets.iter().find(|s| s.name == "_SC_MSSQL").unwrap();
+    assert_eq!(service.kind, SecretKind::ServicePassword);
+    assert_eq!(service.service.as_deref(), Some("MSSQL"));
+    assert_eq!(service.account.as_deref(), Some("CORP\\svc_sql"));
+    assert_eq!(service.nt_hash.as_deref(), Some(NT_PASSWORD));
+    assert_eq!(service.value, None);
+
+    let nlkm = credentials.lsa_secrets.iter().find(|s| s.name == "NL$KM").unwrap();
+    assert_eq!(nlkm.kind, SecretKind::CachedLogonKey);
+
+    let revealed = extract(aes_sam_hive(), true);
+    let service = revealed.lsa_secrets.iter().find(|s| s.name == "_SC_MSSQL").unwrap();
+    assert_eq!(service.value.as_deref(), Some("password"));
+    let nlkm = revealed.lsa_secrets.iter().find(|s| s.name == "NL$KM").unwrap();
+    assert_eq!(nlkm.value.as_deref(), Some(&*(0u8..64).map(|b| format!("{:02x}", b)).collect::<String>()));
+}
+
+#[test]
+fn test_cached_logons() {
+    let credentials = extract(aes_sam_hive(), false);
+    assert_eq!(credentials.cached_logons.len(), 1);
+
+    let logon = &credentials.cached_logons[0];
+    assert_eq!(logon.hash, "$DCC2$10240#tom#e4e938d12fe5974dc42a90120bd9c90f");
+    assert_eq!(logon.username, "tom");
+    assert_eq!(logon.domain, "CORP");
+    assert_eq!(logon.dns_domain.as_deref(), Some("corp.local"));
+    assert_eq!(logon.iteration_count, 10240);
+    assert_eq!(logon.last_logon.unwrap().timestamp(), 1_577_836_800);
+}
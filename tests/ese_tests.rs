@@ -0,0 +1,259 @@
+// tests/ese_tests.rs
+use forensic_triage::ese::{EseDatabase, EseValue};
+use forensic_triage::parsers::parse_srum;
+
+const PAGE_SIZE: usize = 4096;
+const LEAF: u32 = 0x02;
+
+/// 2024-01-02 03:04:05 UTC as an OLE automation date
+const OLE_DATE: f64 = 45293.12783564815;
+
+const NETWORK_USAGE: &str = "{973F5D5C-1D90-4944-BE8E-24B94231A174}";
+
+fn utf16(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// A page with an empty common key in tag 0 and an entry per (key, data)
+fn page(flags: u32, entries: &[(Vec<u8>, Vec<u8>)]) -> Vec<u8> {
+    let mut values = vec![Vec::new()];
+    for (key, data) in entries {
+        let mut value = (key.len() as u16).to_le_bytes().to_vec();
+        value.extend(key);
+        value.extend(data);
+        values.push(value);
+    }
+
+    let mut page = vec![0u8; PAGE_SIZE];
+    page[34..36].copy_from_slice(&(values.len() as u16).to_le_bytes());
+    page[36..40].copy_from_slice(&flags.to_le_bytes());
+    let mut offset = 0;
+    for (i, value) in values.iter().enumerate() {
+        page[40 + offset..40 + offset + value.len()].copy_from_slice(value);
+        let tag = PAGE_SIZE - 4 * (i + 1);
+        page[tag..tag + 2].copy_from_slice(&(value.len() as u16).to_le_bytes());
+        page[tag + 2..tag + 4].copy_from_slice(&(offset as u16).to_le_bytes());
+        offset += value.len();
+    }
+    page
+}
+
+/// Fixed columns (none null), variable columns and tagged columns with an
+/// optional flags byte
+fn record(fixed: &[Vec<u8>], variable: &[Option<Vec<u8>>], tagged: &[(u16, Option<u8>, Vec<u8>)]) -> Vec<u8> {
+    let fixed_data: Vec<u8> = fixed.concat();
+    let bitmap = fixed.len().div_ceil(8);
+    let variable_offset = 4 + fixed_data.len() + bitmap;
+
+    let mut data = vec![fixed.len() as u8, (127 + variable.len()) as u8];
+    data.extend((variable_offset as u16).to_le_bytes());
+    data.extend(fixed_data);
+    data.extend(vec![0u8; bitmap]);
+
+    let mut end = 0u16;
+    let mut variable_data: Vec<u8> = Vec::new();
+    for value in variable {
+        match value {
+            Some(value) => {
+                end += value.len() as u16;
+                data.extend(end.to_le_bytes());
+                variable_data.extend(value);
+            }
+            None => data.extend((end | 0x8000).to_le_bytes()),
+        }
+    }
+    data.extend(variable_data);
+
+    let mut offset = tagged.len() * 4;
+    let mut tagged_data: Vec<u8> = Vec::new();
+    for (id, flags, value) in tagged {
+        data.extend(id.to_le_bytes());
+        let marker = if flags.is_some() { 0x4000 } else { 0 };
+        data.extend((offset as u16 | marker).to_le_bytes());
+        tagged_data.extend(flags);
+        tagged_data.extend(value);
+        offset += value.len() + flags.is_some() as usize;
+    }
+    data.extend(tagged_data);
+    data
+}
+
+/// MSysObjects row: object type, ID, column type or FDP page, size and codepage
+fn catalog_entry(table: i32, kind: i16, id: i32, coltyp_or_fdp: i32, size: i32, codepage: i32, name: &str) -> (Vec<u8>, Vec<u8>) {
+    let fixed = vec![
+        table.to_le_bytes().to_vec(),
+        kind.to_le_bytes().to_vec(),
+        id.to_le_bytes().to_vec(),
+        coltyp_or_fdp.to_le_bytes().to_vec(),
+        size.to_le_bytes().to_vec(),
+        0i32.to_le_bytes().to_vec(),
+        codepage.to_le_bytes().to_vec(),
+    ];
+    let key = [table.to_be_bytes().as_slice(), &kind.to_be_bytes(), name.as_bytes()].concat();
+    (key, record(&fixed, &[Some(name.as_bytes().to_vec())], &[]))
+}
+
+/// 7-bit compression: ASCII (1) or Unicode (2) characters packed LSB first
+fn pack7(text: &str, scheme: u8) -> Vec<u8> {
+    let (mut bits, mut count) = (0u32, 0);
+    let mut packed = Vec::new();
+    for c in text.bytes() {
+        bits |= (c as u32) << count;
+        count += 7;
+        while count >= 8 {
+            packed.push(bits as u8);
+            bits >>= 8;
+            count -= 8;
+        }
+    }
+    let used = if count > 0 {
+        packed.push(bits as u8);
+        count
+    } else {
+        8
+    };
+    let mut data = vec![(scheme << 3) | (used - 1) as u8];
+    data.extend(packed);
+    data
+}
+
+fn key(id: u32) -> Vec<u8> {
+    id.to_be_bytes().to_vec()
+}
+
+fn build_database() -> Vec<u8> {
+    let mut pages: Vec<(u32, Vec<u8>)> = Vec::new();
+
+    let catalog = vec![
+        catalog_entry(2, 1, 2, 10, 0, 0, "SruDbIdMapTable"),
+        catalog_entry(2, 2, 1, 2, 1, 0, "IdType"),
+        catalog_entry(2, 2, 2, 4, 4, 0, "IdIndex"),
+        catalog_entry(2, 2, 256, 11, 0, 0, "IdBlob"),
+        catalog_entry(3, 1, 3, 11, 0, 0, NETWORK_USAGE),
+        catalog_entry(3, 2, 1, 4, 4, 0, "AutoIncId"),
+        catalog_entry(3, 2, 2, 8, 8, 0, "TimeStamp"),
+        catalog_entry(3, 2, 3, 4, 4, 0, "AppId"),
+        catalog_entry(3, 2, 4, 4, 4, 0, "UserId"),
+        catalog_entry(3, 2, 5, 15, 8, 0, "InterfaceLuid"),
+        catalog_entry(3, 2, 6, 4, 4, 0, "L2ProfileId"),
+        catalog_entry(3, 2, 7, 15, 8, 0, "BytesSent"),
+        catalog_entry(3, 2, 8, 15, 8, 0, "BytesRecvd"),
+        catalog_entry(4, 1, 4, 13, 0, 0, "Test"),
+        catalog_entry(4, 2, 1, 4, 4, 0, "Id"),
+        catalog_entry(4, 2, 128, 10, 0, 1200, "Name"),
+        catalog_entry(4, 2, 129, 9, 0, 0, "Hash"),
+        catalog_entry(4, 2, 256, 12, 0, 1200, "Notes"),
+        catalog_entry(4, 2, 257, 10, 0, 1252, "Ascii"),
+        catalog_entry(4, 2, 258, 11, 0, 0, "Blob"),
+        catalog_entry(4, 2, 259, 11, 0, 0, "Packed"),
+        catalog_entry(4, 2, 260, 10, 0, 1252, "Tags"),
+        catalog_entry(4, 3, 5, 0, 0, 0, "PrimaryIndex"),
+        catalog_entry(4, 4, 6, 14, 0, 0, "LV"),
+    ];
+    pages.push((4, page(LEAF, &catalog)));
+
+    let sid = [
+        &[1u8, 5, 0, 0, 0, 0, 0, 5][..],
+        &21u32.to_le_bytes(), &1u32.to_le_bytes(), &2u32.to_le_bytes(), &3u32.to_le_bytes(), &1001u32.to_le_bytes(),
+    ].concat();
+    let id_map = vec![
+        (key(1), record(&[vec![0], 1i32.to_le_bytes().to_vec()], &[], &[(256, None, utf16("\\Device\\HarddiskVolume2\\Windows\\System32\\svchost.exe"))])),
+        (key(2), record(&[vec![3], 2i32.to_le_bytes().to_vec()], &[], &[(256, None, sid)])),
+    ];
+    pages.push((10, page(LEAF, &id_map)));
+
+    // Network usage rows split across two leaves under a branch page
+    let usage = |id: i32, sent: u64, received: u64| record(&[
+        id.to_le_bytes().to_vec(),
+        OLE_DATE.to_le_bytes().to_vec(),
+        1i32.to_le_bytes().to_vec(),
+        2i32.to_le_bytes().to_vec(),
+        0x0006_0000_0000_0001u64.to_le_bytes().to_vec(),
+        268i32.to_le_bytes().to_vec(),
+        sent.to_le_bytes().to_vec(),
+        received.to_le_bytes().to_vec(),
+    ], &[], &[]);
+    pages.push((11, page(0, &[(key(1), 15u32.to_le_bytes().to_vec()), (Vec::new(), 16u32.to_le_bytes().to_vec())])));
+    pages.push((15, page(LEAF, &[(key(1), usage(1, 1024, 4096))])));
+    pages.push((16, page(LEAF, &[(key(2), usage(2, 10, 20))])));
+
+    // abc, then a match of nine bytes three back
+    let mut packed = vec![3 << 3];
+    packed.extend(12u16.to_le_bytes());
+    packed.extend(0x1000_0000u32.to_le_bytes());
+    packed.extend(b"abc");
+    packed.extend((((3 - 1) << 3) | (9 - 3) as u16).to_le_bytes());
+
+    let test = record(
+        &[7i32.to_le_bytes().to_vec()],
+        &[Some(utf16("Widget")), None],
+        &[
+            (256, Some(0x02), pack7("Compressed notes", 2)),
+            (257, Some(0x02), pack7("Hello", 1)),
+            (258, Some(0x04), 0x20u32.to_le_bytes().to_vec()),
+            (259, Some(0x02), packed),
+            (260, Some(0x08), [&4u16.to_le_bytes()[..], &7u16.to_le_bytes(), b"onetwo"].concat()),
+        ],
+    );
+    pages.push((13, page(LEAF, &[(key(7), test)])));
+
+    // A long value in two segments, after its header
+    let blob: Vec<u8> = (0..3000u32).map(|i| i as u8).collect();
+    let segment = |offset: u32| [0x20u32.to_be_bytes(), offset.to_be_bytes()].concat();
+    pages.push((14, page(LEAF, &[
+        (key(0x20), [1u32.to_le_bytes(), 3000u32.to_le_bytes()].concat()),
+        (segment(0), blob[..2000].to_vec()),
+        (segment(2000), blob[2000..].to_vec()),
+    ])));
+
+    let mut data = vec![0u8; PAGE_SIZE * 18];
+    data[4..8].copy_from_slice(&0x89AB_CDEFu32.to_le_bytes());
+    data[236..240].copy_from_slice(&(PAGE_SIZE as u32).to_le_bytes());
+    for (number, page) in pages {
+        let offset = (number as usize + 1) * PAGE_SIZE;
+        data[offset..offset + PAGE_SIZE].copy_from_slice(&page);
+    }
+    data
+}
+
+#[test]
+fn test_ese_records() {
+    let data = build_database();
+    let database = EseDatabase::parse(&data).unwrap();
+    let names: Vec<&str> = database.tables().iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["SruDbIdMapTable", NETWORK_USAGE, "Test"]);
+
+    let records = database.records("test").unwrap();
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record["Id"], EseValue::Integer(7));
+    assert_eq!(record["Name"].as_str(), Some("Widget"));
+    assert!(!record.contains_key("Hash"));
+    assert_eq!(record["Notes"].as_str(), Some("Compressed notes"));
+    assert_eq!(record["Ascii"].as_str(), Some("Hello"));
+    assert_eq!(record["Packed"].as_bytes(), Some(&b"abcabcabcabc"[..]));
+    assert_eq!(record["Tags"], EseValue::Multiple(vec![EseValue::Text("one".into()), EseValue::Text("two".into())]));
+
+    let blob = record["Blob"].as_bytes().unwrap();
+    assert_eq!(blob.len(), 3000);
+    assert!(blob.iter().enumerate().all(|(i, &b)| b == i as u8));
+
+    assert!(database.records("Missing").is_err());
+}
+
+#[test]
+fn test_srum_network_usage() {
+    let srum = parse_srum(&build_database()).unwrap();
+    assert!(srum.app_usage.is_empty());
+
+    assert_eq!(srum.network_usage.len(), 2);
+    let usage = &srum.network_usage[0];
+    assert_eq!(usage.timestamp.unwrap().to_rfc3339(), "2024-01-02T03:04:05+00:00");
+    assert_eq!(usage.app.as_deref(), Some("\\Device\\HarddiskVolume2\\Windows\\System32\\svchost.exe"));
+    assert_eq!(usage.user_sid.as_deref(), Some("S-1-5-21-1-2-3-1001"));
+    assert_eq!(usage.profile_id, Some(268));
+    assert_eq!((usage.bytes_sent, usage.bytes_received), (1024, 4096));
+    assert_eq!(srum.network_usage[1].bytes_received, 20);
+
+    assert!(parse_srum(&vec![0u8; PAGE_SIZE]).is_err());
+}
@@ -0,0 +1,375 @@
+// tests/evtx_tests.rs
+use std::collections::BTreeMap;
+use forensic_triage::parsers::{parse_evtx, summarize_events, EventSummarizer, EvtxRecord, LogonAction};
+
+const CHUNK_START: usize = 4096;
+const CHUNK_SIZE: usize = 65536;
+
+type Attribute<'a> = (&'a str, &'a dyn Fn(&mut Chunk));
+
+/// Writes BinXML into a chunk, defining names inline at first use
+struct Chunk {
+    data: Vec<u8>,
+}
+
+impl Chunk {
+    fn new() -> Self {
+        let mut data = vec![0u8; 512];
+        data[..8].copy_from_slice(b"ElfChnk\0");
+        Self { data }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    fn u16(&mut self, v: u16) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    fn string(&mut self, s: &str) {
+        let units: Vec<u16> = s.encode_utf16().collect();
+        self.u16(units.len() as u16);
+        for unit in units {
+            self.u16(unit);
+        }
+    }
+
+    fn name(&mut self, name: &str) {
+        let offset = self.data.len() as u32 + 4;
+        self.u32(offset);
+        self.u32(0);
+        self.u16(0);
+        self.string(name);
+        self.u16(0);
+    }
+
+    fn open(&mut self, name: &str, attributes: &[Attribute]) {
+        self.bytes(&[if attributes.is_empty() { 0x01 } else { 0x41 }]);
+        self.u16(0xFFFF);
+        self.u32(0);
+        self.name(name);
+        if !attributes.is_empty() {
+            self.u32(0);
+            for (i, (name, value)) in attributes.iter().enumerate() {
+                self.bytes(&[if i + 1 < attributes.len() { 0x46 } else { 0x06 }]);
+                self.name(name);
+                value(self);
+            }
+        }
+    }
+
+    fn literal(&mut self, text: &str) {
+        self.bytes(&[0x05, 0x01]);
+        self.string(text);
+    }
+
+    fn substitution(&mut self, index: u16, value_type: u8) {
+        self.bytes(&[0x0E]);
+        self.u16(index);
+        self.bytes(&[value_type]);
+    }
+
+    /// `<name>{substitution}</name>`
+    fn text_element(&mut self, name: &str, index: u16, value_type: u8) {
+        self.open(name, &[]);
+        self.bytes(&[0x02]);
+        self.substitution(index, value_type);
+        self.bytes(&[0x04]);
+    }
+
+    fn data_element(&mut self, data_name: &str, index: u16, value_type: u8) {
+        self.open("Data", &[("Name", &|c: &mut Chunk| c.literal(data_name))]);
+        self.bytes(&[0x02]);
+        self.substitution(index, value_type);
+        self.bytes(&[0x04]);
+    }
+
+    fn template(&mut self) {
+        self.bytes(&[0x0F, 0x01, 0x01, 0x00]);
+        self.open("Event", &[("xmlns", &|c: &mut Chunk| c.literal("http://schemas.microsoft.com/win/2004/08/events/event"))]);
+        self.bytes(&[0x02]);
+        self.open("System", &[]);
+        self.bytes(&[0x02]);
+        self.open("Provider", &[("Name", &|c: &mut Chunk| c.substitution(0, 0x01))]);
+        self.bytes(&[0x03]);
+        self.text_element("EventID", 1, 0x06);
+        self.open("TimeCreated", &[("SystemTime", &|c: &mut Chunk| c.substitution(2, 0x11))]);
+        self.bytes(&[0x03]);
+        self.text_element("Channel", 3, 0x01);
+        self.text_element("Computer", 4, 0x01);
+        self.open("Security", &[("UserID", &|c: &mut Chunk| c.substitution(5, 0x13))]);
+        self.bytes(&[0x03, 0x04]);
+        self.open("EventData", &[]);
+        self.bytes(&[0x02]);
+        self.data_element("TargetUserName", 6, 0x01);
+        self.data_element("LogonType", 7, 0x08);
+        self.bytes(&[0x04, 0x04, 0x00]);
+    }
+
+    /// A 4624 record; the first record defines the template inline
+    fn record(&mut self, record_id: u64, user: &str, template: &mut Option<u32>) {
+        let start = self.data.len();
+        self.bytes(b"**\0\0");
+        self.u32(0);
+        self.bytes(&record_id.to_le_bytes());
+        self.bytes(&133_000_000_000_000_000u64.to_le_bytes());
+
+        self.bytes(&[0x0F, 0x01, 0x01, 0x00, 0x0C, 0x01]);
+        self.u32(0);
+        match template {
+            Some(offset) => self.u32(*offset),
+            None => {
+                let offset = self.data.len() as u32 + 4;
+                *template = Some(offset);
+                self.u32(offset);
+                self.bytes(&[0u8; 20]);
+                self.u32(0);
+                let body = self.data.len();
+                self.template();
+                let size = (self.data.len() - body) as u32;
+                self.data[body - 4..body].copy_from_slice(&size.to_le_bytes());
+            }
+        }
+
+        let utf16 = |s: &str| s.encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<u8>>();
+        let values: Vec<(u8, Vec<u8>)> = vec![
+            (0x01, utf16("Microsoft-Windows-Security-Auditing")),
+            (0x06, 4624u16.to_le_bytes().to_vec()),
+            (0x11, 133_000_000_000_000_000u64.to_le_bytes().to_vec()),
+            (0x01, utf16("Security")),
+            (0x01, utf16("WS01")),
+            (0x00, Vec::new()),
+            (0x01, utf16(user)),
+            (0x08, 10u32.to_le_bytes().to_vec()),
+        ];
+        self.u32(values.len() as u32);
+        for (value_type, data) in &values {
+            self.u16(data.len() as u16);
+            self.bytes(&[*value_type, 0]);
+        }
+        for (_, data) in &values {
+            self.bytes(data);
+        }
+
+        while !(self.data.len() - start + 4).is_multiple_of(8) {
+            self.bytes(&[0]);
+        }
+        let size = (self.data.len() - start + 4) as u32;
+        self.u32(size);
+        self.data[start + 4..start + 8].copy_from_slice(&size.to_le_bytes());
+    }
+
+    /// A record of `depth` nested elements, all sharing one name
+    fn nested_record(&mut self, record_id: u64, depth: usize) {
+        let start = self.data.len();
+        self.bytes(b"**\0\0");
+        self.u32(0);
+        self.bytes(&record_id.to_le_bytes());
+        self.bytes(&133_000_000_000_000_000u64.to_le_bytes());
+
+        self.bytes(&[0x0F, 0x01, 0x01, 0x00]);
+        let name = self.data.len() as u32 + 11;
+        for level in 0..depth {
+            self.bytes(&[0x01]);
+            self.u16(0xFFFF);
+            self.u32(0);
+            if level == 0 {
+                self.name("Nested");
+            } else {
+                self.u32(name);
+            }
+            self.bytes(&[0x02]);
+        }
+        self.bytes(&vec![0x04; depth]);
+        self.bytes(&[0x00]);
+
+        while !(self.data.len() - start + 4).is_multiple_of(8) {
+            self.bytes(&[0]);
+        }
+        let size = (self.data.len() - start + 4) as u32;
+        self.u32(size);
+        self.data[start + 4..start + 8].copy_from_slice(&size.to_le_bytes());
+    }
+
+    /// Fill in the header fields and checksums, and pad to the chunk size
+    fn finish(mut self, first: u64, last: u64) -> Vec<u8> {
+        let free = self.data.len() as u32;
+        self.data.resize(CHUNK_SIZE, 0);
+        self.data[24..32].copy_from_slice(&first.to_le_bytes());
+        self.data[32..40].copy_from_slice(&last.to_le_bytes());
+        self.data[48..52].copy_from_slice(&free.to_le_bytes());
+        let data_crc = crc32fast::hash(&self.data[512..free as usize]);
+        self.data[52..56].copy_from_slice(&data_crc.to_le_bytes());
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&self.data[..120]);
+        hasher.update(&self.data[128..512]);
+        let header_crc = hasher.finalize();
+        self.data[124..128].copy_from_slice(&header_crc.to_le_bytes());
+        self.data
+    }
+}
+
+fn build_log() -> Vec<u8> {
+    let mut chunk = Chunk::new();
+    let mut template = None;
+    chunk.record(1, "alice", &mut template);
+    chunk.record(2, "bob", &mut template);
+    log_file(chunk, 1, 2)
+}
+
+fn log_file(chunk: Chunk, first: u64, last: u64) -> Vec<u8> {
+    let mut file = vec![0u8; CHUNK_START];
+    file[..8].copy_from_slice(b"ElfFile\0");
+    file[24..32].copy_from_slice(&3u64.to_le_bytes());
+    file[36..38].copy_from_slice(&2u16.to_le_bytes());
+    file[38..40].copy_from_slice(&3u16.to_le_bytes());
+    file[42..44].copy_from_slice(&1u16.to_le_bytes());
+    let crc = crc32fast::hash(&file[..120]);
+    file[124..128].copy_from_slice(&crc.to_le_bytes());
+    file.extend(chunk.finish(first, last));
+    file
+}
+
+#[test]
+fn test_template_records() {
+    let log = parse_evtx(&build_log()).unwrap();
+    assert!(log.header.checksum_valid);
+    assert!(log.chunks[0].data_checksum_valid);
+    assert_eq!(log.records.len(), 2);
+
+    let record = &log.records[1];
+    assert_eq!(record.record_id, 2);
+    assert_eq!(record.event_id, Some(4624));
+    assert_eq!(record.provider.as_deref(), Some("Microsoft-Windows-Security-Auditing"));
+    assert_eq!(record.channel.as_deref(), Some("Security"));
+    assert_eq!(record.computer.as_deref(), Some("WS01"));
+    assert_eq!(record.user_sid, None);
+    assert_eq!(record.event_data["TargetUserName"], "bob");
+    assert_eq!(record.event_data["LogonType"], "10");
+    assert!(!record.recovered);
+
+    assert!(record.to_xml().contains("<Data Name=\"TargetUserName\">bob</Data>"));
+    assert_eq!(record.to_json()["Event"]["EventData"]["LogonType"], "10");
+}
+
+#[test]
+fn test_corrupt_chunk_is_carved() {
+    let mut data = build_log();
+    // Damage a byte covered by the data checksum but outside any record
+    let last = CHUNK_START + u32::from_le_bytes(data[CHUNK_START + 48..CHUNK_START + 52].try_into().unwrap()) as usize;
+    data[last - 5] ^= 0xFF;
+
+    let log = parse_evtx(&data).unwrap();
+    assert!(!log.chunks[0].data_checksum_valid);
+    assert_eq!(log.records.len(), 2);
+    assert!(log.records.iter().all(|r| r.recovered));
+    assert_eq!(log.records[0].event_data["TargetUserName"], "alice");
+}
+
+#[test]
+fn test_deeply_nested_record_is_rejected() {
+    let mut chunk = Chunk::new();
+    let mut template = None;
+    chunk.record(1, "alice", &mut template);
+    chunk.nested_record(2, 1000);
+    chunk.record(3, "bob", &mut template);
+
+    let log = parse_evtx(&log_file(chunk, 1, 3)).unwrap();
+    let ids: Vec<u64> = log.records.iter().map(|r| r.record_id).collect();
+    assert_eq!(ids, [1, 3]);
+    assert_eq!(log.records[1].event_data["TargetUserName"], "bob");
+
+    // Nesting within the limit still parses
+    let mut chunk = Chunk::new();
+    chunk.nested_record(1, 20);
+    let log = parse_evtx(&log_file(chunk, 1, 1)).unwrap();
+    assert_eq!(log.records.len(), 1);
+}
+
+fn event(record_id: u64, provider: &str, event_id: u32, data: &[(&str, &str)]) -> EvtxRecord {
+    EvtxRecord {
+        record_id,
+        timestamp: chrono::DateTime::from_timestamp(1_700_000_000 + record_id as i64, 0),
+        provider: Some(provider.to_string()),
+        channel: None,
+        event_id: Some(event_id),
+        level: Some(5),
+        computer: None,
+        user_sid: None,
+        event_data: data.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<BTreeMap<_, _>>(),
+        recovered: false,
+        root: Default::default(),
+    }
+}
+
+#[test]
+fn test_event_summary() {
+    let powershell = "Microsoft-Windows-PowerShell";
+    let records = vec![
+        event(1, "Microsoft-Windows-Security-Auditing", 4624, &[
+            ("TargetUserName", "alice"), ("TargetDomainName", "CORP"), ("LogonType", "10"),
+            ("IpAddress", "10.0.0.5"), ("IpPort", "50123"),
+        ]),
+        event(2, powershell, 4104, &[
+            ("ScriptBlockId", "{a}"), ("MessageNumber", "2"), ("MessageTotal", "2"), ("ScriptBlockText", "-enc AAAA"),
+        ]),
+        event(3, powershell, 4104, &[
+            ("ScriptBlockId", "{a}"), ("MessageNumber", "1"), ("MessageTotal", "2"), ("ScriptBlockText", "powershell "),
+        ]),
+        // Event 104 from another provider is not a log clear
+        event(4, "Microsoft-Windows-Kernel-Power", 104, &[]),
+    ];
+
+    let summary = summarize_events(&records);
+    assert_eq!(summary.logons.len(), 1);
+    let logon = &summary.logons[0];
+    assert_eq!(logon.action, LogonAction::Logon);
+    assert_eq!(logon.user.as_deref(), Some("CORP\\alice"));
+    assert_eq!(logon.logon_type_name.as_deref(), Some("RemoteInteractive"));
+    assert_eq!(logon.source_ip.as_deref(), Some("10.0.0.5"));
+
+    assert_eq!(summary.script_blocks.len(), 1);
+    assert_eq!(summary.script_blocks[0].text, "powershell -enc AAAA");
+    assert!(summary.script_blocks[0].complete);
+    assert!(summary.log_clears.is_empty());
+}
+
+#[test]
+fn test_event_summarizer_across_logs() {
+    let powershell = "Microsoft-Windows-PowerShell";
+    let mut summarizer = EventSummarizer::default();
+
+    // A script block split over two logs, each dropped once added
+    let first = vec![
+        event(1, powershell, 4104, &[
+            ("ScriptBlockId", "{a}"), ("MessageNumber", "2"), ("MessageTotal", "2"), ("ScriptBlockText", "-enc AAAA"),
+        ]),
+        event(2, "Service Control Manager", 7045, &[("ServiceName", "evil"), ("ImagePath", "C:\\evil.exe")]),
+    ];
+    for record in &first {
+        summarizer.add(record);
+    }
+    drop(first);
+
+    let second = vec![
+        event(3, powershell, 4104, &[
+            ("ScriptBlockId", "{a}"), ("MessageNumber", "1"), ("MessageTotal", "2"), ("ScriptBlockText", "powershell "),
+        ]),
+    ];
+    for record in &second {
+        summarizer.add(record);
+    }
+    drop(second);
+
+    let summary = summarizer.finish();
+    assert_eq!(summary.service_installs.len(), 1);
+    assert_eq!(summary.service_installs[0].image_path.as_deref(), Some("C:\\evil.exe"));
+    assert_eq!(summary.script_blocks.len(), 1);
+    assert_eq!(summary.script_blocks[0].text, "powershell -enc AAAA");
+    assert!(summary.script_blocks[0].complete);
+}
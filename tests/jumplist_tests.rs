@@ -0,0 +1,206 @@
+// tests/jumplist_tests.rs
+use forensic_triage::parsers::{parse_automatic_destinations, parse_custom_destinations, JumpListKind};
+use forensic_triage::utils::cfb::CompoundFile;
+
+const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+const FREE: u32 = 0xFFFF_FFFF;
+/// 2024-01-02 03:04:05 UTC
+const FILETIME: u64 = 133_486_382_450_000_000;
+
+/// A minimal Unicode shortcut with only a description
+fn lnk(name: &str) -> Vec<u8> {
+    let mut data = vec![0u8; 0x4C];
+    data[0] = 0x4C;
+    data[4..20].copy_from_slice(&[0x01, 0x14, 0x02, 0, 0, 0, 0, 0, 0xC0, 0, 0, 0, 0, 0, 0, 0x46]);
+    data[20..24].copy_from_slice(&(0x04u32 | 0x80).to_le_bytes());
+    data.extend((name.len() as u16).to_le_bytes());
+    data.extend(name.encode_utf16().flat_map(u16::to_le_bytes));
+    data.extend(0u32.to_le_bytes());
+    data
+}
+
+/// A version 4 DestList with one pinned entry
+fn destlist() -> Vec<u8> {
+    let path = "C:\\Users\\alice\\Documents\\plan.txt";
+    let mut data = vec![0u8; 32];
+    data[0..4].copy_from_slice(&4u32.to_le_bytes());
+    data[4..8].copy_from_slice(&1u32.to_le_bytes());
+
+    let mut entry = vec![0u8; 130];
+    entry[72..78].copy_from_slice(b"ws01\0\0");
+    entry[88..92].copy_from_slice(&1u32.to_le_bytes());
+    entry[100..108].copy_from_slice(&FILETIME.to_le_bytes());
+    entry[108..112].copy_from_slice(&0u32.to_le_bytes());
+    entry[116..120].copy_from_slice(&7u32.to_le_bytes());
+    entry[128..130].copy_from_slice(&(path.len() as u16).to_le_bytes());
+    entry.extend(path.encode_utf16().flat_map(u16::to_le_bytes));
+    entry.extend([0; 4]);
+    data.extend(entry);
+    data
+}
+
+fn directory_entry(name: &str, object_type: u8, right: u32, child: u32, start: u32, size: u64) -> Vec<u8> {
+    let mut entry = vec![0u8; 128];
+    let encoded: Vec<u8> = name.encode_utf16().chain([0]).flat_map(u16::to_le_bytes).collect();
+    entry[..encoded.len()].copy_from_slice(&encoded);
+    entry[64..66].copy_from_slice(&(encoded.len() as u16).to_le_bytes());
+    entry[66] = object_type;
+    entry[68..72].copy_from_slice(&FREE.to_le_bytes());
+    entry[72..76].copy_from_slice(&right.to_le_bytes());
+    entry[76..80].copy_from_slice(&child.to_le_bytes());
+    entry[116..120].copy_from_slice(&start.to_le_bytes());
+    entry[120..128].copy_from_slice(&size.to_le_bytes());
+    entry
+}
+
+/// A version 3 compound file with small streams, all in the mini stream.
+/// Sectors: 0 FAT, 1 directory, 2 mini FAT, 3.. mini stream.
+fn compound_file(streams: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut mini_stream = Vec::new();
+    let mut mini_fat = Vec::new();
+    let mut directory = Vec::new();
+    for (i, (name, data)) in streams.iter().enumerate() {
+        let start = mini_fat.len() as u32;
+        let sectors = data.len().div_ceil(64);
+        mini_fat.extend((1..sectors as u32).map(|n| start + n));
+        mini_fat.push(END_OF_CHAIN);
+        let mut padded = data.clone();
+        padded.resize(sectors * 64, 0);
+        mini_stream.extend(padded);
+        let right = if i + 1 < streams.len() { i as u32 + 2 } else { FREE };
+        directory.extend(directory_entry(name, 2, right, FREE, start, data.len() as u64));
+    }
+    let mini_sectors = mini_stream.len().div_ceil(512);
+    mini_stream.resize(mini_sectors * 512, 0);
+
+    let mut root = directory_entry("Root Entry", 5, FREE, 1, 3, mini_stream.len() as u64);
+    root.extend(directory);
+    root.resize(512, 0);
+
+    let mut fat = vec![0xFFFF_FFFD, END_OF_CHAIN, END_OF_CHAIN];
+    fat.extend((1..mini_sectors as u32).map(|n| 3 + n));
+    fat.push(END_OF_CHAIN);
+    fat.resize(128, FREE);
+    mini_fat.resize(128, FREE);
+
+    let mut header = vec![0u8; 512];
+    header[..8].copy_from_slice(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]);
+    for (offset, value) in [(0x18, 0x3Eu16), (0x1A, 3), (0x1C, 0xFFFE), (0x1E, 9), (0x20, 6)] {
+        header[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+    for (offset, value) in [(0x2C, 1u32), (0x30, 1), (0x38, 4096), (0x3C, 2), (0x40, 1), (0x44, END_OF_CHAIN), (0x4C, 0)] {
+        header[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+    for i in 1..109 {
+        header[0x4C + i * 4..0x50 + i * 4].copy_from_slice(&FREE.to_le_bytes());
+    }
+
+    header.extend(fat.iter().flat_map(|v| v.to_le_bytes()));
+    header.extend(root);
+    header.extend(mini_fat.iter().flat_map(|v| v.to_le_bytes()));
+    header.extend(mini_stream);
+    header
+}
+
+/// A version 3 compound file whose directory is one long chain of `count`
+/// empty streams, each the right sibling of the one before.
+/// Sectors: FAT, then the directory.
+fn deep_compound_file(count: usize) -> Vec<u8> {
+    let directory_sectors = (count + 1).div_ceil(4);
+    let mut fat_sectors = 1;
+    while fat_sectors * 128 < fat_sectors + directory_sectors {
+        fat_sectors += 1;
+    }
+    assert!(fat_sectors <= 109);
+
+    let mut directory = directory_entry("Root Entry", 5, FREE, 1, END_OF_CHAIN, 0);
+    for i in 1..=count {
+        let right = if i < count { i as u32 + 1 } else { FREE };
+        directory.extend(directory_entry(&format!("s{}", i), 2, right, FREE, END_OF_CHAIN, 0));
+    }
+    directory.resize(directory_sectors * 512, 0);
+
+    let mut fat = vec![0xFFFF_FFFD; fat_sectors];
+    let first = fat_sectors as u32;
+    fat.extend((1..directory_sectors as u32).map(|n| first + n));
+    fat.push(END_OF_CHAIN);
+    fat.resize(fat_sectors * 128, FREE);
+
+    let mut header = vec![0u8; 512];
+    header[..8].copy_from_slice(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]);
+    for (offset, value) in [(0x18, 0x3Eu16), (0x1A, 3), (0x1C, 0xFFFE), (0x1E, 9), (0x20, 6)] {
+        header[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+    for (offset, value) in [(0x2C, fat_sectors as u32), (0x30, first), (0x38, 4096), (0x3C, END_OF_CHAIN), (0x44, END_OF_CHAIN)] {
+        header[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+    for i in 0..109 {
+        let id = if i < fat_sectors { i as u32 } else { FREE };
+        header[0x4C + i * 4..0x50 + i * 4].copy_from_slice(&id.to_le_bytes());
+    }
+
+    header.extend(fat.iter().flat_map(|v| v.to_le_bytes()));
+    header.extend(directory);
+    header
+}
+
+#[test]
+fn test_compound_file_untrusted_structures() {
+    // Deep enough to overflow the stack if walked recursively
+    let data = deep_compound_file(50_000);
+    let file = CompoundFile::parse(&data).unwrap();
+    assert_eq!(file.entries().len(), 50_001);
+    assert_eq!(file.entries()[1].path, "s1");
+    assert_eq!(file.entries()[50_000].path, "s50000");
+
+    // A DIFAT chain that loops back on itself, with a FAT sector count
+    // that would otherwise never be reached
+    let mut data = compound_file(&[("1", lnk("Plan"))]);
+    data[0x2C..0x30].copy_from_slice(&u32::MAX.to_le_bytes());
+    data[0x44..0x48].copy_from_slice(&2u32.to_le_bytes());
+    // The last entry of sector 2 links to sector 2
+    data[512 * 3 + 508..512 * 4].copy_from_slice(&2u32.to_le_bytes());
+    data.resize(data.len() + 400 * 512, 0);
+    assert!(CompoundFile::parse(&data).is_err());
+}
+
+#[test]
+fn test_automatic_destinations() {
+    let data = compound_file(&[("DestList", destlist()), ("1", lnk("Plan")), ("2", lnk("Orphan"))]);
+
+    let file = CompoundFile::parse(&data).unwrap();
+    assert_eq!(file.read_stream("destlist").unwrap(), destlist());
+
+    let jump_list = parse_automatic_destinations(&data, "9b9cdc69c1c24e2b").unwrap();
+    assert_eq!(jump_list.kind, JumpListKind::Automatic);
+    assert_eq!(jump_list.application.as_deref(), Some("Notepad (64-bit)"));
+    assert_eq!(jump_list.version, Some(4));
+    assert_eq!(jump_list.entries.len(), 2);
+
+    let entry = &jump_list.entries[0];
+    assert_eq!(entry.entry_number, Some(1));
+    assert!(entry.pinned);
+    assert_eq!(entry.access_count, Some(7));
+    assert_eq!(entry.hostname.as_deref(), Some("ws01"));
+    assert_eq!(entry.path.as_deref(), Some("C:\\Users\\alice\\Documents\\plan.txt"));
+    assert_eq!(entry.last_used.unwrap().to_rfc3339(), "2024-01-02T03:04:05+00:00");
+    assert_eq!(entry.link.as_ref().unwrap().name.as_deref(), Some("Plan"));
+
+    // The second LNK stream is no longer in the DestList
+    assert_eq!(jump_list.entries[1].entry_number, Some(2));
+    assert_eq!(jump_list.entries[1].link.as_ref().unwrap().name.as_deref(), Some("Orphan"));
+}
+
+#[test]
+fn test_custom_destinations() {
+    let mut data = vec![2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0];
+    data.extend(lnk("First"));
+    data.extend(lnk("Second"));
+    data.extend(0xBABF_FBABu32.to_le_bytes());
+
+    let jump_list = parse_custom_destinations(&data, "0123456789abcdef").unwrap();
+    assert_eq!(jump_list.kind, JumpListKind::Custom);
+    assert_eq!(jump_list.application, None);
+    let names: Vec<_> = jump_list.entries.iter().filter_map(|e| e.link.as_ref()?.name.clone()).collect();
+    assert_eq!(names, ["First", "Second"]);
+}
@@ -0,0 +1,160 @@
+// tests/lnk_tests.rs
+use forensic_triage::parsers::{parse_lnk, DriveType};
+
+const LINK_CLSID: &str = "{00021401-0000-0000-C000-000000000046}";
+const MY_COMPUTER: &str = "{20D04FE0-3AEA-1069-A2D8-08002B30309D}";
+const DOCUMENTS: &str = "{FDD39AD0-238F-46AF-ADB4-6C85480369C7}";
+const SUMMARY_INFORMATION: &str = "{B725F130-47EF-101A-A5F1-02608C9EEBAC}";
+/// 2024-01-02 03:04:05 UTC
+const FILETIME: u64 = 133_486_382_450_000_000;
+
+fn guid(text: &str) -> Vec<u8> {
+    let hex: String = text.chars().filter(char::is_ascii_hexdigit).collect();
+    let bytes: Vec<u8> = (0..16).map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap()).collect();
+    let mut out = Vec::new();
+    out.extend(bytes[0..4].iter().rev());
+    out.extend(bytes[4..6].iter().rev());
+    out.extend(bytes[6..8].iter().rev());
+    out.extend(&bytes[8..]);
+    out
+}
+
+fn utf16z(text: &str) -> Vec<u8> {
+    text.encode_utf16().chain([0]).flat_map(u16::to_le_bytes).collect()
+}
+
+fn block(signature: u32, body: &[u8]) -> Vec<u8> {
+    let mut out = ((body.len() + 8) as u32).to_le_bytes().to_vec();
+    out.extend(signature.to_le_bytes());
+    out.extend(body);
+    out
+}
+
+/// A shortcut to `C:\Users\alice\Documents\report.docx` with every
+/// structure the parser decodes
+fn shortcut() -> Vec<u8> {
+    let mut data = vec![0u8; 0x4C];
+    data[0] = 0x4C;
+    data[4..20].copy_from_slice(&guid(LINK_CLSID));
+    // Target ID list, link info, name, arguments, Unicode
+    data[20..24].copy_from_slice(&(0x01u32 | 0x02 | 0x04 | 0x20 | 0x80).to_le_bytes());
+    data[24..28].copy_from_slice(&0x20u32.to_le_bytes());
+    data[28..36].copy_from_slice(&FILETIME.to_le_bytes());
+    data[52..56].copy_from_slice(&12345u32.to_le_bytes());
+    data[60..64].copy_from_slice(&1u32.to_le_bytes());
+
+    let mut items = vec![0x14, 0x00, 0x1F, 0x50];
+    items.extend(guid(MY_COMPUTER));
+    let mut volume = vec![0x19, 0x00, 0x2F];
+    volume.extend(b"C:\\");
+    volume.resize(0x19, 0);
+    items.extend(volume);
+    items.extend([0, 0]);
+    data.extend((items.len() as u16).to_le_bytes());
+    data.extend(items);
+
+    // LinkInfo with a VolumeID and ANSI local base path
+    let base_path = b"C:\\Users\\alice\\Documents\\report.docx\0";
+    let mut volume_id = 0x10u32.to_le_bytes().to_vec();
+    volume_id.extend(3u32.to_le_bytes());
+    volume_id.extend(0x1A2B3C4Du32.to_le_bytes());
+    volume_id.extend(0x10u32.to_le_bytes());
+    volume_id.extend(b"OS\0");
+    let volume_offset = 0x1C;
+    let path_offset = volume_offset + volume_id.len();
+    let suffix_offset = path_offset + base_path.len();
+    let mut info = Vec::new();
+    for value in [0, 0x1C, 1, volume_offset, path_offset, 0, suffix_offset] {
+        info.extend((value as u32).to_le_bytes());
+    }
+    info.extend(volume_id);
+    info.extend(base_path);
+    info.push(0);
+    let size = info.len() as u32;
+    info[0..4].copy_from_slice(&size.to_le_bytes());
+    data.extend(info);
+
+    for text in ["Quarterly report", "/q /x"] {
+        data.extend((text.len() as u16).to_le_bytes());
+        data.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    }
+
+    // Tracker block, with a version 1 UUID as the file droid
+    let uuid_time = FILETIME + 5_748_192_000_000_000;
+    let mut droid_file = (uuid_time as u32).to_le_bytes().to_vec();
+    droid_file.extend(((uuid_time >> 32) as u16).to_le_bytes());
+    droid_file.extend(((uuid_time >> 48) as u16 & 0x0FFF | 0x1000).to_le_bytes());
+    droid_file.extend([0x80, 0x01, 0x00, 0x0C, 0x29, 0xAB, 0xCD, 0xEF]);
+    let mut tracker = 0x58u32.to_le_bytes().to_vec();
+    tracker.extend(0u32.to_le_bytes());
+    let mut machine = b"ws01".to_vec();
+    machine.resize(16, 0);
+    tracker.extend(machine);
+    for droid in [guid(DOCUMENTS), droid_file.clone(), guid(DOCUMENTS), droid_file] {
+        tracker.extend(droid);
+    }
+    data.extend(block(0xA000_0003, &tracker));
+
+    let mut known_folder = guid(DOCUMENTS);
+    known_folder.extend(0u32.to_le_bytes());
+    data.extend(block(0xA000_000B, &known_folder));
+
+    // Property store holding System.ItemNameDisplay
+    let mut value = 10u32.to_le_bytes().to_vec();
+    value.push(0);
+    value.extend(0x1Fu16.to_le_bytes());
+    value.extend([0, 0]);
+    let name = utf16z("report.docx");
+    value.extend(((name.len() / 2) as u32).to_le_bytes());
+    value.extend(name);
+    let mut storage = 0x5350_5331u32.to_le_bytes().to_vec();
+    storage.extend(guid(SUMMARY_INFORMATION));
+    storage.extend(((value.len() + 4) as u32).to_le_bytes());
+    storage.extend(value);
+    storage.extend(0u32.to_le_bytes());
+    let mut store = ((storage.len() + 4) as u32).to_le_bytes().to_vec();
+    store.extend(storage);
+    store.extend(0u32.to_le_bytes());
+    data.extend(block(0xA000_0009, &store));
+
+    data.extend(0u32.to_le_bytes());
+    data
+}
+
+#[test]
+fn test_parse_lnk() {
+    let link = parse_lnk(&shortcut()).unwrap();
+
+    assert_eq!(link.target_created.unwrap().to_rfc3339(), "2024-01-02T03:04:05+00:00");
+    assert_eq!(link.target_size, 12345);
+    assert_eq!(link.target_id_list.len(), 2);
+    assert_eq!(link.name.as_deref(), Some("Quarterly report"));
+    assert_eq!(link.arguments.as_deref(), Some("/q /x"));
+    assert_eq!(link.target_path().as_deref(), Some("C:\\Users\\alice\\Documents\\report.docx"));
+
+    let info = link.link_info.as_ref().unwrap();
+    assert_eq!(info.drive_type, Some(DriveType::Fixed));
+    assert_eq!(info.drive_serial_number.as_deref(), Some("1A2B-3C4D"));
+    assert_eq!(info.volume_label.as_deref(), Some("OS"));
+
+    let tracker = link.tracker.as_ref().unwrap();
+    assert_eq!(tracker.machine_id, "ws01");
+    assert_eq!(tracker.mac_address.as_deref(), Some("00:0c:29:ab:cd:ef"));
+    assert_eq!(tracker.droid_timestamp, link.target_created);
+
+    assert_eq!(link.known_folder.as_deref(), Some("Documents ({FDD39AD0-238F-46AF-ADB4-6C85480369C7})"));
+    assert_eq!(link.properties["System.ItemNameDisplay"], "report.docx");
+}
+
+#[test]
+fn test_target_path_falls_back_to_id_list() {
+    let mut data = shortcut();
+    // Drop everything after the ID list and clear the LinkInfo flag
+    let id_list_end = 0x4C + 2 + u16::from_le_bytes([data[0x4C], data[0x4D]]) as usize;
+    data.truncate(id_list_end);
+    data[20..24].copy_from_slice(&0x01u32.to_le_bytes());
+
+    let link = parse_lnk(&data).unwrap();
+    assert_eq!(link.target_path().as_deref(), Some("My Computer\\C:"));
+    assert!(parse_lnk(&data[..0x40]).is_err());
+}
@@ -0,0 +1,174 @@
+// tests/network_tests.rs
+mod common;
+
+use common::{build_hive, Key, REG_BINARY};
+use forensic_triage::registry::{
+    parse_mapped_drives, parse_network_list, parse_rdp_connections, NetworkProfile, RegistryHive,
+};
+
+/// 2020-01-01 as a FILETIME
+const JAN_2020: u64 = 132_223_104_000_000_000;
+const NETWORK_LIST: &str = "Microsoft\\Windows NT\\CurrentVersion\\NetworkList";
+const HOME: &str = "{11111111-2222-3333-4444-555555555555}";
+const CORP: &str = "{AAAAAAAA-BBBB-CCCC-DDDD-EEEEEEEEEEEE}";
+const OTHER: &str = "{99999999-8888-7777-6666-555555555555}";
+
+/// SYSTEMTIME: year, month, day of week, day, hour, minute, second, milliseconds
+fn systemtime(fields: [u16; 8]) -> Vec<u8> {
+    fields.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+fn hive(root: Key) -> RegistryHive {
+    RegistryHive::from_bytes(build_hive(root)).unwrap()
+}
+
+fn software_hive() -> RegistryHive {
+    let profile = |guid: &str| format!("{}\\Profiles\\{}", NETWORK_LIST, guid);
+    let signature = |kind: &str, id: &str| format!("{}\\Signatures\\{}\\{}", NETWORK_LIST, kind, id);
+
+    hive(Key::new("ROOT")
+        .subkey(&profile(HOME), Key::new(HOME)
+            .last_write(JAN_2020)
+            .string("ProfileName", "HomeWifi")
+            .string("Description", "HomeWifi")
+            .dword("Category", 1)
+            .dword("NameType", 71)
+            .value("DateCreated", REG_BINARY, &systemtime([2024, 3, 2, 5, 14, 30, 0, 0]))
+            .value("DateLastConnected", REG_BINARY, &systemtime([2024, 6, 6, 1, 0, 15, 0, 0])))
+        .subkey(&profile(CORP), Key::new(CORP)
+            .string("ProfileName", "corp.example")
+            .dword("Category", 2)
+            .dword("NameType", 6))
+        // No signature, so the profile's own Managed flag is used
+        .subkey(&profile(OTHER), Key::new(OTHER)
+            .dword("Category", 5)
+            .dword("NameType", 999)
+            .dword("Managed", 1))
+        .subkey(&signature("Unmanaged", "0102"), Key::new("0102")
+            .string("ProfileGuid", &HOME.to_lowercase())
+            .value("DefaultGatewayMac", REG_BINARY, &[0x00, 0x11, 0x22, 0xAA, 0xBB, 0xCC])
+            .string("DnsSuffix", "<none>")
+            .string("FirstNetwork", "HomeWifi"))
+        .subkey(&signature("Managed", "0304"), Key::new("0304")
+            .string("ProfileGuid", CORP)
+            // Not a MAC address
+            .value("DefaultGatewayMac", REG_BINARY, &[0x00, 0x11, 0x22, 0xAA, 0xBB])
+            .string("DnsSuffix", "corp.example")))
+}
+
+fn find<'a>(profiles: &'a [NetworkProfile], guid: &str) -> &'a NetworkProfile {
+    profiles.iter().find(|p| p.guid == guid).unwrap_or_else(|| panic!("{} not found", guid))
+}
+
+#[test]
+fn test_network_profiles_and_signatures() {
+    let profiles = parse_network_list(&software_hive(), 0);
+    assert_eq!(profiles.len(), 3);
+
+    let home = find(&profiles, HOME);
+    assert_eq!(home.name.as_deref(), Some("HomeWifi"));
+    assert_eq!(home.description.as_deref(), Some("HomeWifi"));
+    assert_eq!(home.category.as_deref(), Some("private"));
+    assert_eq!(home.network_type.as_deref(), Some("wireless"));
+    assert!(!home.managed);
+    assert_eq!(home.gateway_mac.as_deref(), Some("00-11-22-AA-BB-CC"));
+    assert_eq!(home.dns_suffix, None);
+    assert_eq!(home.first_network.as_deref(), Some("HomeWifi"));
+    assert_eq!(home.last_write.unwrap().timestamp(), 1_577_836_800);
+
+    let corp = find(&profiles, CORP);
+    assert_eq!(corp.category.as_deref(), Some("domain"));
+    assert_eq!(corp.network_type.as_deref(), Some("wired"));
+    assert!(corp.managed);
+    assert_eq!(corp.gateway_mac, None);
+    assert_eq!(corp.dns_suffix.as_deref(), Some("corp.example"));
+    assert_eq!((corp.first_connected, corp.last_connected), (None, None));
+
+    // Unknown values are kept as numbers
+    let other = find(&profiles, OTHER);
+    assert_eq!(other.category.as_deref(), Some("5"));
+    assert_eq!(other.network_type.as_deref(), Some("999"));
+    assert!(other.managed);
+    assert_eq!(other.gateway_mac, None);
+}
+
+#[test]
+fn test_network_profile_times_use_bias() {
+    let format = |p: &NetworkProfile| (
+        p.first_connected.unwrap().format("%Y-%m-%d %H:%M").to_string(),
+        p.last_connected.unwrap().format("%Y-%m-%d %H:%M").to_string(),
+    );
+
+    let profiles = parse_network_list(&software_hive(), 0);
+    assert_eq!(format(find(&profiles, HOME)), ("2024-03-05 14:30".to_string(), "2024-06-01 00:15".to_string()));
+
+    // UTC+2 has a bias of -120 minutes; the last connection crosses midnight
+    let profiles = parse_network_list(&software_hive(), -120);
+    assert_eq!(format(find(&profiles, HOME)), ("2024-03-05 12:30".to_string(), "2024-05-31 22:15".to_string()));
+
+    // US Eastern Standard Time
+    let profiles = parse_network_list(&software_hive(), 300);
+    assert_eq!(format(find(&profiles, HOME)), ("2024-03-05 19:30".to_string(), "2024-06-01 05:15".to_string()));
+}
+
+#[test]
+fn test_rdp_servers_and_mru() {
+    let client = "Software\\Microsoft\\Terminal Server Client";
+    let hive = hive(Key::new("ROOT")
+        .subkey(&format!("{}\\Servers\\10.0.0.5", client), Key::new("10.0.0.5")
+            .last_write(JAN_2020)
+            .string("UsernameHint", "CORP\\alice"))
+        .subkey(&format!("{}\\Servers\\fileserver", client), Key::new("fileserver"))
+        .subkey(&format!("{}\\Default", client), Key::new("Default")
+            .string("MRU0", "FILESERVER")
+            .string("MRU1", "oldhost.corp.example")));
+
+    let connections = parse_rdp_connections(&hive, "alice");
+    let summary: Vec<(&str, Option<&str>, Option<usize>)> = connections.iter()
+        .map(|c| (c.server.as_str(), c.username_hint.as_deref(), c.mru_position))
+        .collect();
+    assert_eq!(summary, [
+        ("10.0.0.5", Some("CORP\\alice"), None),
+        ("fileserver", None, Some(0)),
+        // Only in the MRU
+        ("oldhost.corp.example", None, Some(1)),
+    ]);
+    assert!(connections.iter().all(|c| c.user == "alice"));
+    assert_eq!(connections[0].last_write.unwrap().timestamp(), 1_577_836_800);
+    assert_eq!(connections[2].last_write, None);
+}
+
+#[test]
+fn test_mapped_drives_and_mru() {
+    let hive = hive(Key::new("ROOT")
+        .subkey("Network\\z", Key::new("z")
+            .last_write(JAN_2020)
+            .string("RemotePath", "\\\\server\\share")
+            .string("UserName", "CORP\\alice")
+            .string("ProviderName", "Microsoft Windows Network"))
+        // Without a remote path the mapping is skipped
+        .subkey("Network\\y", Key::new("y").string("UserName", "CORP\\alice"))
+        .subkey("Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\Map Network Drive MRU", Key::new("Map Network Drive MRU")
+            .last_write(JAN_2020 + 1)
+            .string("a", "\\\\SERVER\\share")
+            .string("b", "\\\\nas\\media")
+            .string("MRUList", "ba")));
+
+    let drives = parse_mapped_drives(&hive, "alice");
+    assert_eq!(drives.len(), 2);
+
+    let share = &drives[0];
+    assert_eq!(share.drive_letter.as_deref(), Some("Z:"));
+    assert_eq!(share.remote_path, "\\\\server\\share");
+    assert_eq!(share.username.as_deref(), Some("CORP\\alice"));
+    assert_eq!(share.provider.as_deref(), Some("Microsoft Windows Network"));
+    assert_eq!(share.mru_position, Some(1));
+    assert_eq!(share.last_write.unwrap().timestamp(), 1_577_836_800);
+
+    // The most recent MRU entry takes the key's last write time
+    let media = &drives[1];
+    assert_eq!(media.drive_letter, None);
+    assert_eq!(media.remote_path, "\\\\nas\\media");
+    assert_eq!(media.mru_position, Some(0));
+    assert!(media.last_write.is_some());
+}
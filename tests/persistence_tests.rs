@@ -0,0 +1,184 @@
+// tests/persistence_tests.rs
+mod common;
+
+use common::{build_hive, security_descriptor, Key, REG_BINARY};
+use forensic_triage::registry::{analyze_persistence, PersistenceEntry, PersistenceReason, RegistryParser};
+
+const SID: &str = "S-1-5-21-1111111111-2222222222-3333333333-1001";
+const CLSID: &str = "{B5F8350B-0548-48B1-A6EE-88BD00B4A5E7}";
+/// 2020-01-01 as a FILETIME
+const JAN_2020: u64 = 132_223_104_000_000_000;
+
+fn system_hive() -> Vec<u8> {
+    let mut bam_entry = JAN_2020.to_le_bytes().to_vec();
+    bam_entry.extend([0; 16]);
+
+    build_hive(Key::new("ROOT")
+        .subkey("Select", Key::new("Select").dword("Current", 1))
+        .subkey(&format!("ControlSet001\\Services\\bam\\State\\UserSettings\\{}", SID), Key::new(SID)
+            .value("\\Device\\HarddiskVolume3\\Users\\alice\\evil.exe", REG_BINARY, &bam_entry)
+            .dword("Version", 1))
+        .subkey("ControlSet001\\Services\\Updater", Key::new("Updater")
+            .string("ImagePath", "C:\\ProgramData\\updater.exe")
+            .string("DisplayName", "Updater")
+            .string("ObjectName", "LocalSystem")
+            .dword("Start", 2)
+            .dword("Type", 0x10))
+        .subkey("ControlSet001\\Services\\Netman\\Parameters", Key::new("Parameters")
+            .string("ServiceDll", "C:\\Windows\\System32\\evil.dll"))
+        .subkey("ControlSet001\\Services\\Netman", Key::new("Netman")
+            .string("ImagePath", "svchost.exe -k netsvcs")
+            .dword("Start", 3))
+        // No ImagePath, so not a runnable service
+        .subkey("ControlSet001\\Services\\Empty", Key::new("Empty").dword("Start", 4))
+        .subkey("ControlSet001\\Control\\Lsa", Key::new("Lsa")
+            .multi_string("Security Packages", &["kerberos", "\"\"", "mimilib"])
+            .multi_string("Notification Packages", &["scecli"]))
+        .subkey("ControlSet001\\Control\\Print\\Monitors\\Evil", Key::new("Evil")
+            .string("Driver", "evilmon.dll")))
+}
+
+fn software_hive() -> Vec<u8> {
+    build_hive(Key::new("ROOT")
+        // Users may set values
+        .subkey("Microsoft\\Windows\\CurrentVersion\\Run", Key::new("Run")
+            .security(&security_descriptor("S-1-5-32-544", "S-1-5-18", &[
+                (0, 0x000F_003F, "S-1-5-18"),
+                (0, 0x0002_0006, "S-1-5-32-545"),
+            ]))
+            .string("Updater", "C:\\ProgramData\\updater.exe /silent")
+            .string("Empty", ""))
+        .subkey("Wow6432Node\\Microsoft\\Windows\\CurrentVersion\\RunOnce", Key::new("RunOnce")
+            .string("Setup", "C:\\setup32.exe"))
+        .subkey("Microsoft\\Windows NT\\CurrentVersion\\Winlogon", Key::new("Winlogon")
+            .string("Userinit", "C:\\Windows\\system32\\userinit.exe,C:\\evil.exe,")
+            .string("Shell", "explorer.exe"))
+        .subkey("Microsoft\\Windows NT\\CurrentVersion\\Image File Execution Options\\sethc.exe", Key::new("sethc.exe")
+            .string("Debugger", "cmd.exe"))
+        .subkey("Microsoft\\Windows NT\\CurrentVersion\\Image File Execution Options\\notepad.exe", Key::new("notepad.exe")
+            .string("GlobalFlag", "0x200"))
+        .subkey("Microsoft\\Windows NT\\CurrentVersion\\SilentProcessExit\\notepad.exe", Key::new("notepad.exe")
+            .string("MonitorProcess", "C:\\monitor.exe"))
+        .subkey("Microsoft\\Windows NT\\CurrentVersion\\Windows", Key::new("Windows")
+            .string("AppInit_DLLs", "C:\\appinit.dll")
+            .dword("LoadAppInit_DLLs", 1))
+        .subkey("Microsoft\\Active Setup\\Installed Components\\{AAAA}", Key::new("{AAAA}")
+            .string("", "Evil Setup")
+            .string("StubPath", "C:\\stub.exe"))
+        .subkey(&format!("Classes\\CLSID\\{}\\InprocServer32", CLSID), Key::new("InprocServer32")
+            .string("", "C:\\Windows\\System32\\shell32.dll")))
+}
+
+fn user_hive() -> Vec<u8> {
+    build_hive(Key::new("ROOT")
+        .subkey("Software\\Microsoft\\Windows\\CurrentVersion\\Run", Key::new("Run")
+            .string("OneDrive", "C:\\Users\\alice\\AppData\\Local\\OneDrive.exe")))
+}
+
+fn user_classes_hive() -> Vec<u8> {
+    build_hive(Key::new("ROOT")
+        .subkey(&format!("CLSID\\{}\\InprocServer32", CLSID), Key::new("InprocServer32")
+            .string("", "C:\\Users\\alice\\AppData\\hijack.dll"))
+        .subkey("CLSID\\{CCCCCCCC-0000-0000-0000-000000000000}\\LocalServer32", Key::new("LocalServer32")
+            .string("", "C:\\Users\\alice\\AppData\\server.exe")))
+}
+
+fn analyze() -> Vec<PersistenceEntry> {
+    let mut registry = RegistryParser::new();
+    registry.load_hive_bytes(system_hive(), "HKLM\\SYSTEM").unwrap();
+    registry.load_hive_bytes(software_hive(), "HKLM\\SOFTWARE").unwrap();
+    registry.load_hive_bytes(user_hive(), "HKU\\alice").unwrap();
+    registry.load_hive_bytes(user_classes_hive(), "HKU\\alice_Classes").unwrap();
+    analyze_persistence(&registry)
+}
+
+fn find(entries: &[PersistenceEntry], reason: PersistenceReason) -> Vec<&PersistenceEntry> {
+    entries.iter().filter(|e| e.reason == reason).collect()
+}
+
+#[test]
+fn test_persistence_system_hive() {
+    let entries = analyze();
+
+    let bam = find(&entries, PersistenceReason::BamExecution);
+    assert_eq!(bam.len(), 1);
+    assert_eq!(bam[0].command, "\\Device\\HarddiskVolume3\\Users\\alice\\evil.exe");
+    assert_eq!(bam[0].user.as_deref(), Some(SID));
+    assert_eq!(bam[0].timestamp.unwrap().timestamp(), 1_577_836_800);
+
+    let services = find(&entries, PersistenceReason::Service);
+    assert_eq!(services.len(), 2);
+    let netman = services.iter().find(|e| e.name == "Netman").unwrap();
+    assert_eq!(netman.details["service_dll"], "C:\\Windows\\System32\\evil.dll");
+    assert_eq!(netman.details["start_type"], "manual");
+    let updater = services.iter().find(|e| e.name == "Updater").unwrap();
+    assert_eq!(updater.command, "C:\\ProgramData\\updater.exe");
+    assert_eq!(updater.key_path, "ControlSet001\\Services\\Updater");
+    assert_eq!(updater.details["start_type"], "automatic");
+    assert_eq!(updater.details["account"], "LocalSystem");
+    assert_eq!(updater.details["type"], "0x10");
+
+    let packages: Vec<&str> = find(&entries, PersistenceReason::LsaSecurityPackage).iter()
+        .map(|e| e.command.as_str())
+        .collect();
+    assert_eq!(packages, ["kerberos", "mimilib"]);
+    assert_eq!(find(&entries, PersistenceReason::LsaNotificationPackage)[0].command, "scecli");
+
+    let monitors = find(&entries, PersistenceReason::PrintMonitor);
+    assert_eq!(monitors.len(), 1);
+    assert_eq!((monitors[0].name.as_str(), monitors[0].command.as_str()), ("Evil", "evilmon.dll"));
+}
+
+#[test]
+fn test_persistence_software_and_user_hives() {
+    let entries = analyze();
+
+    let run = find(&entries, PersistenceReason::Run);
+    let run: Vec<(&str, &str, Option<&str>)> = run.iter()
+        .map(|e| (e.hive.as_str(), e.command.as_str(), e.user.as_deref()))
+        .collect();
+    assert_eq!(run, [
+        ("HKLM\\SOFTWARE", "C:\\ProgramData\\updater.exe /silent", None),
+        ("HKU\\alice", "C:\\Users\\alice\\AppData\\Local\\OneDrive.exe", Some("alice")),
+    ]);
+    let machine_run = &find(&entries, PersistenceReason::Run)[0];
+    let security = machine_run.security.as_ref().unwrap();
+    assert_eq!(security.sddl, "O:BAG:SYD:AI(A;;KA;;;SY)(A;;KW;;;BU)");
+    let writable_by: Vec<&str> = security.weak_write_aces.iter().map(|ace| ace.sid.as_str()).collect();
+    assert_eq!(writable_by, ["S-1-5-32-545"]);
+
+    let run_once = find(&entries, PersistenceReason::RunOnce);
+    assert!(run_once[0].security.is_none());
+    assert_eq!(run_once[0].key_path, "Wow6432Node\\Microsoft\\Windows\\CurrentVersion\\RunOnce");
+
+    assert_eq!(find(&entries, PersistenceReason::WinlogonUserinit)[0].command, "C:\\Windows\\system32\\userinit.exe,C:\\evil.exe,");
+    assert_eq!(find(&entries, PersistenceReason::WinlogonShell)[0].command, "explorer.exe");
+
+    let debugger = find(&entries, PersistenceReason::IfeoDebugger);
+    assert_eq!(debugger.len(), 1);
+    assert_eq!((debugger[0].name.as_str(), debugger[0].command.as_str()), ("sethc.exe", "cmd.exe"));
+
+    let silent = find(&entries, PersistenceReason::SilentProcessExit);
+    assert_eq!(silent[0].command, "C:\\monitor.exe");
+    assert_eq!(silent[0].details["monitoring_enabled"], "true");
+
+    let appinit = find(&entries, PersistenceReason::AppInitDlls);
+    assert_eq!(appinit[0].command, "C:\\appinit.dll");
+    assert_eq!(appinit[0].details["enabled"], "true");
+
+    let active_setup = find(&entries, PersistenceReason::ActiveSetup);
+    assert_eq!(active_setup[0].command, "C:\\stub.exe");
+    assert_eq!(active_setup[0].details["display_name"], "Evil Setup");
+
+    let hijacks = find(&entries, PersistenceReason::ComHijack);
+    assert_eq!(hijacks.len(), 2);
+    let hijack = hijacks.iter().find(|e| e.name == CLSID).unwrap();
+    assert_eq!(hijack.hive, "HKU\\alice_Classes");
+    assert_eq!(hijack.command, "C:\\Users\\alice\\AppData\\hijack.dll");
+    assert_eq!(hijack.user.as_deref(), Some("alice"));
+    assert_eq!(hijack.details["overrides_machine"], "true");
+    assert_eq!(hijack.details["machine_server"], "C:\\Windows\\System32\\shell32.dll");
+    let local = hijacks.iter().find(|e| e.name != CLSID).unwrap();
+    assert_eq!(local.details["server"], "LocalServer32");
+    assert_eq!(local.details["overrides_machine"], "false");
+}
@@ -0,0 +1,159 @@
+// tests/prefetch_tests.rs
+use forensic_triage::parsers::parse_prefetch;
+
+const VOLUME: &str = "\\VOLUME{01d9a3b4c5d6e7f8-a1b2c3d4}";
+const FILES: [&str; 2] = [
+    "\\VOLUME{01d9a3b4c5d6e7f8-a1b2c3d4}\\WINDOWS\\SYSTEM32\\NTDLL.DLL",
+    "\\VOLUME{01d9a3b4c5d6e7f8-a1b2c3d4}\\TOOLS\\MIMIKATZ.EXE",
+];
+const DIRECTORIES: [&str; 2] = [
+    "\\VOLUME{01d9a3b4c5d6e7f8-a1b2c3d4}\\WINDOWS",
+    "\\VOLUME{01d9a3b4c5d6e7f8-a1b2c3d4}\\TOOLS",
+];
+/// 2024-01-02 03:04:05 UTC
+const FILETIME: u64 = 133_486_382_450_000_000;
+
+fn utf16(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn put(data: &mut [u8], offset: usize, value: &[u8]) {
+    data[offset..offset + value.len()].copy_from_slice(value);
+}
+
+/// A Windows 10 format (version 30) prefetch file
+fn prefetch_v30() -> Vec<u8> {
+    const METRICS: usize = 0x130;
+    const STRINGS: usize = METRICS + 2 * 32;
+
+    let mut strings = Vec::new();
+    let mut metrics = Vec::new();
+    for file in FILES {
+        let mut entry = vec![0u8; 32];
+        put(&mut entry, 12, &(strings.len() as u32).to_le_bytes());
+        put(&mut entry, 16, &(file.len() as u32).to_le_bytes());
+        metrics.extend(entry);
+        strings.extend(utf16(file));
+        strings.extend([0, 0]);
+    }
+    let volumes = STRINGS + strings.len();
+
+    // The volume entry, then its device path and directory strings
+    let mut volume = vec![0u8; 96];
+    put(&mut volume, 0, &96u32.to_le_bytes());
+    put(&mut volume, 4, &(VOLUME.len() as u32).to_le_bytes());
+    put(&mut volume, 8, &FILETIME.to_le_bytes());
+    put(&mut volume, 16, &0xA1B2C3D4u32.to_le_bytes());
+    volume.extend(utf16(VOLUME));
+    volume.extend([0, 0]);
+    let directories = volume.len() as u32;
+    put(&mut volume, 28, &directories.to_le_bytes());
+    put(&mut volume, 32, &(DIRECTORIES.len() as u32).to_le_bytes());
+    for directory in DIRECTORIES {
+        volume.extend((directory.len() as u16).to_le_bytes());
+        volume.extend(utf16(directory));
+        volume.extend([0, 0]);
+    }
+
+    let mut data = vec![0u8; METRICS];
+    put(&mut data, 0, &30u32.to_le_bytes());
+    put(&mut data, 4, b"SCCA");
+    put(&mut data, 16, &utf16("MIMIKATZ.EXE"));
+    put(&mut data, 76, &0x5E6F7A8Bu32.to_le_bytes());
+    for (offset, value) in [(0, METRICS), (4, 2), (8, STRINGS), (16, STRINGS), (20, strings.len()), (24, volumes), (28, 1), (32, volume.len())] {
+        put(&mut data, 84 + offset, &(value as u32).to_le_bytes());
+    }
+    // Two runs, an hour apart
+    put(&mut data, 128, &FILETIME.to_le_bytes());
+    put(&mut data, 136, &(FILETIME - 36_000_000_000).to_le_bytes());
+    put(&mut data, 208, &2u32.to_le_bytes());
+
+    data.extend(metrics);
+    data.extend(strings);
+    data.extend(volume);
+    let size = data.len() as u32;
+    put(&mut data, 12, &size.to_le_bytes());
+    data
+}
+
+/// Wrap a file in a MAM header, LZXPRESS Huffman encoded with every symbol
+/// given a 9-bit code, so each symbol's code is its own value
+fn compress_mam(data: &[u8]) -> Vec<u8> {
+    let mut words = Vec::new();
+    let (mut bits, mut count) = (0u32, 0);
+    let mut emit = |value: u32, length: u32| {
+        bits = (bits << length) | value;
+        count += length;
+        while count >= 16 {
+            count -= 16;
+            words.push((bits >> count) as u16);
+        }
+    };
+
+    // Literals, except that runs of zeros become offset 1 matches
+    let mut pos = 0;
+    while pos < data.len() {
+        let run = data[pos..].iter().take(17).take_while(|&&b| b == 0).count();
+        if pos > 0 && data[pos - 1] == 0 && run >= 3 {
+            emit(256 + (run as u32 - 3), 9);
+            pos += run;
+        } else {
+            emit(data[pos] as u32, 9);
+            pos += 1;
+        }
+    }
+    // Flush the last partial word
+    emit(0, 15);
+
+    let mut out = b"MAM\x04".to_vec();
+    out.extend((data.len() as u32).to_le_bytes());
+    out.extend([0x99; 256]);
+    out.extend(words.iter().flat_map(|w| w.to_le_bytes()));
+    out
+}
+
+#[test]
+fn test_prefetch_v30() {
+    let prefetch = parse_prefetch(&prefetch_v30()).unwrap();
+
+    assert_eq!(prefetch.version, 30);
+    assert_eq!(prefetch.executable, "MIMIKATZ.EXE");
+    assert_eq!(prefetch.path_hash, "5E6F7A8B");
+    assert_eq!(prefetch.run_count, 2);
+    assert!(!prefetch.compressed);
+    assert_eq!(prefetch.last_run_times.len(), 2);
+    assert_eq!(prefetch.last_run_times[0].to_rfc3339(), "2024-01-02T03:04:05+00:00");
+    assert_eq!(prefetch.last_run_times[1].to_rfc3339(), "2024-01-02T02:04:05+00:00");
+    assert_eq!(prefetch.files, FILES);
+    assert_eq!(prefetch.directories, DIRECTORIES);
+    assert_eq!(prefetch.volumes.len(), 1);
+    assert_eq!(prefetch.volumes[0].device_path, VOLUME);
+    assert_eq!(prefetch.volumes[0].serial_number, "A1B2C3D4");
+    assert_eq!(prefetch.volumes[0].created, prefetch.last_run_times.first().copied());
+}
+
+#[test]
+fn test_mam_compressed_prefetch() {
+    let prefetch = parse_prefetch(&compress_mam(&prefetch_v30())).unwrap();
+    assert!(prefetch.compressed);
+    assert_eq!(prefetch.executable, "MIMIKATZ.EXE");
+    assert_eq!(prefetch.files, FILES);
+    assert_eq!(prefetch.directories, DIRECTORIES);
+
+    assert!(parse_prefetch(b"MAM\x04\x00\x10\x00\x00").is_err());
+}
+
+#[test]
+fn test_untrusted_sizes() {
+    // A MAM header claiming 4 GiB of output from a single block
+    let mut mam = compress_mam(&prefetch_v30());
+    mam[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(parse_prefetch(&mam).is_err());
+
+    // A metrics count far past the end of the file: reading stops there
+    let mut data = prefetch_v30();
+    put(&mut data, 84 + 4, &u32::MAX.to_le_bytes());
+    let files = parse_prefetch(&data).unwrap().files;
+    assert_eq!(files[..FILES.len()], FILES);
+    assert!(files.len() <= data.len() / 32);
+}
@@ -0,0 +1,75 @@
+// tests/recyclebin_tests.rs
+use forensic_triage::parsers::{info2_recycled_name, parse_info2, parse_recycle_bin_info};
+
+/// 2024-01-02 03:04:05 UTC
+const FILETIME: u64 = 133_486_382_450_000_000;
+
+fn utf16(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn info_header(version: u64, size: u64) -> Vec<u8> {
+    let mut data = version.to_le_bytes().to_vec();
+    data.extend(size.to_le_bytes());
+    data.extend(FILETIME.to_le_bytes());
+    data
+}
+
+#[test]
+fn test_recycle_bin_info_versions() {
+    let path = "C:\\Users\\alice\\Desktop\\passwords.xlsx";
+
+    let mut v1 = info_header(1, 2048);
+    let mut fixed = utf16(path);
+    fixed.resize(520, 0);
+    v1.extend(fixed);
+
+    let mut v2 = info_header(2, 2048);
+    v2.extend((path.len() as u32 + 1).to_le_bytes());
+    v2.extend(utf16(path));
+    v2.extend([0, 0]);
+
+    for (data, version) in [(v1, 1), (v2, 2)] {
+        let file = parse_recycle_bin_info(&data).unwrap();
+        assert_eq!(file.version, Some(version));
+        assert_eq!(file.original_path, path);
+        assert_eq!(file.size, 2048);
+        assert_eq!(file.deleted.unwrap().to_rfc3339(), "2024-01-02T03:04:05+00:00");
+    }
+
+    assert!(parse_recycle_bin_info(&info_header(3, 0)).is_err());
+}
+
+#[test]
+fn test_info2() {
+    let mut data = Vec::new();
+    for value in [5u32, 0, 2, 800, 0] {
+        data.extend(value.to_le_bytes());
+    }
+    for (index, path, removed) in [(1u32, "C:\\Documents and Settings\\bob\\report.doc", false), (2, "D:\\tools\\nc.exe", true)] {
+        let mut record = vec![0u8; 800];
+        record[..path.len()].copy_from_slice(path.as_bytes());
+        if removed {
+            record[0] = 0;
+        }
+        record[260..264].copy_from_slice(&index.to_le_bytes());
+        record[264..268].copy_from_slice(&((path.as_bytes()[0] - b'A') as u32).to_le_bytes());
+        record[268..276].copy_from_slice(&FILETIME.to_le_bytes());
+        record[276..280].copy_from_slice(&4096u32.to_le_bytes());
+        let unicode = utf16(path);
+        record[280..280 + unicode.len()].copy_from_slice(&unicode);
+        data.extend(record);
+    }
+
+    let files = parse_info2(&data).unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].original_path, "C:\\Documents and Settings\\bob\\report.doc");
+    assert_eq!(files[0].size, 4096);
+    assert!(!files[0].removed);
+    assert_eq!(info2_recycled_name(&files[0]).as_deref(), Some("Dc1.doc"));
+
+    // Purged: the ANSI path is cleared but the Unicode one survives
+    assert!(files[1].removed);
+    assert_eq!(files[1].original_path, "D:\\tools\\nc.exe");
+    assert_eq!(info2_recycled_name(&files[1]).as_deref(), Some("Dd2.exe"));
+}
@@ -0,0 +1,154 @@
+// tests/registry_diff_tests.rs
+mod common;
+
+use common::{build_hive, key_offset, redirect_subkey, Key};
+use forensic_triage::registry::{ChangeKind, HiveDiff, RegistryHive};
+
+const JAN_2020: u64 = 132_223_104_000_000_000;
+const JAN_2021: u64 = 132_539_328_000_000_000;
+
+fn diff(old: Key, new: Key) -> HiveDiff {
+    diff_hives(build_hive(old), build_hive(new))
+}
+
+fn diff_hives(old: Vec<u8>, new: Vec<u8>) -> HiveDiff {
+    let old = RegistryHive::from_bytes(old).unwrap();
+    let new = RegistryHive::from_bytes(new).unwrap();
+    old.diff(&new).unwrap()
+}
+
+/// Path and kind of each changed key, with the names and kinds of its value changes
+type Summary = Vec<(String, ChangeKind, Vec<(String, ChangeKind)>)>;
+
+fn summary(diff: &HiveDiff) -> Summary {
+    diff.keys.iter()
+        .map(|key| (
+            key.path.clone(),
+            key.kind,
+            key.values.iter().map(|value| (value.name.clone(), value.kind)).collect(),
+        ))
+        .collect()
+}
+
+#[test]
+fn test_diff_keys_and_values() {
+    let old = Key::new("ROOT")
+        .subkey("Run", Key::new("Run").last_write(JAN_2020)
+            .string("Updater", "C:\\updater.exe")
+            .dword("Flags", 1)
+            .string("Gone", "x"))
+        .subkey("Old\\Child", Key::new("Child").dword("Count", 2));
+    let new = Key::new("ROOT")
+        .subkey("Run", Key::new("Run").last_write(JAN_2021)
+            .string("Updater", "C:\\Users\\alice\\evil.exe")
+            .dword("Flags", 1)
+            .dword("Added", 7))
+        .subkey("Fresh", Key::new("Fresh").string("Name", "new"));
+
+    let diff = diff(old, new);
+    assert_eq!(summary(&diff), [
+        ("Old".to_string(), ChangeKind::Removed, vec![]),
+        ("Old\\Child".to_string(), ChangeKind::Removed, vec![("Count".to_string(), ChangeKind::Removed)]),
+        ("Run".to_string(), ChangeKind::Modified, vec![
+            ("Gone".to_string(), ChangeKind::Removed),
+            ("Updater".to_string(), ChangeKind::Modified),
+            ("Added".to_string(), ChangeKind::Added),
+        ]),
+        ("Fresh".to_string(), ChangeKind::Added, vec![("Name".to_string(), ChangeKind::Added)]),
+    ]);
+
+    let run = &diff.keys[2];
+    assert_eq!(run.old_last_write.unwrap().timestamp(), 1_577_836_800);
+    assert_eq!(run.new_last_write.unwrap().timestamp(), 1_609_459_200);
+    let updater = &run.values[1];
+    assert_eq!(updater.old.as_ref().unwrap().as_string(), Some("C:\\updater.exe"));
+    assert_eq!(updater.new.as_ref().unwrap().as_string(), Some("C:\\Users\\alice\\evil.exe"));
+    assert!(run.values[0].new.is_none());
+    assert!(run.values[2].old.is_none());
+
+    let unchanged = Key::new("ROOT").subkey("Run", Key::new("Run").dword("Flags", 1));
+    assert!(self::diff(unchanged.clone(), unchanged).is_empty());
+}
+
+#[test]
+fn test_diff_to_reg_skips_removed_descendants() {
+    let old = Key::new("ROOT")
+        .subkey("Old\\Child\\Grandchild", Key::new("Grandchild").dword("Count", 2))
+        .subkey("Older", Key::new("Older"));
+    let new = Key::new("ROOT")
+        .subkey("Run", Key::new("Run").dword("Flags", 1));
+
+    let diff = diff(old, new);
+    assert_eq!(diff.keys.len(), 5);
+    assert_eq!(diff.to_reg("HKLM\\SOFTWARE"), concat!(
+        "Windows Registry Editor Version 5.00\r\n",
+        "\r\n[-HKEY_LOCAL_MACHINE\\SOFTWARE\\Old]\r\n",
+        // A sibling sharing the removed key's name as a prefix still gets a line
+        "\r\n[-HKEY_LOCAL_MACHINE\\SOFTWARE\\Older]\r\n",
+        "\r\n[HKEY_LOCAL_MACHINE\\SOFTWARE\\Run]\r\n",
+        "\"Flags\"=dword:00000001\r\n",
+    ));
+}
+
+#[test]
+fn test_diff_ignores_name_case() {
+    let old = Key::new("ROOT")
+        .subkey("Software\\Run", Key::new("Run").dword("Flags", 1).string("Updater", "a"));
+    let new = Key::new("ROOT")
+        .subkey("SOFTWARE\\run", Key::new("run").dword("FLAGS", 1).string("updater", "b"));
+
+    let diff = diff(old, new);
+    assert_eq!(summary(&diff), [
+        ("Software\\Run".to_string(), ChangeKind::Modified, vec![("Updater".to_string(), ChangeKind::Modified)]),
+    ]);
+}
+
+#[test]
+fn test_diff_reports_touched_keys() {
+    let old = Key::new("ROOT").subkey("Run", Key::new("Run").last_write(JAN_2020).dword("Flags", 1));
+    let new = Key::new("ROOT").subkey("Run", Key::new("Run").last_write(JAN_2021).dword("Flags", 1));
+
+    let diff = diff(old, new);
+    assert_eq!(summary(&diff), [("Run".to_string(), ChangeKind::Touched, vec![])]);
+    assert_eq!(diff.keys[0].old_last_write.unwrap().timestamp(), 1_577_836_800);
+    assert_eq!(diff.keys[0].new_last_write.unwrap().timestamp(), 1_609_459_200);
+    // There is nothing to write for a timestamp
+    assert_eq!(diff.to_reg("HKLM\\SOFTWARE"), "Windows Registry Editor Version 5.00\r\n");
+}
+
+#[test]
+fn test_diff_survives_subkey_cycles() {
+    let tree = Key::new("ROOT").subkey("Loop\\Child", Key::new("Child").dword("Count", 1));
+    let plain = build_hive(tree.clone());
+    // Loop's only subkey becomes Loop itself
+    let mut cyclic = build_hive(tree);
+    redirect_subkey(&mut cyclic, "Child", "Loop");
+
+    assert!(diff_hives(cyclic.clone(), cyclic.clone()).is_empty());
+    assert_eq!(summary(&diff_hives(cyclic.clone(), plain.clone())), [
+        ("Loop\\Child".to_string(), ChangeKind::Added, vec![("Count".to_string(), ChangeKind::Added)]),
+    ]);
+    assert_eq!(summary(&diff_hives(plain, cyclic)), [
+        ("Loop\\Child".to_string(), ChangeKind::Removed, vec![("Count".to_string(), ChangeKind::Removed)]),
+    ]);
+}
+
+#[test]
+fn test_diff_skips_unreadable_keys() {
+    let old = build_hive(Key::new("ROOT")
+        .subkey("Broken\\Child", Key::new("Child"))
+        .subkey("Run", Key::new("Run").dword("Flags", 1)));
+    let mut new = build_hive(Key::new("ROOT")
+        .subkey("Broken\\Child", Key::new("Child"))
+        .subkey("Run", Key::new("Run").dword("Flags", 2)));
+    // Clobber the signature of Broken's subkey list, which holds Child's
+    // offset and name hint
+    let mut entry = key_offset(&new, "Child").to_le_bytes().to_vec();
+    entry.extend(b"Chil");
+    let list = new.windows(8).position(|w| w == entry).unwrap() - 4;
+    new[list..list + 2].copy_from_slice(b"xx");
+
+    assert_eq!(summary(&diff_hives(old, new)), [
+        ("Run".to_string(), ChangeKind::Modified, vec![("Flags".to_string(), ChangeKind::Modified)]),
+    ]);
+}
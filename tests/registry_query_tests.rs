@@ -0,0 +1,150 @@
+// tests/registry_query_tests.rs
+mod common;
+
+use common::{build_hive, redirect_subkey, security_descriptor, Key};
+use forensic_triage::registry::{matches_to_table, RegistryHive, RegistryParser, RegistryQuery};
+use forensic_triage::utils::time::parse_datetime;
+
+const RUN: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+const RUN_ONCE: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\RunOnce";
+/// 2020-01-01 and 2021-01-01 as FILETIMEs
+const JAN_2020: u64 = 132_223_104_000_000_000;
+const JAN_2021: u64 = 132_539_328_000_000_000;
+
+fn user_hive(command: &str, last_write: u64) -> Vec<u8> {
+    build_hive(Key::new("ROOT")
+        .subkey(RUN, Key::new("Run").last_write(last_write).string("Updater", command).dword("Flags", 1))
+        .subkey(RUN_ONCE, Key::new("RunOnce").last_write(last_write)))
+}
+
+fn parser() -> RegistryParser {
+    let mut parser = RegistryParser::new();
+    parser.load_hive_bytes(user_hive("C:\\Users\\alice\\evil.exe", JAN_2020), "HKU\\alice").unwrap();
+    parser.load_hive_bytes(user_hive("C:\\Program Files\\Updater.exe", JAN_2021), "HKU\\bob").unwrap();
+    parser
+}
+
+fn paths(parser: &RegistryParser, query: &RegistryQuery) -> Vec<String> {
+    parser.query(query).unwrap().iter()
+        .map(|m| format!("{}:{}", m.full_path(), m.value_name.as_deref().unwrap_or("")))
+        .collect()
+}
+
+#[test]
+fn test_query_globs() {
+    let parser = parser();
+
+    let query = RegistryQuery::new("HKEY_USERS\\*\\Software\\Microsoft\\Windows\\CurrentVersion\\Run").unwrap();
+    assert_eq!(paths(&parser, &query), [
+        format!("HKU\\alice\\{}:Updater", RUN),
+        format!("HKU\\alice\\{}:Flags", RUN),
+        format!("HKU\\bob\\{}:Updater", RUN),
+        format!("HKU\\bob\\{}:Flags", RUN),
+    ]);
+
+    // `?` matches one character, so RunOnce is excluded; keys without
+    // values are still reported
+    let query = RegistryQuery::new("HKU\\b?b\\**\\r?n*").unwrap();
+    assert_eq!(paths(&parser, &query), [
+        format!("HKU\\bob\\{}:Updater", RUN),
+        format!("HKU\\bob\\{}:Flags", RUN),
+        format!("HKU\\bob\\{}:", RUN_ONCE),
+    ]);
+    let query = RegistryQuery::new("**\\RunOnce").unwrap();
+    assert_eq!(paths(&parser, &query), [format!("HKU\\alice\\{}:", RUN_ONCE), format!("HKU\\bob\\{}:", RUN_ONCE)]);
+}
+
+#[test]
+fn test_query_value_filters() {
+    let parser = parser();
+
+    let query = RegistryQuery::new("HKU\\**\\Run").unwrap().data("\\\\users\\\\.*\\.exe$").unwrap();
+    let matches = parser.query(&query).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].hive, "HKU\\alice");
+    assert_eq!(matches[0].value.as_ref().unwrap().as_string(), Some("C:\\Users\\alice\\evil.exe"));
+
+    let query = RegistryQuery::new("HKU\\**\\Run").unwrap().value_name("^flags$").unwrap();
+    assert_eq!(paths(&parser, &query), [format!("HKU\\alice\\{}:Flags", RUN), format!("HKU\\bob\\{}:Flags", RUN)]);
+
+    // A filter that matches nothing reports nothing, not the bare keys
+    let query = RegistryQuery::new("HKU\\**\\RunOnce").unwrap().value_name(".").unwrap();
+    assert!(parser.query(&query).unwrap().is_empty());
+    assert!(RegistryQuery::new("HKU").unwrap().data("(").is_err());
+}
+
+#[test]
+fn test_query_modified_between() {
+    let parser = parser();
+    let query = || RegistryQuery::new("HKU\\*\\Software\\**\\Run").unwrap().value_name("Updater").unwrap();
+    let hives = |query: RegistryQuery| -> Vec<String> {
+        parser.query(&query).unwrap().into_iter().map(|m| m.hive).collect()
+    };
+    let jan_2020 = parse_datetime("2020-01-01").unwrap();
+    let jan_2021 = parse_datetime("2021-01-01").unwrap();
+
+    // Both bounds are inclusive
+    assert_eq!(hives(query().modified_between(Some(jan_2020), Some(jan_2021))), ["HKU\\alice", "HKU\\bob"]);
+    assert_eq!(hives(query().modified_between(Some(parse_datetime("2020-06-01").unwrap()), None)), ["HKU\\bob"]);
+    assert_eq!(hives(query().modified_between(None, Some(parse_datetime("2020-06-01").unwrap()))), ["HKU\\alice"]);
+    assert!(hives(query().modified_between(Some(parse_datetime("2021-01-02").unwrap()), None)).is_empty());
+}
+
+#[test]
+fn test_query_skips_corrupt_subkey() {
+    let mut hive = build_hive(Key::new("ROOT")
+        .subkey("Software\\Broken", Key::new("Broken").string("Lost", "x"))
+        .subkey(RUN, Key::new("Run").string("Kept", "C:\\kept.exe")));
+    // Clobber the signature of the Broken key cell, 76 bytes before its name
+    let name = hive.windows(6).position(|w| w == b"Broken").unwrap();
+    hive[name - 76..name - 74].copy_from_slice(b"xx");
+
+    let mut parser = RegistryParser::new();
+    parser.load_hive_bytes(hive, "HKLM\\SOFTWARE").unwrap();
+    let query = RegistryQuery::new("HKLM\\SOFTWARE\\**").unwrap().value_name(".").unwrap();
+    assert_eq!(paths(&parser, &query), [format!("HKLM\\SOFTWARE\\{}:Kept", RUN)]);
+}
+
+#[test]
+fn test_query_survives_subkey_cycles() {
+    let mut hive = build_hive(Key::new("ROOT")
+        .subkey("Software\\Loop\\Child", Key::new("Child").string("Hidden", "x"))
+        .subkey(RUN, Key::new("Run").string("Kept", "C:\\kept.exe")));
+    // Loop's only subkey becomes Loop itself
+    redirect_subkey(&mut hive, "Child", "Loop");
+
+    let mut parser = RegistryParser::new();
+    parser.load_hive_bytes(hive.clone(), "HKLM\\SOFTWARE").unwrap();
+    let query = RegistryQuery::new("HKLM\\SOFTWARE\\**").unwrap().value_name(".").unwrap();
+    assert_eq!(paths(&parser, &query), [format!("HKLM\\SOFTWARE\\{}:Kept", RUN)]);
+    let query = RegistryQuery::new("HKLM\\SOFTWARE\\Software\\Loop\\Loop\\Loop").unwrap();
+    assert_eq!(paths(&parser, &query), ["HKLM\\SOFTWARE\\Software\\Loop\\Loop\\Loop:"]);
+
+    let hive = RegistryHive::from_bytes(hive).unwrap();
+    let found: Vec<String> = hive.search_value("Kept").unwrap().into_iter().map(|(path, _)| path).collect();
+    assert_eq!(found, [RUN]);
+}
+
+#[test]
+fn test_query_reports_key_security() {
+    // Everyone may set values
+    let descriptor = security_descriptor("S-1-5-32-544", "S-1-5-18", &[(0, 0x0002_0006, "S-1-1-0")]);
+    let mut parser = RegistryParser::new();
+    parser.load_hive_bytes(build_hive(Key::new("ROOT")
+        .subkey(RUN, Key::new("Run").security(&descriptor).string("Updater", "C:\\evil.exe"))
+        .subkey(RUN_ONCE, Key::new("RunOnce"))), "HKU\\alice").unwrap();
+
+    let matches = parser.query(&RegistryQuery::new(&format!("HKU\\alice\\{}", RUN)).unwrap()).unwrap();
+    let security = matches[0].security.as_ref().unwrap();
+    assert_eq!(security.owner.as_deref(), Some("S-1-5-32-544"));
+    assert_eq!(security.sddl, "O:BAG:SYD:AI(A;;KW;;;WD)");
+    assert_eq!(security.weak_write_aces[0].sid, "S-1-1-0");
+
+    let table = matches_to_table(&matches);
+    let mut lines = table.lines();
+    assert!(lines.next().unwrap().starts_with("Last Write  Key"));
+    assert!(table.contains("S-1-5-32-544  S-1-1-0"));
+
+    let matches = parser.query(&RegistryQuery::new(&format!("HKU\\alice\\{}", RUN_ONCE)).unwrap()).unwrap();
+    assert!(matches[0].security.is_none());
+}
@@ -0,0 +1,57 @@
+// tests/registry_security_tests.rs
+mod common;
+
+use common::{build_hive, security_descriptor, Key};
+use forensic_triage::registry::{AceType, RegistryHive, SecurityDescriptor};
+
+/// Administrators own the key; SYSTEM has full control, Users read and
+/// Everyone write access
+fn descriptor() -> Vec<u8> {
+    security_descriptor("S-1-5-32-544", "S-1-5-18", &[
+        (0x12, 0x000F_003F, "S-1-5-18"),
+        (0x00, 0x0002_0019, "S-1-5-32-545"),
+        (0x00, 0x0002_0006, "S-1-1-0"),
+    ])
+}
+
+#[test]
+fn test_descriptor_to_sddl() {
+    let sd = SecurityDescriptor::parse(&descriptor()).unwrap();
+
+    assert_eq!(sd.owner.as_deref(), Some("S-1-5-32-544"));
+    assert_eq!(sd.group.as_deref(), Some("S-1-5-18"));
+    assert!(sd.sacl.is_none());
+    assert_eq!(sd.dacl.as_ref().unwrap().aces[0].ace_type, AceType::AccessAllowed);
+    assert_eq!(sd.to_sddl(), "O:BAG:SYD:AI(A;CIID;KA;;;SY)(A;;KR;;;BU)(A;;KW;;;WD)");
+}
+
+#[test]
+fn test_weak_write_aces() {
+    let sd = SecurityDescriptor::parse(&descriptor()).unwrap();
+    let weak = sd.weak_write_aces();
+
+    // Users only have read access; Everyone can write
+    assert_eq!(weak.len(), 1);
+    assert_eq!(weak[0].sid, "S-1-1-0");
+}
+
+#[test]
+fn test_key_security() {
+    let hive = RegistryHive::from_bytes(build_hive(Key::new("ROOT")
+        .subkey("Run", Key::new("Run").security(&descriptor()))
+        .subkey("Plain", Key::new("Plain")))).unwrap();
+
+    let run = hive.get_key("Run").unwrap();
+    assert_eq!(run.security_descriptor().unwrap().dacl.unwrap().aces.len(), 3);
+    let security = run.security().unwrap();
+    assert_eq!(security.owner.as_deref(), Some("S-1-5-32-544"));
+    assert_eq!(security.sddl, "O:BAG:SYD:AI(A;CIID;KA;;;SY)(A;;KR;;;BU)(A;;KW;;;WD)");
+    assert_eq!(security.weak_write_aces.len(), 1);
+    assert_eq!(security.weak_write_aces[0].rights, ["KW"]);
+
+    let json = serde_json::to_value(&security).unwrap();
+    assert_eq!(json["weak_write_aces"][0]["sid"], "S-1-1-0");
+    assert_eq!(json["weak_write_aces"][0]["ace_type"], "AccessAllowed");
+
+    assert!(hive.get_key("Plain").unwrap().security().is_none());
+}
@@ -0,0 +1,100 @@
+// tests/registry_value_tests.rs
+mod common;
+
+use common::{build_hive, utf16z, Key, REG_SZ};
+use forensic_triage::registry::{RegistryHive, RegistryValue};
+
+fn utf16(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
+}
+
+#[test]
+fn test_string_with_and_without_terminator() {
+    let mut terminated = utf16("C:\\Windows");
+    terminated.extend_from_slice(&[0, 0]);
+
+    assert_eq!(RegistryValue::from_raw(1, &terminated).as_string(), Some("C:\\Windows"));
+    assert_eq!(RegistryValue::from_raw(1, &utf16("C:\\Windows")).as_string(), Some("C:\\Windows"));
+}
+
+#[test]
+fn test_string_stops_at_embedded_nul() {
+    let mut data = utf16("cmd.exe");
+    data.extend_from_slice(&[0, 0]);
+    data.extend_from_slice(&utf16("/c hidden"));
+    data.extend_from_slice(&[0, 0, 0, 0]);
+
+    assert_eq!(RegistryValue::from_raw(1, &data).as_string(), Some("cmd.exe"));
+    assert_eq!(RegistryValue::from_raw(2, &data).as_string(), Some("cmd.exe"));
+
+    // Slack after the NUL need not be valid UTF-16
+    let mut data = utf16("cmd.exe");
+    data.extend_from_slice(&[0, 0, 0x00, 0xd8]);
+    assert_eq!(RegistryValue::from_raw(1, &data).as_string(), Some("cmd.exe"));
+}
+
+#[test]
+fn test_raw_value_keeps_data_after_embedded_nul() {
+    let mut data = utf16z("cmd.exe");
+    data.extend(utf16z("/c hidden"));
+    let hive = RegistryHive::from_bytes(build_hive(Key::new("ROOT").value("Debugger", REG_SZ, &data))).unwrap();
+    let root = hive.get_root_key().unwrap();
+
+    assert_eq!(root.string_value("Debugger").as_deref(), Some("cmd.exe"));
+    assert_eq!(root.raw_value("debugger").unwrap(), (REG_SZ, data));
+}
+
+#[test]
+fn test_malformed_utf16_kept_raw() {
+    // Unpaired high surrogate followed by 'A'
+    let data = [0x00, 0xd8, 0x41, 0x00];
+    let value = RegistryValue::from_raw(1, &data);
+
+    assert_eq!(value, RegistryValue::Raw { value_type: 1, data: data.to_vec() });
+    assert_eq!(value.value_type(), 1);
+    assert_eq!(value.as_string_lossy().as_deref(), Some("\u{fffd}A"));
+}
+
+#[test]
+fn test_extended_types() {
+    assert_eq!(RegistryValue::from_raw(5, &[0, 0, 1, 0]).as_integer(), Some(256));
+    assert_eq!(RegistryValue::from_raw(6, &utf16("\\Registry\\Machine")).type_name(), "REG_LINK");
+    assert_eq!(RegistryValue::from_raw(0x1234, &[1, 2]).type_name(), "REG_UNKNOWN(0x1234)");
+    assert!(matches!(RegistryValue::from_raw(4, &[1, 2]), RegistryValue::Raw { value_type: 4, .. }));
+}
+
+#[test]
+fn test_multi_string() {
+    let mut data = utf16("one");
+    data.extend_from_slice(&[0, 0]);
+    data.extend_from_slice(&utf16("two"));
+    data.extend_from_slice(&[0, 0, 0, 0]);
+
+    let value = RegistryValue::from_raw(7, &data);
+    assert_eq!(value.as_multi_string(), Some(&["one".to_string(), "two".to_string()][..]));
+}
+
+#[test]
+fn test_binary_helpers() {
+    let filetime = RegistryValue::Binary(132_000_000_000_000_000u64.to_le_bytes().to_vec());
+    assert_eq!(filetime.as_filetime().unwrap().to_rfc3339(), "2019-04-17T18:40:00+00:00");
+
+    // 2021-03-04 05:06:07.008
+    let systemtime: Vec<u8> = [2021u16, 3, 4, 4, 5, 6, 7, 8]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    assert_eq!(
+        RegistryValue::Binary(systemtime).as_systemtime().unwrap().to_rfc3339(),
+        "2021-03-04T05:06:07.008+00:00"
+    );
+
+    let guid = RegistryValue::Binary(vec![
+        0x33, 0x22, 0x11, 0x00, 0x55, 0x44, 0x77, 0x66,
+        0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,
+    ]);
+    assert_eq!(guid.as_guid().as_deref(), Some("{00112233-4455-6677-8899-AABBCCDDEEFF}"));
+
+    let sid = RegistryValue::Binary(vec![1, 2, 0, 0, 0, 0, 0, 5, 32, 0, 0, 0, 0x20, 2, 0, 0]);
+    assert_eq!(sid.as_sid().as_deref(), Some("S-1-5-32-544"));
+}
@@ -0,0 +1,61 @@
+// tests/sam_tests.rs
+mod common;
+
+use common::{build_hive, Key, REG_BINARY};
+use forensic_triage::registry::{parse_sam, RegistryHive, SamAccount};
+
+const USERS: &str = "SAM\\Domains\\Account\\Users";
+/// 2020-01-01 as a FILETIME
+const JAN_2020: u64 = 132_223_104_000_000_000;
+
+/// An F value with the given expiry FILETIME and the account disabled
+fn f_value(expires: u64) -> Vec<u8> {
+    let mut f = vec![0u8; 0x50];
+    f[0x08..0x10].copy_from_slice(&JAN_2020.to_le_bytes());
+    f[0x20..0x28].copy_from_slice(&expires.to_le_bytes());
+    f[0x38..0x3A].copy_from_slice(&0x0001u16.to_le_bytes());
+    f[0x42..0x44].copy_from_slice(&7u16.to_le_bytes());
+    f
+}
+
+/// A V value holding only the username
+fn v_value(username: &str) -> Vec<u8> {
+    let name: Vec<u8> = username.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let mut v = vec![0u8; 0xCC];
+    v[16..20].copy_from_slice(&(name.len() as u32).to_le_bytes());
+    v.extend(name);
+    v
+}
+
+fn accounts(users: &[(u32, &str, u64)]) -> Vec<SamAccount> {
+    let mut root = Key::new("ROOT");
+    for (rid, username, expires) in users {
+        root = root.subkey(&format!("{}\\{:08X}", USERS, rid), Key::new("")
+            .value("F", REG_BINARY, &f_value(*expires))
+            .value("V", REG_BINARY, &v_value(username)));
+    }
+    parse_sam(&RegistryHive::from_bytes(build_hive(root)).unwrap()).unwrap()
+}
+
+#[test]
+fn test_sam_account_expiry() {
+    let accounts = accounts(&[
+        (0x3E9, "expiring", JAN_2020),
+        (0x3EA, "never", 0x7FFF_FFFF_FFFF_FFFF),
+        (0x3EB, "unset", 0),
+    ]);
+    assert_eq!(accounts.len(), 3);
+
+    let expiring = &accounts[0];
+    assert_eq!(expiring.rid, 0x3E9);
+    assert_eq!(expiring.username, "expiring");
+    assert_eq!(expiring.account_expires.unwrap().timestamp(), 1_577_836_800);
+    assert_eq!(expiring.last_login.unwrap().timestamp(), 1_577_836_800);
+    assert_eq!(expiring.login_count, 7);
+    assert!(expiring.disabled);
+
+    assert_eq!(accounts[1].username, "never");
+    assert_eq!(accounts[1].account_expires, None);
+    assert_eq!(accounts[2].username, "unset");
+    assert_eq!(accounts[2].account_expires, None);
+}
@@ -0,0 +1,25 @@
+// tests/setupapi_tests.rs
+use forensic_triage::parsers::parse_setupapi_log;
+
+const LOG: &str = "\
+[Device Install Log]
+     OS Version = 10.0.19045
+
+>>>  [Device Install (Hardware initiated) - SWD\\WPDBUSENUM\\_??_USBSTOR#Disk&Ven_SanDisk&Prod_Cruzer&Rev_1.00#4C5300012311&0#{53f56307-b6bf-11d0-94f2-00a0c91efb8b}]
+>>>  Section start 2023/01/02 10:11:13.100
+<<<  Section end 2023/01/02 10:11:14.000
+>>>  [Device Install (Hardware initiated) - USBSTOR\\Disk&Ven_SanDisk&Prod_Cruzer&Rev_1.00\\4C5300012311&0]
+>>>  Section start 2023/01/02 10:11:12.345
+     dvi: {Build Driver List} 10:11:12.400
+<<<  Section end 2023/01/02 10:11:13.000
+";
+
+#[test]
+fn test_setupapi_device_installs() {
+    // UTC-5: local time plus 300 minutes
+    let installs = parse_setupapi_log(LOG, 300);
+
+    assert_eq!(installs.len(), 2);
+    assert_eq!(installs[1].device_id, "USBSTOR\\Disk&Ven_SanDisk&Prod_Cruzer&Rev_1.00\\4C5300012311&0");
+    assert_eq!(installs[1].timestamp.to_rfc3339(), "2023-01-02T15:11:12.345+00:00");
+}
@@ -0,0 +1,53 @@
+// tests/shellitem_tests.rs
+mod common;
+
+use common::shell_items::{directory_item, root_item, volume_item, with_size};
+use common::utf16z;
+use forensic_triage::shellitem::{id_list_path, parse_id_list, ShellItem};
+
+#[test]
+fn test_id_list_path() {
+    let mut list = root_item();
+    list.extend(volume_item());
+    list.extend(directory_item("PROGRA~1", "Program Files"));
+    list.extend_from_slice(&[0, 0]);
+
+    let items = parse_id_list(&list);
+    assert_eq!(items.len(), 3);
+    assert_eq!(id_list_path(&items), "My Computer\\C:\\Program Files");
+
+    let ShellItem::FileEntry(entry) = &items[2] else {
+        panic!("expected a file entry, got {:?}", items[2]);
+    };
+    assert!(entry.is_directory);
+    assert_eq!(entry.short_name, "PROGRA~1");
+    assert_eq!(entry.mft_entry, Some(1234));
+    assert_eq!(entry.mft_sequence, Some(3));
+    assert_eq!(entry.modified.map(|t| t.to_rfc3339()).as_deref(), Some("2025-01-01T12:00:00+00:00"));
+}
+
+#[test]
+fn test_truncated_item_stops_parsing() {
+    let mut list = root_item();
+    list.extend_from_slice(&[0x40, 0x00, 0x31]);
+
+    assert_eq!(parse_id_list(&list).len(), 1);
+}
+
+#[test]
+fn test_network_and_uri_items() {
+    let mut network = vec![0, 0, 0x42, 0, 0x80];
+    network.extend_from_slice(b"\\\\fileserver\0Finance share\0");
+    let items = parse_id_list(&with_size(network));
+    let ShellItem::Network { location, description, comments } = &items[0] else {
+        panic!("expected a network item, got {:?}", items[0]);
+    };
+    assert_eq!(location, "\\\\fileserver");
+    assert_eq!(description.as_deref(), Some("Finance share"));
+    assert!(comments.is_none());
+
+    let mut uri = vec![0, 0, 0x61, 0x80, 0, 0];
+    uri.extend(utf16z("ftp://example.com"));
+    let items = parse_id_list(&with_size(uri));
+    assert_eq!(id_list_path(&items), "ftp://example.com");
+}
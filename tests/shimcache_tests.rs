@@ -0,0 +1,63 @@
+// tests/shimcache_tests.rs
+use forensic_triage::registry::{decode_shimcache, ShimCacheFormat};
+
+/// 2019-04-17T18:40:00Z
+const MODIFIED: u64 = 132_000_000_000_000_000;
+
+fn utf16(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
+}
+
+#[test]
+fn test_windows10_entries() {
+    let mut data = vec![0u8; 0x34];
+    data[0..4].copy_from_slice(&0x34u32.to_le_bytes());
+
+    for path in ["C:\\Windows\\system32\\cmd.exe", "C:\\Temp\\tool.exe"] {
+        let path = utf16(path);
+        let mut body = (path.len() as u16).to_le_bytes().to_vec();
+        body.extend(&path);
+        body.extend(MODIFIED.to_le_bytes());
+        body.extend(0u32.to_le_bytes());
+
+        data.extend(b"10ts");
+        data.extend([0u8; 4]);
+        data.extend((body.len() as u32).to_le_bytes());
+        data.extend(body);
+    }
+
+    let cache = decode_shimcache(&data).unwrap();
+    assert_eq!(cache.format, ShimCacheFormat::Windows10);
+    assert_eq!(cache.entries.len(), 2);
+    assert_eq!(cache.entries[1].position, 1);
+    assert_eq!(cache.entries[1].path, "C:\\Temp\\tool.exe");
+    assert_eq!(cache.entries[1].executed, None);
+    assert_eq!(cache.entries[1].last_modified.unwrap().to_rfc3339(), "2019-04-17T18:40:00+00:00");
+}
+
+#[test]
+fn test_windows7_64bit_executed_flag() {
+    let path = utf16("C:\\Users\\alice\\evil.exe");
+    let mut data = vec![0u8; 128];
+    data[0..4].copy_from_slice(&0xBADC_0FEEu32.to_le_bytes());
+    data[4..8].copy_from_slice(&1u32.to_le_bytes());
+
+    let mut record = vec![0u8; 48];
+    record[0..2].copy_from_slice(&(path.len() as u16).to_le_bytes());
+    record[8..16].copy_from_slice(&(128u64 + 48).to_le_bytes());
+    record[16..24].copy_from_slice(&MODIFIED.to_le_bytes());
+    record[24..28].copy_from_slice(&0x2u32.to_le_bytes());
+    data.extend(record);
+    data.extend(path);
+
+    let cache = decode_shimcache(&data).unwrap();
+    assert_eq!(cache.format, ShimCacheFormat::Windows7);
+    assert!(cache.is_64bit);
+    assert_eq!(cache.entries[0].path, "C:\\Users\\alice\\evil.exe");
+    assert_eq!(cache.entries[0].executed, Some(true));
+}
+
+#[test]
+fn test_unknown_format_is_an_error() {
+    assert!(decode_shimcache(&[0x11, 0x22, 0x33, 0x44, 0, 0, 0, 0]).is_err());
+}
@@ -0,0 +1,125 @@
+// tests/sigma_tests.rs
+use forensic_triage::parsers::EvtxRecord;
+use forensic_triage::sigma::{evaluate_sigma, SigmaRule};
+
+fn event(channel: &str, event_id: u32, data: &[(&str, &str)]) -> EvtxRecord {
+    EvtxRecord {
+        record_id: 1,
+        timestamp: None,
+        provider: None,
+        channel: Some(channel.to_string()),
+        event_id: Some(event_id),
+        level: Some(4),
+        computer: Some("WS01".to_string()),
+        user_sid: None,
+        event_data: data.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        recovered: false,
+        root: Default::default(),
+    }
+}
+
+const ENCODED_POWERSHELL: &str = r#"
+title: Encoded PowerShell launched by Office
+id: 5b0c5e54-0000-4000-8000-000000000001
+level: high
+tags:
+    - attack.execution
+    - attack.t1059.001
+logsource:
+    product: windows
+    category: process_creation
+detection:
+    selection_parent:
+        ParentImage|endswith:
+            - '\WINWORD.EXE'
+            - '\EXCEL.EXE'
+    selection_cli:
+        Image|endswith: '\powershell.exe'
+        CommandLine|contains|all:
+            - ' -enc'
+            - 'hidden'
+    filter:
+        CommandLine|re: 'Get-Help\s'
+    condition: all of selection_* and not filter
+"#;
+
+#[test]
+fn test_process_creation_rule() {
+    let rule = SigmaRule::parse(ENCODED_POWERSHELL).unwrap();
+    let matching = event("Security", 4688, &[
+        ("NewProcessName", "C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\PowerShell.exe"),
+        ("ParentProcessName", "C:\\Program Files\\Microsoft Office\\root\\Office16\\WINWORD.EXE"),
+        ("CommandLine", "powershell -w Hidden -enc SQBFAFgA"),
+    ]);
+    let other_parent = event("Security", 4688, &[
+        ("NewProcessName", "C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe"),
+        ("ParentProcessName", "C:\\Windows\\explorer.exe"),
+        ("CommandLine", "powershell -w hidden -enc SQBFAFgA"),
+    ]);
+    let wrong_channel = event("System", 4688, &[]);
+
+    let hits = evaluate_sigma(std::slice::from_ref(&rule), [&matching, &other_parent, &wrong_channel]);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].level.as_deref(), Some("high"));
+    assert_eq!(hits[0].tags, vec!["attack.execution", "attack.t1059.001"]);
+    assert_eq!(hits[0].record.event_data["CommandLine"], "powershell -w Hidden -enc SQBFAFgA");
+}
+
+#[test]
+fn test_condition_keywords_ignore_case() {
+    let rule = SigmaRule::parse(&ENCODED_POWERSHELL.replace("all of selection_* and not", "ALL Of selection_* AND NOT")).unwrap();
+    // Only the parent selection matches
+    let other_image = event("Security", 4688, &[
+        ("NewProcessName", "C:\\Windows\\System32\\cmd.exe"),
+        ("ParentProcessName", "C:\\Program Files\\Microsoft Office\\root\\Office16\\WINWORD.EXE"),
+        ("CommandLine", "cmd /c whoami"),
+    ]);
+    let matching = event("Security", 4688, &[
+        ("NewProcessName", "C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe"),
+        ("ParentProcessName", "C:\\Program Files\\Microsoft Office\\root\\Office16\\EXCEL.EXE"),
+        ("CommandLine", "powershell -w hidden -enc SQBFAFgA"),
+    ]);
+
+    assert!(!rule.matches(&other_image));
+    assert!(rule.matches(&matching));
+}
+
+#[test]
+fn test_base64offset_keyword_and_one_of() {
+    let rule = SigmaRule::parse(r#"
+title: Base64 encoded IEX
+logsource:
+    product: windows
+    service: powershell
+detection:
+    selection_b64:
+        ScriptBlockText|base64offset|contains: 'IEX'
+    selection_plain:
+        ScriptBlockText|contains: 'Invoke-Expression'
+    condition: 1 of selection_*
+"#).unwrap();
+
+    // "IEX" encoded at each of the three offsets
+    for script in ["SUVYIChOZXctT2JqZWN0", "ICBJRVggKE5ldy1PYmplY3Q=", "IElFWCAoTmV3LU9iamVjdA=="] {
+        let record = event("Microsoft-Windows-PowerShell/Operational", 4104, &[("ScriptBlockText", script)]);
+        assert!(rule.matches(&record), "{}", script);
+    }
+    let plain = event("Microsoft-Windows-PowerShell/Operational", 4104, &[("ScriptBlockText", "Get-Date")]);
+    assert!(!rule.matches(&plain));
+}
+
+#[test]
+fn test_unsupported_rules_are_rejected() {
+    let aggregation = r#"
+title: Many failed logons
+logsource:
+    product: windows
+    service: security
+detection:
+    selection:
+        EventID: 4625
+    condition: selection | count() by IpAddress > 10
+"#;
+    assert!(SigmaRule::parse(aggregation).is_err());
+    assert!(SigmaRule::parse(&aggregation.replace("windows", "linux")).is_err());
+}
@@ -0,0 +1,143 @@
+// tests/software_tests.rs
+mod common;
+
+use common::{build_hive, Key};
+use forensic_triage::registry::{collect_installed_software, InstalledProgram, RegistryParser, SoftwareSource};
+
+const UNINSTALL: &str = "Microsoft\\Windows\\CurrentVersion\\Uninstall";
+const USER_DATA: &str = "Microsoft\\Windows\\CurrentVersion\\Installer\\UserData";
+/// `{12345678-ABCD-EF01-2345-6789ABCDEF01}` in Windows Installer's packed form
+const PACKED_DUPLICATE: &str = "87654321DCBA10FE32547698BADCFE10";
+/// `{90160000-008C-0000-0000-0000000FF1CE}`
+const PACKED_OFFICE: &str = "00006109C80000000000000000F01FEC";
+const CALCULATOR: &str = "Microsoft.WindowsCalculator_10.2103.8.0_x64__8wekyb3d8bbwe";
+
+fn software_hive() -> Vec<u8> {
+    build_hive(Key::new("ROOT")
+        .subkey(&format!("{}\\7-Zip", UNINSTALL), Key::new("7-Zip")
+            .string("DisplayName", "7-Zip 23.01 (x64)")
+            .string("DisplayVersion", "23.01")
+            .string("Publisher", "Igor Pavlov")
+            .string("InstallDate", "20230615")
+            .string("InstallLocation", "C:\\Program Files\\7-Zip\\")
+            .string("UninstallString", "\"C:\\Program Files\\7-Zip\\Uninstall.exe\""))
+        .subkey(&format!("{}\\{{12345678-ABCD-EF01-2345-6789ABCDEF01}}", UNINSTALL), Key::new("{12345678-ABCD-EF01-2345-6789ABCDEF01}")
+            .string("DisplayName", "Listed Twice")
+            .string("InstallDate", "06/15/2023"))
+        // Patches and components have no display name
+        .subkey(&format!("{}\\KB5034441", UNINSTALL), Key::new("KB5034441")
+            .string("InstallDate", "20240109"))
+        .subkey(&format!("Wow6432Node\\{}\\Notepad++", UNINSTALL), Key::new("Notepad++")
+            .string("DisplayName", "Notepad++ (32-bit x86)")
+            .string("DisplayVersion", "8.6")
+            .string("InstallDate", "not a date"))
+        .subkey(&format!("{}\\S-1-5-18\\Products\\{}\\InstallProperties", USER_DATA, PACKED_OFFICE), Key::new("InstallProperties")
+            .string("DisplayName", "Microsoft Office Professional Plus 2016")
+            .string("DisplayVersion", "16.0.4266.1001"))
+        .subkey(&format!("{}\\S-1-5-18\\Products\\{}\\InstallProperties", USER_DATA, PACKED_DUPLICATE), Key::new("InstallProperties")
+            .string("DisplayName", "Listed Twice"))
+        .subkey(&format!("{}\\S-1-5-21-1-2-3-1001\\Products\\00000000000000000000000000000001\\InstallProperties", USER_DATA), Key::new("InstallProperties")
+            .string("DisplayName", "Per-User MSI"))
+        // Not a packed product code
+        .subkey(&format!("{}\\S-1-5-18\\Products\\Junk\\InstallProperties", USER_DATA), Key::new("InstallProperties")
+            .string("DisplayName", "Junk"))
+        .subkey(&format!("Microsoft\\Windows\\CurrentVersion\\Appx\\AppxAllUserStore\\Applications\\{}", CALCULATOR), Key::new(CALCULATOR)
+            .string("Path", "C:\\Program Files\\WindowsApps\\Calculator")))
+}
+
+fn user_hive() -> Vec<u8> {
+    build_hive(Key::new("ROOT")
+        .subkey(&format!("Software\\{}\\Spotify", UNINSTALL), Key::new("Spotify")
+            .string("DisplayName", "Spotify")
+            .string("Publisher", "Spotify AB")))
+}
+
+fn user_classes_hive() -> Vec<u8> {
+    let package = "Microsoft.ZuneMusic_11.2312.7.0_x64__8wekyb3d8bbwe";
+    build_hive(Key::new("ROOT")
+        .subkey(&format!("Local Settings\\Software\\Microsoft\\Windows\\CurrentVersion\\AppModel\\Repository\\Packages\\{}", package), Key::new(package)
+            .string("DisplayName", "@{Microsoft.ZuneMusic_11.2312.7.0_x64__8wekyb3d8bbwe?ms-resource://Microsoft.ZuneMusic/Resources/AppStoreName}")
+            .string("PackageRootFolder", "C:\\Program Files\\WindowsApps\\Microsoft.ZuneMusic")))
+}
+
+fn collect() -> Vec<InstalledProgram> {
+    let mut registry = RegistryParser::new();
+    registry.load_hive_bytes(software_hive(), "HKLM\\SOFTWARE").unwrap();
+    registry.load_hive_bytes(user_hive(), "HKU\\alice").unwrap();
+    registry.load_hive_bytes(user_classes_hive(), "HKU\\alice_Classes").unwrap();
+    collect_installed_software(&registry)
+}
+
+fn find<'a>(programs: &'a [InstalledProgram], name: &str) -> &'a InstalledProgram {
+    programs.iter().find(|p| p.name == name).unwrap_or_else(|| panic!("{} not collected", name))
+}
+
+#[test]
+fn test_uninstall_entries() {
+    let programs = collect();
+
+    let zip = find(&programs, "7-Zip 23.01 (x64)");
+    assert_eq!(zip.source, SoftwareSource::Uninstall);
+    assert_eq!(zip.id, "7-Zip");
+    assert_eq!(zip.version.as_deref(), Some("23.01"));
+    assert_eq!(zip.publisher.as_deref(), Some("Igor Pavlov"));
+    assert_eq!(zip.install_date.unwrap().format("%Y-%m-%d").to_string(), "2023-06-15");
+    assert_eq!(zip.install_location.as_deref(), Some("C:\\Program Files\\7-Zip\\"));
+    assert_eq!(zip.uninstall_string.as_deref(), Some("\"C:\\Program Files\\7-Zip\\Uninstall.exe\""));
+    assert_eq!(zip.user, None);
+
+    let twice = find(&programs, "Listed Twice");
+    assert_eq!(twice.install_date.unwrap().format("%Y-%m-%d").to_string(), "2023-06-15");
+
+    let notepad = find(&programs, "Notepad++ (32-bit x86)");
+    assert_eq!(notepad.source, SoftwareSource::UninstallWow64);
+    assert_eq!(notepad.install_date, None);
+
+    let spotify = find(&programs, "Spotify");
+    assert_eq!(spotify.source, SoftwareSource::UserUninstall);
+    assert_eq!(spotify.user.as_deref(), Some("alice"));
+
+    assert!(programs.iter().all(|p| p.id != "KB5034441"));
+}
+
+#[test]
+fn test_msi_products() {
+    let programs = collect();
+
+    let office = find(&programs, "Microsoft Office Professional Plus 2016");
+    assert_eq!(office.source, SoftwareSource::MsiProduct);
+    assert_eq!(office.id, "{90160000-008C-0000-0000-0000000FF1CE}");
+    assert_eq!(office.version.as_deref(), Some("16.0.4266.1001"));
+    assert_eq!(office.user, None);
+
+    let per_user = find(&programs, "Per-User MSI");
+    assert_eq!(per_user.id, "{00000000-0000-0000-0000-000000000010}");
+    assert_eq!(per_user.user.as_deref(), Some("S-1-5-21-1-2-3-1001"));
+
+    // Already listed under Uninstall by product code
+    let twice: Vec<SoftwareSource> = programs.iter()
+        .filter(|p| p.name == "Listed Twice")
+        .map(|p| p.source)
+        .collect();
+    assert_eq!(twice, [SoftwareSource::Uninstall]);
+
+    assert!(programs.iter().all(|p| p.name != "Junk"));
+}
+
+#[test]
+fn test_appx_packages() {
+    let programs = collect();
+
+    let calculator = find(&programs, "Microsoft.WindowsCalculator");
+    assert_eq!(calculator.source, SoftwareSource::Appx);
+    assert_eq!(calculator.id, CALCULATOR);
+    assert_eq!(calculator.version.as_deref(), Some("10.2103.8.0"));
+    assert_eq!(calculator.install_location.as_deref(), Some("C:\\Program Files\\WindowsApps\\Calculator"));
+    assert_eq!(calculator.user, None);
+
+    // The resource reference display name falls back to the package name
+    let music = find(&programs, "Microsoft.ZuneMusic");
+    assert_eq!(music.version.as_deref(), Some("11.2312.7.0"));
+    assert_eq!(music.install_location.as_deref(), Some("C:\\Program Files\\WindowsApps\\Microsoft.ZuneMusic"));
+    assert_eq!(music.user.as_deref(), Some("alice"));
+}
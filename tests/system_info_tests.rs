@@ -0,0 +1,122 @@
+// tests/system_info_tests.rs
+mod common;
+
+use common::{build_hive, Key, REG_BINARY};
+use forensic_triage::artifacts::extract_registry_info;
+use forensic_triage::registry::RegistryParser;
+
+const CONTROL: &str = "ControlSet002\\Control";
+const TCPIP: &str = "ControlSet002\\Services\\Tcpip\\Parameters";
+/// 2020-01-01 and 2021-01-01 as FILETIMEs
+const JAN_2020: u64 = 132_223_104_000_000_000;
+const JAN_2021: u64 = 132_539_328_000_000_000;
+
+fn system_hive() -> Vec<u8> {
+    build_hive(Key::new("ROOT")
+        // ControlSet001 is stale; Select points at ControlSet002
+        .subkey("Select", Key::new("Select").dword("Current", 2))
+        .subkey("ControlSet001\\Control\\ComputerName\\ComputerName", Key::new("ComputerName")
+            .string("ComputerName", "OLD-NAME"))
+        .subkey(&format!("{}\\ComputerName\\ComputerName", CONTROL), Key::new("ComputerName")
+            .string("ComputerName", "WS-042"))
+        .subkey(&format!("{}\\TimeZoneInformation", CONTROL), Key::new("TimeZoneInformation")
+            .string("TimeZoneKeyName", "W. Europe Standard Time")
+            .dword("Bias", -60i32 as u32))
+        .subkey(&format!("{}\\Windows", CONTROL), Key::new("Windows")
+            .value("ShutdownTime", REG_BINARY, &JAN_2021.to_le_bytes()))
+        .subkey(TCPIP, Key::new("Parameters")
+            .string("Hostname", "ws-042")
+            .string("Domain", "corp.example.com"))
+        .subkey(&format!("{}\\Interfaces\\{{11111111-1111-1111-1111-111111111111}}", TCPIP), Key::new("")
+            .dword("EnableDHCP", 0)
+            .multi_string("IPAddress", &["10.0.0.5", "10.0.0.6"])
+            .multi_string("SubnetMask", &["255.255.255.0", "255.255.255.0"])
+            .multi_string("DefaultGateway", &["10.0.0.1"])
+            .string("NameServer", "10.0.0.2,10.0.0.3"))
+        .subkey(&format!("{}\\Interfaces\\{{22222222-2222-2222-2222-222222222222}}", TCPIP), Key::new("")
+            .dword("EnableDHCP", 1)
+            .multi_string("IPAddress", &["0.0.0.0"])
+            .string("DhcpIPAddress", "192.168.1.20")
+            .string("DhcpSubnetMask", "255.255.255.0")
+            .multi_string("DhcpDefaultGateway", &["192.168.1.1"])
+            .string("DhcpServer", "192.168.1.1")
+            .string("DhcpNameServer", "192.168.1.1")
+            .string("DhcpDomain", "home.lan")
+            .dword("LeaseObtainedTime", 1_600_000_000)
+            .dword("LeaseTerminatesTime", 1_600_086_400)))
+}
+
+fn software_hive() -> Vec<u8> {
+    build_hive(Key::new("ROOT")
+        .subkey("Microsoft\\Windows NT\\CurrentVersion", Key::new("CurrentVersion")
+            .string("ProductName", "Windows 10 Pro")
+            .string("EditionID", "Professional")
+            .string("DisplayVersion", "23H2")
+            .string("CurrentBuildNumber", "22631")
+            .dword("UBR", 2861)
+            .string("RegisteredOwner", "alice")
+            .string("RegisteredOrganization", "")
+            .dword("InstallDate", 1_577_836_800)
+            .qword("InstallTime", JAN_2020 + 10_000_000)))
+}
+
+#[test]
+fn test_extract_registry_info() {
+    let mut registry = RegistryParser::new();
+    registry.load_hive_bytes(system_hive(), "HKLM\\SYSTEM").unwrap();
+    registry.load_hive_bytes(software_hive(), "HKLM\\SOFTWARE").unwrap();
+    let info = extract_registry_info(&registry).unwrap();
+
+    assert_eq!(info.hostname, "WS-042");
+    assert_eq!(info.domain.as_deref(), Some("corp.example.com"));
+    assert_eq!(info.timezone.as_deref(), Some("W. Europe Standard Time"));
+    assert_eq!(info.timezone_bias, Some(-60));
+    assert_eq!(info.last_shutdown.unwrap().timestamp(), 1_609_459_200);
+
+    // Windows 11 still calls itself Windows 10; InstallTime beats InstallDate
+    assert_eq!(info.os_version.as_deref(), Some("Windows 11 Pro 23H2 (build 22631.2861)"));
+    let details = info.os_details.unwrap();
+    assert_eq!(details.edition.as_deref(), Some("Professional"));
+    assert_eq!(details.registered_owner.as_deref(), Some("alice"));
+    assert_eq!(details.registered_organization, None);
+    assert_eq!(info.install_date.unwrap().timestamp(), 1_577_836_801);
+
+    assert_eq!(info.interfaces.len(), 2);
+    let fixed = &info.interfaces[0];
+    assert_eq!(fixed.guid, "{11111111-1111-1111-1111-111111111111}");
+    assert!(!fixed.dhcp_enabled);
+    assert_eq!(fixed.ip_addresses, ["10.0.0.5", "10.0.0.6"]);
+    assert_eq!(fixed.default_gateways, ["10.0.0.1"]);
+    assert_eq!(fixed.dns_servers, ["10.0.0.2", "10.0.0.3"]);
+
+    let dhcp = &info.interfaces[1];
+    assert!(dhcp.dhcp_enabled);
+    assert_eq!(dhcp.ip_addresses, ["192.168.1.20"]);
+    assert_eq!(dhcp.subnet_masks, ["255.255.255.0"]);
+    assert_eq!(dhcp.default_gateways, ["192.168.1.1"]);
+    assert_eq!(dhcp.dhcp_server.as_deref(), Some("192.168.1.1"));
+    assert_eq!(dhcp.dns_servers, ["192.168.1.1"]);
+    assert_eq!(dhcp.domain.as_deref(), Some("home.lan"));
+    assert_eq!(dhcp.lease_obtained.unwrap().timestamp(), 1_600_000_000);
+    assert_eq!(dhcp.lease_expires.unwrap().timestamp(), 1_600_086_400);
+}
+
+#[test]
+fn test_extract_registry_info_fallbacks() {
+    let system = build_hive(Key::new("ROOT")
+        .subkey("Select", Key::new("Select").dword("Current", 1))
+        .subkey("ControlSet001\\Services\\Tcpip\\Parameters", Key::new("Parameters")
+            .string("Hostname", "ws-043")
+            .string("NV Domain", "corp.example.com")));
+    let mut registry = RegistryParser::new();
+    registry.load_hive_bytes(system, "HKLM\\SYSTEM").unwrap();
+    let info = extract_registry_info(&registry).unwrap();
+
+    assert_eq!(info.hostname, "ws-043");
+    assert_eq!(info.domain.as_deref(), Some("corp.example.com"));
+    assert!(info.interfaces.is_empty());
+    assert!(info.os_details.is_none());
+    assert!(info.timezone.is_none());
+
+    assert!(extract_registry_info(&RegistryParser::new()).is_err());
+}
@@ -0,0 +1,240 @@
+// tests/task_tests.rs
+mod common;
+
+use common::{build_hive, redirect_subkey, Key, REG_BINARY};
+use forensic_triage::parsers::{parse_scheduled_task, parse_task_actions, ScheduledTaskActionKind};
+use forensic_triage::registry::{parse_task_cache, RegistryHive, TaskCacheEntry};
+
+const TASK_CACHE: &str = "Microsoft\\Windows NT\\CurrentVersion\\Schedule\\TaskCache";
+/// 2020-01-01 as a FILETIME
+const JAN_2020: u64 = 132_223_104_000_000_000;
+const DAY: u64 = 864_000_000_000;
+const DEFRAG_ID: &str = "{0A1B2C3D-0000-4000-8000-000000000001}";
+const UPDATER_ID: &str = "{0A1B2C3D-0000-4000-8000-000000000002}";
+
+const TASK: &str = r#"<?xml version="1.0" encoding="UTF-16"?>
+<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">
+  <RegistrationInfo>
+    <Date>2024-01-02T03:04:05</Date>
+    <Author>CORP\admin</Author>
+    <URI>\Updater</URI>
+  </RegistrationInfo>
+  <Triggers>
+    <LogonTrigger>
+      <Repetition>
+        <Interval>PT1H</Interval>
+      </Repetition>
+      <Enabled>true</Enabled>
+      <UserId>CORP\user</UserId>
+    </LogonTrigger>
+    <CalendarTrigger>
+      <StartBoundary>2024-01-02T09:00:00</StartBoundary>
+      <Enabled>false</Enabled>
+      <ScheduleByDay>
+        <DaysInterval>1</DaysInterval>
+      </ScheduleByDay>
+    </CalendarTrigger>
+  </Triggers>
+  <Principals>
+    <Principal id="Author">
+      <UserId>S-1-5-18</UserId>
+      <RunLevel>HighestAvailable</RunLevel>
+    </Principal>
+  </Principals>
+  <Settings>
+    <IdleSettings>
+      <StopOnIdleEnd>true</StopOnIdleEnd>
+    </IdleSettings>
+    <Enabled>true</Enabled>
+    <Hidden>true</Hidden>
+  </Settings>
+  <Actions Context="Author">
+    <!-- <Exec><Command>ignored</Command></Exec> -->
+    <Exec>
+      <Command>powershell.exe</Command>
+      <Arguments>-nop -w hidden -c "iex 1 &gt; 0"</Arguments>
+    </Exec>
+    <ComHandler>
+      <ClassId>{0E8A1E23-6D6A-4C27-9C1E-0DA9AE3F1B2C}</ClassId>
+    </ComHandler>
+  </Actions>
+</Task>"#;
+
+fn utf16(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// A byte count followed by UTF-16LE text, as in TaskCache `Actions` values
+fn counted(text: &str) -> Vec<u8> {
+    let text = utf16(text);
+    let mut data = (text.len() as u32).to_le_bytes().to_vec();
+    data.extend(text);
+    data
+}
+
+#[test]
+fn test_parse_scheduled_task() {
+    let mut data = vec![0xFF, 0xFE];
+    data.extend(utf16(TASK));
+    let task = parse_scheduled_task(&data).unwrap();
+
+    assert_eq!(task.uri.as_deref(), Some("\\Updater"));
+    assert_eq!(task.author.as_deref(), Some("CORP\\admin"));
+    assert!(task.enabled);
+    assert!(task.hidden);
+
+    let principal = task.principal.unwrap();
+    assert_eq!(principal.user_id.as_deref(), Some("S-1-5-18"));
+    assert_eq!(principal.run_level.as_deref(), Some("HighestAvailable"));
+
+    assert_eq!(task.triggers.len(), 2);
+    let logon = &task.triggers[0];
+    assert_eq!(logon.kind, "LogonTrigger");
+    assert!(logon.enabled);
+    assert_eq!(logon.details["Repetition/Interval"], "PT1H");
+    assert_eq!(logon.details["UserId"], "CORP\\user");
+    let calendar = &task.triggers[1];
+    assert!(!calendar.enabled);
+    assert_eq!(calendar.start_boundary.as_deref(), Some("2024-01-02T09:00:00"));
+    assert_eq!(calendar.details["ScheduleByDay/DaysInterval"], "1");
+
+    assert_eq!(task.actions.len(), 2);
+    assert_eq!(task.actions[0].kind, ScheduledTaskActionKind::Exec);
+    assert_eq!(task.actions[0].command.as_deref(), Some("powershell.exe"));
+    assert_eq!(task.actions[0].arguments.as_deref(), Some("-nop -w hidden -c \"iex 1 > 0\""));
+    assert_eq!(task.actions[1].kind, ScheduledTaskActionKind::ComHandler);
+    assert_eq!(task.actions[1].class_id.as_deref(), Some("{0E8A1E23-6D6A-4C27-9C1E-0DA9AE3F1B2C}"));
+
+    assert!(parse_scheduled_task(b"<WLANProfile></WLANProfile>").is_err());
+}
+
+#[test]
+fn test_parse_task_actions() {
+    let mut data = 3u16.to_le_bytes().to_vec();
+    data.extend(counted("Author"));
+    data.extend(0x6666u16.to_le_bytes());
+    data.extend(counted(""));
+    data.extend(counted("C:\\Windows\\System32\\cmd.exe"));
+    data.extend(counted("/c whoami"));
+    data.extend(counted(""));
+    data.extend(0u16.to_le_bytes());
+    data.extend(0x7777u16.to_le_bytes());
+    data.extend(counted(""));
+    data.extend([0x23, 0x1E, 0x8A, 0x0E, 0x6A, 0x6D, 0x27, 0x4C, 0x9C, 0x1E, 0x0D, 0xA9, 0xAE, 0x3F, 0x1B, 0x2C]);
+    data.extend(counted("payload"));
+
+    let actions = parse_task_actions(&data);
+    assert_eq!(actions.len(), 2);
+    assert_eq!(actions[0].kind, ScheduledTaskActionKind::Exec);
+    assert_eq!(actions[0].command.as_deref(), Some("C:\\Windows\\System32\\cmd.exe"));
+    assert_eq!(actions[0].arguments.as_deref(), Some("/c whoami"));
+    assert_eq!(actions[0].working_directory, None);
+    assert_eq!(actions[1].kind, ScheduledTaskActionKind::ComHandler);
+    assert_eq!(actions[1].class_id.as_deref(), Some("{0E8A1E23-6D6A-4C27-9C1E-0DA9AE3F1B2C}"));
+    assert_eq!(actions[1].data.as_deref(), Some("payload"));
+
+    // Windows 7 action lists have no context and are not decoded
+    assert!(parse_task_actions(&1u16.to_le_bytes()).is_empty());
+}
+
+/// Version, created, last run, state, last result and, when `successful`
+/// is given, the Windows 10 last successful run
+fn dynamic_info(created: u64, last_run: u64, last_result: u32, successful: Option<u64>) -> Vec<u8> {
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend(created.to_le_bytes());
+    data.extend(last_run.to_le_bytes());
+    data.extend(0u32.to_le_bytes());
+    data.extend(last_result.to_le_bytes());
+    if let Some(successful) = successful {
+        data.extend(successful.to_le_bytes());
+    }
+    data
+}
+
+fn task_cache(root: Key) -> Vec<TaskCacheEntry> {
+    parse_task_cache(&RegistryHive::from_bytes(build_hive(root)).unwrap())
+}
+
+fn find<'a>(entries: &'a [TaskCacheEntry], path: &str) -> &'a TaskCacheEntry {
+    entries.iter().find(|e| e.path == path).unwrap_or_else(|| panic!("{} not found", path))
+}
+
+#[test]
+fn test_task_cache_tree_and_tasks() {
+    let tree = format!("{}\\Tree", TASK_CACHE);
+    let mut actions = 3u16.to_le_bytes().to_vec();
+    actions.extend(counted("Author"));
+    actions.extend(0x6666u16.to_le_bytes());
+    actions.extend(counted(""));
+    actions.extend(counted("C:\\Windows\\System32\\defrag.exe"));
+    actions.extend(counted("-c -h"));
+    actions.extend(counted(""));
+    actions.extend(0u16.to_le_bytes());
+
+    let entries = task_cache(Key::new("ROOT")
+        .subkey(&format!("{}\\Microsoft\\Windows\\Defrag\\ScheduledDefrag", tree), Key::new("ScheduledDefrag")
+            .last_write(JAN_2020)
+            .string("Id", DEFRAG_ID)
+            .dword("Index", 3)
+            .value("SD", REG_BINARY, &[1, 0, 4, 0x80]))
+        // Index 0 and a deleted SD hide a task
+        .subkey(&format!("{}\\Updater", tree), Key::new("Updater")
+            .string("Id", UPDATER_ID)
+            .dword("Index", 0))
+        // A tree entry without a Tasks key
+        .subkey(&format!("{}\\Orphan", tree), Key::new("Orphan")
+            .string("Id", "{0A1B2C3D-0000-4000-8000-000000000003}")
+            .value("SD", REG_BINARY, &[1, 0, 4, 0x80]))
+        .subkey(&format!("{}\\Tasks\\{}", TASK_CACHE, DEFRAG_ID), Key::new(DEFRAG_ID)
+            .string("Author", "Microsoft Corporation")
+            .string("Description", "Optimizes local storage drives")
+            .value("DynamicInfo", REG_BINARY, &dynamic_info(JAN_2020, JAN_2020 + 2 * DAY, 0x41303, Some(JAN_2020 + DAY)))
+            .value("Actions", REG_BINARY, &actions))
+        // Windows 7 records have no last successful run
+        .subkey(&format!("{}\\Tasks\\{}", TASK_CACHE, UPDATER_ID), Key::new(UPDATER_ID)
+            .value("DynamicInfo", REG_BINARY, &dynamic_info(JAN_2020 + 10 * DAY, 0, 1, None))));
+    assert_eq!(entries.len(), 3);
+
+    let defrag = find(&entries, "\\Microsoft\\Windows\\Defrag\\ScheduledDefrag");
+    assert_eq!(defrag.id, DEFRAG_ID);
+    assert_eq!(defrag.index, Some(3));
+    assert!(!defrag.sd_removed);
+    assert_eq!(defrag.author.as_deref(), Some("Microsoft Corporation"));
+    assert_eq!(defrag.description.as_deref(), Some("Optimizes local storage drives"));
+    assert_eq!(defrag.created.unwrap().timestamp(), 1_577_836_800);
+    assert_eq!(defrag.last_run.unwrap().timestamp(), 1_577_836_800 + 2 * 86_400);
+    assert_eq!(defrag.last_successful_run.unwrap().timestamp(), 1_577_836_800 + 86_400);
+    assert_eq!(defrag.last_result, Some(0x41303));
+    assert_eq!(defrag.last_write.unwrap().timestamp(), 1_577_836_800);
+    assert_eq!(defrag.actions.len(), 1);
+    assert_eq!(defrag.actions[0].command.as_deref(), Some("C:\\Windows\\System32\\defrag.exe"));
+    assert_eq!(defrag.actions[0].arguments.as_deref(), Some("-c -h"));
+
+    let updater = find(&entries, "\\Updater");
+    assert_eq!(updater.index, Some(0));
+    assert!(updater.sd_removed);
+    assert_eq!(updater.created.unwrap().timestamp(), 1_577_836_800 + 10 * 86_400);
+    // A zero FILETIME means the task never ran
+    assert_eq!(updater.last_run, None);
+    assert_eq!(updater.last_result, Some(1));
+    assert_eq!(updater.last_successful_run, None);
+    assert!(updater.actions.is_empty());
+
+    let orphan = find(&entries, "\\Orphan");
+    assert!(!orphan.sd_removed);
+    assert_eq!((orphan.author.as_deref(), orphan.created, orphan.last_result), (None, None, None));
+}
+
+#[test]
+fn test_task_cache_survives_subkey_cycles() {
+    let tree = format!("{}\\Tree", TASK_CACHE);
+    let mut hive = build_hive(Key::new("ROOT")
+        .subkey(&format!("{}\\Loop\\Child", tree), Key::new("Child"))
+        .subkey(&format!("{}\\Loop\\Updater", tree), Key::new("Updater").string("Id", UPDATER_ID)));
+    // Child, a folder, becomes Loop itself
+    redirect_subkey(&mut hive, "Child", "Loop");
+
+    let entries = parse_task_cache(&RegistryHive::from_bytes(hive).unwrap());
+    let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(paths, ["\\Loop\\Updater"]);
+}
@@ -0,0 +1,176 @@
+// tests/usb_tests.rs
+mod common;
+
+use chrono::DateTime;
+use common::{build_hive, utf16z, Key, REG_BINARY};
+use forensic_triage::parsers::SetupApiInstall;
+use forensic_triage::registry::{reconstruct_usb_history, RegistryParser, UsbDevice};
+
+/// Device property type used by Windows 8 and later
+const REG_FILETIME: u32 = 0x10;
+/// 2020-01-01 as a FILETIME
+const JAN_2020: u64 = 132_223_104_000_000_000;
+const DAY: u64 = 864_000_000_000;
+
+const PROPERTIES: &str = "Properties\\{83da6326-97a6-4088-9453-a1923f573b29}";
+const SANDISK_CLASS: &str = "Disk&Ven_SanDisk&Prod_Cruzer_Blade&Rev_1.00";
+const SANDISK_SERIAL: &str = "4C530001231120115142";
+const KINGSTON_CLASS: &str = "Disk&Ven_Kingston&Prod_DataTraveler&Rev_PMAP";
+/// Generated by Windows for a device without a serial number
+const KINGSTON_INSTANCE: &str = "7&2A6B1F3D&0";
+const VOLUME_CLASS: &str = "{53f56307-b6bf-11d0-94f2-00a0c91efb8b}";
+const SANDISK_VOLUME: &str = "{a1b2c3d4-0000-11ee-9c5b-000c29aabbcc}";
+
+fn sandisk_link() -> String {
+    format!("\\??\\USBSTOR#{}#{}&0#{}", SANDISK_CLASS, SANDISK_SERIAL, VOLUME_CLASS)
+}
+
+fn kingston_link() -> String {
+    format!("\\??\\USBSTOR#{}#{}#{}", KINGSTON_CLASS, KINGSTON_INSTANCE, VOLUME_CLASS)
+}
+
+fn system_hive() -> Vec<u8> {
+    let usbstor = "ControlSet001\\Enum\\USBSTOR";
+    let sandisk = format!("{}\\{}\\{}&0", usbstor, SANDISK_CLASS, SANDISK_SERIAL);
+    let kingston = format!("{}\\{}\\{}", usbstor, KINGSTON_CLASS, KINGSTON_INSTANCE);
+    let property = |id: &str, filetime: u64| Key::new(id).value("", REG_FILETIME, &filetime.to_le_bytes());
+
+    // MBR disks map drive letters to a disk signature and partition offset
+    let mut mbr = 0x1234_5678u32.to_le_bytes().to_vec();
+    mbr.extend(0x10_0000u64.to_le_bytes());
+
+    build_hive(Key::new("ROOT")
+        .subkey("Select", Key::new("Select").dword("Current", 1))
+        .subkey(&sandisk, Key::new("Instance").string("FriendlyName", "SanDisk Cruzer Blade USB Device"))
+        // Windows 8 and later: the FILETIME is the default value of each property key
+        .subkey(&format!("{}\\{}\\0064", sandisk, PROPERTIES), property("0064", JAN_2020 + DAY))
+        .subkey(&format!("{}\\{}\\0065", sandisk, PROPERTIES), property("0065", JAN_2020 + 2 * DAY))
+        .subkey(&format!("{}\\{}\\0066", sandisk, PROPERTIES), property("0066", JAN_2020 + 30 * DAY))
+        .subkey(&format!("{}\\{}\\0067", sandisk, PROPERTIES), property("0067", JAN_2020 + 31 * DAY))
+        .subkey(&kingston, Key::new("Instance").last_write(JAN_2020 + 60 * DAY))
+        // Windows 7: the FILETIME is the Data value of a numbered subkey
+        .subkey(&format!("{}\\{}\\00000064\\00000000", kingston, PROPERTIES), Key::new("00000000")
+            .value("Data", REG_BINARY, &(JAN_2020 + 50 * DAY).to_le_bytes()))
+        .subkey(&format!("ControlSet001\\Enum\\USB\\VID_0781&PID_5567\\{}", SANDISK_SERIAL), Key::new(SANDISK_SERIAL))
+        .subkey("MountedDevices", Key::new("MountedDevices")
+            .value("\\DosDevices\\E:", REG_BINARY, &utf16z(&sandisk_link()))
+            .value(&format!("\\??\\Volume{}", SANDISK_VOLUME), REG_BINARY, &utf16z(&sandisk_link()))
+            .value("\\DosDevices\\F:", REG_BINARY, &utf16z(&kingston_link()))
+            .value("\\DosDevices\\C:", REG_BINARY, &mbr)))
+}
+
+fn software_hive() -> Vec<u8> {
+    let wpd = format!("SWD#WPDBUSENUM#_??_USBSTOR#{}#{}&0#{}", SANDISK_CLASS.to_uppercase(), SANDISK_SERIAL, VOLUME_CLASS);
+    // `}<label>_<decimal volume serial>`: 305419896 is 0x12345678
+    let sandisk_emd = format!("_??_USBSTOR#{}#{}&0#{}CRUZER_305419896", SANDISK_CLASS, SANDISK_SERIAL, VOLUME_CLASS);
+    let kingston_emd = format!("_??_USBSTOR#{}#{}#{}KINGSTON_2882338817", KINGSTON_CLASS, KINGSTON_INSTANCE, VOLUME_CLASS);
+
+    build_hive(Key::new("ROOT")
+        .subkey(&format!("Microsoft\\Windows Portable Devices\\Devices\\{}", wpd), Key::new(&wpd)
+            .string("FriendlyName", "BACKUP"))
+        .subkey(&format!("Microsoft\\Windows NT\\CurrentVersion\\EMDMgmt\\{}", sandisk_emd), Key::new(&sandisk_emd))
+        .subkey(&format!("Microsoft\\Windows NT\\CurrentVersion\\EMDMgmt\\{}", kingston_emd), Key::new(&kingston_emd)))
+}
+
+fn user_hive(volume: &str, last_write: u64) -> Vec<u8> {
+    build_hive(Key::new("ROOT")
+        .subkey(&format!("Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\MountPoints2\\{}", volume), Key::new(volume)
+            .last_write(last_write))
+        // Network shares are listed too
+        .subkey("Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\MountPoints2\\##server#share", Key::new("##server#share")))
+}
+
+fn reconstruct() -> Vec<UsbDevice> {
+    let mut registry = RegistryParser::new();
+    registry.load_hive_bytes(system_hive(), "HKLM\\SYSTEM").unwrap();
+    registry.load_hive_bytes(software_hive(), "HKLM\\SOFTWARE").unwrap();
+    registry.load_hive_bytes(user_hive(SANDISK_VOLUME, JAN_2020 + 40 * DAY), "HKU\\alice").unwrap();
+    registry.load_hive_bytes(user_hive("{00000000-1111-2222-3333-444444444444}", JAN_2020), "HKU\\bob").unwrap();
+
+    let setupapi = [
+        SetupApiInstall {
+            device_id: format!("USBSTOR\\{}\\{}&0", SANDISK_CLASS, SANDISK_SERIAL),
+            timestamp: DateTime::from_timestamp(1_577_836_800 + 3600, 0).unwrap(),
+        },
+        SetupApiInstall {
+            device_id: format!("USBSTOR\\{}\\{}&0", SANDISK_CLASS, "OTHER"),
+            timestamp: DateTime::from_timestamp(0, 0).unwrap(),
+        },
+    ];
+    reconstruct_usb_history(&registry, &setupapi)
+}
+
+fn find<'a>(devices: &'a [UsbDevice], serial: &str) -> &'a UsbDevice {
+    devices.iter().find(|d| d.serial == serial).unwrap_or_else(|| panic!("{} not found", serial))
+}
+
+fn date(time: Option<DateTime<chrono::Utc>>) -> String {
+    time.unwrap().format("%Y-%m-%d").to_string()
+}
+
+#[test]
+fn test_usbstor_and_device_properties() {
+    let devices = reconstruct();
+    assert_eq!(devices.len(), 2);
+
+    let sandisk = find(&devices, SANDISK_SERIAL);
+    assert_eq!(sandisk.vendor.as_deref(), Some("SanDisk"));
+    assert_eq!(sandisk.product.as_deref(), Some("Cruzer Blade"));
+    assert_eq!(sandisk.revision.as_deref(), Some("1.00"));
+    assert!(sandisk.serial_is_unique);
+    assert_eq!(sandisk.friendly_name.as_deref(), Some("SanDisk Cruzer Blade USB Device"));
+    assert_eq!((sandisk.vid.as_deref(), sandisk.pid.as_deref()), (Some("0781"), Some("5567")));
+    assert_eq!(date(sandisk.first_install), "2020-01-02");
+    assert_eq!(date(sandisk.install_date), "2020-01-03");
+    assert_eq!(date(sandisk.last_arrival), "2020-01-31");
+    assert_eq!(date(sandisk.last_removal), "2020-02-01");
+    assert_eq!(sandisk.last_connected, sandisk.last_arrival);
+
+    // The Windows 7 layout, without an arrival time
+    let kingston = find(&devices, "7&2A6B1F3D");
+    assert!(!kingston.serial_is_unique);
+    assert_eq!(kingston.friendly_name, None);
+    assert_eq!(kingston.vid, None);
+    assert_eq!(date(kingston.first_install), "2020-02-20");
+    assert_eq!(kingston.first_connected, kingston.first_install);
+    assert_eq!(kingston.last_arrival, None);
+    assert_eq!(date(kingston.last_connected), "2020-03-01");
+}
+
+#[test]
+fn test_mounted_devices_and_volume_names() {
+    let devices = reconstruct();
+
+    let sandisk = find(&devices, SANDISK_SERIAL);
+    assert_eq!(sandisk.drive_letter.as_deref(), Some("E:"));
+    assert_eq!(sandisk.volume_guids, [SANDISK_VOLUME]);
+    // The portable device name wins over the EMDMgmt label
+    assert_eq!(sandisk.volume_name.as_deref(), Some("BACKUP"));
+    assert_eq!(sandisk.volume_serial.as_deref(), Some("1234-5678"));
+
+    let kingston = find(&devices, "7&2A6B1F3D");
+    assert_eq!(kingston.drive_letter.as_deref(), Some("F:"));
+    assert!(kingston.volume_guids.is_empty());
+    assert_eq!(kingston.volume_name.as_deref(), Some("KINGSTON"));
+    assert_eq!(kingston.volume_serial.as_deref(), Some("ABCD-0001"));
+}
+
+#[test]
+fn test_user_mounts_and_setupapi() {
+    let devices = reconstruct();
+
+    let sandisk = find(&devices, SANDISK_SERIAL);
+    assert_eq!(sandisk.users.len(), 1);
+    assert_eq!(sandisk.users[0].user, "alice");
+    assert_eq!(sandisk.users[0].volume_guid, SANDISK_VOLUME);
+    assert_eq!(date(sandisk.users[0].last_mounted), "2020-02-10");
+
+    // The log entry for another serial is ignored, and the earliest install
+    // time is the first connection
+    assert_eq!(sandisk.setupapi_install.unwrap().timestamp(), 1_577_836_800 + 3600);
+    assert_eq!(sandisk.first_connected, sandisk.setupapi_install);
+
+    let kingston = find(&devices, "7&2A6B1F3D");
+    assert!(kingston.users.is_empty());
+    assert_eq!(kingston.setupapi_install, None);
+}
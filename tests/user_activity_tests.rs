@@ -0,0 +1,195 @@
+// tests/user_activity_tests.rs
+mod common;
+
+use common::shell_items::{directory_item, root_item, volume_item};
+use common::{build_hive, utf16z, Key, REG_BINARY};
+use forensic_triage::registry::{
+    parse_mru, parse_shellbags, parse_userassist, MruEntry, MruSource, RegistryHive, ShellBagEntry,
+};
+use forensic_triage::shellitem::ShellItem;
+
+/// 2020-01-01 as a FILETIME
+const JAN_2020: u64 = 132_223_104_000_000_000;
+const USERASSIST: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\UserAssist";
+const EXPLORER: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer";
+const BAG_MRU: &str = "Local Settings\\Software\\Microsoft\\Windows\\Shell\\BagMRU";
+
+fn rot13(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'a'..='z' => ((c as u8 - b'a' + 13) % 26 + b'a') as char,
+            'A'..='Z' => ((c as u8 - b'A' + 13) % 26 + b'A') as char,
+            _ => c,
+        })
+        .collect()
+}
+
+/// MRUListEx data: value numbers, most recent first, then the terminator
+fn mru_list_ex(order: &[u32]) -> Vec<u8> {
+    order.iter().chain(&[u32::MAX]).flat_map(|i| i.to_le_bytes()).collect()
+}
+
+fn hive(root: Key) -> RegistryHive {
+    RegistryHive::from_bytes(build_hive(root)).unwrap()
+}
+
+#[test]
+fn test_userassist_records() {
+    let mut win7 = vec![0u8; 72];
+    win7[4..8].copy_from_slice(&7u32.to_le_bytes());
+    win7[8..12].copy_from_slice(&3u32.to_le_bytes());
+    win7[12..16].copy_from_slice(&60_000u32.to_le_bytes());
+    win7[60..68].copy_from_slice(&JAN_2020.to_le_bytes());
+
+    // XP run counts start at 5
+    let mut xp = vec![0u8; 16];
+    xp[4..8].copy_from_slice(&8u32.to_le_bytes());
+    xp[8..16].copy_from_slice(&JAN_2020.to_le_bytes());
+
+    let hive = hive(Key::new("ROOT")
+        .subkey(&format!("{}\\{{CEBFF5CD-ACE2-4F4F-9178-9926F41749EA}}\\Count", USERASSIST), Key::new("Count")
+            .last_write(JAN_2020)
+            .value(&rot13("{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\cmd.exe"), REG_BINARY, &win7)
+            .value(&rot13("C:\\Tools\\Unknown.exe"), REG_BINARY, &[0; 12]))
+        .subkey(&format!("{}\\{{75048700-EF1F-11D0-9888-006097DEACF9}}\\Count", USERASSIST), Key::new("Count")
+            .value(&rot13("UEME_RUNPATH:C:\\Windows\\notepad.exe"), REG_BINARY, &xp)));
+
+    let entries = parse_userassist(&hive, "alice");
+    assert_eq!(entries.len(), 2);
+
+    let cmd = &entries[0];
+    assert_eq!(cmd.name, "System32\\cmd.exe");
+    assert_eq!(cmd.user, "alice");
+    assert_eq!(cmd.guid, "{CEBFF5CD-ACE2-4F4F-9178-9926F41749EA}");
+    assert_eq!(cmd.key_path, format!("{}\\{{CEBFF5CD-ACE2-4F4F-9178-9926F41749EA}}\\Count", USERASSIST));
+    assert_eq!(cmd.key_last_write.unwrap().timestamp(), 1_577_836_800);
+    assert_eq!((cmd.run_count, cmd.focus_count, cmd.focus_time_ms), (7, Some(3), Some(60_000)));
+    assert_eq!(cmd.last_run.unwrap().timestamp(), 1_577_836_800);
+
+    let notepad = &entries[1];
+    assert_eq!(notepad.name, "UEME_RUNPATH:C:\\Windows\\notepad.exe");
+    assert_eq!((notepad.run_count, notepad.focus_count, notepad.focus_time_ms), (3, None, None));
+    assert_eq!(notepad.last_run.unwrap().timestamp(), 1_577_836_800);
+}
+
+fn mru_entries(source: MruSource, entries: &[MruEntry]) -> Vec<(usize, &str, Option<&str>)> {
+    entries.iter()
+        .filter(|e| e.source == source)
+        .map(|e| (e.position, e.value.as_str(), e.category.as_deref()))
+        .collect()
+}
+
+#[test]
+fn test_recent_docs_order_and_shell_items() {
+    let recent_doc = |name: &str, short_name: &str| {
+        let mut data = utf16z(name);
+        data.extend(directory_item(short_name, name));
+        data.extend([0, 0]);
+        data
+    };
+    let recent_docs = format!("{}\\RecentDocs", EXPLORER);
+
+    let hive = hive(Key::new("ROOT")
+        .subkey(&recent_docs, Key::new("RecentDocs")
+            .last_write(JAN_2020)
+            .value("0", REG_BINARY, &recent_doc("report.txt", "REPORT~1.TXT"))
+            .value("1", REG_BINARY, &recent_doc("Finance", "FINANCE"))
+            .value("2", REG_BINARY, &recent_doc("notes.txt", "NOTES.TXT"))
+            .value("MRUListEx", REG_BINARY, &mru_list_ex(&[2, 0, 1])))
+        .subkey(&format!("{}\\.txt", recent_docs), Key::new(".txt")
+            .value("0", REG_BINARY, &recent_doc("notes.txt", "NOTES.TXT"))
+            .value("MRUListEx", REG_BINARY, &mru_list_ex(&[0]))));
+
+    let entries = parse_mru(&hive, "alice");
+    assert_eq!(mru_entries(MruSource::RecentDocs, &entries), [
+        (0, "notes.txt", None),
+        (1, "report.txt", None),
+        (2, "Finance", None),
+        (0, "notes.txt", Some(".txt")),
+    ]);
+
+    let report = &entries[1];
+    assert_eq!(report.user, "alice");
+    assert_eq!(report.key_path, recent_docs);
+    assert_eq!(report.key_last_write.unwrap().timestamp(), 1_577_836_800);
+    let ShellItem::FileEntry(item) = &report.shell_items[0] else {
+        panic!("expected a file entry, got {:?}", report.shell_items);
+    };
+    assert_eq!(item.short_name, "REPORT~1.TXT");
+    assert_eq!(item.long_name.as_deref(), Some("report.txt"));
+    assert_eq!(item.mft_entry, Some(1234));
+}
+
+#[test]
+fn test_run_mru_and_typed_urls() {
+    let hive = hive(Key::new("ROOT")
+        // MRUList orders values by letter
+        .subkey(&format!("{}\\RunMRU", EXPLORER), Key::new("RunMRU")
+            .string("a", "cmd\\1")
+            .string("b", "regedit\\1")
+            .string("MRUList", "ba"))
+        .subkey("Software\\Microsoft\\Internet Explorer\\TypedURLs", Key::new("TypedURLs")
+            .string("url2", "http://second.example")
+            .string("url1", "http://first.example"))
+        .subkey("Software\\Microsoft\\Internet Explorer\\TypedURLsTime", Key::new("TypedURLsTime")
+            .value("url1", REG_BINARY, &JAN_2020.to_le_bytes())));
+
+    let entries = parse_mru(&hive, "alice");
+    assert_eq!(mru_entries(MruSource::RunMru, &entries), [(0, "regedit", None), (1, "cmd", None)]);
+    assert_eq!(mru_entries(MruSource::TypedUrls, &entries), [
+        (0, "http://first.example", None),
+        (1, "http://second.example", None),
+    ]);
+
+    let typed: Vec<Option<i64>> = entries.iter()
+        .filter(|e| e.source == MruSource::TypedUrls)
+        .map(|e| e.timestamp.map(|t| t.timestamp()))
+        .collect();
+    assert_eq!(typed, [Some(1_577_836_800), None]);
+}
+
+fn find<'a>(entries: &'a [ShellBagEntry], path: &str) -> &'a ShellBagEntry {
+    entries.iter().find(|e| e.path == path).unwrap_or_else(|| panic!("{} not found", path))
+}
+
+#[test]
+fn test_shellbags_paths() {
+    let hive = hive(Key::new("ROOT")
+        .subkey(BAG_MRU, Key::new("BagMRU")
+            .value("0", REG_BINARY, &root_item())
+            .value("MRUListEx", REG_BINARY, &mru_list_ex(&[0])))
+        .subkey(&format!("{}\\0", BAG_MRU), Key::new("0")
+            .value("0", REG_BINARY, &volume_item())
+            .value("MRUListEx", REG_BINARY, &mru_list_ex(&[0])))
+        .subkey(&format!("{}\\0\\0", BAG_MRU), Key::new("0")
+            .value("0", REG_BINARY, &directory_item("PROGRA~1", "Program Files"))
+            .value("1", REG_BINARY, &directory_item("USERS", "Users"))
+            .value("MRUListEx", REG_BINARY, &mru_list_ex(&[1, 0]))
+            .dword("NodeSlot", 2))
+        .subkey(&format!("{}\\0\\0\\0", BAG_MRU), Key::new("0")
+            .last_write(JAN_2020)
+            .dword("NodeSlot", 5)));
+
+    let entries = parse_shellbags(&hive, "alice");
+    assert_eq!(entries.len(), 4);
+
+    let computer = find(&entries, "My Computer");
+    assert_eq!(computer.key_path, format!("{}\\0", BAG_MRU));
+    assert_eq!(computer.mru_position, Some(0));
+
+    let volume = find(&entries, "My Computer\\C:");
+    assert_eq!(volume.node_slot, Some(2));
+
+    let program_files = find(&entries, "My Computer\\C:\\Program Files");
+    assert_eq!(program_files.key_path, format!("{}\\0\\0\\0", BAG_MRU));
+    assert_eq!(program_files.key_last_write.unwrap().timestamp(), 1_577_836_800);
+    assert_eq!(program_files.mru_position, Some(1));
+    assert_eq!(program_files.node_slot, Some(5));
+    assert_eq!((program_files.mft_entry, program_files.mft_sequence), (Some(1234), Some(3)));
+    assert_eq!(program_files.user, "alice");
+
+    // Users has no subkey of its own
+    let users = find(&entries, "My Computer\\C:\\Users");
+    assert_eq!(users.mru_position, Some(0));
+    assert_eq!((users.node_slot, users.key_last_write), (None, None));
+}
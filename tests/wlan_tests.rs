@@ -0,0 +1,47 @@
+// tests/wlan_tests.rs
+use forensic_triage::parsers::parse_wlan_profile;
+
+const PROFILE: &str = r#"<?xml version="1.0"?>
+<WLANProfile xmlns="http://www.microsoft.com/networking/WLAN/profile/v1">
+	<name>Cafe &amp; Bar</name>
+	<SSIDConfig>
+		<SSID>
+			<hex>43616665202620426172</hex>
+			<name>Cafe &amp; Bar</name>
+		</SSID>
+	</SSIDConfig>
+	<connectionType>ESS</connectionType>
+	<connectionMode>auto</connectionMode>
+	<MSM>
+		<security>
+			<authEncryption>
+				<authentication>WPA2PSK</authentication>
+				<encryption>AES</encryption>
+				<useOneX>false</useOneX>
+			</authEncryption>
+			<sharedKey>
+				<keyType>passPhrase</keyType>
+				<protected>true</protected>
+				<keyMaterial>01000000D08C9DDF0115D1118C7A00C04FC297EB</keyMaterial>
+			</sharedKey>
+		</security>
+	</MSM>
+</WLANProfile>"#;
+
+#[test]
+fn test_wlan_profile() {
+    let profile = parse_wlan_profile(PROFILE).unwrap();
+
+    assert_eq!(profile.name, "Cafe & Bar");
+    assert_eq!(profile.ssid.as_deref(), Some("Cafe & Bar"));
+    assert_eq!(profile.ssid_hex.as_deref(), Some("43616665202620426172"));
+    assert_eq!(profile.connection_mode.as_deref(), Some("auto"));
+    assert_eq!(profile.authentication.as_deref(), Some("WPA2PSK"));
+    assert!(profile.has_key);
+    assert_eq!(profile.key_protected, Some(true));
+}
+
+#[test]
+fn test_wlan_profile_rejects_other_xml() {
+    assert!(parse_wlan_profile("<Task><name>x</name></Task>").is_err());
+}
@@ -0,0 +1,173 @@
+// tests/wmi_tests.rs
+use forensic_triage::parsers::parse_wmi_repository;
+use sha2::{Digest, Sha256};
+
+const PAGE_SIZE: usize = 0x2000;
+
+const QUERY: &str = "SELECT * FROM __InstanceModificationEvent WITHIN 60 WHERE TargetInstance ISA 'Win32_PerfFormattedData_PerfOS_System'";
+
+fn utf16(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn class_hash(class: &str) -> String {
+    Sha256::digest(utf16(&class.to_uppercase())).iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// An instance: class hash, two FILETIMEs, some fixed property data, then
+/// heap strings, ASCII or UTF-16 as flagged
+fn instance(class: &str, strings: &[(&str, bool)]) -> Vec<u8> {
+    let mut record = utf16(&class_hash(class));
+    record.extend([0x11; 16]);
+    record.extend([0x05, 0x00, 0x00, 0x80, 0xFF, 0x03]);
+    for (text, wide) in strings {
+        if *wide {
+            record.push(1);
+            record.extend(utf16(text));
+            record.extend([0, 0]);
+        } else {
+            record.push(0);
+            record.extend(text.as_bytes());
+            record.push(0);
+        }
+    }
+    record
+}
+
+/// A mapping file holding the OBJECTS.DATA map and then the INDEX.BTR map
+fn mapping_file(version: u32, objects: &[u32], index: &[u32]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for pages in [objects, index] {
+        for field in [0xABCD, version, 0, 0, pages.len() as u32, pages.len() as u32] {
+            data.extend(u32::to_le_bytes(field));
+        }
+        for page in pages {
+            data.extend(page.to_le_bytes());
+            data.extend([0; 20]);
+        }
+        data.extend(0u32.to_le_bytes());
+        data.extend(0xDCBAu32.to_le_bytes());
+    }
+    data
+}
+
+/// A record table page followed by as many continuation pages as the last
+/// record needs
+fn object_pages(records: &[Vec<u8>]) -> Vec<u8> {
+    let mut offset = (records.len() + 1) * 16;
+    let mut data = Vec::new();
+    for (i, record) in records.iter().enumerate() {
+        for field in [i as u32 + 1, offset as u32, record.len() as u32, 0] {
+            data.extend(field.to_le_bytes());
+        }
+        offset += record.len();
+    }
+    data.extend([0; 16]);
+    for record in records {
+        data.extend(record);
+    }
+    data.resize(data.len().div_ceil(PAGE_SIZE) * PAGE_SIZE, 0);
+    data
+}
+
+/// A single B-tree page holding the given keys, each a list of strings
+fn index_page(keys: &[Vec<String>]) -> Vec<u8> {
+    let mut strings: Vec<&str> = Vec::new();
+    let mut key_info: Vec<u16> = Vec::new();
+    let mut key_offsets = Vec::new();
+    for key in keys {
+        key_offsets.push(key_info.len() as u16);
+        key_info.push(key.len() as u16);
+        for part in key {
+            key_info.push(strings.len() as u16);
+            strings.push(part);
+        }
+    }
+
+    let mut page = Vec::new();
+    for field in [0xACCC, 0, 0, 0, keys.len() as u32] {
+        page.extend(u32::to_le_bytes(field));
+    }
+    page.extend(vec![0; keys.len() * 4 + (keys.len() + 1) * 4]);
+    page.extend(key_offsets.iter().flat_map(|o| o.to_le_bytes()));
+    page.extend((key_info.len() as u16).to_le_bytes());
+    page.extend(key_info.iter().flat_map(|i| i.to_le_bytes()));
+    page.extend((strings.len() as u16).to_le_bytes());
+    let mut offset = 0u16;
+    for string in &strings {
+        page.extend(offset.to_le_bytes());
+        offset += string.len() as u16 + 1;
+    }
+    page.extend(offset.to_le_bytes());
+    for string in &strings {
+        page.extend(string.as_bytes());
+        page.push(0);
+    }
+    page.resize(PAGE_SIZE, 0);
+    page
+}
+
+#[test]
+fn test_wmi_event_subscription() {
+    let command = format!("powershell.exe -nop -enc {}", "QQ".repeat(4500));
+    let records = vec![
+        instance("__EventFilter", &[
+            ("UpdaterFilter", false),
+            (QUERY, false),
+            ("WQL", false),
+            ("root\\cimv2", true),
+        ]),
+        instance("__FilterToConsumerBinding", &[
+            ("CommandLineEventConsumer.Name=\"Updater\"", true),
+            ("__EventFilter.Name=\"UpdaterFilter\"", true),
+        ]),
+        instance("CommandLineEventConsumer", &[
+            ("Updater", false),
+            (&command, true),
+        ]),
+    ];
+    let sizes: Vec<usize> = records.iter().map(Vec::len).collect();
+    let pages = object_pages(&records);
+    assert!(pages.len() > PAGE_SIZE);
+
+    // Physical page 0 is stale; the records live on pages 1 and 2
+    let mut objects = vec![0; PAGE_SIZE];
+    objects.extend(pages);
+    let keys: Vec<Vec<String>> = ["__EventFilter", "__FilterToConsumerBinding", "CommandLineEventConsumer"].iter()
+        .zip(&sizes)
+        .enumerate()
+        .map(|(i, (class, size))| vec![
+            "NS_892F8DB69C4EDFBC68165C91087B7A08323F6CE5B5EF342C0F93E02A0590BFC4".to_string(),
+            format!("CI_{}", class_hash(class)),
+            format!("IL_{:064X}.0.{}.{}", i, i + 1, size),
+        ])
+        .collect();
+    let index = index_page(&keys);
+    let old = mapping_file(7, &[0], &[0]);
+    let current = mapping_file(8, &[1, 2, 3], &[0]);
+
+    for index in [index.as_slice(), &[]] {
+        let persistence = parse_wmi_repository(&objects, index, &[&old, &current]).unwrap();
+
+        assert_eq!(persistence.filters.len(), 1);
+        let filter = &persistence.filters[0];
+        assert_eq!(filter.name.as_deref(), Some("UpdaterFilter"));
+        assert_eq!(filter.query.as_deref(), Some(QUERY));
+        assert_eq!(filter.query_language.as_deref(), Some("WQL"));
+        assert_eq!(filter.event_namespace.as_deref(), Some("root\\cimv2"));
+
+        assert_eq!(persistence.consumers.len(), 1);
+        let consumer = &persistence.consumers[0];
+        assert_eq!(consumer.class, "CommandLineEventConsumer");
+        assert_eq!(consumer.name.as_deref(), Some("Updater"));
+        assert_eq!(consumer.command.as_deref(), Some(command.as_str()));
+
+        assert_eq!(persistence.bindings.len(), 1);
+        let binding = &persistence.bindings[0];
+        assert_eq!(binding.filter.as_deref(), Some("UpdaterFilter"));
+        assert_eq!(binding.consumer_class.as_deref(), Some("CommandLineEventConsumer"));
+        assert_eq!(binding.consumer.as_deref(), Some("Updater"));
+    }
+
+    assert!(parse_wmi_repository(&objects, &index, &[&[0; 64]]).is_err());
+}